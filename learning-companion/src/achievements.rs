@@ -0,0 +1,213 @@
+//! 成就模块
+//!
+//! 成就定义来自 achievements.toml，每个成就带一个解锁条件；
+//! 每次学习进度、练习或计时变化后运行评估，解锁新成就并发送通知
+
+use crate::clock::SystemClock;
+use crate::db::Store;
+use crate::progress::TaskType;
+use crate::repo::LearningRepo;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// 内置的成就定义
+const ACHIEVEMENTS_TOML: &str = include_str!("../achievements.toml");
+
+/// 解锁条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// 连续学习天数
+    StreakDays { days: u32 },
+    /// 单次练习得分（0-100）
+    PracticeScore { score: u32 },
+    /// 完成的模块数
+    ModulesCompleted { count: u32 },
+    /// 完成的模块比例（百分比）
+    ModulesPercent { percent: u32 },
+    /// 通过全部测试的练习数
+    ExercisesPassed { count: u32 },
+    /// 累计作答题数
+    Reviews { count: u32 },
+}
+
+/// 一个成就的定义
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AchievementDef {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default = "default_icon")]
+    pub icon: String,
+    pub condition: Condition,
+}
+
+fn default_icon() -> String {
+    "🏅".to_string()
+}
+
+#[derive(Deserialize)]
+struct AchievementFile {
+    achievements: Vec<AchievementDef>,
+}
+
+/// 解析成就定义，id 不能重复
+pub fn parse_definitions(content: &str) -> Result<Vec<AchievementDef>> {
+    let file: AchievementFile = toml::from_str(content).context("成就定义格式错误")?;
+    for (i, def) in file.achievements.iter().enumerate() {
+        if file.achievements[..i].iter().any(|d| d.id == def.id) {
+            anyhow::bail!("成就 id 重复：{}", def.id);
+        }
+    }
+    Ok(file.achievements)
+}
+
+/// 所有成就定义
+pub fn definitions() -> &'static [AchievementDef] {
+    static DEFINITIONS: OnceLock<Vec<AchievementDef>> = OnceLock::new();
+    DEFINITIONS.get_or_init(|| parse_definitions(ACHIEVEMENTS_TOML).expect("内置成就定义无效"))
+}
+
+/// 评估成就所需的学习数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AchievementStats {
+    pub streak_days: u32,
+    pub best_practice_score: f32,
+    pub modules_completed: u32,
+    pub total_modules: u32,
+    pub exercises_passed: u32,
+    pub reviews: u32,
+}
+
+impl AchievementStats {
+    /// 从数据库收集，已完成的模块以五项任务全部完成为准
+    pub fn collect(store: &Store, total_modules: usize) -> Result<Self> {
+        let fields = store.get_progress_fields()?;
+        let mut modules: Vec<&str> = fields.iter().map(|f| f.module_id.as_str()).collect();
+        modules.dedup();
+        let modules_completed = modules.iter()
+            .filter(|module| TaskType::ALL.iter().all(|task| fields.iter().any(|f| {
+                f.module_id == **module && f.field == task.key() && f.value.as_deref() == Some("true")
+            })))
+            .count() as u32;
+
+        Ok(AchievementStats {
+            streak_days: crate::streak::calculate(store, &SystemClock)?.current,
+            best_practice_score: store.get_best_practice_score()?,
+            modules_completed,
+            total_modules: total_modules as u32,
+            exercises_passed: store.count_passed_exercises()?,
+            reviews: store.count_question_reviews()?,
+        })
+    }
+}
+
+/// 条件的完成进度
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConditionProgress {
+    pub current: u32,
+    pub target: u32,
+    pub unit: &'static str,
+}
+
+impl ConditionProgress {
+    pub fn is_met(&self) -> bool {
+        self.current >= self.target
+    }
+
+    /// 如「5/7 天」
+    pub fn display(&self) -> String {
+        format!("{}/{} {}", self.current.min(self.target), self.target, self.unit)
+    }
+}
+
+/// 计算条件的完成进度
+pub fn progress(condition: &Condition, stats: &AchievementStats) -> ConditionProgress {
+    let (current, target, unit) = match condition {
+        Condition::StreakDays { days } => (stats.streak_days, *days, "天"),
+        Condition::PracticeScore { score } => (stats.best_practice_score.round() as u32, *score, "分"),
+        Condition::ModulesCompleted { count } => (stats.modules_completed, *count, "个模块"),
+        Condition::ModulesPercent { percent } => {
+            // 换算成需要完成的模块数（向上取整）
+            let target = (stats.total_modules * percent).div_ceil(100).max(1);
+            (stats.modules_completed, target, "个模块")
+        }
+        Condition::ExercisesPassed { count } => (stats.exercises_passed, *count, "个练习"),
+        Condition::Reviews { count } => (stats.reviews, *count, "道题"),
+    };
+    ConditionProgress { current, target, unit }
+}
+
+/// 一个成就的当前状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct AchievementStatus {
+    pub def: AchievementDef,
+    pub unlocked_at: Option<chrono::DateTime<chrono::Local>>,
+    pub unlocked: bool,
+    pub progress: ConditionProgress,
+}
+
+/// 所有成就的状态（含未解锁成就的进度）
+pub fn statuses(store: &Store, total_modules: usize) -> Result<Vec<AchievementStatus>> {
+    let stats = AchievementStats::collect(store, total_modules)?;
+    let unlocked = store.get_unlocked_achievements()?;
+
+    Ok(definitions().iter()
+        .map(|def| {
+            let record = unlocked.iter().find(|u| u.achievement_type == def.id);
+            AchievementStatus {
+                def: def.clone(),
+                unlocked_at: record.and_then(|r| r.unlocked_at),
+                unlocked: record.is_some(),
+                progress: progress(&def.condition, &stats),
+            }
+        })
+        .collect())
+}
+
+/// 找出满足条件但尚未解锁的成就
+pub fn newly_met<'a>(
+    defs: &'a [AchievementDef],
+    stats: &AchievementStats,
+    unlocked_ids: &[String],
+) -> Vec<(&'a AchievementDef, ConditionProgress)> {
+    defs.iter()
+        .filter(|def| !unlocked_ids.contains(&def.id))
+        .map(|def| (def, progress(&def.condition, stats)))
+        .filter(|(_, progress)| progress.is_met())
+        .collect()
+}
+
+/// 评估所有成就，解锁新达成的成就并发送通知，返回新解锁的成就
+pub fn evaluate(store: &Store, total_modules: usize) -> Result<Vec<AchievementDef>> {
+    let stats = AchievementStats::collect(store, total_modules)?;
+    let unlocked_ids: Vec<String> = store.get_unlocked_achievements()?
+        .into_iter()
+        .map(|u| u.achievement_type)
+        .collect();
+
+    let mut unlocked = Vec::new();
+    for (def, progress) in newly_met(definitions(), &stats, &unlocked_ids) {
+        let metadata = serde_json::json!({
+            "condition": def.condition,
+            "progress": progress,
+            "stats": stats,
+        });
+        if store.unlock_achievement(&def.id, Some(&metadata.to_string()))? {
+            // 通知失败（如没有桌面环境）不影响解锁
+            let _ = crate::notify::send_achievement_notification(&def.name);
+            unlocked.push(def.clone());
+        }
+    }
+
+    Ok(unlocked)
+}
+
+/// 评估成就并打印新解锁的成就（命令行）
+pub fn check_and_report(store: &Store, repo: &LearningRepo) -> Result<()> {
+    for def in evaluate(store, repo.modules.len())? {
+        println!("🏆 解锁成就：{} {} - {}", def.icon, def.name, def.description);
+    }
+    Ok(())
+}
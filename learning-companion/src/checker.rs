@@ -0,0 +1,631 @@
+//! 练习检查模块
+//!
+//! 在子进程中运行练习文件自带的测试，解析结果并记录到数据库；
+//! 编译错误、警告和测试 panic 解析为带文件和行号的诊断，TUI 练习工作区据此跳转到出错位置
+
+use crate::db::{DbResult, ExerciseResult, Store};
+use crate::repo::{LearningModule, LearningRepo};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 代码片段的最长运行时间
+const SNIPPET_TIMEOUT: Duration = Duration::from_secs(10);
+/// 练习编译和运行测试各自的最长时间，超时后结束进程（测试中的死循环不会让界面卡住）
+pub const TEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// 本进程中用 rustc 检查的次数，用于区分临时目录
+static RUSTC_CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+/// 单个测试用例的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub passed: bool,
+}
+
+/// 一个练习的检查结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExerciseCheck {
+    pub exercise: String,
+    pub compiled: bool,
+    pub tests: Vec<TestCaseResult>,
+    /// 编译器和测试程序的原始输出
+    pub output: String,
+}
+
+impl ExerciseCheck {
+    pub fn passed_count(&self) -> usize {
+        self.tests.iter().filter(|t| t.passed).count()
+    }
+
+    pub fn failed_tests(&self) -> Vec<String> {
+        self.tests.iter()
+            .filter(|t| !t.passed)
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.compiled && !self.tests.is_empty() && self.passed_count() == self.tests.len()
+    }
+}
+
+/// 模块练习完成情况汇总
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExerciseSummary {
+    /// 练习文件总数
+    pub total: usize,
+    /// 已运行过检查的练习数
+    pub checked: usize,
+    /// 带有测试、参与判分的练习数
+    pub graded: usize,
+    /// 测试全部通过的练习数
+    pub passed: usize,
+    /// 通过的练习中，测试通过前看过答案的练习数
+    pub assisted: usize,
+}
+
+impl ExerciseSummary {
+    /// 根据练习列表和数据库记录汇总
+    ///
+    /// 没有测试的练习不参与判分，但必须至少运行过一次检查
+    pub fn from_results(exercises: &[String], results: &[ExerciseResult]) -> Self {
+        let mut summary = ExerciseSummary { total: exercises.len(), ..Default::default() };
+
+        for name in exercises {
+            if let Some(result) = results.iter().find(|r| &r.exercise == name) {
+                summary.checked += 1;
+                if !result.compiled || result.tests_total > 0 {
+                    summary.graded += 1;
+                }
+                if result.passed() {
+                    summary.passed += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// 所有练习都已检查，且参与判分的练习全部通过
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.checked == self.total && self.graded > 0 && self.passed == self.graded
+    }
+}
+
+/// 解析 libtest 输出中的 `test xxx ... ok/FAILED` 行
+pub fn parse_test_output(output: &str) -> Vec<TestCaseResult> {
+    let re = Regex::new(r"(?m)^test (\S+) \.\.\. (ok|FAILED)").unwrap();
+
+    re.captures_iter(output)
+        .map(|cap| TestCaseResult {
+            name: cap[1].to_string(),
+            passed: &cap[2] == "ok",
+        })
+        .collect()
+}
+
+/// 诊断的级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+    /// 测试运行时 panic（断言失败等）
+    Panic,
+}
+
+impl DiagnosticLevel {
+    pub fn icon(self) -> &'static str {
+        match self {
+            DiagnosticLevel::Error => "❌",
+            DiagnosticLevel::Warning => "⚠️",
+            DiagnosticLevel::Panic => "💥",
+        }
+    }
+}
+
+/// 源文件中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: PathBuf,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// 从编译器或测试输出中解析出的一条诊断
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    /// 错误码，如 `E0382`
+    pub code: Option<String>,
+    pub message: String,
+    pub location: SourceLocation,
+    /// 诊断在输出中的行号（从 0 开始），用于滚动到这条诊断
+    pub output_line: usize,
+}
+
+/// 把输出中的相对路径解析为实际文件
+///
+/// cargo 输出相对于工作区根目录的路径，rustc 输出命令行上的路径，所以依次尝试模块目录和它的上级目录
+fn resolve_source(path: &str, module_dir: &Path) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    module_dir.ancestors()
+        .map(|dir| dir.join(path))
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| module_dir.join(path))
+}
+
+/// 解析 rustc 的错误和警告（`error[E0382]: ...` 加下一行的 `--> 文件:行:列`）以及测试 panic 的位置
+///
+/// 没有位置的汇总信息（如 `error: aborting due to ...`）不算诊断
+pub fn parse_diagnostics(output: &str, module_dir: &Path) -> Vec<Diagnostic> {
+    let header = Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").unwrap();
+    let arrow = Regex::new(r"^\s*--> (.+):(\d+):(\d+)$").unwrap();
+    // 新版本的格式为 `thread '名称' (线程号) panicked at 文件:行:列:`，消息在下一行；
+    // 旧版本为 `panicked at '消息', 文件:行:列`
+    let panic = Regex::new(r"^thread '(.+?)'(?: \(\d+\))? panicked at (?:'(.*)', )?(.+):(\d+):(\d+):?$").unwrap();
+
+    let lines: Vec<&str> = output.lines().collect();
+    let mut diagnostics = Vec::new();
+    let mut pending: Option<(DiagnosticLevel, Option<String>, String, usize)> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(cap) = header.captures(line) {
+            let level = if &cap[1] == "error" { DiagnosticLevel::Error } else { DiagnosticLevel::Warning };
+            pending = Some((level, cap.get(2).map(|m| m.as_str().to_string()), cap[3].to_string(), index));
+        } else if let Some(cap) = arrow.captures(line) {
+            if let Some((level, code, message, output_line)) = pending.take() {
+                diagnostics.push(Diagnostic {
+                    level,
+                    code,
+                    message,
+                    location: SourceLocation {
+                        file: resolve_source(&cap[1], module_dir),
+                        line: cap[2].parse().unwrap_or(1),
+                        column: cap[3].parse().unwrap_or(1),
+                    },
+                    output_line,
+                });
+            }
+        } else if let Some(cap) = panic.captures(line) {
+            pending = None;
+            let detail = match cap.get(2) {
+                Some(message) => message.as_str().to_string(),
+                None => lines.get(index + 1).map(|l| l.trim().to_string()).unwrap_or_default(),
+            };
+            diagnostics.push(Diagnostic {
+                level: DiagnosticLevel::Panic,
+                code: None,
+                message: format!("{}：{}", &cap[1], detail),
+                location: SourceLocation {
+                    file: resolve_source(&cap[3], module_dir),
+                    line: cap[4].parse().unwrap_or(1),
+                    column: cap[5].parse().unwrap_or(1),
+                },
+                output_line: index,
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// 列出模块的练习文件名（不含 solutions 目录），按练习编号排序
+pub fn list_exercises(module_dir: &Path) -> Result<Vec<String>> {
+    let dir = module_dir.join("exercises");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "rs") {
+            if let Some(stem) = path.file_stem() {
+                let stem = stem.to_string_lossy().to_string();
+                if stem.starts_with("exercise") {
+                    names.push(stem);
+                }
+            }
+        }
+    }
+
+    names.sort_by_key(|name| (exercise_number(name), name.clone()));
+    Ok(names)
+}
+
+/// 从 `exercise12_vecdeque` 中提取编号 12
+fn exercise_number(name: &str) -> u32 {
+    name.trim_start_matches("exercise")
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .unwrap_or(u32::MAX)
+}
+
+/// 在模块 Cargo.toml 中查找练习文件对应的 `[[bin]]` 目标名
+fn find_bin_target(module_dir: &Path, exercise: &str) -> Option<String> {
+    let manifest = fs::read_to_string(module_dir.join("Cargo.toml")).ok()?;
+    let table: toml::Table = manifest.parse().ok()?;
+    let expected_path = format!("exercises/{}.rs", exercise);
+
+    table.get("bin")?
+        .as_array()?
+        .iter()
+        .find(|bin| bin.get("path").and_then(|p| p.as_str()) == Some(expected_path.as_str()))
+        .and_then(|bin| bin.get("name")?.as_str().map(String::from))
+}
+
+/// 运行单个练习的测试
+///
+/// 已注册为 `[[bin]]` 的练习通过 `cargo test --bin` 运行，其余练习用 `rustc --test` 单独编译
+pub fn run_exercise_tests(module: &LearningModule, exercise: &str) -> Result<ExerciseCheck> {
+    run_tests(module, exercise, TEST_TIMEOUT, &AtomicBool::new(false))
+}
+
+fn run_tests(module: &LearningModule, exercise: &str, timeout: Duration, cancel: &AtomicBool) -> Result<ExerciseCheck> {
+    let file = module.directory.join("exercises").join(format!("{}.rs", exercise));
+    if !file.exists() {
+        anyhow::bail!("练习文件不存在：{}", file.display());
+    }
+
+    let (compiled, output) = match find_bin_target(&module.directory, exercise) {
+        Some(bin) => run_with_cargo(&module.directory, &bin, timeout, cancel)?,
+        None => run_with_rustc(&file, &format!("{}-{}", module.id, exercise), timeout, cancel)?,
+    };
+
+    Ok(ExerciseCheck {
+        exercise: exercise.to_string(),
+        compiled,
+        tests: parse_test_output(&output),
+        output,
+    })
+}
+
+/// 一次在后台进行的练习测试
+///
+/// 界面每帧调用 `poll` 取结果，不会阻塞；丢弃时取消测试
+#[derive(Debug)]
+pub struct TestRun {
+    pub module_id: String,
+    pub exercise: String,
+    pub started_at: Instant,
+    result: Receiver<Result<ExerciseCheck>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl TestRun {
+    /// 在后台线程中运行练习的测试
+    pub fn start(module: &LearningModule, exercise: &str, timeout: Duration) -> Self {
+        let (sender, result) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let flag = Arc::clone(&cancel);
+        let target = module.clone();
+        let name = exercise.to_string();
+        thread::spawn(move || {
+            let _ = sender.send(run_tests(&target, &name, timeout, &flag));
+        });
+
+        Self {
+            module_id: module.id.clone(),
+            exercise: exercise.to_string(),
+            started_at: Instant::now(),
+            result,
+            cancel,
+        }
+    }
+
+    /// 测试结束时返回结果，还在运行时返回 None
+    pub fn poll(&self) -> Option<Result<ExerciseCheck>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow::anyhow!("测试线程意外退出"))),
+        }
+    }
+
+    /// 阻塞等待结果
+    pub fn recv(&self) -> Result<ExerciseCheck> {
+        self.result.recv().unwrap_or_else(|_| Err(anyhow::anyhow!("测试线程意外退出")))
+    }
+
+    /// 请求结束测试，随后收到「已取消」的错误
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for TestRun {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// 运行命令并收集输出；超过时限或请求取消时结束进程并返回错误
+fn output_with_timeout(command: &mut Command, timeout: Duration, cancel: &AtomicBool) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // 在单独的线程中读取，避免子进程写满管道后卡住
+    let stdout = child.stdout.take().map(read_all);
+    let stderr = child.stderr.take().map(read_all);
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if cancel.load(Ordering::Relaxed) {
+            kill_process_tree(&mut child);
+            anyhow::bail!("测试已取消");
+        }
+        if started.elapsed() > timeout {
+            kill_process_tree(&mut child);
+            anyhow::bail!("超过 {} 秒没有结束，已停止（测试中是否有死循环？）", timeout.as_secs());
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let join = |reader: Option<thread::JoinHandle<Vec<u8>>>| reader.and_then(|r| r.join().ok()).unwrap_or_default();
+    Ok(Output { status, stdout: join(stdout), stderr: join(stderr) })
+}
+
+fn read_all(mut stream: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stream.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// 结束子进程；Unix 上先结束它启动的进程（`cargo test` 运行的测试程序）
+fn kill_process_tree(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("pkill")
+        .args(["-KILL", "-P", &child.id().to_string()])
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// 使用 cargo 运行 bin 目标的测试
+fn run_with_cargo(module_dir: &Path, bin: &str, timeout: Duration, cancel: &AtomicBool) -> Result<(bool, String)> {
+    let output = output_with_timeout(
+        Command::new("cargo")
+            .arg("test")
+            .arg("--manifest-path")
+            .arg(module_dir.join("Cargo.toml"))
+            .args(["--bin", bin]),
+        timeout,
+        cancel,
+    ).context("无法运行 cargo test")?;
+
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stderr),
+        String::from_utf8_lossy(&output.stdout)
+    );
+    // 测试程序启动后一定会输出 "running N tests"
+    let compiled = Regex::new(r"(?m)^running \d+ tests?").unwrap().is_match(&text);
+
+    Ok((compiled, text))
+}
+
+/// 使用 rustc 单独编译练习文件并运行测试
+///
+/// 每次检查使用单独的临时目录，用完删除；多个进程或后台测试同时检查同一个练习时不会互相覆盖
+fn run_with_rustc(file: &Path, binary_name: &str, timeout: Duration, cancel: &AtomicBool) -> Result<(bool, String)> {
+    let out_dir = std::env::temp_dir().join(format!(
+        "learning-companion-check-{}-{}",
+        std::process::id(),
+        RUSTC_CHECKS.fetch_add(1, Ordering::Relaxed),
+    ));
+    fs::create_dir_all(&out_dir)?;
+    let result = compile_and_test(file, &out_dir.join(binary_name), timeout, cancel);
+    let _ = fs::remove_dir_all(&out_dir);
+    result
+}
+
+fn compile_and_test(file: &Path, binary: &Path, timeout: Duration, cancel: &AtomicBool) -> Result<(bool, String)> {
+    let compile = output_with_timeout(
+        Command::new("rustc")
+            .args(["--edition", "2021", "--test"])
+            .arg(file)
+            .arg("-o")
+            .arg(binary),
+        timeout,
+        cancel,
+    ).context("无法编译练习")?;
+
+    if !compile.status.success() {
+        return Ok((false, String::from_utf8_lossy(&compile.stderr).to_string()));
+    }
+
+    let run = output_with_timeout(&mut Command::new(binary), timeout, cancel).context("无法运行测试程序")?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&run.stdout),
+        String::from_utf8_lossy(&run.stderr)
+    );
+
+    Ok((true, text))
+}
+
+/// 代码片段的实际行为
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetOutcome {
+    pub compiled: bool,
+    /// 程序的标准输出（编译失败时为空）
+    pub stdout: String,
+    /// 编译错误或程序的标准错误输出
+    pub stderr: String,
+}
+
+/// 用本机 rustc 编译并运行一段代码
+pub fn run_snippet(code: &str) -> Result<SnippetOutcome> {
+    let out_dir = std::env::temp_dir().join("learning-companion-snippets");
+    fs::create_dir_all(&out_dir)?;
+
+    // 临时文件按代码内容命名，不同片段可以并行编译
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
+    let name = format!("snippet-{:016x}", hasher.finish());
+
+    let source = out_dir.join(format!("{}.rs", name));
+    let binary = out_dir.join(&name);
+    fs::write(&source, code)?;
+
+    let compile = Command::new("rustc")
+        .args(["--edition", "2021", "-A", "warnings"])
+        .arg(&source)
+        .arg("-o")
+        .arg(&binary)
+        .output()
+        .context("无法运行 rustc")?;
+
+    if !compile.status.success() {
+        return Ok(SnippetOutcome {
+            compiled: false,
+            stdout: String::new(),
+            stderr: String::from_utf8_lossy(&compile.stderr).to_string(),
+        });
+    }
+
+    // 输出写入文件而不是管道，避免等待超时时管道写满阻塞
+    let stdout_path = out_dir.join(format!("{}.stdout", name));
+    let stderr_path = out_dir.join(format!("{}.stderr", name));
+    let mut child = Command::new(&binary)
+        .stdin(Stdio::null())
+        .stdout(File::create(&stdout_path)?)
+        .stderr(File::create(&stderr_path)?)
+        .spawn()
+        .context("无法运行代码片段")?;
+
+    let started = Instant::now();
+    while child.try_wait()?.is_none() {
+        if started.elapsed() > SNIPPET_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("代码片段运行超过 {} 秒", SNIPPET_TIMEOUT.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    Ok(SnippetOutcome {
+        compiled: true,
+        stdout: String::from_utf8_lossy(&fs::read(&stdout_path)?).to_string(),
+        stderr: String::from_utf8_lossy(&fs::read(&stderr_path)?).to_string(),
+    })
+}
+
+/// 汇总模块的练习完成情况（读取数据库中的测试结果）
+///
+/// 读取失败时返回错误，不能当作没有通过的练习，否则会取消 进度.md 中的勾选
+pub fn exercise_summary(store: &Store, module: &LearningModule) -> DbResult<ExerciseSummary> {
+    let exercises = list_exercises(&module.directory).unwrap_or_default();
+    let results = store.get_exercise_results(&module.id)?;
+    let mut summary = ExerciseSummary::from_results(&exercises, &results);
+    for result in results.iter().filter(|r| r.passed() && exercises.contains(&r.exercise)) {
+        if crate::hints::viewed_before_pass(store, &module.id, &result.exercise)? {
+            summary.assisted += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// 记录一个练习的检查结果
+pub fn record_check(store: &Store, module: &LearningModule, check: &ExerciseCheck) -> Result<()> {
+    store.record_exercise_result(
+        &module.id,
+        &check.exercise,
+        check.compiled,
+        check.tests.len() as u32,
+        check.passed_count() as u32,
+        check.failed_tests(),
+    )?;
+    Ok(())
+}
+
+/// 记录完检查结果后调用：按测试结果勾选 进度.md 中的「练习题完成」并同步，返回模块的练习汇总
+pub fn finish_checks(store: &Store, repo: &LearningRepo, module: &LearningModule) -> Result<(ExerciseSummary, crate::sync::SyncPlan)> {
+    let summary = exercise_summary(store, module)?;
+    crate::study::touch_module(store, &module.id)?;
+    crate::sync::record_task(store, repo, &module.id, crate::progress::TaskType::Exercises, summary.is_complete())?;
+    let plan = crate::sync::sync(store, repo, None)?;
+    Ok((summary, plan))
+}
+
+/// 检查模块练习并记录结果
+pub fn run_check(store: &Store, repo: &LearningRepo, module_id: &str, exercise: Option<&str>) -> Result<()> {
+    let module = repo.find_module(module_id)
+        .ok_or_else(|| anyhow::anyhow!("未找到模块：{}", module_id))?;
+
+    let exercises = match exercise {
+        Some(name) => vec![name.trim_end_matches(".rs").to_string()],
+        None => list_exercises(&module.directory)?,
+    };
+
+    if exercises.is_empty() {
+        println!("❌ 该模块没有练习文件");
+        return Ok(());
+    }
+
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🧪 Rust 学习伴侣 - 练习检查      ║");
+    println!("╚════════════════════════════════════════╝\n");
+
+    for name in &exercises {
+        println!("🔧 正在测试 {} ...", name);
+        let check = run_exercise_tests(module, name)?;
+        record_check(store, module, &check)?;
+
+        if !check.compiled {
+            println!("  ❌ 编译失败");
+        } else if check.tests.is_empty() {
+            println!("  ⚪ 没有测试用例");
+        } else if check.all_passed() {
+            println!("  ✅ 通过 {}/{}", check.passed_count(), check.tests.len());
+        } else {
+            println!("  ❌ 通过 {}/{}", check.passed_count(), check.tests.len());
+            for test in check.failed_tests() {
+                println!("     • {}", test);
+            }
+        }
+    }
+
+    let (summary, plan) = finish_checks(store, repo, module)?;
+    println!("\n{}", "─".repeat(50));
+    println!("📊 {}：{}/{} 个练习通过测试（已检查 {}/{}）",
+        module.name, summary.passed, summary.graded, summary.checked, summary.total);
+    if summary.assisted > 0 {
+        println!("👀 其中 {} 个练习在测试通过前看过答案，按 {:.0}% 计入掌握程度",
+            summary.assisted, crate::hints::ASSISTED_CREDIT * 100.0);
+    }
+
+    if summary.is_complete() {
+        println!("🎉 所有练习题已通过测试，仪表板已标记「练习题完成」！");
+    }
+
+    crate::sync::print_plan(&plan);
+    crate::achievements::check_and_report(store, repo)?;
+
+    Ok(())
+}
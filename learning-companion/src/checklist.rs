@@ -0,0 +1,151 @@
+//! 自检清单模块
+//!
+//! 解析模块目录下的 自检清单.md，按勾选比例判断「自检通过」，并支持勾选后写回文件
+
+use crate::markdown;
+use crate::repo::LearningModule;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 自检清单文件名
+pub const CHECKLIST_FILE: &str = "自检清单.md";
+
+/// 勾选比例达到该值即视为自检通过
+pub const PASS_RATIO: f32 = 0.8;
+
+/// 清单中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    /// 所在行号（从 0 开始），用于写回文件
+    pub line: usize,
+    pub text: String,
+    pub checked: bool,
+}
+
+/// `##` 标题下的一组清单项
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistSection {
+    pub title: String,
+    pub items: Vec<ChecklistItem>,
+}
+
+/// 一个模块的自检清单
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checklist {
+    pub path: PathBuf,
+    pub sections: Vec<ChecklistSection>,
+}
+
+impl Checklist {
+    /// 按文件顺序列出所有清单项
+    pub fn items(&self) -> impl Iterator<Item = &ChecklistItem> {
+        self.sections.iter().flat_map(|s| s.items.iter())
+    }
+
+    pub fn total(&self) -> usize {
+        self.items().count()
+    }
+
+    pub fn checked_count(&self) -> usize {
+        self.items().filter(|i| i.checked).count()
+    }
+
+    /// 已勾选的比例（0.0 - 1.0）
+    pub fn ratio(&self) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        self.checked_count() as f32 / total as f32
+    }
+
+    /// 勾选比例达到 PASS_RATIO 即为自检通过
+    pub fn is_passed(&self) -> bool {
+        self.total() > 0 && self.ratio() >= PASS_RATIO
+    }
+}
+
+/// 解析清单内容
+///
+/// 第一个 `##` 标题之前的清单项归入标题为空的分组
+pub fn parse_checklist(content: &str, path: &Path) -> Checklist {
+    let mut sections: Vec<ChecklistSection> = Vec::new();
+    let lines: Vec<&str> = content.lines().collect();
+    let in_code = markdown::code_block_mask(&lines);
+
+    for (line_no, line) in lines.iter().enumerate() {
+        if in_code[line_no] {
+            continue;
+        }
+
+        if let Some(title) = line.strip_prefix("## ") {
+            sections.push(ChecklistSection {
+                title: title.trim().to_string(),
+                items: Vec::new(),
+            });
+        } else if let Some((checked, text)) = markdown::parse_task(line) {
+            if sections.is_empty() {
+                sections.push(ChecklistSection { title: String::new(), items: Vec::new() });
+            }
+            if let Some(section) = sections.last_mut() {
+                section.items.push(ChecklistItem { line: line_no, text, checked });
+            }
+        }
+    }
+
+    sections.retain(|s| !s.items.is_empty());
+
+    Checklist {
+        path: path.to_path_buf(),
+        sections,
+    }
+}
+
+/// 加载模块的自检清单，文件不存在时返回 None
+pub fn load_checklist(module: &LearningModule) -> Result<Option<Checklist>> {
+    let path = module.directory.join(CHECKLIST_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("无法读取自检清单：{}", path.display()))?;
+    Ok(Some(parse_checklist(&content, &path)))
+}
+
+/// 模块是否已通过自检（以清单勾选比例为准）
+pub fn is_passed(module: &LearningModule) -> bool {
+    load_checklist(module).ok().flatten().is_some_and(|c| c.is_passed())
+}
+
+/// 切换第 line 行清单项的勾选状态，其余内容原样保留
+pub fn toggle_line(content: &str, line: usize) -> Option<String> {
+    let mut toggled = false;
+    let updated: String = content.split_inclusive('\n')
+        .enumerate()
+        .map(|(i, text)| {
+            let task = if i == line { markdown::parse_task(text.trim_end()) } else { None };
+            let Some((checked, _)) = task else {
+                return text.to_string();
+            };
+            toggled = true;
+            // 只改列表符号后面的标记，正文中的 `[ ]` 保持不变
+            markdown::set_task_checked(text, !checked)
+        })
+        .collect();
+
+    toggled.then_some(updated)
+}
+
+/// 切换清单项并写回文件，返回切换后的清单
+pub fn toggle_item(path: &Path, line: usize) -> Result<Checklist> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("无法读取自检清单：{}", path.display()))?;
+    let updated = toggle_line(&content, line)
+        .ok_or_else(|| anyhow::anyhow!("第 {} 行不是清单项", line + 1))?;
+
+    fs::write(path, &updated)
+        .with_context(|| format!("无法写入自检清单：{}", path.display()))?;
+    Ok(parse_checklist(&updated, path))
+}
@@ -0,0 +1,620 @@
+//! 课堂模式
+//!
+//! 学员用 `class join` 生成身份（名字和 Ed25519 密钥），`class bundle` 把导出数据签名打包；
+//! 讲师把数据包放到同一个目录，`class roster` 校验签名后合并成班级名册：
+//! 各模块的完成情况、全班的薄弱知识点和停滞的学员。全部基于本地文件，不需要服务器
+
+use crate::clock::{SystemClock, TimeZone};
+use crate::db::{ImportMode, Row, Store};
+use crate::progress::TaskType;
+use crate::repo::LearningRepo;
+use crate::storage::{csv_field, Export};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 学员身份文件（在数据目录中，包含私钥，不要分享）
+pub const LEARNER_FILE: &str = "learner.json";
+/// 数据包的格式标识
+pub const BUNDLE_FORMAT: &str = "learning-companion-bundle";
+/// 数据包的版本，结构不兼容地变化时递增
+pub const BUNDLE_VERSION: u32 = 1;
+/// 上次查看的数据包目录的设置项
+pub const CLASSROOM_DIR_KEY: &str = "classroom_dir";
+/// 默认多少天没有学习算停滞
+pub const DEFAULT_STALLED_DAYS: i64 = 7;
+/// 名册最多列出的薄弱知识点数
+const WEAK_TOPIC_LIMIT: usize = 10;
+
+/// 去掉首尾空白的学员名字，不能为空
+fn checked_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        anyhow::bail!("学员名字不能为空");
+    }
+    Ok(name.to_string())
+}
+
+/// 学员身份
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: String,
+    /// Ed25519 私钥（十六进制）
+    secret_key: String,
+}
+
+impl Identity {
+    /// 生成新的身份
+    pub fn generate(name: &str) -> Result<Self> {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        Ok(Identity { name: checked_name(name)?, secret_key: hex::encode(key.to_bytes()) })
+    }
+
+    fn signing_key(&self) -> Result<SigningKey> {
+        let bytes: [u8; 32] = hex::decode(&self.secret_key).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("学员身份文件中的密钥无效")?;
+        Ok(SigningKey::from_bytes(&bytes))
+    }
+
+    /// 公钥（十六进制），讲师据此区分学员
+    pub fn public_key(&self) -> Result<String> {
+        Ok(hex::encode(self.signing_key()?.verifying_key().to_bytes()))
+    }
+
+    /// 读取数据目录中的身份，没有时为 None
+    pub fn load(data_dir: &Path) -> Result<Option<Self>> {
+        let path = data_dir.join(LEARNER_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("无法读取 {}", path.display()))?;
+        let identity = serde_json::from_str(&content)
+            .with_context(|| format!("{} 格式错误", path.display()))?;
+        Ok(Some(identity))
+    }
+
+    /// 写入数据目录（Unix 上只有本人可读）
+    pub fn save(&self, data_dir: &Path) -> Result<()> {
+        let path = data_dir.join(LEARNER_FILE);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("无法写入 {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+}
+
+/// 数据包中的学员信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Learner {
+    pub name: String,
+    /// Ed25519 公钥（十六进制）
+    pub public_key: String,
+}
+
+impl Learner {
+    /// 公钥的前 8 位，同名学员靠它区分
+    pub fn fingerprint(&self) -> &str {
+        self.public_key.get(..8).unwrap_or(&self.public_key)
+    }
+}
+
+/// 签名的学习数据包：学员信息和 export 生成的全部数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub learner: Learner,
+    pub export: Export,
+    /// 对以上内容的 Ed25519 签名（十六进制）
+    pub signature: String,
+}
+
+/// 参与签名的内容
+#[derive(Serialize)]
+struct Signed<'a> {
+    format: &'a str,
+    version: u32,
+    learner: &'a Learner,
+    export: &'a Export,
+}
+
+impl Bundle {
+    /// 用学员的私钥给导出数据签名
+    pub fn create(identity: &Identity, export: Export) -> Result<Self> {
+        let learner = Learner { name: identity.name.clone(), public_key: identity.public_key()? };
+        let mut bundle = Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            learner,
+            export,
+            signature: String::new(),
+        };
+        let signature = identity.signing_key()?.sign(&bundle.signed_bytes()?);
+        bundle.signature = hex::encode(signature.to_bytes());
+        Ok(bundle)
+    }
+
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&Signed {
+            format: &self.format,
+            version: self.version,
+            learner: &self.learner,
+            export: &self.export,
+        })?)
+    }
+
+    /// 解析数据包并校验签名
+    pub fn parse(content: &str) -> Result<Self> {
+        let bundle: Bundle = serde_json::from_str(content).context("不是学习数据包")?;
+        if bundle.format != BUNDLE_FORMAT {
+            anyhow::bail!("不是学习数据包（format = {}）", bundle.format);
+        }
+        if bundle.version > BUNDLE_VERSION {
+            anyhow::bail!("数据包版本 {} 比当前程序支持的版本 {} 新，请先升级", bundle.version, BUNDLE_VERSION);
+        }
+        bundle.export.validate()?;
+        bundle.verify()?;
+        Ok(bundle)
+    }
+
+    /// 校验签名：数据包生成后没有被修改过
+    pub fn verify(&self) -> Result<()> {
+        let public_key: [u8; 32] = hex::decode(&self.learner.public_key).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("公钥格式错误")?;
+        let signature: [u8; 64] = hex::decode(&self.signature).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("签名格式错误")?;
+        VerifyingKey::from_bytes(&public_key)
+            .context("公钥无效")?
+            .verify(&self.signed_bytes()?, &Signature::from_bytes(&signature))
+            .map_err(|_| anyhow::anyhow!("签名无效，数据包可能被修改过"))
+    }
+
+    /// 导出时间，无法解析时按最早处理
+    pub fn exported_at(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.export.exported_at).ok().map(|t| t.with_timezone(&Local))
+    }
+}
+
+/// 学员在某个模块上的进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleStatus {
+    NotStarted,
+    Started,
+    Done,
+}
+
+impl ModuleStatus {
+    pub fn icon(self) -> &'static str {
+        match self {
+            ModuleStatus::NotStarted => "⬜",
+            ModuleStatus::Started => "🟡",
+            ModuleStatus::Done => "✅",
+        }
+    }
+
+    fn csv_label(self) -> &'static str {
+        match self {
+            ModuleStatus::NotStarted => "",
+            ModuleStatus::Started => "学习中",
+            ModuleStatus::Done => "完成",
+        }
+    }
+}
+
+/// 名册中的一位学员
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearnerSummary {
+    pub learner: Learner,
+    pub exported_at: Option<DateTime<Local>>,
+    /// 模块目录名到进度，没有记录的模块不在其中
+    pub modules: BTreeMap<String, ModuleStatus>,
+    /// 所有练习的平均得分
+    pub average_score: Option<f32>,
+    /// 最近一次学习、练习或复习的日期
+    pub last_active: Option<NaiveDate>,
+    /// 知识点到（答错次数, 作答次数）
+    pub topics: BTreeMap<String, (u32, u32)>,
+}
+
+impl LearnerSummary {
+    /// 把数据包导入内存数据库，用和本机相同的查询统计进度
+    pub fn from_bundle(bundle: &Bundle, tz: TimeZone) -> Result<Self> {
+        let store = Store::open_in_memory()?;
+        let tables: Vec<(String, Vec<Row>)> = bundle.export.tables.clone().into_iter().collect();
+        store.import_tables(&tables, ImportMode::Replace)?;
+
+        // 数据包生成前已同步过 进度.md，这里按同样的规则读取任务状态
+        let fields = store.get_progress_fields()?;
+        let modules = fields.iter()
+            .map(|f| f.module_id.as_str())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|module_id| {
+                let done = crate::sync::tasks_done(&fields, module_id, None).len();
+                let status = match done {
+                    0 => return None,
+                    n if n == TaskType::ALL.len() => ModuleStatus::Done,
+                    _ => ModuleStatus::Started,
+                };
+                Some((module_id.to_string(), status))
+            })
+            .collect();
+
+        let practice = store.get_all_practice_results()?;
+        let average_score = (!practice.is_empty())
+            .then(|| practice.iter().map(|(_, p)| p.score).sum::<f32>() / practice.len() as f32);
+
+        let reviews = store.get_question_reviews()?;
+        let mut topics: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+        for review in &reviews {
+            let entry = topics.entry(review.topic.clone()).or_default();
+            entry.1 += 1;
+            if !review.correct {
+                entry.0 += 1;
+            }
+        }
+
+        let last_active = [
+            crate::streak::study_dates(&store, tz)?.last().copied(),
+            practice.iter().map(|(_, p)| p.timestamp.date_naive()).max(),
+            reviews.iter().map(|r| r.reviewed_at.date_naive()).max(),
+            store.get_mastery_history()?.iter().map(|s| s.recorded_at.date_naive()).max(),
+        ].into_iter().flatten().max();
+
+        Ok(LearnerSummary {
+            learner: bundle.learner.clone(),
+            exported_at: bundle.exported_at(),
+            modules,
+            average_score,
+            last_active,
+            topics,
+        })
+    }
+
+    pub fn status(&self, module_id: &str) -> ModuleStatus {
+        self.modules.get(module_id).copied().unwrap_or(ModuleStatus::NotStarted)
+    }
+
+    pub fn completed(&self) -> usize {
+        self.modules.values().filter(|s| **s == ModuleStatus::Done).count()
+    }
+
+    /// 距今多少天没有学习，从来没有学习时为 None
+    pub fn idle_days(&self, today: NaiveDate) -> Option<i64> {
+        self.last_active.map(|date| (today - date).num_days().max(0))
+    }
+}
+
+/// 全班的一个薄弱知识点
+#[derive(Debug, Clone, PartialEq)]
+pub struct CohortTopic {
+    pub topic: String,
+    /// 答错过的学员数
+    pub learners: usize,
+    pub misses: u32,
+    pub attempts: u32,
+}
+
+/// 被拒绝的数据包
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejected {
+    pub file: String,
+    pub reason: String,
+}
+
+/// 班级名册
+#[derive(Debug, Clone, PartialEq)]
+pub struct Roster {
+    /// 数据包目录
+    pub dir: PathBuf,
+    /// 模块目录名和显示名称，按模块编号
+    pub modules: Vec<(String, String)>,
+    /// 按名字排列，同一个密钥只保留最新的数据包
+    pub learners: Vec<LearnerSummary>,
+    /// 按答错的学员数从多到少
+    pub weak_topics: Vec<CohortTopic>,
+    pub rejected: Vec<Rejected>,
+    pub warnings: Vec<String>,
+    pub today: NaiveDate,
+    pub stalled_days: i64,
+}
+
+impl Roster {
+    /// 合并已校验的数据包
+    pub fn build(repo: &LearningRepo, dir: &Path, bundles: Vec<Bundle>, tz: TimeZone, today: NaiveDate, stalled_days: i64) -> Result<Self> {
+        // 同一位学员多次提交时只保留最新的数据包
+        let mut latest: BTreeMap<String, Bundle> = BTreeMap::new();
+        for bundle in bundles {
+            let newer = latest.get(&bundle.learner.public_key)
+                .map_or(true, |existing| bundle.exported_at() > existing.exported_at());
+            if newer {
+                latest.insert(bundle.learner.public_key.clone(), bundle);
+            }
+        }
+
+        let mut learners = latest.values()
+            .map(|bundle| LearnerSummary::from_bundle(bundle, tz))
+            .collect::<Result<Vec<_>>>()?;
+        learners.sort_by(|a, b| (&a.learner.name, &a.learner.public_key).cmp(&(&b.learner.name, &b.learner.public_key)));
+
+        let mut warnings = Vec::new();
+        for pair in learners.windows(2) {
+            if pair[0].learner.name == pair[1].learner.name {
+                warnings.push(format!("{} 有多个不同的密钥（{}、{}），可能是重新 join 过或有人冒名",
+                    pair[0].learner.name, pair[0].learner.fingerprint(), pair[1].learner.fingerprint()));
+            }
+        }
+
+        let mut topics: BTreeMap<&str, CohortTopic> = BTreeMap::new();
+        for learner in &learners {
+            for (topic, (misses, attempts)) in &learner.topics {
+                let entry = topics.entry(topic).or_insert_with(|| CohortTopic {
+                    topic: topic.clone(),
+                    learners: 0,
+                    misses: 0,
+                    attempts: 0,
+                });
+                entry.misses += misses;
+                entry.attempts += attempts;
+                if *misses > 0 {
+                    entry.learners += 1;
+                }
+            }
+        }
+        let mut weak_topics: Vec<CohortTopic> = topics.into_values().filter(|t| t.misses > 0).collect();
+        weak_topics.sort_by(|a, b| b.learners.cmp(&a.learners)
+            .then((b.misses * a.attempts).cmp(&(a.misses * b.attempts))));
+        weak_topics.truncate(WEAK_TOPIC_LIMIT);
+
+        Ok(Roster {
+            dir: dir.to_path_buf(),
+            modules: repo.modules.iter().map(|m| (m.id.clone(), m.name.clone())).collect(),
+            learners,
+            weak_topics,
+            rejected: Vec::new(),
+            warnings,
+            today,
+            stalled_days,
+        })
+    }
+
+    /// 读取目录中的所有 .json 数据包，签名无效或无法解析的记为拒绝
+    pub fn load(repo: &LearningRepo, dir: &Path, tz: TimeZone, today: NaiveDate, stalled_days: i64) -> Result<Self> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("无法读取数据包目录 {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        let mut bundles = Vec::new();
+        let mut rejected = Vec::new();
+        for path in files {
+            let file = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string());
+            let parsed = fs::read_to_string(&path)
+                .with_context(|| format!("无法读取 {}", path.display()))
+                .and_then(|content| Bundle::parse(&content));
+            match parsed {
+                Ok(bundle) => bundles.push(bundle),
+                Err(e) => rejected.push(Rejected { file, reason: format!("{:#}", e) }),
+            }
+        }
+
+        let mut roster = Roster::build(repo, dir, bundles, tz, today, stalled_days)?;
+        roster.rejected = rejected;
+        Ok(roster)
+    }
+
+    /// 完成某个模块的学员数
+    pub fn completion(&self, module_id: &str) -> usize {
+        self.learners.iter().filter(|l| l.status(module_id) == ModuleStatus::Done).count()
+    }
+
+    /// 学员是否停滞：还有模块没完成，且超过 stalled_days 天没有学习（或从未学习）
+    pub fn is_stalled(&self, learner: &LearnerSummary) -> bool {
+        let finished = !self.modules.is_empty() && learner.completed() >= self.modules.len();
+        !finished && learner.idle_days(self.today).map_or(true, |days| days >= self.stalled_days)
+    }
+
+    pub fn stalled(&self) -> Vec<&LearnerSummary> {
+        self.learners.iter().filter(|l| self.is_stalled(l)).collect()
+    }
+
+    /// 每位学员一行的 CSV：基本信息、每个模块的进度、平均得分和是否停滞
+    pub fn to_csv(&self) -> String {
+        let mut header: Vec<String> = ["学员", "密钥指纹", "导出时间", "最后学习", "未学习天数", "完成模块"]
+            .iter().map(|s| s.to_string()).collect();
+        header.extend(self.modules.iter().map(|(_, name)| name.clone()));
+        header.extend(["平均练习得分", "薄弱知识点", "停滞"].iter().map(|s| s.to_string()));
+
+        let mut lines = vec![header];
+        for learner in &self.learners {
+            let mut weak: Vec<(&String, &(u32, u32))> = learner.topics.iter().filter(|(_, (misses, _))| *misses > 0).collect();
+            weak.sort_by_key(|(_, (misses, _))| std::cmp::Reverse(*misses));
+
+            let mut line = vec![
+                learner.learner.name.clone(),
+                learner.learner.fingerprint().to_string(),
+                learner.exported_at.map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default(),
+                learner.last_active.map(|d| d.to_string()).unwrap_or_default(),
+                learner.idle_days(self.today).map(|d| d.to_string()).unwrap_or_default(),
+                format!("{}/{}", learner.completed(), self.modules.len()),
+            ];
+            line.extend(self.modules.iter().map(|(id, _)| learner.status(id).csv_label().to_string()));
+            line.push(learner.average_score.map(|s| format!("{:.1}", s)).unwrap_or_default());
+            line.push(weak.iter().take(3).map(|(topic, _)| topic.as_str()).collect::<Vec<_>>().join("、"));
+            line.push(if self.is_stalled(learner) { "是" } else { "否" }.to_string());
+            lines.push(line);
+        }
+
+        lines.iter()
+            .map(|fields| fields.iter()
+                .map(|field| csv_field(&serde_json::Value::String(field.clone())))
+                .collect::<Vec<_>>()
+                .join(","))
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// 名册的文本行（命令行和 TUI 共用）
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        lines.push(format!("📁 数据包目录：{}（{} 位学员）", self.dir.display(), self.learners.len()));
+        for rejected in &self.rejected {
+            lines.push(format!("❌ 已拒绝 {}：{}", rejected.file, rejected.reason));
+        }
+        for warning in &self.warnings {
+            lines.push(format!("⚠️  {}", warning));
+        }
+
+        lines.push(String::new());
+        lines.push("👥 学员".to_string());
+        if self.learners.is_empty() {
+            lines.push("   还没有数据包，让学员运行 class bundle 把数据包放到这个目录".to_string());
+        }
+        for learner in &self.learners {
+            let score = learner.average_score.map_or_else(|| "-".to_string(), |s| format!("{:.0}%", s));
+            let active = match learner.idle_days(self.today) {
+                Some(0) => "今天学习过".to_string(),
+                Some(days) => format!("{} 天前学习", days),
+                None => "还没有学习记录".to_string(),
+            };
+            lines.push(format!("   {} [{}]  完成 {}/{}  平均分 {}  {}",
+                learner.learner.name, learner.learner.fingerprint(),
+                learner.completed(), self.modules.len(), score, active));
+            let icons: String = self.modules.iter().map(|(id, _)| learner.status(id).icon()).collect();
+            lines.push(format!("      {}", icons));
+        }
+
+        lines.push(String::new());
+        lines.push("📚 模块完成情况".to_string());
+        for (id, name) in &self.modules {
+            let done = self.completion(id);
+            let started = self.learners.iter().filter(|l| l.status(id) == ModuleStatus::Started).count();
+            lines.push(format!("   {}  完成 {}/{} 人，学习中 {} 人", name, done, self.learners.len(), started));
+        }
+
+        lines.push(String::new());
+        lines.push("🧩 全班薄弱知识点".to_string());
+        if self.weak_topics.is_empty() {
+            lines.push("   暂无答错记录".to_string());
+        }
+        for topic in &self.weak_topics {
+            lines.push(format!("   • {}：{} 人答错过，共答错 {}/{} 次",
+                topic.topic, topic.learners, topic.misses, topic.attempts));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("💤 停滞的学员（{} 天以上没有学习）", self.stalled_days));
+        let stalled = self.stalled();
+        if stalled.is_empty() {
+            lines.push("   没有".to_string());
+        }
+        for learner in stalled {
+            let since = learner.last_active
+                .map_or_else(|| "还没有学习记录".to_string(), |d| format!("最后学习 {}", d));
+            lines.push(format!("   • {} [{}]：{}", learner.learner.name, learner.learner.fingerprint(), since));
+        }
+        lines
+    }
+
+    /// 写出 CSV，返回写入的路径（默认写到数据包目录）
+    pub fn write_csv(&self, output: Option<&Path>) -> Result<PathBuf> {
+        let path = output.map(Path::to_path_buf)
+            .unwrap_or_else(|| self.dir.join(format!("roster-{}.csv", self.today.format("%Y%m%d"))));
+        fs::write(&path, self.to_csv()).with_context(|| format!("无法写入 {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// 数据包的默认文件名：名字中的路径分隔符等换成 -
+fn bundle_file_name(name: &str) -> String {
+    let safe: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '-' })
+        .collect();
+    format!("{}-{}.json", safe, Local::now().format("%Y%m%d"))
+}
+
+/// 设置学员身份（命令行）：第一次生成密钥，之后只改名字
+pub fn run_join(store: &Store, name: &str) -> Result<()> {
+    let data_dir = store.data_dir()?;
+    let identity = match Identity::load(data_dir)? {
+        Some(mut identity) => {
+            identity.name = checked_name(name)?;
+            println!("✅ 已改名为 {}（密钥不变）", identity.name);
+            identity
+        }
+        None => {
+            let identity = Identity::generate(name)?;
+            println!("✅ 已加入课堂：{}", identity.name);
+            identity
+        }
+    };
+    identity.save(data_dir)?;
+    println!("🔑 密钥指纹：{}（讲师靠它区分同名学员）", &identity.public_key()?[..8]);
+    println!("💡 运行 class bundle -o <共享目录> 提交学习数据包");
+    Ok(())
+}
+
+/// 生成签名的学习数据包（命令行）；output 是目录时写到目录中
+pub fn run_bundle(store: &Store, repo: &LearningRepo, output: Option<&Path>) -> Result<()> {
+    let identity = Identity::load(store.data_dir()?)?
+        .context("还没有学员身份，请先运行 class join <名字>")?;
+
+    // 先把 进度.md 同步进数据库，数据包里的任务状态才是最新的
+    // 有冲突时数据包中以数据库记录为准，提示学员处理
+    let plan = crate::sync::sync(store, repo, None)?;
+    crate::sync::print_plan(&plan);
+    let bundle = Bundle::create(&identity, Export::collect(store)?)?;
+
+    let file_name = bundle_file_name(&identity.name);
+    let path = match output {
+        Some(path) if path.is_dir() => path.join(file_name),
+        Some(path) => path.to_path_buf(),
+        None => PathBuf::from(file_name),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&bundle)?)
+        .with_context(|| format!("无法写入 {}", path.display()))?;
+    println!("✅ 学习数据包已生成：{}", path.display());
+    println!("   学员 {} [{}]，共 {} 条记录", identity.name, bundle.learner.fingerprint(), bundle.export.row_count());
+    Ok(())
+}
+
+/// 合并数据包并显示班级名册（命令行）；dir 为空时使用上次的目录
+pub fn run_roster(store: &Store, repo: &LearningRepo, dir: Option<&Path>, csv: Option<&Path>, stalled_days: i64) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => dir.to_path_buf(),
+        None => store.get_setting(CLASSROOM_DIR_KEY)?
+            .map(PathBuf::from)
+            .context("请指定数据包目录：class roster <目录>")?,
+    };
+    let tz = TimeZone::load(store)?;
+    let roster = Roster::load(repo, &dir, tz, tz.today(&SystemClock), stalled_days)?;
+    store.save_setting(CLASSROOM_DIR_KEY, &dir.to_string_lossy())?;
+
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        👥 Rust 学习伴侣 - 班级名册      ║");
+    println!("╚════════════════════════════════════════╝\n");
+    for line in roster.lines() {
+        println!("{}", line);
+    }
+
+    if let Some(csv) = csv {
+        println!("\n✅ 名册已导出到：{}", roster.write_csv(Some(csv))?.display());
+    } else {
+        println!("\n💡 加 --csv <文件> 导出表格，TUI 主菜单「班级名册」可以查看同一份名册");
+    }
+    Ok(())
+}
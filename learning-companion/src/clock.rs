@@ -0,0 +1,115 @@
+//! 时钟和时区
+//!
+//! 学习天数按设置的时区划分日期：默认使用系统时区，出差或旅行时可以固定为家里的 UTC 偏移，
+//! 这样在外地学习的记录仍然算在家里的日期上
+
+use crate::db::Store;
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use std::str::FromStr;
+
+/// 时区的设置项
+pub const TIMEZONE_KEY: &str = "timezone";
+
+/// 时钟
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// 系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// 固定的时间（按指定时间生成报告、测试时使用）
+impl Clock for DateTime<Local> {
+    fn now(&self) -> DateTime<Local> {
+        *self
+    }
+}
+
+/// 划分日期使用的时区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeZone {
+    /// 系统时区
+    #[default]
+    Local,
+    /// 固定的 UTC 偏移
+    Fixed(FixedOffset),
+}
+
+impl FromStr for TimeZone {
+    type Err = anyhow::Error;
+
+    /// 解析 `local`、`UTC`、`+08:00`、`-0530`、`UTC+8` 等写法
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(TimeZone::Local);
+        }
+        let offset = s.strip_prefix("UTC").or_else(|| s.strip_prefix("utc")).unwrap_or(s);
+        if offset.is_empty() || offset.eq_ignore_ascii_case("z") {
+            return Ok(TimeZone::Fixed(FixedOffset::east_opt(0).expect("UTC 偏移有效")));
+        }
+
+        let invalid = || anyhow::anyhow!("无法识别的时区：{}（例如 local、UTC、+08:00）", s);
+        let (sign, rest) = match offset.as_bytes()[0] {
+            b'+' => (1, &offset[1..]),
+            b'-' => (-1, &offset[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() == 4 => rest.split_at(2),
+            None => (rest, "0"),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(TimeZone::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl TimeZone {
+    /// 读取设置，没有设置时为系统时区
+    pub fn load(store: &Store) -> Result<Self> {
+        match store.get_setting(TIMEZONE_KEY)? {
+            Some(value) => value.parse(),
+            None => Ok(TimeZone::Local),
+        }
+    }
+
+    pub fn save(&self, store: &Store) -> Result<()> {
+        store.save_setting(TIMEZONE_KEY, &self.describe())?;
+        Ok(())
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            TimeZone::Local => "local".to_string(),
+            TimeZone::Fixed(offset) if offset.local_minus_utc() == 0 => "UTC".to_string(),
+            TimeZone::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    /// 某个时刻在该时区的日期
+    pub fn date_of(&self, time: DateTime<FixedOffset>) -> NaiveDate {
+        match self {
+            TimeZone::Local => time.with_timezone(&Local).date_naive(),
+            TimeZone::Fixed(offset) => time.with_timezone(offset).date_naive(),
+        }
+    }
+
+    /// 该时区的今天
+    pub fn today(&self, clock: &impl Clock) -> NaiveDate {
+        self.date_of(clock.now().fixed_offset())
+    }
+}
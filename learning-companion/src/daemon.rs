@@ -0,0 +1,541 @@
+//! 提醒守护进程模块
+//!
+//! 后台常驻进程按 settings 中的每周提醒计划发送学习提醒：安静日不提醒，
+//! 当天已经学习过（有学习记录或正在计时）也不提醒。
+//! 时钟和通知方式可替换，便于在没有桌面通知服务的环境下测试。
+
+use crate::clock::{Clock, SystemClock};
+use crate::db::{DbResult, Store};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 提醒计划的设置项
+pub const SCHEDULES_KEY: &str = "reminder_schedules";
+/// 安静日的设置项
+pub const QUIET_DAYS_KEY: &str = "reminder_quiet_days";
+/// 旧版单个提醒时间的设置项（HH:MM，每天）
+const LEGACY_TIME_KEY: &str = "remind_time";
+
+/// 守护进程检查间隔
+pub const POLL_INTERVAL_SECS: u64 = 30;
+
+/// 启动后等待守护进程创建 PID 文件的最长时间
+const START_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 星期的英文缩写和全称
+const WEEKDAY_NAMES: [(&str, &str, Weekday); 7] = [
+    ("mon", "monday", Weekday::Mon),
+    ("tue", "tuesday", Weekday::Tue),
+    ("wed", "wednesday", Weekday::Wed),
+    ("thu", "thursday", Weekday::Thu),
+    ("fri", "friday", Weekday::Fri),
+    ("sat", "saturday", Weekday::Sat),
+    ("sun", "sunday", Weekday::Sun),
+];
+
+/// 解析星期名（mon、tue…，也接受 monday 等全称）；只接受完整的缩写或全称，`month` 之类的拼写错误不会被当成星期
+pub fn parse_weekday(s: &str) -> Option<Weekday> {
+    let s = s.trim().to_lowercase();
+    WEEKDAY_NAMES.iter()
+        .find(|(short, long, _)| s == *short || s == *long)
+        .map(|(_, _, day)| *day)
+}
+
+/// 星期的英文缩写（mon…sun）
+pub fn weekday_name(day: Weekday) -> &'static str {
+    WEEKDAY_NAMES.iter().find(|(_, _, d)| *d == day).map_or("?", |(name, _, _)| name)
+}
+
+/// 解析星期列表：`mon,wed,fri`、`weekdays`（周一到周五）、`weekends`、`daily`
+pub fn parse_days(s: &str) -> Result<Vec<Weekday>> {
+    let mut days = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let parsed: Vec<Weekday> = match part.to_lowercase().as_str() {
+            "daily" | "everyday" | "all" => WEEKDAY_NAMES.iter().map(|(_, _, d)| *d).collect(),
+            "weekdays" => WEEKDAY_NAMES[..5].iter().map(|(_, _, d)| *d).collect(),
+            "weekends" => WEEKDAY_NAMES[5..].iter().map(|(_, _, d)| *d).collect(),
+            _ => vec![parse_weekday(part).ok_or_else(|| anyhow::anyhow!("无法识别的星期：{}", part))?],
+        };
+        for day in parsed {
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+    }
+    if days.is_empty() {
+        anyhow::bail!("至少需要指定一天");
+    }
+    days.sort_by_key(|d| d.num_days_from_monday());
+    Ok(days)
+}
+
+/// 每周提醒计划：在 days 中的每一天 hour:minute 提醒
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawSchedule", into = "RawSchedule")]
+pub struct ReminderSchedule {
+    pub days: Vec<Weekday>,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawSchedule {
+    days: Vec<String>,
+    time: String,
+}
+
+impl TryFrom<RawSchedule> for ReminderSchedule {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawSchedule) -> Result<Self> {
+        let (hour, minute) = parse_time(&raw.time)?;
+        Ok(ReminderSchedule { days: parse_days(&raw.days.join(","))?, hour, minute })
+    }
+}
+
+impl From<ReminderSchedule> for RawSchedule {
+    fn from(schedule: ReminderSchedule) -> Self {
+        RawSchedule {
+            days: schedule.days.iter().map(|d| weekday_name(*d).to_string()).collect(),
+            time: format!("{:02}:{:02}", schedule.hour, schedule.minute),
+        }
+    }
+}
+
+/// 解析 HH:MM
+pub fn parse_time(s: &str) -> Result<(u8, u8)> {
+    let (h, m) = s.trim().split_once(':').ok_or_else(|| anyhow::anyhow!("时间格式应为 HH:MM：{}", s))?;
+    let hour: u8 = h.parse().with_context(|| format!("无效的小时：{}", h))?;
+    let minute: u8 = m.parse().with_context(|| format!("无效的分钟：{}", m))?;
+    if hour > 23 || minute > 59 {
+        anyhow::bail!("时间超出范围：{}", s);
+    }
+    Ok((hour, minute))
+}
+
+impl ReminderSchedule {
+    pub fn new(days: Vec<Weekday>, hour: u8, minute: u8) -> Result<Self> {
+        if hour > 23 || minute > 59 {
+            anyhow::bail!("时间超出范围：{:02}:{:02}", hour, minute);
+        }
+        Ok(ReminderSchedule { days, hour, minute })
+    }
+
+    /// 某天的提醒时间，当天不在计划中时为 None
+    pub fn time_on(&self, date: NaiveDate) -> Option<DateTime<Local>> {
+        if !self.days.contains(&date.weekday()) {
+            return None;
+        }
+        let time = NaiveTime::from_hms_opt(self.hour as u32, self.minute as u32, 0)?;
+        // 夏令时跳过的时刻没有对应的本地时间，这一天不提醒
+        Local.from_local_datetime(&date.and_time(time)).earliest()
+    }
+
+    /// 如「mon,wed,fri 20:00」或「每天 20:00」
+    pub fn describe(&self) -> String {
+        let days = if self.days.len() == 7 {
+            "每天".to_string()
+        } else {
+            self.days.iter().map(|d| weekday_name(*d)).collect::<Vec<_>>().join(",")
+        };
+        format!("{} {:02}:{:02}", days, self.hour, self.minute)
+    }
+}
+
+/// 安静日：每周固定的某天，或某个日期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuietDay {
+    Weekday(Weekday),
+    Date(NaiveDate),
+}
+
+impl QuietDay {
+    /// 解析 `sun` 或 `2024-12-25`
+    pub fn parse(s: &str) -> Result<Self> {
+        if let Ok(date) = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d") {
+            return Ok(QuietDay::Date(date));
+        }
+        parse_weekday(s)
+            .map(QuietDay::Weekday)
+            .ok_or_else(|| anyhow::anyhow!("安静日应为星期（如 sun）或日期（如 2024-12-25）：{}", s))
+    }
+
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        match self {
+            QuietDay::Weekday(day) => date.weekday() == *day,
+            QuietDay::Date(d) => *d == date,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            QuietDay::Weekday(day) => weekday_name(*day).to_string(),
+            QuietDay::Date(date) => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// 提醒配置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReminderConfig {
+    pub schedules: Vec<ReminderSchedule>,
+    pub quiet_days: Vec<QuietDay>,
+}
+
+impl ReminderConfig {
+    /// 从 settings 读取；只有旧版 remind_time 时视为每天一次
+    pub fn load(store: &Store) -> Result<Self> {
+        let schedules = match store.get_setting(SCHEDULES_KEY)? {
+            Some(json) => serde_json::from_str(&json).context("提醒计划格式错误")?,
+            None => match store.get_setting(LEGACY_TIME_KEY)? {
+                Some(time) => {
+                    let (hour, minute) = parse_time(&time)?;
+                    vec![ReminderSchedule::new(parse_days("daily")?, hour, minute)?]
+                }
+                None => Vec::new(),
+            },
+        };
+
+        let quiet_days = match store.get_setting(QUIET_DAYS_KEY)? {
+            Some(json) => serde_json::from_str::<Vec<String>>(&json)
+                .context("安静日格式错误")?
+                .iter()
+                .map(|s| QuietDay::parse(s))
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(ReminderConfig { schedules, quiet_days })
+    }
+
+    /// 保存到 settings
+    pub fn save(&self, store: &Store) -> Result<()> {
+        store.save_setting(SCHEDULES_KEY, &serde_json::to_string(&self.schedules)?)?;
+        let quiet: Vec<String> = self.quiet_days.iter().map(|q| q.describe()).collect();
+        store.save_setting(QUIET_DAYS_KEY, &serde_json::to_string(&quiet)?)?;
+        Ok(())
+    }
+
+    /// 添加提醒计划（完全相同的计划不重复添加）
+    pub fn add_schedule(&mut self, schedule: ReminderSchedule) {
+        if !self.schedules.contains(&schedule) {
+            self.schedules.push(schedule);
+        }
+    }
+
+    pub fn add_quiet_day(&mut self, day: QuietDay) {
+        if !self.quiet_days.contains(&day) {
+            self.quiet_days.push(day);
+        }
+    }
+
+    pub fn is_quiet(&self, date: NaiveDate) -> bool {
+        self.quiet_days.iter().any(|q| q.matches(date))
+    }
+
+    /// (after, until] 之间到点的提醒时间
+    pub fn due_between(&self, after: DateTime<Local>, until: DateTime<Local>) -> Vec<DateTime<Local>> {
+        let mut due = Vec::new();
+        let mut date = after.date_naive();
+        while date <= until.date_naive() {
+            for schedule in &self.schedules {
+                if let Some(time) = schedule.time_on(date) {
+                    if time > after && time <= until && !due.contains(&time) {
+                        due.push(time);
+                    }
+                }
+            }
+            date += Duration::days(1);
+        }
+        due.sort();
+        due
+    }
+
+    /// now 之后下一次会提醒的时间（跳过安静日），一周内没有则为 None
+    pub fn next_reminder(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.due_between(now, now + Duration::days(8))
+            .into_iter()
+            .find(|t| !self.is_quiet(t.date_naive()))
+    }
+}
+
+/// 提醒的发送方式
+pub trait Notifier {
+    fn notify(&mut self, title: &str, body: &str) -> Result<()>;
+}
+
+/// 桌面通知
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify(&mut self, title: &str, body: &str) -> Result<()> {
+        notify_rust::Notification::new()
+            .summary(title)
+            .body(body)
+            .show()?;
+        Ok(())
+    }
+}
+
+/// 一次检查的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickOutcome {
+    /// 没有到点的提醒
+    Idle,
+    /// 已发送提醒
+    Sent(DateTime<Local>),
+    /// 安静日，跳过
+    SkippedQuiet(DateTime<Local>),
+    /// 当天已经学习过，跳过
+    SkippedStudied(DateTime<Local>),
+}
+
+/// 提醒调度器：记录上次检查的时间，每次检查发送期间到点的提醒
+pub struct Reminder<C: Clock, N: Notifier> {
+    clock: C,
+    notifier: N,
+    last_check: DateTime<Local>,
+}
+
+impl<C: Clock, N: Notifier> Reminder<C, N> {
+    /// 从当前时间开始计时，启动前已过的提醒不补发
+    pub fn new(clock: C, notifier: N) -> Self {
+        let last_check = clock.now();
+        Reminder { clock, notifier, last_check }
+    }
+
+    pub fn notifier(&self) -> &N {
+        &self.notifier
+    }
+
+    /// 检查一次；studied 判断某天是否已经学习过，判断失败时不发送提醒并返回错误
+    pub fn tick(&mut self, config: &ReminderConfig, studied: impl Fn(NaiveDate) -> Result<bool>) -> Result<TickOutcome> {
+        let now = self.clock.now();
+        let due = config.due_between(self.last_check, now);
+        self.last_check = now;
+
+        // 一次检查内多个提醒到点（如休眠后唤醒）只提醒一次
+        let Some(&time) = due.last() else {
+            return Ok(TickOutcome::Idle);
+        };
+        let date = time.date_naive();
+        if config.is_quiet(date) {
+            return Ok(TickOutcome::SkippedQuiet(time));
+        }
+        if studied(date)? {
+            return Ok(TickOutcome::SkippedStudied(time));
+        }
+
+        self.notifier.notify("Rust 学习伴侣", &crate::notify::reminder_message(time.timestamp() as u64))?;
+        Ok(TickOutcome::Sent(time))
+    }
+}
+
+/// 某天是否已经学习过：有学习记录，或当天有正在进行的计时
+pub fn studied_on(store: &Store, date: NaiveDate) -> DbResult<bool> {
+    Ok(store.has_study_session_on(date)?
+        || store.get_active_session()?.is_some_and(|s| s.last_seen.date_naive() == date))
+}
+
+/// PID 文件路径
+pub fn pid_file(store: &Store) -> Result<PathBuf> {
+    Ok(store.data_dir()?.join("daemon.pid"))
+}
+
+/// 守护进程的日志文件（标准输出和错误输出）
+pub fn log_file(store: &Store) -> Result<PathBuf> {
+    Ok(store.data_dir()?.join("daemon.log"))
+}
+
+/// 读取 PID 文件中的进程号
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// 进程是否仍在运行
+fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    }
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+    }
+}
+
+/// 正在运行的守护进程号（PID 文件存在且进程存活）
+pub fn running_pid(store: &Store) -> Option<u32> {
+    pid_file(store).ok()
+        .and_then(|path| read_pid(&path))
+        .filter(|pid| process_alive(*pid))
+}
+
+/// 创建 PID 文件作为锁；已有存活的守护进程时报错，残留的 PID 文件会被替换
+fn acquire_lock(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    for _ in 0..2 {
+        match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                write!(file, "{}", std::process::id())?;
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if let Some(pid) = read_pid(path).filter(|pid| process_alive(*pid)) {
+                    anyhow::bail!("守护进程已在运行（PID {}）", pid);
+                }
+                fs::remove_file(path)?;
+            }
+            Err(e) => return Err(e).with_context(|| format!("无法创建 {}", path.display())),
+        }
+    }
+    anyhow::bail!("无法创建 {}", path.display())
+}
+
+/// 在前台运行守护进程，直到 PID 文件被删除或不再属于本进程
+pub fn run_foreground(store: &Store) -> Result<()> {
+    let path = pid_file(store)?;
+    acquire_lock(&path)?;
+
+    let mut reminder = Reminder::new(SystemClock, DesktopNotifier);
+    loop {
+        std::thread::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS));
+
+        if read_pid(&path) != Some(std::process::id()) {
+            return Ok(());
+        }
+
+        // 每次检查都重新读取配置，修改提醒计划后不必重启
+        let result = ReminderConfig::load(store).and_then(|config| reminder.tick(&config, |date| Ok(studied_on(store, date)?)));
+        if let Err(e) = result {
+            eprintln!("提醒失败：{:#}", e);
+        }
+    }
+}
+
+/// 启动后台守护进程，等它创建 PID 文件后返回进程号
+pub fn start(store: &Store) -> Result<u32> {
+    if let Some(pid) = running_pid(store) {
+        anyhow::bail!("守护进程已在运行（PID {}）", pid);
+    }
+
+    // 守护进程使用同一个数据目录（学习档案）
+    let data_dir = store.data_dir()?;
+    let log_path = log_file(store)?;
+    let log = fs::OpenOptions::new().create(true).append(true).open(&log_path)
+        .with_context(|| format!("无法打开 {}", log_path.display()))?;
+
+    let exe = std::env::current_exe()?;
+    let mut child = std::process::Command::new(exe)
+        .arg("--data-dir")
+        .arg(data_dir)
+        .args(["daemon", "run"])
+        .stdin(std::process::Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()
+        .context("无法启动守护进程")?;
+
+    let pid = child.id();
+    let deadline = std::time::Instant::now() + START_TIMEOUT;
+    while running_pid(store) != Some(pid) {
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!("守护进程启动失败（{}），详见 {}", status, log_path.display());
+        }
+        if std::time::Instant::now() > deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("守护进程 {} 秒内未就绪，详见 {}", START_TIMEOUT.as_secs(), log_path.display());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    Ok(pid)
+}
+
+/// 停止守护进程，返回被停止的进程号
+pub fn stop(store: &Store) -> Result<Option<u32>> {
+    let path = pid_file(store)?;
+    let Some(pid) = read_pid(&path) else {
+        return Ok(None);
+    };
+    // 先删除 PID 文件，守护进程下次检查时会自行退出
+    fs::remove_file(&path)?;
+    if !process_alive(pid) {
+        return Ok(None);
+    }
+
+    #[cfg(unix)]
+    let status = std::process::Command::new("kill").arg(pid.to_string()).status();
+    #[cfg(windows)]
+    let status = std::process::Command::new("taskkill").args(["/PID", &pid.to_string(), "/F"]).status();
+    status.context("无法停止守护进程")?;
+
+    Ok(Some(pid))
+}
+
+/// 守护进程状态（命令行）
+pub fn run_status(store: &Store) -> Result<()> {
+    match running_pid(store) {
+        Some(pid) => println!("🟢 提醒守护进程运行中（PID {}）", pid),
+        None => println!("⚪ 提醒守护进程未运行（启动：learning-companion daemon start）"),
+    }
+
+    print_config(&ReminderConfig::load(store)?);
+    Ok(())
+}
+
+/// 修改提醒计划（命令行）：time 为 None 时只修改安静日或查看
+pub fn run_remind(store: &Store, time: Option<(u8, u8)>, days: &str, quiet: &[String], clear: bool) -> Result<()> {
+    let mut config = if clear { ReminderConfig::default() } else { ReminderConfig::load(store)? };
+
+    if let Some((hour, minute)) = time {
+        let schedule = ReminderSchedule::new(parse_days(days)?, hour, minute)?;
+        println!("⏰ 已添加提醒：{}", schedule.describe());
+        config.add_schedule(schedule);
+    }
+    for day in quiet {
+        config.add_quiet_day(QuietDay::parse(day)?);
+    }
+    if clear || time.is_some() || !quiet.is_empty() {
+        config.save(store)?;
+    }
+
+    print_config(&config);
+    if !config.schedules.is_empty() && running_pid(store).is_none() {
+        println!("💡 提醒由守护进程发送，启动：learning-companion daemon start");
+    }
+    Ok(())
+}
+
+/// 打印提醒配置
+pub fn print_config(config: &ReminderConfig) {
+    if config.schedules.is_empty() {
+        println!("⏰ 还没有提醒计划（设置：learning-companion remind -H 20 -M 0）");
+        return;
+    }
+
+    println!("⏰ 提醒计划：");
+    for schedule in &config.schedules {
+        println!("   • {}", schedule.describe());
+    }
+    if !config.quiet_days.is_empty() {
+        let quiet: Vec<String> = config.quiet_days.iter().map(|q| q.describe()).collect();
+        println!("🔕 安静日：{}", quiet.join("、"));
+    }
+    if let Some(next) = config.next_reminder(Local::now()) {
+        println!("⏭️  下次提醒：{}", next.format("%m-%d %H:%M"));
+    }
+}
@@ -1,279 +1,1190 @@
 //! 数据库模块
 //!
-//! 使用 SQLite 存储学习记录和统计数据
+//! 使用 SQLite 存储学习记录和统计数据；`Store` 持有唯一的数据库连接
 
 use rusqlite::{Connection, OptionalExtension};
 use chrono::{Local, NaiveDate};
-use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// 数据库文件路径
-fn db_path() -> PathBuf {
+/// 数据库层的错误
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("数据库操作失败：{0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("无法访问数据目录 {}：{source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("数据库结构迁移失败：{0:#}")]
+    Migration(anyhow::Error),
+    /// 某行数据无法解析（例如手动修改过数据库或写入时中断）
+    #[error("数据表 {table} 第 {rowid} 行的 {column} 无法解析：{value:?}（运行 `learning-companion db doctor` 检查并修复）")]
+    Corrupt { table: &'static str, column: &'static str, rowid: i64, value: String },
+    #[error("内存数据库没有数据目录")]
+    InMemory,
+    #[error("未知的数据表：{0}")]
+    UnknownTable(String),
+    #[error("学习档案名称只能包含字母、数字、- 和 _：{0}")]
+    InvalidProfile(String),
+}
+
+pub type DbResult<T> = std::result::Result<T, DbError>;
+
+impl DbError {
+    /// 是否为数据损坏（可以用 `db doctor` 修复）
+    pub fn is_corrupt(&self) -> bool {
+        match self {
+            DbError::Corrupt { .. } => true,
+            DbError::Sqlite(e) => is_corrupt_sqlite(e),
+            DbError::Migration(e) => e.chain()
+                .filter_map(|c| c.downcast_ref::<rusqlite::Error>())
+                .any(is_corrupt_sqlite),
+            _ => false,
+        }
+    }
+}
+
+/// 数据类型不符或数据库文件损坏
+fn is_corrupt_sqlite(e: &rusqlite::Error) -> bool {
+    matches!(e, rusqlite::Error::FromSqlConversionFailure(..) | rusqlite::Error::InvalidColumnType(..))
+        || matches!(
+            e.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+        )
+}
+
+/// 解析日期列（`%Y-%m-%d`）
+fn parse_date(table: &'static str, column: &'static str, rowid: i64, value: &str) -> DbResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| DbError::Corrupt { table, column, rowid, value: value.to_string() })
+}
+
+/// 解析时间列（RFC 3339）
+fn parse_time(table: &'static str, column: &'static str, rowid: i64, value: &str) -> DbResult<chrono::DateTime<Local>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Local))
+        .map_err(|_| DbError::Corrupt { table, column, rowid, value: value.to_string() })
+}
+
+/// 指定数据目录的环境变量
+pub const DATA_DIR_ENV: &str = "LEARNING_COMPANION_DATA_DIR";
+/// 指定学习档案的环境变量
+pub const PROFILE_ENV: &str = "LEARNING_COMPANION_PROFILE";
+
+/// 默认数据目录 `~/.learning-companion`
+pub fn default_data_dir() -> PathBuf {
     let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
     path.push(".learning-companion");
-    path.push("data.db");
     path
 }
 
-/// 初始化数据库
-pub fn init_db() -> Result<()> {
-    let db_path = db_path();
-
-    // 创建目录（如果不存在）
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let conn = Connection::open(&db_path)?;
-
-    // 创建表
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS study_sessions (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            date TEXT NOT NULL,
-            duration_minutes INTEGER NOT NULL,
-            modules_studied TEXT NOT NULL,
-            practice_count INTEGER NOT NULL DEFAULT 0,
-            notes TEXT
-        )",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS module_progress (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            module_id TEXT NOT NULL UNIQUE,
-            started_at TEXT,
-            completed_at TEXT,
-            mastery_score REAL DEFAULT 0.0,
-            last_updated TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS practice_results (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            module_id TEXT NOT NULL,
-            timestamp TEXT NOT NULL,
-            questions_total INTEGER NOT NULL,
-            questions_correct INTEGER NOT NULL,
-            score REAL NOT NULL,
-            weak_topics TEXT
-        )",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS achievements (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            achievement_type TEXT NOT NULL UNIQUE,
-            unlocked_at TEXT NOT NULL,
-            metadata TEXT
-        )",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    Ok(())
-}
-
-/// 记录学习会话
-pub fn record_study_session(
-    duration_minutes: u32,
-    modules_studied: Vec<String>,
-    practice_count: u32,
-    notes: Option<String>,
-) -> Result<()> {
-    let conn = Connection::open(db_path())?;
-
-    let date = Local::now().format("%Y-%m-%d").to_string();
-    let modules = modules_studied.join(",");
-
-    conn.execute(
-        "INSERT INTO study_sessions (date, duration_minutes, modules_studied, practice_count, notes)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        [date, duration_minutes.to_string(), modules, practice_count.to_string(), notes.unwrap_or_default()],
-    )?;
-
-    Ok(())
-}
-
-/// 更新模块进度
-pub fn update_module_progress(
-    module_id: &str,
-    mastery_score: f32,
-) -> Result<()> {
-    let conn = Connection::open(db_path())?;
-    let now = Local::now().to_rfc3339();
-
-    conn.execute(
-        "INSERT INTO module_progress (module_id, mastery_score, last_updated)
-         VALUES (?1, ?2, ?3)
-         ON CONFLICT(module_id) DO UPDATE SET
-         mastery_score = ?2,
-         last_updated = ?3",
-        [module_id, &mastery_score.to_string(), &now],
-    )?;
-
-    Ok(())
-}
-
-/// 记录练习结果
-pub fn record_practice_result(
-    module_id: &str,
-    questions_total: u32,
-    questions_correct: u32,
-    score: f32,
-    weak_topics: Vec<String>,
-) -> Result<()> {
-    let conn = Connection::open(db_path())?;
-    let timestamp = Local::now().to_rfc3339();
-    let topics = weak_topics.join(",");
-
-    conn.execute(
-        "INSERT INTO practice_results (module_id, timestamp, questions_total, questions_correct, score, weak_topics)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        [
-            module_id,
-            &timestamp,
-            &questions_total.to_string(),
-            &questions_correct.to_string(),
-            &score.to_string(),
-            &topics,
-        ],
-    )?;
-
-    Ok(())
-}
-
-/// 检查并解锁成就
-pub fn check_and_unlock_achievement(achievement_type: &str) -> Result<bool> {
-    let conn = Connection::open(db_path())?;
-
-    // 检查是否已解锁
-    let mut stmt = conn.prepare(
-        "SELECT COUNT(*) FROM achievements WHERE achievement_type = ?1"
-    )?;
-
-    let count: i64 = stmt.query_row([achievement_type], |row| row.get(0))?;
-
-    if count > 0 {
-        return Ok(false); // 已解锁
-    }
-
-    // 解锁成就
-    let now = Local::now().to_rfc3339();
-    conn.execute(
-        "INSERT INTO achievements (achievement_type, unlocked_at) VALUES (?1, ?2)",
-        [achievement_type, &now],
-    )?;
-
-    Ok(true)
-}
-
-/// 获取连续学习天数
-pub fn get_streak_days() -> Result<u32> {
-    let conn = Connection::open(db_path())?;
-
-    let mut stmt = conn.prepare(
-        "SELECT DISTINCT date FROM study_sessions ORDER BY date DESC"
-    )?;
-
-    let dates: Result<Vec<String>, _> = stmt
-        .query_map([], |row| row.get(0))
-        .unwrap()
-        .collect();
-
-    let dates = dates?;
-    if dates.is_empty() {
-        return Ok(0);
-    }
-
-    let mut streak = 1u32;
-    let mut prev_date = NaiveDate::parse_from_str(&dates[0], "%Y-%m-%d").unwrap();
-
-    for date_str in dates.iter().skip(1) {
-        let current_date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").unwrap();
-        let days_diff = (prev_date - current_date).num_days();
-
-        if days_diff == 1 {
-            streak += 1;
-            prev_date = current_date;
-        } else if days_diff > 1 {
-            break;
+/// 确定数据目录：`--data-dir` > 环境变量 > 学习档案 > 默认目录
+///
+/// 学习档案（`--profile` 或环境变量）放在默认目录的 `profiles/<名称>` 下，
+/// 同一台电脑上的多个学习者各用一个档案
+pub fn resolve_data_dir(data_dir: Option<&Path>, profile: Option<&str>) -> DbResult<PathBuf> {
+    if let Some(dir) = data_dir {
+        return Ok(dir.to_path_buf());
+    }
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+
+    let profile = profile.map(str::to_string)
+        .or_else(|| std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty()));
+    match profile {
+        Some(name) => profile_dir(&name),
+        None => Ok(default_data_dir()),
+    }
+}
+
+/// 学习档案的数据目录；名称只能包含字母、数字、`-` 和 `_`
+pub fn profile_dir(name: &str) -> DbResult<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err(DbError::InvalidProfile(name.to_string()));
+    }
+    Ok(default_data_dir().join("profiles").join(name))
+}
+
+/// 已有的学习档案名称
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = std::fs::read_dir(default_data_dir().join("profiles"))
+        .map(|entries| entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().join(DB_FILE).exists())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect())
+        .unwrap_or_default();
+    profiles.sort();
+    profiles
+}
+
+/// 数据库文件名
+pub const DB_FILE: &str = "data.db";
+
+/// 学习数据存储：持有一个数据库连接，由命令行和 TUI 创建后传给各模块
+pub struct Store {
+    conn: Connection,
+    /// 数据目录，内存数据库为 None
+    data_dir: Option<PathBuf>,
+}
+
+impl Store {
+    /// 打开数据目录中的数据库，执行尚未应用的结构迁移（迁移前备份到 `backups/`）
+    pub fn open(data_dir: &Path) -> DbResult<Self> {
+        let mut store = Self::open_unmigrated(data_dir)?;
+        let report = crate::migrate::migrate(&mut store.conn, Some(&data_dir.join("backups")))
+            .map_err(DbError::Migration)?;
+        if let Some(backup) = report.backup {
+            eprintln!("🗄️  数据库已从版本 {} 升级到 {}（备份：{}）", report.from, report.to, backup.display());
+        }
+
+        Ok(store)
+    }
+
+    /// 打开数据目录中的数据库但不执行迁移（`db migrate`、`db doctor` 使用）
+    pub fn open_unmigrated(data_dir: &Path) -> DbResult<Self> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|source| DbError::Io { path: data_dir.to_path_buf(), source })?;
+        let conn = Connection::open(data_dir.join(DB_FILE))?;
+        Ok(Store { conn, data_dir: Some(data_dir.to_path_buf()) })
+    }
+
+    /// 内存数据库（测试用），关闭后数据丢失
+    pub fn open_in_memory() -> DbResult<Self> {
+        let mut conn = Connection::open_in_memory()?;
+        crate::migrate::migrate(&mut conn, None).map_err(DbError::Migration)?;
+        Ok(Store { conn, data_dir: None })
+    }
+
+    /// 数据目录；内存数据库没有数据目录
+    pub fn data_dir(&self) -> DbResult<&Path> {
+        self.data_dir.as_deref().ok_or(DbError::InMemory)
+    }
+
+    /// 数据库文件路径，内存数据库为 None
+    pub fn db_path(&self) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(|dir| dir.join(DB_FILE))
+    }
+
+    /// 底层连接（迁移等需要直接执行 SQL 的场合）
+    pub fn connection(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+
+    /// 备份数据库到 backup_dir，返回备份文件路径
+    pub fn backup(&self, backup_dir: &Path) -> anyhow::Result<PathBuf> {
+        crate::migrate::backup(&self.conn, backup_dir)
+    }
+}
+
+impl Store {
+    /// 记录学习会话
+    pub fn record_study_session(
+        &self,
+        duration_minutes: u32,
+        modules_studied: Vec<String>,
+        practice_count: u32,
+        notes: Option<String>,
+    ) -> DbResult<()> {
+        self.record_study_session_at(Local::now(), duration_minutes, modules_studied, practice_count, notes)
+    }
+
+    /// 记录从 started_at 开始的学习会话（恢复上次未结束的计时时使用开始时间）
+    ///
+    /// 开始时间连同当时的 UTC 偏移一起保存，统计连续天数时按设置的时区换算日期
+    pub fn record_study_session_at(
+        &self,
+        started_at: chrono::DateTime<Local>,
+        duration_minutes: u32,
+        modules_studied: Vec<String>,
+        practice_count: u32,
+        notes: Option<String>,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+
+        let date = started_at.format("%Y-%m-%d").to_string();
+        let modules = modules_studied.join(",");
+
+        conn.execute(
+            "INSERT INTO study_sessions (date, duration_minutes, modules_studied, practice_count, notes, started_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            [date, duration_minutes.to_string(), modules, practice_count.to_string(), notes.unwrap_or_default(), started_at.to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 学习记录的时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionTime {
+    /// 带 UTC 偏移的开始时间
+    At(chrono::DateTime<chrono::FixedOffset>),
+    /// 早期版本只记录了本地日期
+    Date(NaiveDate),
+}
+
+/// 正在进行的学习计时
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveSession {
+    pub started_at: chrono::DateTime<Local>,
+    /// 最近一次有学习活动的时间
+    pub last_seen: chrono::DateTime<Local>,
+    pub modules_studied: Vec<String>,
+    pub practice_count: u32,
+    pub notes: Option<String>,
+    /// 番茄钟专注时长（分钟），None 表示普通计时
+    pub pomodoro_minutes: Option<u32>,
+    /// TUI 是否在为这次计时写入心跳（命令行开始的计时没有心跳，last_seen 不代表学习已中断）
+    pub heartbeat: bool,
+}
+
+impl Store {
+    /// 保存正在进行的学习计时（覆盖旧记录）
+    pub fn save_active_session(&self, session: &ActiveSession) -> DbResult<()> {
+        let conn = &self.conn;
+        conn.execute(
+            "INSERT INTO active_session (id, started_at, last_seen, modules_studied, practice_count, notes, pomodoro_minutes, heartbeat)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+             started_at = ?1,
+             last_seen = ?2,
+             modules_studied = ?3,
+             practice_count = ?4,
+             notes = ?5,
+             pomodoro_minutes = ?6,
+             heartbeat = ?7",
+            rusqlite::params![
+                session.started_at.to_rfc3339(),
+                session.last_seen.to_rfc3339(),
+                session.modules_studied.join(","),
+                session.practice_count,
+                session.notes,
+                session.pomodoro_minutes,
+                session.heartbeat,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 获取正在进行的学习计时
+    pub fn get_active_session(&self) -> DbResult<Option<ActiveSession>> {
+        let conn = &self.conn;
+        let row = conn.query_row(
+            "SELECT started_at, last_seen, modules_studied, practice_count, notes, pomodoro_minutes, heartbeat
+             FROM active_session WHERE id = 1",
+            [],
+            |row| Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<u32>>(5)?,
+                row.get::<_, bool>(6)?,
+            )),
+        ).optional()?;
+
+        let Some((started_at, last_seen, modules, practice_count, notes, pomodoro_minutes, heartbeat)) = row else {
+            return Ok(None);
+        };
+        let started_at = parse_time("active_session", "started_at", 1, &started_at)?;
+
+        Ok(Some(ActiveSession {
+            started_at,
+            last_seen: parse_time("active_session", "last_seen", 1, &last_seen)?,
+            modules_studied: modules.split(',').filter(|m| !m.is_empty()).map(String::from).collect(),
+            practice_count,
+            notes,
+            pomodoro_minutes,
+            heartbeat,
+        }))
+    }
+
+    /// 清除正在进行的学习计时
+    pub fn clear_active_session(&self) -> DbResult<()> {
+        let conn = &self.conn;
+        conn.execute("DELETE FROM active_session", [])?;
+        Ok(())
+    }
+
+    /// 更新模块进度
+    pub fn update_module_progress(
+        &self,
+        module_id: &str,
+        mastery_score: f32,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        let now = Local::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO module_progress (module_id, mastery_score, last_updated)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(module_id) DO UPDATE SET
+             mastery_score = ?2,
+             last_updated = ?3",
+            [module_id, &mastery_score.to_string(), &now],
+        )?;
+
+        // 掌握程度变化时记录一次历史
+        let last: Option<f64> = conn.query_row(
+            "SELECT mastery FROM mastery_history WHERE module_id = ?1 ORDER BY id DESC LIMIT 1",
+            [module_id],
+            |row| row.get(0),
+        ).optional()?;
+        if last.map_or(true, |last| (last - mastery_score as f64).abs() > 0.05) {
+            conn.execute(
+                "INSERT INTO mastery_history (module_id, recorded_at, mastery) VALUES (?1, ?2, ?3)",
+                rusqlite::params![module_id, now, mastery_score as f64],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 一次掌握程度记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct MasterySnapshot {
+    pub module_id: String,
+    pub recorded_at: chrono::DateTime<Local>,
+    pub mastery: f32,
+}
+
+impl Store {
+    /// 所有模块的掌握程度历史（按时间先后）
+    pub fn get_mastery_history(&self) -> DbResult<Vec<MasterySnapshot>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT rowid, module_id, recorded_at, mastery FROM mastery_history ORDER BY recorded_at"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, f64>(3)?))
+        })?;
+
+        let mut history = Vec::new();
+        for row in rows {
+            let (rowid, module_id, recorded_at, mastery) = row?;
+            history.push(MasterySnapshot {
+                module_id,
+                recorded_at: parse_time("mastery_history", "recorded_at", rowid, &recorded_at)?,
+                mastery: mastery as f32,
+            });
+        }
+
+        Ok(history)
+    }
+
+    /// 记录练习结果
+    pub fn record_practice_result(
+        &self,
+        module_id: &str,
+        questions_total: u32,
+        questions_correct: u32,
+        score: f32,
+        weak_topics: Vec<String>,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        let timestamp = Local::now().to_rfc3339();
+        let topics = weak_topics.join(",");
+
+        conn.execute(
+            "INSERT INTO practice_results (module_id, timestamp, questions_total, questions_correct, score, weak_topics)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            [
+                module_id,
+                &timestamp,
+                &questions_total.to_string(),
+                &questions_correct.to_string(),
+                &score.to_string(),
+                &topics,
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 一次练习的得分记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct PracticeResult {
+    pub timestamp: chrono::DateTime<Local>,
+    pub questions_total: u32,
+    pub questions_correct: u32,
+    /// 得分（0-100）
+    pub score: f32,
+}
+
+impl Store {
+    /// 获取模块的所有练习记录（按时间先后）
+    pub fn get_practice_results(&self, module_id: &str) -> DbResult<Vec<PracticeResult>> {
+        Ok(self.query_practice_results(Some(module_id))?
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect())
+    }
+
+    /// 获取所有模块的练习记录（模块 id, 记录），按时间先后
+    pub fn get_all_practice_results(&self) -> DbResult<Vec<(String, PracticeResult)>> {
+        self.query_practice_results(None)
+    }
+
+    fn query_practice_results(&self, module_id: Option<&str>) -> DbResult<Vec<(String, PracticeResult)>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT rowid, module_id, timestamp, questions_total, questions_correct, score
+             FROM practice_results WHERE ?1 IS NULL OR module_id = ?1 ORDER BY timestamp"
+        )?;
+
+        let rows = stmt.query_map([module_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, f64>(5)?,
+            ))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (rowid, module_id, timestamp, questions_total, questions_correct, score) = row?;
+            results.push((module_id, PracticeResult {
+                timestamp: parse_time("practice_results", "timestamp", rowid, &timestamp)?,
+                questions_total,
+                questions_correct,
+                score: score as f32,
+            }));
+        }
+
+        Ok(results)
+    }
+}
+
+/// 练习题测试结果
+#[derive(Debug, Clone)]
+pub struct ExerciseResult {
+    pub exercise: String,
+    pub checked_at: String,
+    pub compiled: bool,
+    pub tests_total: u32,
+    pub tests_passed: u32,
+    pub failed_tests: Vec<String>,
+}
+
+impl ExerciseResult {
+    /// 编译通过且所有测试均通过
+    pub fn passed(&self) -> bool {
+        self.compiled && self.tests_total > 0 && self.tests_passed == self.tests_total
+    }
+}
+
+impl Store {
+    /// 记录练习题测试结果（每个练习只保留最近一次）
+    pub fn record_exercise_result(
+        &self,
+        module_id: &str,
+        exercise: &str,
+        compiled: bool,
+        tests_total: u32,
+        tests_passed: u32,
+        failed_tests: Vec<String>,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        let checked_at = Local::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO exercise_results (module_id, exercise, checked_at, compiled, tests_total, tests_passed, failed_tests)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(module_id, exercise) DO UPDATE SET
+             checked_at = ?3,
+             compiled = ?4,
+             tests_total = ?5,
+             tests_passed = ?6,
+             failed_tests = ?7",
+            rusqlite::params![
+                module_id,
+                exercise,
+                checked_at,
+                compiled,
+                tests_total,
+                tests_passed,
+                failed_tests.join(","),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// 获取模块的练习题测试结果
+    pub fn get_exercise_results(&self, module_id: &str) -> DbResult<Vec<ExerciseResult>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT exercise, checked_at, compiled, tests_total, tests_passed, failed_tests
+             FROM exercise_results WHERE module_id = ?1 ORDER BY exercise"
+        )?;
+
+        let results = stmt.query_map([module_id], |row| {
+            let failed: Option<String> = row.get(5)?;
+            Ok(ExerciseResult {
+                exercise: row.get(0)?,
+                checked_at: row.get(1)?,
+                compiled: row.get(2)?,
+                tests_total: row.get(3)?,
+                tests_passed: row.get(4)?,
+                failed_tests: failed
+                    .filter(|f| !f.is_empty())
+                    .map(|f| f.split(',').map(String::from).collect())
+                    .unwrap_or_default(),
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(results)
+    }
+}
+
+/// 一个代码示例的运行记录
+#[derive(Debug, Clone)]
+pub struct ExampleRunRecord {
+    pub example: String,
+    pub last_run_at: String,
+    pub last_success: bool,
+    pub last_duration_ms: u64,
+    pub runs: u32,
+    /// 成功运行的次数
+    pub successes: u32,
+}
+
+impl Store {
+    /// 记录一次示例运行（每个示例保留最近一次的结果和累计次数）
+    pub fn record_example_run(&self, module_id: &str, example: &str, success: bool, duration_ms: u64) -> DbResult<()> {
+        let conn = &self.conn;
+        let ran_at = Local::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO example_runs (module_id, example, last_run_at, last_success, last_duration_ms, runs, successes)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1, ?4)
+             ON CONFLICT(module_id, example) DO UPDATE SET
+             last_run_at = ?3,
+             last_success = ?4,
+             last_duration_ms = ?5,
+             runs = runs + 1,
+             successes = successes + ?4",
+            rusqlite::params![module_id, example, ran_at, success, duration_ms as i64],
+        )?;
+
+        Ok(())
+    }
+
+    /// 获取模块的示例运行记录
+    pub fn get_example_runs(&self, module_id: &str) -> DbResult<Vec<ExampleRunRecord>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT example, last_run_at, last_success, last_duration_ms, runs, successes
+             FROM example_runs WHERE module_id = ?1 ORDER BY example"
+        )?;
+
+        let records = stmt.query_map([module_id], |row| {
+            Ok(ExampleRunRecord {
+                example: row.get(0)?,
+                last_run_at: row.get(1)?,
+                last_success: row.get(2)?,
+                last_duration_ms: row.get::<_, i64>(3)? as u64,
+                runs: row.get(4)?,
+                successes: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(records)
+    }
+}
+
+/// 一次查看练习骨架或答案的记录
+#[derive(Debug, Clone)]
+pub struct SolutionView {
+    pub exercise: String,
+    /// 查看的内容：`skeleton`、`solution` 或 `diff`
+    pub level: String,
+    pub viewed_at: String,
+    /// 查看时练习的测试是否还没有通过
+    pub before_pass: bool,
+}
+
+impl Store {
+    /// 记录一次查看骨架或答案
+    pub fn record_solution_view(&self, module_id: &str, exercise: &str, level: &str, before_pass: bool) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT INTO solution_views (module_id, exercise, level, viewed_at, before_pass) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![module_id, exercise, level, Local::now().to_rfc3339(), before_pass],
+        )?;
+        Ok(())
+    }
+
+    /// 获取模块的查看记录（按时间先后）
+    pub fn get_solution_views(&self, module_id: &str) -> DbResult<Vec<SolutionView>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise, level, viewed_at, before_pass FROM solution_views WHERE module_id = ?1 ORDER BY id"
+        )?;
+
+        let views = stmt.query_map([module_id], |row| {
+            Ok(SolutionView {
+                exercise: row.get(0)?,
+                level: row.get(1)?,
+                viewed_at: row.get(2)?,
+                before_pass: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(views)
+    }
+}
+
+/// 与 进度.md 同步的一项进度（任务勾选状态或完成日期）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressField {
+    pub module_id: String,
+    pub field: String,
+    /// 数据库中的当前值，None 表示从未记录
+    pub value: Option<String>,
+    /// 上次同步时双方一致的值，None 表示从未同步
+    pub synced_value: Option<String>,
+}
+
+impl Store {
+    /// 设置一项进度（只修改数据库一侧，同步时写入 进度.md）
+    pub fn set_progress_field(&self, module_id: &str, field: &str, value: &str) -> DbResult<()> {
+        let conn = &self.conn;
+        let now = Local::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO progress_fields (module_id, field, value, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(module_id, field) DO UPDATE SET
+             value = ?3,
+             updated_at = ?4",
+            [module_id, field, value, &now],
+        )?;
+
+        Ok(())
+    }
+
+    /// 记录同步结果：数据库值与同步基准都设为 value
+    pub fn mark_progress_synced(&self, module_id: &str, field: &str, value: &str) -> DbResult<()> {
+        let conn = &self.conn;
+        let now = Local::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO progress_fields (module_id, field, value, synced_value, updated_at)
+             VALUES (?1, ?2, ?3, ?3, ?4)
+             ON CONFLICT(module_id, field) DO UPDATE SET
+             value = ?3,
+             synced_value = ?3,
+             updated_at = ?4",
+            [module_id, field, value, &now],
+        )?;
+
+        Ok(())
+    }
+
+    /// 获取所有进度项
+    pub fn get_progress_fields(&self) -> DbResult<Vec<ProgressField>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT module_id, field, value, synced_value FROM progress_fields ORDER BY module_id, field"
+        )?;
+
+        let fields = stmt.query_map([], |row| {
+            Ok(ProgressField {
+                module_id: row.get(0)?,
+                field: row.get(1)?,
+                value: row.get(2)?,
+                synced_value: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(fields)
+    }
+}
+
+/// 单道题的复习计划
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewSchedule {
+    pub module_id: String,
+    pub question_key: String,
+    pub ease_factor: f64,
+    pub interval_days: u32,
+    pub repetitions: u32,
+    pub due_date: NaiveDate,
+}
+
+impl Store {
+    /// 记录一次作答
+    pub fn record_question_review(
+        &self,
+        module_id: &str,
+        question_key: &str,
+        topic: &str,
+        correct: bool,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        let reviewed_at = Local::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO question_reviews (module_id, question_key, topic, correct, reviewed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![module_id, question_key, topic, correct, reviewed_at],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 一次作答记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestionReview {
+    pub module_id: String,
+    pub topic: String,
+    pub correct: bool,
+    pub reviewed_at: chrono::DateTime<Local>,
+}
+
+impl Store {
+    /// 获取所有作答记录（按时间先后）
+    pub fn get_question_reviews(&self) -> DbResult<Vec<QuestionReview>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT rowid, module_id, topic, correct, reviewed_at FROM question_reviews ORDER BY reviewed_at"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, bool>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        let mut reviews = Vec::new();
+        for row in rows {
+            let (rowid, module_id, topic, correct, reviewed_at) = row?;
+            let reviewed_at = parse_time("question_reviews", "reviewed_at", rowid, &reviewed_at)?;
+            reviews.push(QuestionReview { module_id, topic, correct, reviewed_at });
+        }
+
+        Ok(reviews)
+    }
+}
+
+impl Store {
+    /// 按条件查询复习计划（where_clause 中的参数依次为 params）
+    fn query_review_schedules(&self, where_clause: &str, params: impl rusqlite::Params) -> DbResult<Vec<ReviewSchedule>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT rowid, module_id, question_key, ease_factor, interval_days, repetitions, due_date
+             FROM review_schedule WHERE {}",
+            where_clause,
+        ))?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, u32>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+
+        let mut schedules = Vec::new();
+        for row in rows {
+            let (rowid, module_id, question_key, ease_factor, interval_days, repetitions, due_date) = row?;
+            schedules.push(ReviewSchedule {
+                module_id,
+                question_key,
+                ease_factor,
+                interval_days,
+                repetitions,
+                due_date: parse_date("review_schedule", "due_date", rowid, &due_date)?,
+            });
         }
+
+        Ok(schedules)
+    }
+
+    /// 获取题目的复习计划
+    pub fn get_review_schedule(&self, module_id: &str, question_key: &str) -> DbResult<Option<ReviewSchedule>> {
+        Ok(self.query_review_schedules("module_id = ?1 AND question_key = ?2", [module_id, question_key])?
+            .into_iter()
+            .next())
+    }
+
+    /// 保存题目的复习计划
+    pub fn save_review_schedule(&self, schedule: &ReviewSchedule) -> DbResult<()> {
+        let conn = &self.conn;
+        conn.execute(
+            "INSERT INTO review_schedule (module_id, question_key, ease_factor, interval_days, repetitions, due_date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(module_id, question_key) DO UPDATE SET
+             ease_factor = ?3,
+             interval_days = ?4,
+             repetitions = ?5,
+             due_date = ?6",
+            rusqlite::params![
+                schedule.module_id,
+                schedule.question_key,
+                schedule.ease_factor,
+                schedule.interval_days,
+                schedule.repetitions,
+                schedule.due_date,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 获取截至指定日期到期的复习计划（最早到期的在前）
+    pub fn get_due_reviews(&self, today: NaiveDate) -> DbResult<Vec<ReviewSchedule>> {
+        self.query_review_schedules("due_date <= ?1 ORDER BY due_date, module_id", [today])
+    }
+
+    /// 解锁成就（不带元数据），返回是否为首次解锁
+    pub fn check_and_unlock_achievement(&self, achievement_type: &str) -> DbResult<bool> {
+        self.unlock_achievement(achievement_type, None)
+    }
+
+    /// 解锁成就并记录解锁时的元数据，已解锁时不做修改，返回是否为首次解锁
+    pub fn unlock_achievement(&self, achievement_type: &str, metadata: Option<&str>) -> DbResult<bool> {
+        let conn = &self.conn;
+        let now = Local::now().to_rfc3339();
+
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO achievements (achievement_type, unlocked_at, metadata) VALUES (?1, ?2, ?3)",
+            rusqlite::params![achievement_type, now, metadata],
+        )?;
+
+        Ok(inserted > 0)
     }
+}
 
-    Ok(streak)
+/// 已解锁的成就记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnlockedAchievement {
+    pub achievement_type: String,
+    pub unlocked_at: Option<chrono::DateTime<Local>>,
+    pub metadata: Option<String>,
 }
 
-/// 保存设置
-pub fn save_setting(key: &str, value: &str) -> Result<()> {
-    let conn = Connection::open(db_path())?;
-    conn.execute(
-        "INSERT INTO settings (key, value) VALUES (?1, ?2)
-         ON CONFLICT(key) DO UPDATE SET value = ?2",
-        [key, value],
-    )?;
-    Ok(())
+impl Store {
+    /// 获取所有已解锁的成就
+    pub fn get_unlocked_achievements(&self) -> DbResult<Vec<UnlockedAchievement>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT rowid, achievement_type, unlocked_at, metadata FROM achievements ORDER BY unlocked_at"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Option<String>>(3)?))
+        })?;
+
+        let mut unlocked = Vec::new();
+        for row in rows {
+            let (rowid, achievement_type, unlocked_at, metadata) = row?;
+            unlocked.push(UnlockedAchievement {
+                achievement_type,
+                unlocked_at: Some(parse_time("achievements", "unlocked_at", rowid, &unlocked_at)?),
+                metadata,
+            });
+        }
+
+        Ok(unlocked)
+    }
+
+    /// 单次练习的最高得分，没有练习记录时为 0
+    pub fn get_best_practice_score(&self) -> DbResult<f32> {
+        let conn = &self.conn;
+        let best: Option<f64> = conn.query_row("SELECT MAX(score) FROM practice_results", [], |row| row.get(0))?;
+        Ok(best.unwrap_or(0.0) as f32)
+    }
+
+    /// 通过全部测试的练习数
+    pub fn count_passed_exercises(&self) -> DbResult<u32> {
+        let conn = &self.conn;
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM exercise_results WHERE compiled = 1 AND tests_total > 0 AND tests_passed = tests_total",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// 累计作答的题目数（练习和复习）
+    pub fn count_question_reviews(&self) -> DbResult<u32> {
+        let conn = &self.conn;
+        let count = conn.query_row("SELECT COUNT(*) FROM question_reviews", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    /// 某天是否有学习记录
+    pub fn has_study_session_on(&self, date: NaiveDate) -> DbResult<bool> {
+        let conn = &self.conn;
+        let count: u32 = conn.query_row(
+            "SELECT COUNT(*) FROM study_sessions WHERE date = ?1",
+            [date.format("%Y-%m-%d").to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// 每天的学习分钟数（按日期先后）
+    pub fn get_study_minutes_by_date(&self) -> DbResult<Vec<(NaiveDate, u32)>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT MIN(rowid), date, SUM(duration_minutes) FROM study_sessions GROUP BY date ORDER BY date"
+        )?;
+
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, u32>(2)?)))?;
+
+        let mut days = Vec::new();
+        for row in rows {
+            let (rowid, date, minutes) = row?;
+            days.push((parse_date("study_sessions", "date", rowid, &date)?, minutes));
+        }
+
+        Ok(days)
+    }
+
+    /// 所有学习记录的开始时间（按时间先后）
+    pub fn get_session_times(&self) -> DbResult<Vec<SessionTime>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(
+            "SELECT rowid, date, started_at FROM study_sessions ORDER BY date, rowid"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+        })?;
+
+        let mut times = Vec::new();
+        for row in rows {
+            let (rowid, date, started_at) = row?;
+            times.push(match started_at {
+                Some(started_at) => SessionTime::At(
+                    chrono::DateTime::parse_from_rfc3339(&started_at).map_err(|_| DbError::Corrupt {
+                        table: "study_sessions",
+                        column: "started_at",
+                        rowid,
+                        value: started_at.clone(),
+                    })?,
+                ),
+                None => SessionTime::Date(parse_date("study_sessions", "date", rowid, &date)?),
+            });
+        }
+
+        Ok(times)
+    }
+
+    /// 保存设置
+    pub fn save_setting(&self, key: &str, value: &str) -> DbResult<()> {
+        let conn = &self.conn;
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = ?2",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
+    /// 获取设置
+    pub fn get_setting(&self, key: &str) -> DbResult<Option<String>> {
+        let conn = &self.conn;
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+
+        Ok(stmt.query_row([key], |row| row.get(0)).optional()?)
+    }
 }
 
-/// 获取设置
-pub fn get_setting(key: &str) -> Result<Option<String>> {
-    let conn = Connection::open(db_path())?;
-    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+/// 导出和导入的所有数据表
+pub const DATA_TABLES: [&str; 13] = [
+    "study_sessions",
+    "module_progress",
+    "mastery_history",
+    "practice_results",
+    "achievements",
+    "exercise_results",
+    "example_runs",
+    "solution_views",
+    "question_reviews",
+    "review_schedule",
+    "progress_fields",
+    "active_session",
+    "settings",
+];
 
-    let result: Result<Option<String>, _> = stmt
-        .query_row([key], |row| row.get(0))
-        .optional();
+/// 一行数据：列名到值
+pub type Row = serde_json::Map<String, serde_json::Value>;
 
-    result.map_err(Into::into)
+/// 导入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// 合并到现有数据：相同的记录跳过，唯一键冲突时保留本机数据
+    Merge,
+    /// 清空现有数据后导入
+    Replace,
 }
 
-/// 导出所有数据
-pub fn export_all_data() -> Result<String> {
-    let conn = Connection::open(db_path())?;
+/// 每张表导入的行数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    /// (表名, 新增行数, 跳过行数)
+    pub tables: Vec<(String, usize, usize)>,
+}
 
-    let mut sessions = String::new();
-    let mut stmt = conn.prepare("SELECT * FROM study_sessions")?;
-    let session_rows = stmt.query_map([], |row| {
-        Ok((
-            row.get::<_, i32>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, i32>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, i32>(4)?,
-            row.get::<_, String>(5)?,
-        ))
-    })?;
+impl ImportStats {
+    pub fn inserted(&self) -> usize {
+        self.tables.iter().map(|(_, inserted, _)| inserted).sum()
+    }
 
-    for session in session_rows {
-        let (id, date, duration, modules, practice, notes) = session?;
-        sessions.push_str(&format!(
-            "Session {}: {} | {}min | modules: {} | practice: {} | notes: {}\n",
-            id, date, duration, modules, practice, notes
-        ));
+    pub fn skipped(&self) -> usize {
+        self.tables.iter().map(|(_, _, skipped)| skipped).sum()
     }
+}
 
-    Ok(sessions)
+fn to_json(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => b.to_vec().into(),
+    }
 }
 
-/// 获取模块掌握程度
-pub fn get_module_mastery(module_id: &str) -> Option<f32> {
-    let conn = Connection::open(db_path()).ok()?;
-    let mut stmt = conn.prepare(
-        "SELECT mastery_score FROM module_progress WHERE module_id = ?1"
-    ).ok()?;
+fn from_json(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(i64::from(*b)),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        serde_json::Value::Array(items) => Value::Blob(items.iter().filter_map(|i| i.as_u64()).map(|b| b as u8).collect()),
+        serde_json::Value::Object(_) => Value::Text(value.to_string()),
+    }
+}
+
+impl Store {
+    /// 读取一张表的所有行（按 rowid 排序）
+    pub fn dump_table(&self, table: &str) -> DbResult<Vec<Row>> {
+        if !DATA_TABLES.contains(&table) {
+            return Err(DbError::UnknownTable(table.to_string()));
+        }
+        let conn = &self.conn;
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {} ORDER BY rowid", table))?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+        let rows = stmt.query_map([], |row| {
+            let mut map = Row::new();
+            for (i, column) in columns.iter().enumerate() {
+                map.insert(column.clone(), to_json(row.get_ref(i)?));
+            }
+            Ok(map)
+        })?;
+
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// 导入数据（在一个事务中完成，出错时不修改任何数据）
+    ///
+    /// 只导入本机数据库中存在的表和列；合并时自增 id 不导入，内容完全相同的行视为重复
+    pub fn import_tables(&self, tables: &[(String, Vec<Row>)], mode: ImportMode) -> DbResult<ImportStats> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut stats = ImportStats::default();
+
+        if mode == ImportMode::Replace {
+            for table in DATA_TABLES {
+                tx.execute(&format!("DELETE FROM {}", table), [])?;
+            }
+        }
+
+        for (table, rows) in tables {
+            if !DATA_TABLES.contains(&table.as_str()) || rows.is_empty() {
+                continue;
+            }
+
+            let mut columns = Vec::new();
+            let mut has_autoincrement = false;
+            {
+                let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+                let info = stmt.query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i32>(5)?)))?;
+                for column in info {
+                    let (name, kind, pk) = column?;
+                    // 合并时自增 id 由本机重新分配
+                    if mode == ImportMode::Merge && pk == 1 && kind == "INTEGER" && table != "active_session" {
+                        has_autoincrement = true;
+                        continue;
+                    }
+                    // 旧版本导出中没有的列使用默认值
+                    if rows.iter().any(|row| row.contains_key(&name)) {
+                        columns.push(name);
+                    }
+                }
+            }
+
+            let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+            let mut sql = format!(
+                "INSERT OR IGNORE INTO {} ({}) SELECT {}",
+                table,
+                columns.join(", "),
+                placeholders.join(", "),
+            );
+            if has_autoincrement {
+                // 没有唯一键的表（如学习记录）按内容去重
+                let conditions: Vec<String> = columns.iter().zip(&placeholders)
+                    .map(|(column, placeholder)| format!("{} IS {}", column, placeholder))
+                    .collect();
+                sql.push_str(&format!(" WHERE NOT EXISTS (SELECT 1 FROM {} WHERE {})", table, conditions.join(" AND ")));
+            }
+
+            let mut stmt = tx.prepare(&sql)?;
+            let (mut inserted, mut skipped) = (0, 0);
+            for row in rows {
+                let values: Vec<rusqlite::types::Value> = columns.iter()
+                    .map(|column| row.get(column).map(from_json).unwrap_or(rusqlite::types::Value::Null))
+                    .collect();
+                if stmt.execute(rusqlite::params_from_iter(values))? > 0 {
+                    inserted += 1;
+                } else {
+                    skipped += 1;
+                }
+            }
+            stats.tables.push((table.clone(), inserted, skipped));
+        }
 
-    stmt.query_row([module_id], |row| row.get(0)).ok()
+        tx.commit()?;
+        Ok(stats)
+    }
+
+    /// 获取模块掌握程度，没有记录时为 None
+    pub fn get_module_mastery(&self, module_id: &str) -> DbResult<Option<f32>> {
+        let conn = &self.conn;
+        let mastery = conn.query_row(
+            "SELECT mastery_score FROM module_progress WHERE module_id = ?1",
+            [module_id],
+            |row| row.get::<_, Option<f32>>(0),
+        ).optional()?;
+        Ok(mastery.flatten())
+    }
 }
 
 /// 成就记录
@@ -285,38 +1196,24 @@ pub struct Achievement {
     pub unlocked_at: Option<chrono::DateTime<Local>>,
 }
 
-/// 成就定义
-const ALL_ACHIEVEMENTS: &[(&str, &str)] = &[
-    ("first_steps", "初次学习 - 完成第一个模块"),
-    ("week_warrior", "坚持一周 - 连续学习 7 天"),
-    ("month_master", "坚持一月 - 连续学习 30 天"),
-    ("practice_perfect", "练习达人 - 单次练习 100% 正确"),
-    ("half_way", "半程高手 - 完成 50% 的学习内容"),
-    ("completionist", "学习大师 - 完成所有模块"),
-];
+impl Store {
+    /// 获取所有成就（定义见 achievements.toml）
+    pub fn get_all_achievements(&self) -> DbResult<Vec<Achievement>> {
+        let unlocked = self.get_unlocked_achievements()?;
 
-/// 获取所有成就
-pub fn get_all_achievements() -> Result<Vec<Achievement>> {
-    let conn = Connection::open(db_path())?;
+        let achievements = crate::achievements::definitions()
+            .iter()
+            .map(|def| {
+                let record = unlocked.iter().find(|u| u.achievement_type == def.id);
+                Achievement {
+                    name: def.id.clone(),
+                    description: format!("{} - {}", def.name, def.description),
+                    unlocked: record.is_some(),
+                    unlocked_at: record.and_then(|r| r.unlocked_at),
+                }
+            })
+            .collect();
 
-    // 获取已解锁的成就
-    let mut stmt = conn.prepare(
-        "SELECT achievement_type FROM achievements"
-    )?;
-
-    let unlocked_types: Vec<String> = stmt.query_map([], |row| row.get(0))?
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let mut achievements = Vec::new();
-    for (achievement_type, description) in ALL_ACHIEVEMENTS {
-        let unlocked = unlocked_types.contains(&achievement_type.to_string());
-        achievements.push(Achievement {
-            name: achievement_type.to_string(),
-            description: description.to_string(),
-            unlocked,
-            unlocked_at: None, // 简化实现
-        });
+        Ok(achievements)
     }
-
-    Ok(achievements)
 }
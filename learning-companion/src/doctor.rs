@@ -0,0 +1,219 @@
+//! 数据库检查模块
+//!
+//! `db doctor` 检查数据库文件的完整性和每张表中无法解析的行（日期、时间、计数），
+//! `--fix` 先备份数据库，再删除损坏的行
+
+use anyhow::{Context, Result};
+use crate::db::Store;
+use crate::migrate;
+use rusqlite::Connection;
+use rusqlite::types::Value;
+
+/// 列的取值要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// `%Y-%m-%d` 格式的日期
+    Date,
+    /// RFC 3339 格式的时间
+    Timestamp,
+    /// RFC 3339 格式的时间，可以为空
+    OptionalTimestamp,
+    /// 非负整数
+    Count,
+    /// 数值
+    Number,
+}
+
+impl ColumnKind {
+    /// 检查取值，返回问题描述
+    pub fn check(self, value: &Value) -> Option<&'static str> {
+        match (self, value) {
+            (ColumnKind::Date, Value::Text(text)) => chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .is_err()
+                .then_some("不是有效的日期"),
+            (ColumnKind::OptionalTimestamp, Value::Null) => None,
+            (ColumnKind::Timestamp | ColumnKind::OptionalTimestamp, Value::Text(text)) => chrono::DateTime::parse_from_rfc3339(text)
+                .is_err()
+                .then_some("不是有效的时间"),
+            (ColumnKind::Date | ColumnKind::Timestamp | ColumnKind::OptionalTimestamp, _) => Some("不是文本"),
+            (ColumnKind::Count, Value::Integer(n)) => (*n < 0 || *n > u32::MAX as i64).then_some("超出计数范围"),
+            (ColumnKind::Count, _) => Some("不是整数"),
+            (ColumnKind::Number, Value::Integer(_) | Value::Real(_)) => None,
+            (ColumnKind::Number, _) => Some("不是数值"),
+        }
+    }
+}
+
+/// 需要检查的列
+pub const CHECKS: &[(&str, &str, ColumnKind)] = &[
+    ("study_sessions", "date", ColumnKind::Date),
+    ("study_sessions", "duration_minutes", ColumnKind::Count),
+    ("study_sessions", "practice_count", ColumnKind::Count),
+    ("study_sessions", "started_at", ColumnKind::OptionalTimestamp),
+    ("module_progress", "mastery_score", ColumnKind::Number),
+    ("module_progress", "last_updated", ColumnKind::Timestamp),
+    ("mastery_history", "recorded_at", ColumnKind::Timestamp),
+    ("mastery_history", "mastery", ColumnKind::Number),
+    ("practice_results", "timestamp", ColumnKind::Timestamp),
+    ("practice_results", "questions_total", ColumnKind::Count),
+    ("practice_results", "questions_correct", ColumnKind::Count),
+    ("practice_results", "score", ColumnKind::Number),
+    ("achievements", "unlocked_at", ColumnKind::Timestamp),
+    ("exercise_results", "checked_at", ColumnKind::Timestamp),
+    ("exercise_results", "tests_total", ColumnKind::Count),
+    ("exercise_results", "tests_passed", ColumnKind::Count),
+    ("example_runs", "last_run_at", ColumnKind::Timestamp),
+    ("example_runs", "last_duration_ms", ColumnKind::Count),
+    ("example_runs", "runs", ColumnKind::Count),
+    ("example_runs", "successes", ColumnKind::Count),
+    ("solution_views", "viewed_at", ColumnKind::Timestamp),
+    ("question_reviews", "reviewed_at", ColumnKind::Timestamp),
+    ("review_schedule", "ease_factor", ColumnKind::Number),
+    ("review_schedule", "interval_days", ColumnKind::Count),
+    ("review_schedule", "repetitions", ColumnKind::Count),
+    ("review_schedule", "due_date", ColumnKind::Date),
+    ("progress_fields", "updated_at", ColumnKind::Timestamp),
+    ("active_session", "started_at", ColumnKind::Timestamp),
+    ("active_session", "last_seen", ColumnKind::Timestamp),
+];
+
+/// 一行损坏的数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowIssue {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub rowid: i64,
+    pub value: String,
+    pub problem: &'static str,
+}
+
+/// 检查结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnosis {
+    /// SQLite 完整性检查发现的问题（为空表示通过）
+    pub integrity: Vec<String>,
+    /// 无法解析的行
+    pub rows: Vec<RowIssue>,
+}
+
+impl Diagnosis {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity.is_empty() && self.rows.is_empty()
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(n) => n.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(text) => format!("{:?}", text),
+        Value::Blob(bytes) => format!("<{} 字节的二进制数据>", bytes.len()),
+    }
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// 检查数据库：文件完整性和每一列的取值
+pub fn diagnose(conn: &Connection) -> Result<Diagnosis> {
+    let mut diagnosis = Diagnosis::default();
+
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let messages = stmt.query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    diagnosis.integrity = messages.into_iter().filter(|m| m != "ok").collect();
+
+    for &(table, column, kind) in CHECKS {
+        // 旧版本数据库可能还没有这张表（迁移后才有）
+        if !table_exists(conn, table)? {
+            continue;
+        }
+        let mut stmt = conn.prepare(&format!("SELECT rowid, {} FROM {} ORDER BY rowid", column, table))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Value>(1)?)))?;
+        for row in rows {
+            let (rowid, value) = row?;
+            if let Some(problem) = kind.check(&value) {
+                diagnosis.rows.push(RowIssue { table, column, rowid, value: describe(&value), problem });
+            }
+        }
+    }
+
+    Ok(diagnosis)
+}
+
+/// 删除损坏的行（同一行有多处问题只删除一次），返回删除的行数
+pub fn repair(conn: &mut Connection, issues: &[RowIssue]) -> Result<usize> {
+    let tx = conn.transaction()?;
+    let mut removed = 0;
+    for issue in issues {
+        removed += tx.execute(&format!("DELETE FROM {} WHERE rowid = ?1", issue.table), [issue.rowid])?;
+    }
+    tx.commit()?;
+    Ok(removed)
+}
+
+/// 检查数据库（命令行）；fix 时备份后删除损坏的行
+///
+/// store 应由 `Store::open_unmigrated` 打开，结构迁移失败的数据库也能检查
+pub fn run_doctor(store: &mut Store, fix: bool) -> Result<()> {
+    let path = store.db_path().map_or_else(|| ":memory:".to_string(), |p| p.display().to_string());
+    let backup_dir = store.data_dir().ok().map(|dir| dir.join("backups"));
+    let conn = store.connection();
+
+    println!("🩺 检查数据库：{}", path);
+    let current = migrate::current_version(conn).context(
+        "无法读取数据库文件，可以从数据目录的 backups/ 恢复备份，或用 import 导入之前导出的数据",
+    )?;
+    if current < migrate::latest_version() {
+        println!("   数据库版本 {}，最新版本 {}（运行 db migrate 升级）", current, migrate::latest_version());
+    }
+
+    let diagnosis = diagnose(conn)?;
+    if diagnosis.integrity.is_empty() {
+        println!("   ✓ SQLite 完整性检查通过");
+    } else {
+        println!("   ❌ SQLite 完整性检查发现问题：");
+        for message in &diagnosis.integrity {
+            println!("      {}", message);
+        }
+    }
+
+    if diagnosis.rows.is_empty() {
+        println!("   ✓ 所有记录都能正常读取");
+    } else {
+        println!("   ⚠️  {} 处无法解析的记录：", diagnosis.rows.len());
+        for issue in &diagnosis.rows {
+            println!("      {} 第 {} 行 {} = {}：{}", issue.table, issue.rowid, issue.column, issue.value, issue.problem);
+        }
+    }
+
+    if diagnosis.is_healthy() {
+        println!("\n✅ 数据库正常");
+        return Ok(());
+    }
+
+    if !fix {
+        if !diagnosis.rows.is_empty() {
+            println!("\n💡 运行 db doctor --fix 删除损坏的记录（修复前自动备份）");
+        }
+    } else if !diagnosis.rows.is_empty() {
+        if let Some(dir) = &backup_dir {
+            println!("\n💾 修复前的备份：{}", migrate::backup(conn, dir)?.display());
+        }
+        let removed = repair(conn, &diagnosis.rows)?;
+        println!("✅ 已删除 {} 条损坏的记录", removed);
+    }
+
+    if !diagnosis.integrity.is_empty() {
+        println!("\n⚠️  数据库文件本身已损坏，无法自动修复：");
+        println!("   可以从数据目录的 backups/ 恢复备份，或用 import 导入之前导出的数据");
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! 外部编辑器
+//!
+//! 按 `$VISUAL`、`$EDITOR` 的约定选择编辑器，常见编辑器打开时直接跳到指定行
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::Command;
+
+/// 没有设置 `$VISUAL` 和 `$EDITOR` 时使用的编辑器
+#[cfg(windows)]
+pub const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// 配置的编辑器命令（可以带参数，如 `code --wait`）
+pub fn configured_editor() -> Vec<String> {
+    ["VISUAL", "EDITOR"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|value| value.split_whitespace().map(String::from).collect::<Vec<_>>())
+        .find(|words| !words.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_EDITOR.to_string()])
+}
+
+/// 打开 file 的参数（不含程序名）；认识的编辑器在 line 不为空时跳到该行
+pub fn editor_args(editor: &[String], file: &Path, line: Option<u32>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = editor.iter().skip(1).map(OsString::from).collect();
+    let program = editor.first()
+        .and_then(|p| Path::new(p).file_stem())
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let Some(line) = line else {
+        args.push(file.into());
+        return args;
+    };
+    let with_line = || {
+        let mut arg = file.as_os_str().to_os_string();
+        arg.push(format!(":{}", line));
+        arg
+    };
+
+    match program.as_str() {
+        "vi" | "vim" | "nvim" | "gvim" | "nano" | "emacs" | "emacsclient" | "kak" | "micro" | "joe" | "mg" => {
+            args.push(format!("+{}", line).into());
+            args.push(file.into());
+        }
+        "code" | "code-insiders" | "codium" => {
+            args.push("--goto".into());
+            args.push(with_line());
+        }
+        "subl" | "hx" | "helix" | "zed" => args.push(with_line()),
+        _ => args.push(file.into()),
+    }
+    args
+}
+
+/// 用配置的编辑器打开 file 的命令，由调用方决定如何等待
+pub fn editor_command(file: &Path, line: Option<u32>) -> Command {
+    let editor = configured_editor();
+    let mut command = Command::new(&editor[0]);
+    command.args(editor_args(&editor, file, line));
+    command
+}
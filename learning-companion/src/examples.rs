@@ -0,0 +1,274 @@
+//! 代码示例运行器
+//!
+//! 列出模块的示例程序，在后台用 cargo 编译运行，把输出逐行发给界面；
+//! 运行成功的示例记入数据库，作为「代码示例」任务的完成依据
+
+use crate::db::{DbResult, Store};
+use crate::repo::{LearningModule, LearningRepo};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 示例编译加运行的最长时间，超时后结束进程
+pub const EXAMPLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 模块中的一个示例程序
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExampleTarget {
+    /// cargo 的示例名（`--example` 的参数）
+    pub name: String,
+    /// 源文件
+    pub path: PathBuf,
+}
+
+/// 列出模块的示例：先是 Cargo.toml 中 `[[example]]` 声明的，再是 cargo 自动发现的 `examples/*.rs`
+///
+/// 与 cargo 的规则一致：`[package] autoexamples = false` 时只使用声明的示例
+pub fn list_examples(module_dir: &Path) -> Result<Vec<ExampleTarget>> {
+    let manifest_path = module_dir.join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("无法读取 {}", manifest_path.display()))?;
+    let table: toml::Table = manifest.parse()
+        .with_context(|| format!("{} 格式错误", manifest_path.display()))?;
+
+    let mut examples: Vec<ExampleTarget> = table.get("example")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|example| {
+            let name = example.get("name")?.as_str()?;
+            let path = example.get("path")
+                .and_then(|p| p.as_str())
+                .map_or_else(|| format!("examples/{}.rs", name), String::from);
+            Some(ExampleTarget { name: name.to_string(), path: module_dir.join(path) })
+        })
+        .collect();
+
+    let auto = table.get("package")
+        .and_then(|p| p.get("autoexamples"))
+        .and_then(|a| a.as_bool())
+        .unwrap_or(true);
+    let dir = module_dir.join("examples");
+    if auto && dir.is_dir() {
+        let mut found: Vec<ExampleTarget> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .filter(|path| !examples.iter().any(|e| &e.path == path))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                Some(ExampleTarget { name, path })
+            })
+            .filter(|found| !examples.iter().any(|e| e.name == found.name))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        examples.extend(found);
+    }
+
+    Ok(examples)
+}
+
+/// 输出来自哪个流
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// 示例运行的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunOutcome {
+    /// 进程退出（code 为退出码，被信号结束时为空）
+    Exited { success: bool, code: Option<i32> },
+    /// 超过时限被结束
+    TimedOut,
+    /// 被学习者取消
+    Cancelled,
+}
+
+impl RunOutcome {
+    pub fn success(&self) -> bool {
+        matches!(self, RunOutcome::Exited { success: true, .. })
+    }
+
+    /// 简短的结果说明
+    pub fn describe(&self) -> String {
+        match self {
+            RunOutcome::Exited { success: true, .. } => "✅ 运行成功".to_string(),
+            RunOutcome::Exited { code: Some(code), .. } => format!("❌ 退出码 {}", code),
+            RunOutcome::Exited { code: None, .. } => "❌ 被信号结束".to_string(),
+            RunOutcome::TimedOut => format!("⏰ 超过 {} 秒，已结束", EXAMPLE_TIMEOUT.as_secs()),
+            RunOutcome::Cancelled => "⏹ 已取消".to_string(),
+        }
+    }
+}
+
+/// 后台运行发出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunEvent {
+    /// 一行输出（cargo 的编译信息在 stderr 中）
+    Output(OutputStream, String),
+    /// 运行结束，之后不再有事件
+    Finished { outcome: RunOutcome, duration: Duration },
+}
+
+/// 一次在后台进行的示例运行
+///
+/// 界面每帧调用 `poll` 取出新的事件，不会阻塞；丢弃时取消运行
+#[derive(Debug)]
+pub struct ExampleRun {
+    pub example: String,
+    pub started_at: Instant,
+    events: Receiver<RunEvent>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ExampleRun {
+    /// 开始编译运行模块中的一个示例
+    pub fn start(module_dir: &Path, example: &str, timeout: Duration) -> Result<Self> {
+        let mut child = Command::new("cargo")
+            .arg("run")
+            .arg("--manifest-path").arg(module_dir.join("Cargo.toml"))
+            .args(["--color", "never", "--example", example])
+            .current_dir(module_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("无法运行 cargo")?;
+
+        let (sender, events) = mpsc::channel();
+        let readers = [
+            child.stdout.take().map(|out| forward_lines(out, OutputStream::Stdout, sender.clone())),
+            child.stderr.take().map(|err| forward_lines(err, OutputStream::Stderr, sender.clone())),
+        ];
+        let cancel = Arc::new(AtomicBool::new(false));
+        let started_at = Instant::now();
+
+        let flag = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let outcome = wait_child(&mut child, &flag, started_at, timeout);
+            // 等输出全部转发后再报告结束，界面收到 Finished 时输出已经完整
+            for reader in readers.into_iter().flatten() {
+                let _ = reader.join();
+            }
+            let _ = sender.send(RunEvent::Finished { outcome, duration: started_at.elapsed() });
+        });
+
+        Ok(Self { example: example.to_string(), started_at, events, cancel })
+    }
+
+    /// 取出目前收到的所有事件
+    pub fn poll(&self) -> Vec<RunEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// 阻塞等待下一个事件；运行线程已退出时返回 None
+    pub fn recv(&self) -> Option<RunEvent> {
+        self.events.recv().ok()
+    }
+
+    /// 请求结束运行，随后会收到 `RunOutcome::Cancelled`
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ExampleRun {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// 把一个输出流按行转发到 channel（非 UTF-8 的内容有损转换）
+fn forward_lines(stream: impl Read + Send + 'static, kind: OutputStream, sender: Sender<RunEvent>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut buf = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+            let line = String::from_utf8_lossy(&buf).trim_end_matches(['\n', '\r']).to_string();
+            buf.clear();
+            // 界面已关闭也要读完，避免子进程写满管道后卡住
+            let _ = sender.send(RunEvent::Output(kind, line));
+        }
+    })
+}
+
+/// 等待子进程结束，期间检查取消和超时
+fn wait_child(child: &mut Child, cancel: &AtomicBool, started_at: Instant, timeout: Duration) -> RunOutcome {
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return RunOutcome::Exited { success: status.success(), code: status.code() },
+            Ok(None) => {}
+            Err(_) => return RunOutcome::Exited { success: false, code: None },
+        }
+        let outcome = if cancel.load(Ordering::Relaxed) {
+            RunOutcome::Cancelled
+        } else if started_at.elapsed() > timeout {
+            RunOutcome::TimedOut
+        } else {
+            thread::sleep(Duration::from_millis(20));
+            continue;
+        };
+        let _ = child.kill();
+        let _ = child.wait();
+        return outcome;
+    }
+}
+
+/// 模块示例的运行情况
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExamplesSummary {
+    pub total: usize,
+    /// 至少成功运行过一次的示例数
+    pub succeeded: usize,
+}
+
+impl ExamplesSummary {
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.succeeded == self.total
+    }
+}
+
+/// 汇总模块示例的运行情况（读取数据库中的运行记录）
+pub fn examples_summary(store: &Store, module: &LearningModule) -> DbResult<ExamplesSummary> {
+    let examples = list_examples(&module.directory).unwrap_or_default();
+    let runs = store.get_example_runs(&module.id)?;
+    let succeeded = examples.iter()
+        .filter(|e| runs.iter().any(|r| r.example == e.name && r.successes > 0))
+        .count();
+    Ok(ExamplesSummary { total: examples.len(), succeeded })
+}
+
+/// 记录一次运行；取消的运行不算数
+///
+/// 所有示例都成功运行过后勾选 进度.md 中的「代码示例」并同步（不会因为失败取消已有的勾选）
+pub fn record_run(
+    store: &Store,
+    repo: &LearningRepo,
+    module: &LearningModule,
+    example: &str,
+    outcome: &RunOutcome,
+    duration: Duration,
+) -> Result<ExamplesSummary> {
+    if *outcome == RunOutcome::Cancelled {
+        return Ok(examples_summary(store, module)?);
+    }
+
+    store.record_example_run(&module.id, example, outcome.success(), duration.as_millis() as u64)?;
+    crate::study::touch_module(store, &module.id)?;
+
+    let summary = examples_summary(store, module)?;
+    if summary.is_complete() {
+        crate::sync::record_task(store, repo, &module.id, crate::progress::TaskType::Examples, true)?;
+        crate::sync::sync(store, repo, None)?;
+    }
+    Ok(summary)
+}
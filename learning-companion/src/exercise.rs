@@ -2,17 +2,19 @@
 //!
 //! 生成练习题、判分和分析掌握程度
 
+use crate::db::Store;
 use crate::repo::LearningRepo;
 use anyhow::Result;
-use rand::seq::SliceRandom;
 
 /// 练习题目
 #[derive(Debug, Clone, PartialEq)]
 pub struct Question {
     pub question_type: QuestionType,
     pub prompt: String,
+    /// 题目附带的 Rust 代码片段
+    pub code: Option<String>,
     pub options: Option<Vec<String>>,
-    pub correct_answer: String,
+    pub correct_answer: CorrectAnswer,
     pub explanation: String,
     pub topic: String,
 }
@@ -22,13 +24,124 @@ pub enum QuestionType {
     MultipleChoice,
     TrueFalse,
     FillInBlank,
+    /// 阅读代码，写出程序输出
+    CodeOutput,
+    /// 阅读代码，判断能否通过编译
+    CompilesOrNot,
+}
+
+impl QuestionType {
+    /// 是否需要运行代码片段来核对答案
+    pub fn is_code_question(&self) -> bool {
+        matches!(self, QuestionType::CodeOutput | QuestionType::CompilesOrNot)
+    }
+}
+
+/// 题目的正确答案
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrectAnswer {
+    /// 正确选项序号（从 0 开始）
+    Choice(usize),
+    /// 判断题的正确结论（代码题为能否编译）
+    Bool(bool),
+    /// 填空题、代码输出题的可接受答案（任意一个匹配即正确）
+    Text(Vec<String>),
+}
+
+/// 学习者的作答
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    /// 选择的选项序号（从 0 开始）
+    Choice(usize),
+    /// 判断题作答
+    Bool(bool),
+    /// 填空题作答
+    Text(String),
+}
+
+impl Question {
+    /// 判断作答是否正确，未作答或作答类型与题型不符均判为错误
+    pub fn is_correct(&self, answer: Option<&Answer>) -> bool {
+        match (&self.correct_answer, answer) {
+            (CorrectAnswer::Choice(expected), Some(Answer::Choice(given))) => expected == given,
+            (CorrectAnswer::Bool(expected), Some(Answer::Bool(given))) => expected == given,
+            // 判断题在界面上显示为「1. 正确 / 2. 错误」两个选项
+            (CorrectAnswer::Bool(expected), Some(Answer::Choice(given))) => {
+                *given < 2 && *expected == (*given == 0)
+            }
+            (CorrectAnswer::Text(accepted), Some(Answer::Text(given))) => {
+                let given = normalize_text(given);
+                !given.is_empty() && accepted.iter().any(|a| normalize_text(a) == given)
+            }
+            _ => false,
+        }
+    }
+
+    /// 把命令行输入解析为作答，无法识别时返回 None
+    pub fn parse_answer(&self, input: &str) -> Option<Answer> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+
+        match self.question_type {
+            QuestionType::MultipleChoice => {
+                let number: usize = input.parse().ok()?;
+                let option_count = self.options.as_ref().map_or(0, Vec::len);
+                (1..=option_count).contains(&number).then(|| Answer::Choice(number - 1))
+            }
+            QuestionType::TrueFalse | QuestionType::CompilesOrNot => match normalize_text(input).as_str() {
+                "1" | "正确" | "对" | "是" | "能" | "能编译" | "t" | "true" | "y" | "yes" => Some(Answer::Bool(true)),
+                "2" | "错误" | "错" | "否" | "不能" | "不能编译" | "f" | "false" | "n" | "no" => Some(Answer::Bool(false)),
+                _ => None,
+            },
+            QuestionType::FillInBlank | QuestionType::CodeOutput => Some(Answer::Text(input.to_string())),
+        }
+    }
+
+    /// 用于展示的正确答案
+    pub fn answer_text(&self) -> String {
+        match &self.correct_answer {
+            CorrectAnswer::Choice(i) => self.options.as_ref()
+                .and_then(|o| o.get(*i))
+                .map(|o| format!("{}. {}", i + 1, o))
+                .unwrap_or_else(|| (i + 1).to_string()),
+            CorrectAnswer::Bool(b) => self.options.as_ref()
+                .and_then(|o| o.get(if *b { 0 } else { 1 }))
+                .cloned()
+                .unwrap_or_else(|| if *b { "正确" } else { "错误" }.to_string()),
+            CorrectAnswer::Text(accepted) => accepted.join(" / "),
+        }
+    }
+}
+
+/// 规范化填空题文本：去除首尾空白、统一大小写、全角转半角、合并连续空白
+pub fn normalize_text(text: &str) -> String {
+    let converted: String = text.chars()
+        .map(|c| match c {
+            // 全角 ASCII 字符区间与半角相差 0xFEE0
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            '。' => '.',
+            '、' => ',',
+            '“' | '”' => '"',
+            '‘' | '’' => '\'',
+            _ => c,
+        })
+        .collect();
+
+    converted.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
 }
 
 /// 练习会话
 pub struct PracticeSession {
     pub module_id: String,
     pub questions: Vec<Question>,
-    pub answers: Vec<usize>,
+    /// 每道题的作答，None 表示未作答
+    pub answers: Vec<Option<Answer>>,
 }
 
 impl PracticeSession {
@@ -40,27 +153,30 @@ impl PracticeSession {
         }
     }
 
+    /// 第 index 题是否答对
+    pub fn is_correct(&self, index: usize) -> bool {
+        self.questions.get(index)
+            .map(|q| q.is_correct(self.answers.get(index).and_then(Option::as_ref)))
+            .unwrap_or(false)
+    }
+
+    pub fn correct_count(&self) -> usize {
+        (0..self.questions.len()).filter(|&i| self.is_correct(i)).count()
+    }
+
     pub fn score(&self) -> f32 {
         if self.questions.is_empty() {
             return 0.0;
         }
 
-        let correct = self.answers.iter()
-            .zip(self.questions.iter())
-            .filter(|(answer, q)| {
-                // 简化处理：假设答案索引对应正确选项
-                *answer == &q.correct_answer.parse().unwrap_or(0)
-            })
-            .count();
-
-        (correct as f32 / self.questions.len() as f32) * 100.0
+        (self.correct_count() as f32 / self.questions.len() as f32) * 100.0
     }
 
     pub fn weak_topics(&self) -> Vec<String> {
         let mut topics = Vec::new();
 
-        for (answer, question) in self.answers.iter().zip(self.questions.iter()) {
-            if *answer != question.correct_answer.parse().unwrap_or(0) {
+        for (i, question) in self.questions.iter().enumerate() {
+            if !self.is_correct(i) {
                 topics.push(question.topic.clone());
             }
         }
@@ -69,192 +185,79 @@ impl PracticeSession {
     }
 }
 
-/// 生成基础入门模块的练习题
-pub fn generate_basics_questions(count: usize) -> Vec<Question> {
-    let all_questions = vec![
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "Rust 中声明不可变变量的关键字是什么？".to_string(),
-            options: Some(vec![
-                "let".to_string(),
-                "var".to_string(),
-                "const".to_string(),
-                "mut".to_string(),
-            ]),
-            correct_answer: "0".to_string(),
-            explanation: "let 是 Rust 中声明变量的关键字，默认创建不可变变量。".to_string(),
-            topic: "变量声明".to_string(),
-        },
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "如何声明一个可变变量？".to_string(),
-            options: Some(vec![
-                "let mut x = 5;".to_string(),
-                "let x = 5;".to_string(),
-                "var x = 5;".to_string(),
-                "const mut x = 5;".to_string(),
-            ]),
-            correct_answer: "0".to_string(),
-            explanation: "使用 let mut 关键字可以声明可变变量。".to_string(),
-            topic: "可变性".to_string(),
-        },
-        Question {
-            question_type: QuestionType::TrueFalse,
-            prompt: "Rust 中，默认情况下变量是不可变的。".to_string(),
-            options: Some(vec!["正确".to_string(), "错误".to_string()]),
-            correct_answer: "0".to_string(),
-            explanation: "是的，Rust 默认变量不可变，这是为了安全性。".to_string(),
-            topic: "可变性".to_string(),
-        },
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "i32 类型的整数范围是多少？".to_string(),
-            options: Some(vec![
-                "-128 到 127".to_string(),
-                "0 到 255".to_string(),
-                "-2^31 到 2^31-1".to_string(),
-                "-2^63 到 2^63-1".to_string(),
-            ]),
-            correct_answer: "2".to_string(),
-            explanation: "i32 是 32 位有符号整数，范围是 -2^31 到 2^31-1。".to_string(),
-            topic: "数据类型".to_string(),
-        },
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "Rust 中哪个类型表示布尔值？".to_string(),
-            options: Some(vec![
-                "bool".to_string(),
-                "boolean".to_string(),
-                "bit".to_string(),
-                "flag".to_string(),
-            ]),
-            correct_answer: "0".to_string(),
-            explanation: "Rust 使用 bool 类型表示布尔值，值为 true 或 false。".to_string(),
-            topic: "数据类型".to_string(),
-        },
-        Question {
-            question_type: QuestionType::TrueFalse,
-            prompt: "元组可以包含不同类型的值。".to_string(),
-            options: Some(vec!["正确".to_string(), "错误".to_string()]),
-            correct_answer: "0".to_string(),
-            explanation: "是的，元组可以将不同类型的值组合在一起。".to_string(),
-            topic: "复合类型".to_string(),
-        },
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "函数返回值的表达式应该：".to_string(),
-            options: Some(vec![
-                "以分号结尾".to_string(),
-                "不以分号结尾".to_string(),
-                "使用 return 关键字".to_string(),
-                "使用 yield 关键字".to_string(),
-            ]),
-            correct_answer: "1".to_string(),
-            explanation: "Rust 中，表达式不以分号结尾会自动作为返回值。".to_string(),
-            topic: "函数".to_string(),
-        },
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "如何访问元组的第一个元素？".to_string(),
-            options: Some(vec![
-                "tuple.1".to_string(),
-                "tuple.0".to_string(),
-                "tuple[0]".to_string(),
-                "tuple.first()".to_string(),
-            ]),
-            correct_answer: "1".to_string(),
-            explanation: "使用 tuple.0 访问元组的第一个元素（索引从 0 开始）。".to_string(),
-            topic: "复合类型".to_string(),
-        },
-        Question {
-            question_type: QuestionType::TrueFalse,
-            prompt: "数组在 Rust 中可以有不同类型的元素。".to_string(),
-            options: Some(vec!["正确".to_string(), "错误".to_string()]),
-            correct_answer: "1".to_string(),
-            explanation: "错误。数组的所有元素必须是相同类型。".to_string(),
-            topic: "复合类型".to_string(),
-        },
-        Question {
-            question_type: QuestionType::MultipleChoice,
-            prompt: "char 类型在 Rust 中占用多少字节？".to_string(),
-            options: Some(vec![
-                "1 字节".to_string(),
-                "2 字节".to_string(),
-                "4 字节".to_string(),
-                "8 字节".to_string(),
-            ]),
-            correct_answer: "2".to_string(),
-            explanation: "Rust 的 char 类型是 Unicode 字符，占用 4 字节。".to_string(),
-            topic: "数据类型".to_string(),
-        },
-    ];
-
-    // 随机选择指定数量的问题
-    let mut rng = rand::thread_rng();
-    let mut indices: Vec<usize> = (0..all_questions.len()).collect();
-    indices.shuffle(&mut rng);
-
-    indices.iter()
-        .take(count.min(all_questions.len()))
-        .map(|&i| all_questions[i].clone())
-        .collect()
+/// 在命令行中提问一道题，显示对错和解析，返回学习者的作答
+pub fn ask_question(q: &Question, index: usize, total: usize) -> Result<Option<Answer>> {
+    println!("📚 题目 {}/{} - [{}]", index + 1, total, q.topic);
+    println!("{}\n", q.prompt);
+
+    if let Some(code) = &q.code {
+        for line in code.lines() {
+            println!("    {}", line);
+        }
+        println!();
+    }
+
+    if let Some(options) = &q.options {
+        for (j, opt) in options.iter().enumerate() {
+            println!("  {}. {}", j + 1, opt);
+        }
+    }
+
+    let hint = match q.question_type {
+        QuestionType::MultipleChoice => "输入选项编号",
+        QuestionType::TrueFalse => "输入 1 正确 / 2 错误",
+        QuestionType::CompilesOrNot => "输入 1 能编译 / 2 不能编译",
+        QuestionType::FillInBlank => "输入答案",
+        QuestionType::CodeOutput => "输入程序输出，多行用空格分隔",
+    };
+    print!("\n你的答案 ({}): ", hint);
+    use std::io::{self, Write};
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let answer = q.parse_answer(&input);
+
+    println!();
+    if q.is_correct(answer.as_ref()) {
+        println!("✅ 正确！");
+    } else if answer.is_none() {
+        println!("❌ 未识别的答案，正确答案：{}", q.answer_text());
+    } else {
+        println!("❌ 错误！正确答案：{}", q.answer_text());
+    }
+
+    println!("💡 解析：{}\n", q.explanation);
+    println!("{}\n", "─".repeat(50));
+
+    Ok(answer)
 }
 
 /// 运行练习测试
-pub fn run_practice(_repo: &LearningRepo, module_id: &str, count: usize) -> Result<()> {
+pub fn run_practice(store: &Store, repo: &LearningRepo, module_id: &str, count: usize) -> Result<()> {
     println!("\n╔════════════════════════════════════════╗");
     println!("║        📝 Rust 学习伴侣 - 练习测试      ║");
     println!("╚════════════════════════════════════════╝\n");
 
-    let questions = match module_id {
-        "module-01-basics" | "01-基础入门" | "basics" => generate_basics_questions(count),
-        _ => {
-            println!("❌ 暂不支持该模块的练习题");
-            println!("💡 目前支持：module-01-basics（基础入门）");
-            return Ok(());
-        }
+    let Some(module) = repo.find_module(module_id) else {
+        println!("❌ 未找到模块：{}", module_id);
+        return Ok(());
     };
+    let module_id = module.id.as_str();
+
+    let questions = crate::questions::pick_questions(module, count)?;
 
     if questions.is_empty() {
-        println!("❌ 没有可用的练习题");
+        println!("❌ 该模块还没有可用的练习题");
+        println!("💡 在 {}/questions.toml 中添加题目", module_id);
         return Ok(());
     }
 
     let mut session = PracticeSession::new(module_id.to_string(), questions);
-    let mut correct_count = 0;
 
     for (i, q) in session.questions.iter().enumerate() {
-        println!("📚 题目 {}/{} - [{}]", i + 1, session.questions.len(), q.topic);
-        println!("{}\n", q.prompt);
-
-        if let Some(options) = &q.options {
-            for (j, opt) in options.iter().enumerate() {
-                println!("  {}. {}", j + 1, opt);
-            }
-        }
-
-        print!("\n你的答案 (输入选项编号): ");
-        use std::io::{self, Write};
-        io::stdout().flush()?;
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let answer: usize = input.trim().parse().unwrap_or(0);
-
-        // 记录答案（转换为 0-indexed）
-        let answer_index = if answer > 0 { answer - 1 } else { 0 };
-        session.answers.push(answer_index);
-
-        println!();
-        if answer_index == q.correct_answer.parse().unwrap_or(0) {
-            println!("✅ 正确！");
-            correct_count += 1;
-        } else {
-            println!("❌ 错误！");
-        }
-
-        println!("💡 解析：{}\n", q.explanation);
-        println!("{}\n", "─".repeat(50));
+        let answer = ask_question(q, i, session.questions.len())?;
+        session.answers.push(answer);
     }
 
     // 显示结果
@@ -264,6 +267,7 @@ pub fn run_practice(_repo: &LearningRepo, module_id: &str, count: usize) -> Resu
     println!("\n╔════════════════════════════════════════╗");
     println!("║            📊 测试结果                ║");
     println!("╚════════════════════════════════════════╝");
+    let correct_count = session.correct_count();
     println!("\n正确率：{:.1}% ({}/{})",
         score, correct_count, session.questions.len());
 
@@ -284,14 +288,19 @@ pub fn run_practice(_repo: &LearningRepo, module_id: &str, count: usize) -> Resu
         }
     }
 
+    // 记录每道题的作答，安排复习
+    crate::review::record_session(store, &session, &vec![module_id.to_string(); session.questions.len()])?;
+
     // 记录结果
-    crate::db::record_practice_result(
+    store.record_practice_result(
         module_id,
         session.questions.len() as u32,
         correct_count as u32,
         score,
         weak,
     )?;
+    crate::study::record_practice(store, module_id)?;
+    crate::achievements::check_and_report(store, repo)?;
 
     Ok(())
 }
@@ -0,0 +1,463 @@
+//! 分级提示与答案
+//!
+//! 每个练习依次给出：练习注释中的提示、答案的骨架（函数体换成 `todo!()`）、完整答案，
+//! 以及学习者代码与答案的逐行对比。测试通过前看过答案会记录下来，计入掌握程度时打折扣
+
+use crate::db::{DbResult, Store};
+use crate::repo::LearningModule;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// 提示的级别，依次解锁
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    /// 练习注释中的提示
+    Hints,
+    /// 答案的骨架：保留类型和函数签名，函数体换成 `todo!()`
+    Skeleton,
+    /// 完整答案
+    Solution,
+    /// 学习者的代码与答案的对比
+    Diff,
+}
+
+impl HintLevel {
+    /// 按解锁顺序排列的各级提示
+    pub const ALL: [HintLevel; 4] = [HintLevel::Hints, HintLevel::Skeleton, HintLevel::Solution, HintLevel::Diff];
+
+    /// 数据库中使用的键
+    pub fn key(self) -> &'static str {
+        match self {
+            HintLevel::Hints => "hints",
+            HintLevel::Skeleton => "skeleton",
+            HintLevel::Solution => "solution",
+            HintLevel::Diff => "diff",
+        }
+    }
+
+    /// 由数据库中的键得到提示级别
+    pub fn from_key(key: &str) -> Option<HintLevel> {
+        HintLevel::ALL.into_iter().find(|level| level.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HintLevel::Hints => "提示",
+            HintLevel::Skeleton => "骨架",
+            HintLevel::Solution => "答案",
+            HintLevel::Diff => "对比答案",
+        }
+    }
+
+    /// 是否展示了答案的完整实现（测试通过前查看会影响掌握程度）
+    pub fn reveals_solution(self) -> bool {
+        matches!(self, HintLevel::Solution | HintLevel::Diff)
+    }
+
+    pub fn next(self) -> Option<HintLevel> {
+        HintLevel::ALL.iter().copied().find(|level| *level > self)
+    }
+
+    pub fn prev(self) -> Option<HintLevel> {
+        HintLevel::ALL.iter().rev().copied().find(|level| *level < self)
+    }
+}
+
+/// 测试通过前看过答案的练习，通过后按这个比例计入掌握程度
+pub const ASSISTED_CREDIT: f32 = 0.5;
+
+/// 练习注释中作为提示的章节
+const HINT_SECTIONS: [&str; 6] = ["任务", "提示", "前置知识", "公式", "注意", "TODO"];
+
+/// 一条提示
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    /// 来源：文档注释的章节名，或者「提示」注释所在的行号
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// 练习的答案文件（`exercises/solutions/` 下的同名文件）
+pub fn solution_file(module_dir: &Path, exercise: &str) -> PathBuf {
+    module_dir.join("exercises").join("solutions").join(format!("{}.rs", exercise))
+}
+
+/// 从练习源码的注释中提取提示
+///
+/// 包括文档注释中「任务」「提示」「前置知识」等章节，以及含有「提示」的普通注释（连同紧跟的注释行）
+pub fn extract_hints(source: &str) -> Vec<Hint> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut hints: Vec<Hint> = Vec::new();
+    // 正在收集的文档注释章节
+    let mut section: Option<Hint> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim_start();
+        let doc = trimmed.strip_prefix("//!").or_else(|| trimmed.strip_prefix("///"));
+
+        if let Some(text) = doc {
+            let text = text.strip_prefix(' ').unwrap_or(text);
+            if let Some(heading) = text.strip_prefix('#') {
+                hints.extend(section.take().filter(|h| !h.lines.is_empty()));
+                let title = heading.trim_start_matches('#').trim();
+                if HINT_SECTIONS.contains(&title) {
+                    section = Some(Hint { title: title.to_string(), lines: Vec::new() });
+                }
+            } else if let Some(hint) = &mut section {
+                if !text.trim().is_empty() {
+                    hint.lines.push(text.trim_end().to_string());
+                }
+            }
+            i += 1;
+            continue;
+        }
+        hints.extend(section.take().filter(|h| !h.lines.is_empty()));
+
+        // 普通注释中的「提示」，后面连续的注释行一起算作这条提示
+        let comment = trimmed.strip_prefix("//").map(str::trim);
+        if let Some(text) = comment.and_then(|c| c.strip_prefix("提示")) {
+            let mut hint = Hint { title: format!("第 {} 行", i + 1), lines: Vec::new() };
+            let first = text.trim_start_matches([':', '：']).trim();
+            if !first.is_empty() {
+                hint.lines.push(first.to_string());
+            }
+            i += 1;
+            while let Some(next) = lines.get(i).and_then(|l| l.trim_start().strip_prefix("//")) {
+                if next.starts_with(['/', '!']) || next.trim().is_empty() {
+                    break;
+                }
+                hint.lines.push(next.trim().to_string());
+                i += 1;
+            }
+            if !hint.lines.is_empty() {
+                hints.push(hint);
+            }
+            continue;
+        }
+        i += 1;
+    }
+    hints.extend(section.filter(|h| !h.lines.is_empty()));
+
+    hints
+}
+
+/// 标出源码中哪些字节是代码（注释、字符串和字符字面量中的为 false）
+fn code_mask(source: &str) -> Vec<bool> {
+    let bytes = source.as_bytes();
+    let len = bytes.len();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut mask = vec![true; len];
+    let mut i = 0;
+
+    while i < len {
+        let start = i;
+        let next = bytes.get(i + 1).copied();
+        match bytes[i] {
+            b'/' if next == Some(b'/') => {
+                while i < len && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if next == Some(b'*') => {
+                let mut depth = 0;
+                while i < len {
+                    if bytes[i..].starts_with(b"/*") {
+                        depth += 1;
+                        i += 2;
+                    } else if bytes[i..].starts_with(b"*/") {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            b'"' => {
+                i += 1;
+                while i < len {
+                    match bytes[i] {
+                        b'\\' => i += 2,
+                        b'"' => {
+                            i += 1;
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+            }
+            // 原始字符串 r"..." / r#"..."#（也包括 br"..."）
+            b'r' if matches!(next, Some(b'"' | b'#'))
+                && (i == 0 || !is_ident(bytes[i - 1]) || (bytes[i - 1] == b'b' && (i < 2 || !is_ident(bytes[i - 2])))) =>
+            {
+                let hashes = bytes[i + 1..].iter().take_while(|&&b| b == b'#').count();
+                if bytes.get(i + 1 + hashes) != Some(&b'"') {
+                    i += 1;
+                    continue;
+                }
+                let closing = format!("\"{}", "#".repeat(hashes));
+                i += 2 + hashes;
+                while i < len && !bytes[i..].starts_with(closing.as_bytes()) {
+                    i += 1;
+                }
+                i = (i + closing.len()).min(len);
+            }
+            // 字符字面量；单引号后面不是一个字符加单引号的是生命周期
+            b'\'' => {
+                if next == Some(b'\\') {
+                    // 跳过反斜杠和被转义的字符（可能就是单引号）
+                    i += 3;
+                    while i < len && bytes[i] != b'\'' {
+                        i += 1;
+                    }
+                    i = (i + 1).min(len);
+                } else {
+                    let width = source[i + 1..].chars().next().map_or(0, char::len_utf8);
+                    if width > 0 && bytes.get(i + 1 + width) == Some(&b'\'') {
+                        i += 2 + width;
+                    } else {
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        for m in &mut mask[start..i.min(len)] {
+            *m = false;
+        }
+    }
+
+    mask
+}
+
+/// 代码中的一个带块的条目（函数或内联模块）：关键字位置、名称、块的左右花括号位置
+struct Item {
+    start: usize,
+    name: String,
+    open: usize,
+    close: usize,
+}
+
+/// 找出 keyword（`fn` 或 `mod`）开头、带有 `{ ... }` 块的条目；只有声明（以 `;` 结尾）的跳过
+fn find_items(source: &str, mask: &[bool], keyword: &str) -> Vec<Item> {
+    let bytes = source.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut items = Vec::new();
+
+    for (start, _) in source.match_indices(keyword) {
+        let end = start + keyword.len();
+        if !mask[start]
+            || (start > 0 && is_ident(bytes[start - 1]))
+            || !bytes.get(end).is_some_and(|b| b.is_ascii_whitespace())
+        {
+            continue;
+        }
+        let name: String = source[end..].trim_start().chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if name.is_empty() {
+            continue;
+        }
+
+        // 签名中的 `[u8; 4]`、`(a, b)` 不算结束
+        let mut depth = 0i32;
+        let mut open = None;
+        for (i, &b) in bytes.iter().enumerate().skip(end) {
+            if !mask[i] {
+                continue;
+            }
+            match b {
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => depth -= 1,
+                b';' if depth == 0 => break,
+                b'{' if depth == 0 => {
+                    open = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let Some(open) = open else {
+            continue;
+        };
+
+        let mut depth = 0;
+        let close = (open..bytes.len()).find(|&i| {
+            if mask[i] {
+                match bytes[i] {
+                    b'{' => depth += 1,
+                    b'}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            depth == 0
+        });
+        if let Some(close) = close {
+            items.push(Item { start, name, open, close });
+        }
+    }
+
+    items
+}
+
+/// 由答案生成骨架：去掉普通注释行（常是备选实现），函数体换成 `todo!()`
+///
+/// `main` 和 `mod tests` 中的函数保持原样，文档注释（包括解答要点）保留
+pub fn skeleton(solution: &str) -> String {
+    let source: String = solution.lines()
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            !trimmed.starts_with("//") || trimmed.starts_with("///") || trimmed.starts_with("//!")
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let mask = code_mask(&source);
+
+    let tests: Vec<(usize, usize)> = find_items(&source, &mask, "mod")
+        .into_iter()
+        .filter(|m| m.name == "tests")
+        .map(|m| (m.open, m.close))
+        .collect();
+
+    let mut result = String::with_capacity(source.len());
+    let mut copied = 0;
+    for item in find_items(&source, &mask, "fn") {
+        if item.start < copied || item.name == "main" || tests.iter().any(|&(open, close)| item.start > open && item.start < close) {
+            continue;
+        }
+        let line_start = source[..item.start].rfind('\n').map_or(0, |i| i + 1);
+        let indent: String = source[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+
+        result.push_str(&source[copied..=item.open]);
+        result.push_str(&format!("\n{}    todo!()\n{}", indent, indent));
+        copied = item.close;
+    }
+    result.push_str(&source[copied..]);
+
+    // 去掉注释行后留下的连续空行只保留一行
+    while result.contains("\n\n\n") {
+        result = result.replace("\n\n\n", "\n\n");
+    }
+    result
+}
+
+/// 对比中一行的来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffOp {
+    Same,
+    /// 只在学习者的代码中
+    Removed,
+    /// 只在答案中
+    Added,
+}
+
+/// 对比结果的一行（行号从 1 开始）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+}
+
+/// 逐行对比（最长公共子序列），行尾空白不计
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let same = |i: usize, j: usize| old[i].trim_end() == new[j].trim_end();
+
+    // lcs[i][j]：old[i..] 与 new[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if same(i, j) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && same(i, j) {
+            diff.push(DiffLine { op: DiffOp::Same, text: new[j].to_string(), old_line: Some(i + 1), new_line: Some(j + 1) });
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            // 同一处改动先列出删除的行，再列出新增的行
+            diff.push(DiffLine { op: DiffOp::Removed, text: old[i].to_string(), old_line: Some(i + 1), new_line: None });
+            i += 1;
+        } else {
+            diff.push(DiffLine { op: DiffOp::Added, text: new[j].to_string(), old_line: None, new_line: Some(j + 1) });
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// 折叠后对比中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffRow {
+    Line(DiffLine),
+    /// 省略的相同行数
+    Skipped(usize),
+}
+
+/// 只保留改动及其前后 context 行，其余相同的行折叠
+pub fn collapse(diff: &[DiffLine], context: usize) -> Vec<DiffRow> {
+    let changed: Vec<usize> = diff.iter().enumerate()
+        .filter(|(_, line)| line.op != DiffOp::Same)
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= context);
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for (i, line) in diff.iter().enumerate() {
+        if near_change(i) {
+            if skipped > 0 {
+                rows.push(DiffRow::Skipped(skipped));
+                skipped = 0;
+            }
+            rows.push(DiffRow::Line(line.clone()));
+        } else {
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        rows.push(DiffRow::Skipped(skipped));
+    }
+    rows
+}
+
+/// 练习当前是否已通过测试
+pub fn exercise_passed(store: &Store, module_id: &str, exercise: &str) -> DbResult<bool> {
+    Ok(store.get_exercise_results(module_id)?
+        .iter()
+        .any(|r| r.exercise == exercise && r.passed()))
+}
+
+/// 练习在测试通过前是否看过答案
+pub fn viewed_before_pass(store: &Store, module_id: &str, exercise: &str) -> DbResult<bool> {
+    Ok(store.get_solution_views(module_id)?
+        .iter()
+        .any(|v| v.exercise == exercise && v.before_pass && HintLevel::from_key(&v.level).is_some_and(HintLevel::reveals_solution)))
+}
+
+/// 记录一次查看骨架或答案，返回是否在测试通过前查看（注释中的提示不记录）
+///
+/// 读不到测试结果时返回错误且不记录，不能把查看当作通过前查看而降低掌握程度
+pub fn record_view(store: &Store, module: &LearningModule, exercise: &str, level: HintLevel) -> Result<bool> {
+    let before_pass = !exercise_passed(store, &module.id, exercise)?;
+    if level != HintLevel::Hints {
+        store.record_solution_view(&module.id, exercise, level.key(), before_pass)?;
+    }
+    Ok(before_pass)
+}
@@ -1,10 +1,31 @@
 //! 学习伴侣库 - 暴露公共 API 给测试使用
 
+pub mod achievements;
+pub mod checker;
+pub mod checklist;
+pub mod classroom;
+pub mod clock;
+pub mod daemon;
 pub mod db;
+pub mod doctor;
+pub mod editor;
+pub mod examples;
 pub mod exercise;
+pub mod hints;
+pub mod markdown;
+pub mod migrate;
+pub mod path;
 pub mod progress;
+pub mod questions;
+pub mod render;
+pub mod report;
 pub mod repo;
+pub mod review;
+pub mod search;
 pub mod storage;
+pub mod streak;
+pub mod study;
+pub mod sync;
 pub mod notify;
 
 // 暴露 TUI 模块给测试
@@ -2,16 +2,14 @@
 //!
 //! 一个帮助追踪 Rust 学习进度、提供练习和激励的命令行工具
 
-mod db;
-mod exercise;
-mod progress;
-mod repo;
-mod storage;
 mod ui;
-mod notify;
 mod tui;
 
+// 核心逻辑由库提供，二进制只负责命令行与界面
+use learning_companion::{achievements, checker, checklist, classroom, clock, daemon, db, doctor, editor, examples, exercise, hints, migrate, notify, path, progress, questions, render, report, repo, review, search, storage, streak, study, sync};
+
 use clap::{Parser, Subcommand};
+use chrono::Local;
 use anyhow::Result;
 
 #[derive(Parser)]
@@ -23,14 +21,22 @@ struct Cli {
     #[arg(short, long, global = true, default_value = ".")]
     path: String,
 
-    /// 启动交互式 TUI 模式
-    #[arg(short, long, global = true)]
+    /// 启动交互式 TUI 模式（`-t` 留给 update 的 --task）
+    #[arg(long, global = true)]
     tui: bool,
 
     /// 启动交互式 TUI 模式（简写）
     #[arg(short = 'i', long, global = true)]
     interactive: bool,
 
+    /// 数据目录（默认 ~/.learning-companion，也可以用环境变量 LEARNING_COMPANION_DATA_DIR 指定）
+    #[arg(long, global = true)]
+    data_dir: Option<std::path::PathBuf>,
+
+    /// 学习档案，每个档案的学习数据单独保存（也可以用环境变量 LEARNING_COMPANION_PROFILE 指定）
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -61,60 +67,392 @@ enum Commands {
         #[arg(short, long, default_value = "5")]
         count: usize,
     },
-    /// 设置提醒
+    /// 复习今天到期的题目（间隔重复）
+    Review {
+        /// 本次最多复习的题目数量
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// 校验各模块题库（questions.toml / questions.json），代码题会实际编译运行
+    ValidateQuestions {
+        /// 只校验指定模块
+        #[arg(short, long)]
+        module: Option<String>,
+        /// 为缺少答案的代码题写入实际运行结果
+        #[arg(short, long)]
+        record: bool,
+    },
+    /// 学习计时（开始、结束、查看状态）
+    Study {
+        #[command(subcommand)]
+        action: StudyAction,
+    },
+    /// 在数据库与 进度.md 之间同步任务状态
+    Sync {
+        /// 冲突时以哪一侧为准（file 或 db），不指定时只列出冲突
+        #[arg(long)]
+        prefer: Option<String>,
+    },
+    /// 设置提醒计划（由提醒守护进程发送）
     Remind {
         /// 小时 (0-23)
-        #[arg(short, long)]
-        hour: u8,
+        #[arg(short = 'H', long, requires = "minute")]
+        hour: Option<u8>,
         /// 分钟 (0-59)
+        #[arg(short = 'M', long, requires = "hour")]
+        minute: Option<u8>,
+        /// 提醒的星期（如 mon,wed,fri、weekdays、weekends，默认每天）
+        #[arg(short, long, default_value = "daily")]
+        days: String,
+        /// 安静日，不提醒（星期如 sun，或日期如 2024-12-25），可重复
         #[arg(short, long)]
-        minute: u8,
+        quiet: Vec<String>,
+        /// 清空已有的提醒计划和安静日
+        #[arg(long)]
+        clear: bool,
+    },
+    /// 提醒守护进程（启动、停止、查看状态）
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+    /// 运行练习题测试并记录结果
+    Check {
+        /// 模块名称（如 module-07-collections）
+        #[arg(short, long)]
+        module: String,
+        /// 只检查指定练习（如 exercise5_merge_maps）
+        #[arg(short, long)]
+        exercise: Option<String>,
     },
     /// 显示成就
     Achievements,
-    /// 导出学习数据
-    Export,
+    /// 学习路线：先修关系图、模块解锁状态和推荐的下一个模块
+    Path {
+        /// 只查看指定路线（不切换），full 为完整路线
+        #[arg(long)]
+        track: Option<String>,
+        #[command(subcommand)]
+        action: Option<PathAction>,
+    },
+    /// 在所有模块的讲解、示例、练习和答案中全文搜索
+    Search {
+        /// 搜索内容，如 "Rc<RefCell<T>>"、"生命周期"、"?"
+        query: String,
+        /// 只搜索指定模块
+        #[arg(short, long)]
+        module: Option<String>,
+        /// 最多显示的结果数
+        #[arg(short, long, default_value_t = search::DEFAULT_LIMIT)]
+        limit: usize,
+    },
+    /// 课堂模式：学员身份、签名的学习数据包和讲师的班级名册
+    Class {
+        #[command(subcommand)]
+        action: ClassAction,
+    },
+    /// 连续学习天数、休息日、冻结日和时区
+    Streak {
+        #[command(subcommand)]
+        action: Option<StreakAction>,
+    },
+    /// 导出学习数据（所有数据表）
+    Export {
+        /// 导出格式
+        #[arg(short, long, value_enum, ignore_case = true, default_value_t = storage::ExportFormat::Json)]
+        format: storage::ExportFormat,
+        /// 输出路径（csv 为目录），默认写到当前目录
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// 生成学习报告（Markdown 或单文件 HTML）
+    Report {
+        /// 报告格式
+        #[arg(short, long, value_enum, ignore_case = true, default_value_t = report::ReportFormat::Markdown)]
+        format: report::ReportFormat,
+        /// 时间范围
+        #[arg(long, value_enum, ignore_case = true, default_value_t = report::Period::Week)]
+        period: report::Period,
+        /// 输出文件，默认写到当前目录
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// 列出学习档案
+    Profiles,
+    /// 数据库维护
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// 从 JSON 导出文件导入学习数据
+    Import {
+        /// export 生成的 JSON 文件
+        file: String,
+        /// 清空本机数据后导入（默认合并，已有的记录保留）
+        #[arg(long)]
+        replace: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbAction {
+    /// 升级数据库结构（迁移前自动备份）
+    Migrate {
+        /// 只列出将要执行的迁移，不修改数据库
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 检查数据库中无法读取的记录
+    Doctor {
+        /// 删除损坏的记录（修复前自动备份）
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DaemonAction {
+    /// 在后台启动
+    Start,
+    /// 停止
+    Stop,
+    /// 查看运行状态和提醒计划
+    Status,
+    /// 在前台运行（由 start 调用）
+    #[command(hide = true)]
+    Run,
+}
+
+#[derive(Subcommand)]
+enum PathAction {
+    /// 列出所有学习路线
+    Tracks,
+    /// 切换学习路线（如 concurrency），full 为完整路线
+    Use { track: String },
+}
+
+#[derive(Subcommand)]
+enum ClassAction {
+    /// 设置学员名字（第一次运行时生成签名密钥）
+    Join { name: String },
+    /// 生成签名的学习数据包，交给讲师
+    Bundle {
+        /// 输出文件或目录（如共享文件夹），默认写到当前目录
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// 校验并合并目录中的数据包，显示班级名册（讲师使用）
+    Roster {
+        /// 数据包目录，默认使用上次的目录
+        dir: Option<String>,
+        /// 同时导出 CSV 名册
+        #[arg(long)]
+        csv: Option<String>,
+        /// 多少天没有学习算停滞
+        #[arg(long, default_value_t = classroom::DEFAULT_STALLED_DAYS)]
+        stalled_days: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum StreakAction {
+    /// 设置每周休息日（如 sun、sat,sun），none 清除
+    Rest { days: String },
+    /// 冻结一天（默认今天），这天不学习不中断连续记录
+    Freeze {
+        /// 日期（YYYY-MM-DD）
+        date: Option<String>,
+        /// 取消冻结
+        #[arg(long)]
+        remove: bool,
+    },
+    /// 设置划分日期的时区：local（系统时区）、UTC 或 +08:00 等固定偏移
+    Timezone { zone: String },
+}
+
+#[derive(Subcommand)]
+enum StudyAction {
+    /// 开始计时
+    Start {
+        /// 使用番茄钟，可指定专注分钟数（默认 25）
+        #[arg(long, num_args = 0..=1, default_missing_value = "25")]
+        pomodoro: Option<u32>,
+        /// 备注
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// 结束计时并记录
+    Stop {
+        /// 备注（追加到开始时的备注后）
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// 查看当前计时
+    Status,
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let result = run(Cli::parse());
+    let corrupt = result.as_ref().err()
+        .and_then(|e| e.downcast_ref::<db::DbError>())
+        .is_some_and(|e| e.is_corrupt() && !matches!(e, db::DbError::Corrupt { .. }));
+    if corrupt {
+        eprintln!("💡 数据库可能已损坏，运行 learning-companion db doctor 检查");
+    }
+    result
+}
+
+fn run(cli: Cli) -> Result<()> {
+
+    let data_dir = db::resolve_data_dir(cli.data_dir.as_deref(), cli.profile.as_deref())?;
 
-    // 确保数据库存在
-    db::init_db()?;
+    // db 命令自行处理迁移和检查，其余命令打开数据库时升级到最新结构
+    if let Some(Commands::Db { action }) = &cli.command {
+        let mut store = db::Store::open_unmigrated(&data_dir)?;
+        match action {
+            DbAction::Migrate { dry_run } => migrate::run_migrate(&mut store, *dry_run)?,
+            DbAction::Doctor { fix } => doctor::run_doctor(&mut store, *fix)?,
+        }
+        return Ok(());
+    }
+    let store = db::Store::open(&data_dir)?;
 
     // 检查是否启动 TUI 模式
     let use_tui = cli.tui || cli.interactive || cli.command.is_none();
 
     if use_tui {
         // 启动 TUI 模式，传递项目路径
-        tui::run_tui(&cli.path)?;
+        tui::run_tui(store, &cli.path)?;
     } else {
         // CLI 模式
+        let store = &store;
+        study::print_recovery(&study::recover(store, Local::now())?);
+
         if let Some(command) = cli.command {
             match command {
                 Commands::Dashboard { path } => {
-                    ui::show_dashboard(&path)?;
+                    ui::show_dashboard(store, &path)?;
                 }
                 Commands::Update { module, task } => {
                     let repo = repo::LearningRepo::new(&cli.path)?;
-                    progress::update_task_status(&repo, &module, &task)?;
+                    progress::update_task_status(store, &repo, &module, &task)?;
                     println!("✅ 已更新 {} 的 {} 任务状态", module, task);
                     ui::show_encouragement();
                 }
                 Commands::Practice { module, count } => {
                     let repo = repo::LearningRepo::new(&cli.path)?;
-                    exercise::run_practice(&repo, &module, count)?;
+                    exercise::run_practice(store, &repo, &module, count)?;
+                }
+                Commands::Review { limit } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    review::run_review(store, &repo, limit)?;
+                }
+                Commands::ValidateQuestions { module, record } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    let modules = match module {
+                        Some(key) => vec![repo.find_module(&key)
+                            .ok_or_else(|| anyhow::anyhow!("未找到模块：{}", key))?
+                            .clone()],
+                        None => repo.modules.clone(),
+                    };
+                    questions::run_validate(&modules, record)?;
+                }
+                Commands::Study { action } => {
+                    match action {
+                        StudyAction::Start { pomodoro, note } => study::run_start(store, note, pomodoro)?,
+                        StudyAction::Stop { note } => study::run_stop(store, note)?,
+                        StudyAction::Status => study::run_status(store)?,
+                    }
+                    // 结束计时会改变连续学习天数
+                    if let Ok(repo) = repo::LearningRepo::new(&cli.path) {
+                        achievements::check_and_report(store, &repo)?;
+                    }
+                }
+                Commands::Sync { prefer } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    let prefer = match prefer.as_deref() {
+                        Some(side) => Some(sync::Side::from_str(side)
+                            .ok_or_else(|| anyhow::anyhow!("--prefer 只支持 file 或 db：{}", side))?),
+                        None => None,
+                    };
+                    sync::run_sync(store, &repo, prefer)?;
+                }
+                Commands::Check { module, exercise } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    checker::run_check(store, &repo, &module, exercise.as_deref())?;
                 }
-                Commands::Remind { hour, minute } => {
-                    notify::set_reminder(hour, minute)?;
-                    println!("⏰ 已设置提醒时间为 {:02}:{:02}", hour, minute);
-                    println!("💡 学习伴侣将在此时间提醒你学习");
+                Commands::Remind { hour, minute, days, quiet, clear } => {
+                    let time = hour.zip(minute);
+                    daemon::run_remind(store, time, &days, &quiet, clear)?;
                 }
+                Commands::Daemon { action } => match action {
+                    DaemonAction::Start => {
+                        let pid = daemon::start(store)?;
+                        println!("🟢 提醒守护进程已启动（PID {}）", pid);
+                    }
+                    DaemonAction::Stop => match daemon::stop(store)? {
+                        Some(pid) => println!("⏹️  提醒守护进程已停止（PID {}）", pid),
+                        None => println!("⚪ 提醒守护进程未运行"),
+                    },
+                    DaemonAction::Status => daemon::run_status(store)?,
+                    DaemonAction::Run => daemon::run_foreground(store)?,
+                },
                 Commands::Achievements => {
-                    ui::show_achievements()?;
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    achievements::check_and_report(store, &repo)?;
+                    ui::show_achievements(store, &repo)?;
+                }
+                Commands::Path { track, action } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    match action {
+                        None => path::run_show(store, &repo, track.as_deref())?,
+                        Some(PathAction::Tracks) => path::run_tracks(store, &repo)?,
+                        Some(PathAction::Use { track }) => path::run_use(store, &repo, &track)?,
+                    }
+                }
+                Commands::Search { query, module, limit } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    search::run_search(store, &repo, &query, module.as_deref(), limit)?;
+                }
+                Commands::Class { action } => match action {
+                    ClassAction::Join { name } => classroom::run_join(store, &name)?,
+                    ClassAction::Bundle { output } => {
+                        let repo = repo::LearningRepo::new(&cli.path)?;
+                        classroom::run_bundle(store, &repo, output.as_deref().map(std::path::Path::new))?;
+                    }
+                    ClassAction::Roster { dir, csv, stalled_days } => {
+                        let repo = repo::LearningRepo::new(&cli.path)?;
+                        classroom::run_roster(store, &repo, dir.as_deref().map(std::path::Path::new),
+                            csv.as_deref().map(std::path::Path::new), stalled_days)?;
+                    }
+                },
+                Commands::Streak { action } => match action {
+                    None => streak::run_status(store)?,
+                    Some(StreakAction::Rest { days }) => streak::run_rest(store, &days)?,
+                    Some(StreakAction::Freeze { date, remove }) => streak::run_freeze(store, date.as_deref(), remove)?,
+                    Some(StreakAction::Timezone { zone }) => streak::run_timezone(store, &zone)?,
+                },
+                Commands::Export { format, output } => {
+                    storage::run_export(store, format, output.as_deref().map(std::path::Path::new))?;
+                }
+                Commands::Report { format, period, output } => {
+                    let repo = repo::LearningRepo::new(&cli.path)?;
+                    report::run_report(store, &repo, format, period, output.as_deref().map(std::path::Path::new))?;
+                }
+                Commands::Profiles => {
+                    let profiles = db::list_profiles();
+                    if profiles.is_empty() {
+                        println!("还没有学习档案（创建：learning-companion --profile <名称> dashboard）");
+                    }
+                    for name in profiles {
+                        println!("  • {}", name);
+                    }
+                    println!("📁 当前数据目录：{}", data_dir.display());
                 }
-                Commands::Export => {
-                    storage::export_data()?;
+                Commands::Db { .. } => unreachable!("db 命令已在打开数据库前处理"),
+                Commands::Import { file, replace } => {
+                    let mode = if replace { db::ImportMode::Replace } else { db::ImportMode::Merge };
+                    storage::run_import(store, std::path::Path::new(&file), mode)?;
                 }
             }
         }
@@ -0,0 +1,170 @@
+//! Markdown 解析模块
+//!
+//! 解析仓库中 Markdown 文件的标题分节、任务清单和 front matter，供进度、自检清单等模块使用
+
+/// 一个标题及其下属内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// 标题级别（`##` 为 2）
+    pub level: usize,
+    pub title: String,
+    /// 标题所在行号（从 0 开始）
+    pub line: usize,
+    /// 本节结束行号（不含），即下一个同级或更高级标题所在行
+    pub end: usize,
+}
+
+impl Section {
+    /// 本节正文（不含标题行）
+    pub fn body<'a>(&self, lines: &[&'a str]) -> Vec<(usize, &'a str)> {
+        ((self.line + 1)..self.end.min(lines.len()))
+            .map(|i| (i, lines[i]))
+            .collect()
+    }
+}
+
+/// 解析标题行，返回 (级别, 标题)
+pub(crate) fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim().to_string()))
+}
+
+/// 是否为代码块围栏行
+pub(crate) fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// 标出每一行是否位于代码块中（围栏行本身也算）
+pub fn code_block_mask(lines: &[&str]) -> Vec<bool> {
+    let mut in_code = false;
+    lines.iter()
+        .map(|line| {
+            if is_fence(line) {
+                in_code = !in_code;
+                true
+            } else {
+                in_code
+            }
+        })
+        .collect()
+}
+
+/// 解析文档中的所有标题分节（忽略代码块内的 `#`）
+pub fn parse_sections(content: &str) -> Vec<Section> {
+    let lines: Vec<&str> = content.lines().collect();
+    let in_code = code_block_mask(&lines);
+
+    let headings: Vec<(usize, usize, String)> = lines.iter()
+        .enumerate()
+        .filter(|(i, _)| !in_code[*i])
+        .filter_map(|(i, line)| parse_heading(line).map(|(level, title)| (i, level, title)))
+        .collect();
+
+    headings.iter()
+        .enumerate()
+        .map(|(n, (line, level, title))| {
+            let end = headings[n + 1..].iter()
+                .find(|(_, next_level, _)| next_level <= level)
+                .map_or(lines.len(), |(next_line, _, _)| *next_line);
+            Section { level: *level, title: title.clone(), line: *line, end }
+        })
+        .collect()
+}
+
+/// 解析任务清单行 `- [ ] xxx` / `- [x] xxx`，返回 (是否勾选, 文本)
+pub fn parse_task(line: &str) -> Option<(bool, String)> {
+    let trimmed = line.trim_start();
+    let rest = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* "))?;
+
+    let (checked, text) = if let Some(text) = rest.strip_prefix("[ ]") {
+        (false, text)
+    } else if let Some(text) = rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]")) {
+        (true, text)
+    } else {
+        return None;
+    };
+
+    Some((checked, text.trim().to_string()))
+}
+
+/// 读取文档开头 `---` 包围的 front matter，返回 (键, 值) 列表
+///
+/// 只支持 `key: value`、`key: [a, b]` 和以 `- ` 开头的列表项（列表项的键为上一个键）
+pub fn front_matter(content: &str) -> Option<Vec<(String, String)>> {
+    let mut lines = content.lines();
+    if lines.next()?.trim() != "---" {
+        return None;
+    }
+
+    let mut entries = Vec::new();
+    let mut current_key: Option<String> = None;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed == "---" {
+            return Some(entries);
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some(key) = &current_key {
+                entries.push((key.clone(), unquote(item)));
+            }
+        } else if let Some((key, value)) = trimmed.split_once(':') {
+            let key = key.trim().to_string();
+            let value = value.trim();
+            if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                entries.extend(list.split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(|v| (key.clone(), unquote(v))));
+            } else if !value.is_empty() {
+                entries.push((key.clone(), unquote(value)));
+            }
+            current_key = Some(key);
+        }
+    }
+
+    // 没有结束的 `---`，不是 front matter
+    None
+}
+
+/// 去掉值两侧的引号
+fn unquote(value: &str) -> String {
+    value.trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_string()
+}
+
+/// 把任务清单行设为勾选或未勾选，其余内容保持不变
+pub fn set_task_checked(line: &str, checked: bool) -> String {
+    let mark = if checked { "[x]" } else { "[ ]" };
+    match ["[ ]", "[x]", "[X]"].iter().filter_map(|m| line.find(m)).min() {
+        Some(pos) => format!("{}{}{}", &line[..pos], mark, &line[pos + 3..]),
+        None => line.to_string(),
+    }
+}
+
+/// 按行号替换内容，保留原有换行符
+pub fn replace_lines(content: &str, replacements: &[(usize, String)]) -> String {
+    content.split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| match replacements.iter().rev().find(|(n, _)| *n == i) {
+            Some((_, text)) => {
+                let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                format!("{}{}", text, ending)
+            }
+            None => line.to_string(),
+        })
+        .collect()
+}
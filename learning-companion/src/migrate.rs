@@ -0,0 +1,287 @@
+//! 数据库迁移模块
+//!
+//! 用 `PRAGMA user_version` 记录数据库结构版本，按顺序执行尚未应用的迁移，
+//! 每个迁移在单独的事务中完成。迁移已有数据前先备份数据库文件。
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use crate::db::Store;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// 一次结构迁移
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// 迁移后的版本号，从 1 开始连续递增
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// 所有迁移（按版本排列，已发布的迁移不能修改，只能追加）
+///
+/// 早期版本没有记录版本号（user_version 为 0），但已经建好了部分表，
+/// 所以建表语句都使用 IF NOT EXISTS，从 0 升级时已有的表保持不变
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "学习记录、模块进度、练习结果、成就和设置",
+        sql: "CREATE TABLE IF NOT EXISTS study_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                duration_minutes INTEGER NOT NULL,
+                modules_studied TEXT NOT NULL,
+                practice_count INTEGER NOT NULL DEFAULT 0,
+                notes TEXT
+            );
+            CREATE TABLE IF NOT EXISTS module_progress (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL UNIQUE,
+                started_at TEXT,
+                completed_at TEXT,
+                mastery_score REAL DEFAULT 0.0,
+                last_updated TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS practice_results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                questions_total INTEGER NOT NULL,
+                questions_correct INTEGER NOT NULL,
+                score REAL NOT NULL,
+                weak_topics TEXT
+            );
+            CREATE TABLE IF NOT EXISTS achievements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                achievement_type TEXT NOT NULL UNIQUE,
+                unlocked_at TEXT NOT NULL,
+                metadata TEXT
+            );
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+    },
+    Migration {
+        version: 2,
+        description: "练习题测试结果",
+        sql: "CREATE TABLE IF NOT EXISTS exercise_results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL,
+                exercise TEXT NOT NULL,
+                checked_at TEXT NOT NULL,
+                compiled INTEGER NOT NULL,
+                tests_total INTEGER NOT NULL,
+                tests_passed INTEGER NOT NULL,
+                failed_tests TEXT,
+                UNIQUE(module_id, exercise)
+            );",
+    },
+    Migration {
+        version: 3,
+        description: "作答记录和间隔复习计划",
+        sql: "CREATE TABLE IF NOT EXISTS question_reviews (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL,
+                question_key TEXT NOT NULL,
+                topic TEXT NOT NULL,
+                correct INTEGER NOT NULL,
+                reviewed_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS review_schedule (
+                module_id TEXT NOT NULL,
+                question_key TEXT NOT NULL,
+                ease_factor REAL NOT NULL,
+                interval_days INTEGER NOT NULL,
+                repetitions INTEGER NOT NULL,
+                due_date TEXT NOT NULL,
+                PRIMARY KEY (module_id, question_key)
+            );",
+    },
+    Migration {
+        version: 4,
+        description: "与 进度.md 同步的任务状态",
+        sql: "CREATE TABLE IF NOT EXISTS progress_fields (
+                module_id TEXT NOT NULL,
+                field TEXT NOT NULL,
+                value TEXT,
+                synced_value TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (module_id, field)
+            );",
+    },
+    Migration {
+        version: 5,
+        description: "进行中的学习计时",
+        sql: "CREATE TABLE IF NOT EXISTS active_session (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                started_at TEXT NOT NULL,
+                last_seen TEXT NOT NULL,
+                modules_studied TEXT NOT NULL,
+                practice_count INTEGER NOT NULL DEFAULT 0,
+                notes TEXT,
+                pomodoro_minutes INTEGER
+            );",
+    },
+    Migration {
+        version: 6,
+        description: "掌握程度历史",
+        sql: "CREATE TABLE IF NOT EXISTS mastery_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                mastery REAL NOT NULL
+            );",
+    },
+    Migration {
+        version: 7,
+        description: "学习记录的开始时间（带时区）",
+        sql: "ALTER TABLE study_sessions ADD COLUMN started_at TEXT;",
+    },
+    Migration {
+        version: 8,
+        description: "代码示例运行记录",
+        sql: "CREATE TABLE IF NOT EXISTS example_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL,
+                example TEXT NOT NULL,
+                last_run_at TEXT NOT NULL,
+                last_success INTEGER NOT NULL,
+                last_duration_ms INTEGER NOT NULL,
+                runs INTEGER NOT NULL DEFAULT 0,
+                successes INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(module_id, example)
+            );",
+    },
+    Migration {
+        version: 9,
+        description: "查看练习骨架和答案的记录",
+        sql: "CREATE TABLE IF NOT EXISTS solution_views (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                module_id TEXT NOT NULL,
+                exercise TEXT NOT NULL,
+                level TEXT NOT NULL,
+                viewed_at TEXT NOT NULL,
+                before_pass INTEGER NOT NULL
+            );",
+    },
+    Migration {
+        version: 10,
+        description: "记录学习计时是否由 TUI 写入心跳",
+        sql: "ALTER TABLE active_session ADD COLUMN heartbeat INTEGER NOT NULL DEFAULT 0;",
+    },
+];
+
+/// 程序支持的最新版本
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// 数据库当前的结构版本
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// 尚未应用的迁移；数据库比程序新时报错
+pub fn pending(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        anyhow::bail!("数据库版本 {} 比程序支持的版本 {} 新，请升级学习伴侣", current, latest_version());
+    }
+    Ok(MIGRATIONS.iter().filter(|m| m.version > current).collect())
+}
+
+/// 数据库中是否已有数据表（全新的数据库不需要备份）
+fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// 备份数据库到 backup_dir，返回备份文件路径
+pub fn backup(conn: &Connection, backup_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(backup_dir)?;
+    let path = backup_dir.join(format!(
+        "data-v{}-{}.db",
+        current_version(conn)?,
+        Local::now().format("%Y%m%d-%H%M%S"),
+    ));
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .with_context(|| format!("无法备份数据库到 {}", path.display()))?;
+    Ok(path)
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+    pub applied: Vec<u32>,
+    /// 迁移前的备份
+    pub backup: Option<PathBuf>,
+}
+
+/// 执行迁移到 target 版本；backup_dir 不为空且数据库已有数据时先备份
+pub fn migrate_to(conn: &mut Connection, target: u32, backup_dir: Option<&Path>) -> Result<MigrationReport> {
+    let from = current_version(conn)?;
+    let todo: Vec<&Migration> = pending(conn)?.into_iter().filter(|m| m.version <= target).collect();
+
+    let backup = match backup_dir {
+        Some(dir) if !todo.is_empty() && has_tables(conn)? => Some(backup(conn, dir)?),
+        _ => None,
+    };
+
+    let mut applied = Vec::new();
+    for migration in todo {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.sql)
+            .with_context(|| format!("迁移到版本 {}（{}）失败", migration.version, migration.description))?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        applied.push(migration.version);
+    }
+
+    Ok(MigrationReport { from, to: current_version(conn)?, applied, backup })
+}
+
+/// 执行所有尚未应用的迁移
+pub fn migrate(conn: &mut Connection, backup_dir: Option<&Path>) -> Result<MigrationReport> {
+    migrate_to(conn, latest_version(), backup_dir)
+}
+
+/// 迁移数据库（命令行）；dry_run 时只列出将要执行的迁移
+///
+/// store 应由 `Store::open_unmigrated` 打开，否则打开时已经完成迁移
+pub fn run_migrate(store: &mut Store, dry_run: bool) -> Result<()> {
+    let path = store.db_path().unwrap_or_else(|| PathBuf::from(":memory:"));
+    let backup_dir = store.data_dir().ok().map(|dir| dir.join("backups"));
+    let conn = store.connection();
+    let current = current_version(conn)?;
+    let todo = pending(conn)?;
+
+    println!("🗄️  数据库：{}", path.display());
+    println!("   当前版本 {}，最新版本 {}", current, latest_version());
+    if todo.is_empty() {
+        println!("✅ 数据库已是最新版本");
+        return Ok(());
+    }
+
+    println!("\n{}以下迁移：", if dry_run { "将执行" } else { "执行" });
+    for migration in &todo {
+        println!("   • v{}：{}", migration.version, migration.description);
+    }
+    if dry_run {
+        println!("\n💡 这是预演，没有修改数据库；去掉 --dry-run 执行迁移");
+        return Ok(());
+    }
+
+    let report = migrate(conn, backup_dir.as_deref())?;
+    if let Some(backup) = &report.backup {
+        println!("\n💾 迁移前的备份：{}", backup.display());
+    }
+    println!("✅ 已升级到版本 {}", report.to);
+    Ok(())
+}
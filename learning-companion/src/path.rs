@@ -0,0 +1,421 @@
+//! 学习路线模块
+//!
+//! 模块在 Cargo.toml 中声明先修模块，组成一张先修关系图：先修模块全部完成后模块才解锁。
+//! 学习路线从目标模块出发，只包含目标和它们的全部先修模块；内置路线来自 tracks.toml，
+//! 学习仓库根目录的 学习路线.toml 可以添加或覆盖路线
+
+use crate::db::Store;
+use crate::repo::LearningRepo;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+/// 内置的学习路线
+const TRACKS_TOML: &str = include_str!("../tracks.toml");
+
+/// 学习仓库中自定义路线的文件名
+pub const USER_TRACKS_FILE: &str = "学习路线.toml";
+
+/// 当前路线的设置项
+pub const TRACK_KEY: &str = "learning_track";
+
+/// 包含全部模块的完整路线
+pub const FULL_TRACK: &str = "full";
+
+/// 一条学习路线的定义
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TrackDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 目标模块（目录名或主题名）
+    pub goals: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TrackFile {
+    #[serde(default)]
+    tracks: Vec<TrackDef>,
+}
+
+/// 解析路线定义，id 不能重复，也不能占用 full
+pub fn parse_tracks(content: &str) -> Result<Vec<TrackDef>> {
+    let file: TrackFile = toml::from_str(content).context("学习路线格式错误")?;
+    for (i, def) in file.tracks.iter().enumerate() {
+        if def.id == FULL_TRACK {
+            anyhow::bail!("路线 id {} 保留给完整路线", FULL_TRACK);
+        }
+        if file.tracks[..i].iter().any(|d| d.id == def.id) {
+            anyhow::bail!("路线 id 重复：{}", def.id);
+        }
+        if def.goals.is_empty() {
+            anyhow::bail!("路线 {} 没有目标模块", def.id);
+        }
+    }
+    Ok(file.tracks)
+}
+
+/// 内置路线和学习仓库中的自定义路线（id 相同时自定义的优先）
+pub fn load_tracks(repo: &LearningRepo) -> Result<Vec<TrackDef>> {
+    let mut tracks = parse_tracks(TRACKS_TOML).expect("内置学习路线无效");
+    let path = repo.path.join(USER_TRACKS_FILE);
+    if path.exists() {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("无法读取 {}", path.display()))?;
+        let custom = parse_tracks(&content)
+            .with_context(|| format!("{} 无效", path.display()))?;
+        for track in custom {
+            match tracks.iter_mut().find(|t| t.id == track.id) {
+                Some(existing) => *existing = track,
+                None => tracks.push(track),
+            }
+        }
+    }
+    Ok(tracks)
+}
+
+/// 按 id 查找路线，full 返回 None
+pub fn find_track(repo: &LearningRepo, id: &str) -> Result<Option<TrackDef>> {
+    if id == FULL_TRACK {
+        return Ok(None);
+    }
+    let tracks = load_tracks(repo)?;
+    match tracks.iter().find(|t| t.id == id) {
+        Some(track) => Ok(Some(track.clone())),
+        None => {
+            let ids: Vec<&str> = tracks.iter().map(|t| t.id.as_str()).collect();
+            anyhow::bail!("没有路线 {}（可选：{}、{}）", id, FULL_TRACK, ids.join("、"))
+        }
+    }
+}
+
+/// 当前选择的路线，未选择或路线已被删除时为完整路线（None）
+pub fn selected_track(store: &Store, repo: &LearningRepo) -> Result<Option<TrackDef>> {
+    match store.get_setting(TRACK_KEY)? {
+        Some(id) => Ok(find_track(repo, &id).ok().flatten()),
+        None => Ok(None),
+    }
+}
+
+/// 先修关系图
+#[derive(Debug, Clone)]
+pub struct Graph {
+    /// 拓扑顺序：先修模块总在前面，没有先后要求的按模块编号
+    order: Vec<String>,
+    /// 每个模块的先修模块（目录名）
+    prerequisites: BTreeMap<String, Vec<String>>,
+    /// 每个模块到起点的最长距离，用于分层显示
+    depths: BTreeMap<String, usize>,
+}
+
+impl Graph {
+    /// 按模块声明的先修模块建图；先修模块不存在或有循环依赖时报错
+    pub fn new(repo: &LearningRepo) -> Result<Self> {
+        let mut prerequisites: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for module in &repo.modules {
+            let mut ids: Vec<String> = Vec::new();
+            for key in &module.prerequisites {
+                let prerequisite = repo.find_module(key)
+                    .with_context(|| format!("{} 的先修模块 {} 不存在", module.id, key))?;
+                if !ids.contains(&prerequisite.id) {
+                    ids.push(prerequisite.id.clone());
+                }
+            }
+            prerequisites.insert(module.id.clone(), ids);
+        }
+
+        // 每次取出先修模块都已排好的模块中编号最小的一个，取不出来说明有循环
+        let mut order: Vec<String> = Vec::new();
+        let mut depths: BTreeMap<String, usize> = BTreeMap::new();
+        let mut remaining: Vec<&str> = repo.modules.iter().map(|m| m.id.as_str()).collect();
+        while !remaining.is_empty() {
+            let Some(pos) = remaining.iter()
+                .position(|id| prerequisites[*id].iter().all(|p| depths.contains_key(p)))
+            else {
+                anyhow::bail!("先修模块存在循环依赖，涉及：{}", remaining.join("、"));
+            };
+            let id = remaining.remove(pos);
+            let depth = prerequisites[id].iter().map(|p| depths[p] + 1).max().unwrap_or(0);
+            depths.insert(id.to_string(), depth);
+            order.push(id.to_string());
+        }
+
+        Ok(Graph { order, prerequisites, depths })
+    }
+
+    /// 拓扑顺序的模块目录名
+    pub fn order(&self) -> &[String] {
+        &self.order
+    }
+
+    /// 直接先修模块
+    pub fn prerequisites(&self, id: &str) -> &[String] {
+        self.prerequisites.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// 分层显示时所在的层（从 0 开始）
+    pub fn depth(&self, id: &str) -> usize {
+        self.depths.get(id).copied().unwrap_or(0)
+    }
+
+    /// 全部（含间接）先修模块
+    pub fn ancestors(&self, id: &str) -> BTreeSet<String> {
+        let mut found = BTreeSet::new();
+        let mut stack: Vec<&str> = vec![id];
+        while let Some(current) = stack.pop() {
+            for prerequisite in self.prerequisites(current) {
+                if found.insert(prerequisite.clone()) {
+                    stack.push(prerequisite);
+                }
+            }
+        }
+        found
+    }
+
+    /// 直接依赖 id 的模块，按拓扑顺序
+    pub fn dependents(&self, id: &str) -> Vec<&str> {
+        self.order.iter()
+            .filter(|m| self.prerequisites(m).iter().any(|p| p == id))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// 路线包含的模块（目标和全部先修模块），按拓扑顺序；track 为 None 时为全部模块
+    pub fn track_modules(&self, repo: &LearningRepo, track: Option<&TrackDef>) -> Result<Vec<String>> {
+        let Some(track) = track else {
+            return Ok(self.order.clone());
+        };
+        let mut included = BTreeSet::new();
+        for goal in &track.goals {
+            let module = repo.find_module(goal)
+                .with_context(|| format!("路线 {} 的目标模块 {} 不存在", track.id, goal))?;
+            included.extend(self.ancestors(&module.id));
+            included.insert(module.id.clone());
+        }
+        Ok(self.order.iter().filter(|id| included.contains(*id)).cloned().collect())
+    }
+}
+
+/// 模块在学习路线上的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleState {
+    /// 五项任务全部完成
+    Done,
+    /// 先修模块已完成，已经开始学习
+    InProgress,
+    /// 先修模块已完成，可以开始学习
+    Available,
+    /// 还有未完成的先修模块
+    Locked { missing: Vec<String> },
+}
+
+impl ModuleState {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ModuleState::Done => "✅",
+            ModuleState::InProgress => "🟡",
+            ModuleState::Available => "🟢",
+            ModuleState::Locked { .. } => "🔒",
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        !matches!(self, ModuleState::Locked { .. })
+    }
+}
+
+fn status(repo: &LearningRepo, id: &str) -> &'static str {
+    match repo.get_module_progress(id).map(|p| p.status.as_str()) {
+        Some("[x]") => "[x]",
+        Some("[~]") => "[~]",
+        _ => "[ ]",
+    }
+}
+
+/// 模块状态：完成以 进度.md 为准，未完成时看先修模块是否都已完成
+pub fn state_of(repo: &LearningRepo, graph: &Graph, id: &str) -> ModuleState {
+    if status(repo, id) == "[x]" {
+        return ModuleState::Done;
+    }
+    let missing: Vec<String> = graph.prerequisites(id).iter()
+        .filter(|p| status(repo, p) != "[x]")
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        ModuleState::Locked { missing }
+    } else if status(repo, id) == "[~]" {
+        ModuleState::InProgress
+    } else {
+        ModuleState::Available
+    }
+}
+
+/// 所有模块的状态
+pub fn module_states(repo: &LearningRepo, graph: &Graph) -> BTreeMap<String, ModuleState> {
+    graph.order().iter().map(|id| (id.clone(), state_of(repo, graph, id))).collect()
+}
+
+/// 推荐的下一个模块：路线上第一个学习中的模块，其次是第一个可以学习的模块
+pub fn recommend<'a>(states: &BTreeMap<String, ModuleState>, modules: &'a [String]) -> Option<&'a str> {
+    let first = |wanted: ModuleState| modules.iter().find(|id| states.get(*id) == Some(&wanted));
+    first(ModuleState::InProgress)
+        .or_else(|| first(ModuleState::Available))
+        .map(String::as_str)
+}
+
+/// 当前路线上推荐的下一个模块，路线全部完成时为 None
+pub fn next_module(store: &Store, repo: &LearningRepo) -> Result<Option<String>> {
+    let graph = Graph::new(repo)?;
+    let track = selected_track(store, repo)?;
+    let modules = graph.track_modules(repo, track.as_ref())?;
+    Ok(recommend(&module_states(repo, &graph), &modules).map(str::to_string))
+}
+
+/// 模块的显示名称（如 08-trait与泛型）
+fn display_name<'a>(repo: &'a LearningRepo, id: &'a str) -> &'a str {
+    repo.find_module(id).map_or(id, |m| m.name.as_str())
+}
+
+/// 模块编号（如 08），用于紧凑地列出先修模块
+fn short_name(repo: &LearningRepo, id: &str) -> String {
+    repo.find_module(id).map_or_else(|| id.to_string(), |m| format!("{:02}", m.number))
+}
+
+/// 学习模块前提示未完成的先修模块
+pub fn warn_if_locked(repo: &LearningRepo, module_id: &str) {
+    let (Ok(graph), Some(module)) = (Graph::new(repo), repo.find_module(module_id)) else {
+        return;
+    };
+    if let ModuleState::Locked { missing } = state_of(repo, &graph, &module.id) {
+        let names: Vec<&str> = missing.iter().map(|id| display_name(repo, id)).collect();
+        println!("🔒 {} 的先修模块还没有完成：{}（建议先完成先修模块）", module.name, names.join("、"));
+    }
+}
+
+/// 模块完成后提示新解锁的模块和推荐的下一个模块
+pub fn report_unlocks(store: &Store, repo: &LearningRepo, module_id: &str) -> Result<()> {
+    let graph = Graph::new(repo)?;
+    if state_of(repo, &graph, module_id) != ModuleState::Done {
+        return Ok(());
+    }
+    let unlocked: Vec<&str> = graph.dependents(module_id).into_iter()
+        .filter(|id| matches!(state_of(repo, &graph, id), ModuleState::Available | ModuleState::InProgress))
+        .map(|id| display_name(repo, id))
+        .collect();
+    if !unlocked.is_empty() {
+        println!("🔓 解锁了新模块：{}", unlocked.join("、"));
+    }
+    match next_module(store, repo)? {
+        Some(next) => println!("👉 推荐下一个模块：{}", display_name(repo, &next)),
+        None => println!("🏁 当前学习路线已全部完成！"),
+    }
+    Ok(())
+}
+
+/// 显示先修关系图和学习路线（命令行）；track 为 None 时使用已选择的路线
+pub fn run_show(store: &Store, repo: &LearningRepo, track: Option<&str>) -> Result<()> {
+    let graph = Graph::new(repo)?;
+    let track = match track {
+        Some(id) => find_track(repo, id)?,
+        None => selected_track(store, repo)?,
+    };
+    let modules = graph.track_modules(repo, track.as_ref())?;
+    let states = module_states(repo, &graph);
+
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🗺️  Rust 学习伴侣 - 学习路线      ║");
+    println!("╚════════════════════════════════════════╝\n");
+
+    let done = modules.iter().filter(|id| states[*id] == ModuleState::Done).count();
+    match &track {
+        Some(track) => {
+            println!("🧭 路线：{}（{} 个模块，已完成 {}）", track.name, modules.len(), done);
+            if !track.description.is_empty() {
+                println!("   {}", track.description);
+            }
+        }
+        None => println!("🧭 路线：完整路线（{} 个模块，已完成 {}）", modules.len(), done),
+    }
+
+    // 按层显示：同一层的模块互不依赖，可以按任意顺序学习
+    let mut layers: BTreeMap<usize, Vec<&String>> = BTreeMap::new();
+    for id in &modules {
+        layers.entry(graph.depth(id)).or_default().push(id);
+    }
+    for (i, layer) in layers.values().enumerate() {
+        println!("\n第 {} 层", i + 1);
+        for id in layer {
+            let state = &states[*id];
+            let prerequisites: Vec<String> = graph.prerequisites(id).iter().map(|p| short_name(repo, p)).collect();
+            let mut line = format!("  {} {}", state.icon(), display_name(repo, id));
+            if !prerequisites.is_empty() {
+                line.push_str(&format!("  ← {}", prerequisites.join("、")));
+            }
+            // 部分先修模块已完成时，单独列出还差哪些
+            match state {
+                ModuleState::Locked { missing } if missing.len() < prerequisites.len() => {
+                    let missing: Vec<String> = missing.iter().map(|p| short_name(repo, p)).collect();
+                    line.push_str(&format!("（还差 {}）", missing.join("、")));
+                }
+                _ => {}
+            }
+            println!("{}", line);
+        }
+    }
+
+    let skipped = graph.order().len() - modules.len();
+    if skipped > 0 {
+        println!("\n⏭️  另有 {} 个模块不在这条路线上", skipped);
+    }
+
+    println!();
+    match recommend(&states, &modules) {
+        Some(next) => {
+            println!("👉 推荐下一个模块：{}", display_name(repo, next));
+            let unlocks: Vec<String> = graph.dependents(next).iter().map(|id| short_name(repo, id)).collect();
+            if !unlocks.is_empty() {
+                println!("   完成后可以解锁：{}", unlocks.join("、"));
+            }
+        }
+        None if done == modules.len() => println!("🏁 这条路线已全部完成！"),
+        None => println!("⚠️  没有可以学习的模块"),
+    }
+    println!("\n图例：✅ 已完成  🟡 学习中  🟢 可以学习  🔒 未解锁");
+    println!("💡 path tracks 查看所有路线，path use <路线> 切换路线");
+    Ok(())
+}
+
+/// 列出所有学习路线（命令行）
+pub fn run_tracks(store: &Store, repo: &LearningRepo) -> Result<()> {
+    let graph = Graph::new(repo)?;
+    let selected = selected_track(store, repo)?.map(|t| t.id);
+    let marker = |id: Option<&str>| if selected.as_deref() == id { "▶" } else { " " };
+
+    println!("🧭 学习路线：\n");
+    println!("{} {} - 完整路线（{} 个模块）", marker(None), FULL_TRACK, graph.order().len());
+    for track in load_tracks(repo)? {
+        let count = graph.track_modules(repo, Some(&track))?.len();
+        println!("{} {} - {}（{} 个模块）", marker(Some(&track.id)), track.id, track.name, count);
+        if !track.description.is_empty() {
+            println!("    {}", track.description);
+        }
+    }
+    println!("\n💡 在学习仓库根目录的 {} 中可以添加自己的路线", USER_TRACKS_FILE);
+    Ok(())
+}
+
+/// 切换学习路线（命令行）
+pub fn run_use(store: &Store, repo: &LearningRepo, id: &str) -> Result<()> {
+    let graph = Graph::new(repo)?;
+    let track = find_track(repo, id)?;
+    let modules = graph.track_modules(repo, track.as_ref())?;
+    store.save_setting(TRACK_KEY, id)?;
+    let name = track.as_ref().map_or("完整路线", |t| t.name.as_str());
+    println!("✅ 已切换到{}（{} 个模块）", name, modules.len());
+    if let Some(next) = recommend(&module_states(repo, &graph), &modules) {
+        println!("👉 推荐下一个模块：{}", display_name(repo, next));
+    }
+    Ok(())
+}
@@ -2,11 +2,12 @@
 //!
 //! 计算学习进度和掌握程度
 
+use crate::db::{DbResult, Store};
 use crate::repo::LearningRepo;
 use anyhow::Result;
 
 /// 任务类型
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TaskType {
     Concept,   // 概念学习
     Examples,  // 代码示例
@@ -16,6 +17,16 @@ pub enum TaskType {
 }
 
 impl TaskType {
+    /// 按 进度.md 中的顺序列出所有任务
+    pub const ALL: [TaskType; 5] = [
+        TaskType::Concept,
+        TaskType::Examples,
+        TaskType::Exercises,
+        TaskType::Project,
+        TaskType::Checklist,
+    ];
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "概念" | "concept" => Some(TaskType::Concept),
@@ -27,6 +38,27 @@ impl TaskType {
         }
     }
 
+    /// 按 进度.md 中的任务文字匹配（如「练习题完成」）
+    pub fn from_label(label: &str) -> Option<Self> {
+        TaskType::ALL.into_iter().find(|t| t.as_str() == label)
+    }
+
+    /// 按数据库中的英文标识查找
+    pub fn from_key(key: &str) -> Option<Self> {
+        TaskType::ALL.into_iter().find(|t| t.key() == key)
+    }
+
+    /// 数据库中使用的英文标识
+    pub fn key(&self) -> &'static str {
+        match self {
+            TaskType::Concept => "concept",
+            TaskType::Examples => "examples",
+            TaskType::Exercises => "exercises",
+            TaskType::Project => "project",
+            TaskType::Checklist => "checklist",
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             TaskType::Concept => "概念学习",
@@ -39,36 +71,67 @@ impl TaskType {
 }
 
 /// 更新任务状态
-pub fn update_task_status(repo: &LearningRepo, module_id: &str, task_str: &str) -> Result<()> {
+pub fn update_task_status(store: &Store, repo: &LearningRepo, module_id: &str, task_str: &str) -> Result<()> {
     let task = TaskType::from_str(task_str);
 
     if let Some(task_type) = task {
-        println!("✓ 标记 {} 的 {} 为已完成", module_id, task_type.as_str());
+        // 模块可以用目录名、中文名或主题名指定，数据库中统一用目录名
+        let module_key = repo.module_key(module_id);
 
-        // 更新数据库中的模块进度
-        // 简化实现：每次更新增加掌握分数
-        let increase = match task_type {
-            TaskType::Concept => 15.0,
-            TaskType::Examples => 15.0,
-            TaskType::Exercises => 30.0,
-            TaskType::Project => 30.0,
-            TaskType::Checklist => 10.0,
-        };
+        // 练习题是否完成由测试结果决定，不接受手动标记
+        if let TaskType::Exercises = task_type {
+            let module = repo.find_module(module_id);
+            let summary = module.map(|m| crate::checker::exercise_summary(store, m)).transpose()?.unwrap_or_default();
+            if !summary.is_complete() {
+                println!("❌ {} 的练习题尚未全部通过测试（{}/{} 通过，已检查 {}/{}）",
+                    module_key, summary.passed, summary.graded, summary.checked, summary.total);
+                println!("💡 请先运行：learning-companion check -m {}", module_key);
+                return Err(anyhow::anyhow!("练习题测试未全部通过"));
+            }
+        }
 
-        // 获取当前进度并更新
-        let current_score = crate::db::get_module_mastery(module_id).unwrap_or(0.0);
-        let new_score = (current_score + increase).min(100.0);
+        // 自检是否通过由清单勾选比例决定
+        if let TaskType::Checklist = task_type {
+            let module = repo.find_module(module_id);
+            let checklist = module.and_then(|m| crate::checklist::load_checklist(m).ok().flatten());
+            if !checklist.as_ref().is_some_and(|c| c.is_passed()) {
+                let (checked, total) = checklist.map_or((0, 0), |c| (c.checked_count(), c.total()));
+                println!("❌ {} 的自检清单尚未达标（已勾选 {}/{}，需要 {:.0}%）",
+                    module_key, checked, total, crate::checklist::PASS_RATIO * 100.0);
+                println!("💡 在 TUI 模块详情中按 C 勾选清单，或直接编辑 {}/{}",
+                    module_key, crate::checklist::CHECKLIST_FILE);
+                return Err(anyhow::anyhow!("自检清单未达标"));
+            }
+        }
 
-        crate::db::update_module_progress(module_id, new_score)?;
+        println!("✓ 标记 {} 的 {} 为已完成", module_id, task_type.as_str());
+        crate::path::warn_if_locked(repo, module_id);
 
-        println!("📊 当前掌握程度：{:.1}%", new_score);
+        // 写回 进度.md，冲突时保留两侧不动并提示
+        crate::sync::record_task(store, repo, module_id, task_type, true)?;
+        let plan = crate::sync::sync(store, repo, None)?;
+        crate::sync::print_plan(&plan);
 
-        if new_score >= 95.0 {
+        // 掌握程度由学习证据计算，重复标记同一任务不会增加
+        let readiness = can_advance_to_next(store, repo, module_key)?;
+        store.update_module_progress(module_key, readiness.mastery)?;
+        crate::study::touch_module(store, module_key)?;
+        crate::achievements::check_and_report(store, repo)?;
+
+        println!("📊 当前掌握程度：{:.1}%", readiness.mastery);
+
+        if readiness.can_advance() {
             println!("🎉 恭喜！你已掌握该模块，可以进入下一阶段学习！");
-        } else if new_score >= 80.0 {
-            println!("💪 做得不错！继续加油！");
         } else {
-            println!("📚 继续学习，你可以的！");
+            println!("📚 还差这些证据：");
+            for missing in &readiness.missing {
+                println!("   • {}", missing);
+            }
+        }
+
+        // 进度.md 刚刚写回，重新读取后才能判断模块是否完成、解锁了哪些模块
+        if let Ok(repo) = LearningRepo::new(&repo.path) {
+            crate::path::report_unlocks(store, &repo, module_key)?;
         }
 
         return Ok(());
@@ -77,15 +140,15 @@ pub fn update_task_status(repo: &LearningRepo, module_id: &str, task_str: &str)
     // 如果不是标准任务名，尝试匹配
     let task_lower = task_str.to_lowercase();
     if task_lower.contains("概念") || task_lower.contains("concept") {
-        return update_task_status(repo, module_id, "concept");
+        return update_task_status(store, repo, module_id, "concept");
     } else if task_lower.contains("示例") || task_lower.contains("example") {
-        return update_task_status(repo, module_id, "examples");
+        return update_task_status(store, repo, module_id, "examples");
     } else if task_lower.contains("练习") || task_lower.contains("exercise") {
-        return update_task_status(repo, module_id, "exercises");
+        return update_task_status(store, repo, module_id, "exercises");
     } else if task_lower.contains("综合") || task_lower.contains("project") {
-        return update_task_status(repo, module_id, "project");
+        return update_task_status(store, repo, module_id, "project");
     } else if task_lower.contains("自检") || task_lower.contains("checklist") {
-        return update_task_status(repo, module_id, "checklist");
+        return update_task_status(store, repo, module_id, "checklist");
     }
 
     println!("❌ 未知的任务类型：{}", task_str);
@@ -93,34 +156,244 @@ pub fn update_task_status(repo: &LearningRepo, module_id: &str, task_str: &str)
     Err(anyhow::anyhow!("未知任务类型"))
 }
 
-/// 计算模块掌握程度（从数据库）
-pub fn calculate_mastery(module_id: &str) -> f32 {
-    crate::db::get_module_mastery(module_id).unwrap_or(0.0)
+/// 练习得分的半衰期（天）：越早的练习权重越低
+pub const PRACTICE_HALF_LIFE_DAYS: f32 = 14.0;
+
+/// 近期练习得分达到该值视为练习证据充分
+pub const PRACTICE_PASS_SCORE: f32 = 80.0;
+
+/// 各项证据在掌握程度中的权重
+///
+/// 通过 `db::save_setting` 修改，键为 `mastery_weight.tasks` 等，未设置时使用默认值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasteryWeights {
+    /// 已完成的任务数
+    pub tasks: f32,
+    /// 练习题测试通过率
+    pub exercises: f32,
+    /// 近期练习得分
+    pub practice: f32,
+    /// 自检清单勾选比例
+    pub checklist: f32,
 }
 
-/// 检查是否可以进入下一模块
-pub fn can_advance_to_next(module_id: &str) -> bool {
-    let mastery = calculate_mastery(module_id);
-    mastery >= 95.0
+impl Default for MasteryWeights {
+    fn default() -> Self {
+        MasteryWeights { tasks: 20.0, exercises: 30.0, practice: 30.0, checklist: 20.0 }
+    }
 }
 
-/// 获取学习建议
-pub fn get_study_recommendations(module_id: &str) -> Vec<String> {
-    let mastery = calculate_mastery(module_id);
-    let mut recommendations = Vec::new();
+impl MasteryWeights {
+    /// 设置项的键名
+    pub const KEYS: [&'static str; 4] = [
+        "mastery_weight.tasks",
+        "mastery_weight.exercises",
+        "mastery_weight.practice",
+        "mastery_weight.checklist",
+    ];
+
+    /// 从设置中读取权重，未设置或无法解析（含负数）时使用默认值
+    pub fn load(store: &Store) -> DbResult<Self> {
+        let defaults = MasteryWeights::default();
+        let read = |key: &str, default: f32| -> DbResult<f32> {
+            Ok(store.get_setting(key)?
+                .and_then(|v| v.trim().parse::<f32>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default))
+        };
+        Ok(MasteryWeights {
+            tasks: read(Self::KEYS[0], defaults.tasks)?,
+            exercises: read(Self::KEYS[1], defaults.exercises)?,
+            practice: read(Self::KEYS[2], defaults.practice)?,
+            checklist: read(Self::KEYS[3], defaults.checklist)?,
+        })
+    }
+
+    /// 保存权重到设置
+    pub fn save(&self, store: &Store) -> Result<()> {
+        let values = [self.tasks, self.exercises, self.practice, self.checklist];
+        for (key, value) in Self::KEYS.iter().zip(values) {
+            store.save_setting(key, &value.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// 计算掌握程度所用的学习证据
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MasteryEvidence {
+    /// 已完成的任务（同一任务只计一次）
+    pub tasks_done: Vec<TaskType>,
+    /// 练习题测试：(通过数, 参与判分数)，模块没有带测试的练习时为 None
+    pub exercises: Option<(usize, usize)>,
+    /// 通过的练习中测试通过前看过答案的数量，这些练习按 `hints::ASSISTED_CREDIT` 计分
+    pub exercises_assisted: usize,
+    /// 按时间衰减加权的练习得分（0-100），从未练习时为 None
+    pub practice_score: Option<f32>,
+    /// 自检清单勾选比例（0.0-1.0），模块没有清单时为 None
+    pub checklist_ratio: Option<f32>,
+    /// 模块是否有练习题和自检清单（没有的证据不参与计算）
+    pub has_exercises: bool,
+    pub has_checklist: bool,
+}
+
+impl MasteryEvidence {
+    /// 收集模块的学习证据：任务状态综合数据库和 进度.md 的当前勾选，练习得分来自数据库，
+    /// 测试结果和清单来自模块目录
+    pub fn collect(store: &Store, repo: &LearningRepo, module_id: &str) -> DbResult<Self> {
+        let module = repo.find_module(module_id);
+        let module_key = repo.module_key(module_id);
+
+        let fields = store.get_progress_fields()?;
+        let tasks_done = crate::sync::tasks_done(&fields, module_key, repo.get_module_progress(module_key));
+
+        let summary = module.map(|m| crate::checker::exercise_summary(store, m)).transpose()?.unwrap_or_default();
+        let checklist = module.and_then(|m| crate::checklist::load_checklist(m).ok().flatten());
+
+        let practice = store.get_practice_results(module_key)?;
+        let now = chrono::Local::now();
+        let practice_score = weighted_practice_score(
+            &practice.iter()
+                .map(|r| ((now - r.timestamp).num_hours() as f32 / 24.0, r.score))
+                .collect::<Vec<_>>(),
+        );
 
-    if mastery < 30.0 {
-        recommendations.push("建议开始学习该模块的基础概念".to_string());
-    } else if mastery < 60.0 {
-        recommendations.push("建议完成代码示例的学习".to_string());
-        recommendations.push("尝试做一些简单的练习".to_string());
-    } else if mastery < 95.0 {
-        recommendations.push("建议完成所有练习题".to_string());
-        recommendations.push("完成综合练习".to_string());
-        recommendations.push("做自检清单".to_string());
-    } else {
-        recommendations.push("恭喜！可以进入下一模块学习了".to_string());
+        Ok(MasteryEvidence {
+            tasks_done,
+            exercises: (summary.graded > 0).then_some((summary.passed, summary.graded)),
+            exercises_assisted: summary.assisted,
+            practice_score,
+            checklist_ratio: checklist.as_ref().map(|c| c.ratio()),
+            has_exercises: summary.total > 0,
+            has_checklist: checklist.is_some_and(|c| c.total() > 0),
+        })
     }
 
-    recommendations
+    /// 按权重计算掌握程度（0-100）
+    ///
+    /// 模块没有练习题或自检清单时，对应权重不参与计算；其余缺失的证据按 0 计
+    pub fn mastery(&self, weights: &MasteryWeights) -> f32 {
+        let mut parts = vec![
+            (weights.tasks, self.tasks_done.len() as f32 / TaskType::ALL.len() as f32),
+            (weights.practice, self.practice_score.unwrap_or(0.0) / 100.0),
+        ];
+        if self.has_exercises {
+            let rate = self.exercises.map_or(0.0, |(passed, graded)| {
+                let assisted = self.exercises_assisted.min(passed) as f32;
+                (passed as f32 - assisted * (1.0 - crate::hints::ASSISTED_CREDIT)) / graded as f32
+            });
+            parts.push((weights.exercises, rate));
+        }
+        if self.has_checklist {
+            parts.push((weights.checklist, self.checklist_ratio.unwrap_or(0.0)));
+        }
+
+        let total_weight: f32 = parts.iter().map(|(w, _)| w).sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        parts.iter().map(|(w, v)| w * v).sum::<f32>() / total_weight * 100.0
+    }
+
+    /// 还缺少的证据，每项一句说明；全部具备时为空
+    pub fn missing(&self, module_id: &str) -> Vec<String> {
+        let mut missing = Vec::new();
+
+        let pending: Vec<&str> = TaskType::ALL.iter()
+            .filter(|t| !self.tasks_done.contains(t))
+            .map(|t| t.as_str())
+            .collect();
+        if !pending.is_empty() {
+            missing.push(format!("还有 {} 项任务未完成：{}", pending.len(), pending.join("、")));
+        }
+
+        if self.has_exercises {
+            match self.exercises {
+                None => missing.push(format!("还没有运行练习题测试（learning-companion check -m {}）", module_id)),
+                Some((passed, graded)) if passed < graded => {
+                    missing.push(format!("练习题测试只通过 {}/{}，请修复未通过的练习", passed, graded));
+                }
+                _ => {}
+            }
+        }
+
+        match self.practice_score {
+            None => missing.push(format!("还没有做过练习题（learning-companion practice -m {}）", module_id)),
+            Some(score) if score < PRACTICE_PASS_SCORE => {
+                missing.push(format!("近期练习得分 {:.0}%，需要达到 {:.0}%，建议多练习并完成复习", score, PRACTICE_PASS_SCORE));
+            }
+            _ => {}
+        }
+
+        if self.has_checklist {
+            let ratio = self.checklist_ratio.unwrap_or(0.0);
+            if ratio < crate::checklist::PASS_RATIO {
+                missing.push(format!("自检清单已勾选 {:.0}%，需要达到 {:.0}%",
+                    ratio * 100.0, crate::checklist::PASS_RATIO * 100.0));
+            }
+        }
+
+        missing
+    }
+}
+
+/// 按时间衰减加权的练习得分；输入为 (距今天数, 得分)
+///
+/// 每过 PRACTICE_HALF_LIFE_DAYS 天权重减半，近期的练习更能代表当前水平
+pub fn weighted_practice_score(results: &[(f32, f32)]) -> Option<f32> {
+    if results.is_empty() {
+        return None;
+    }
+    let (weighted, total) = results.iter()
+        .fold((0.0, 0.0), |(weighted, total), &(age_days, score)| {
+            let weight = 0.5f32.powf(age_days.max(0.0) / PRACTICE_HALF_LIFE_DAYS);
+            (weighted + weight * score, total + weight)
+        });
+    Some(weighted / total)
+}
+
+/// 计算模块掌握程度（0-100）
+pub fn calculate_mastery(store: &Store, repo: &LearningRepo, module_id: &str) -> DbResult<f32> {
+    Ok(MasteryEvidence::collect(store, repo, module_id)?.mastery(&MasteryWeights::load(store)?))
+}
+
+/// 能否进入下一模块，以及还缺少哪些证据
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readiness {
+    pub mastery: f32,
+    pub missing: Vec<String>,
+}
+
+impl Readiness {
+    /// 所有证据都已具备
+    pub fn can_advance(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// 检查是否可以进入下一模块
+pub fn can_advance_to_next(store: &Store, repo: &LearningRepo, module_id: &str) -> DbResult<Readiness> {
+    let module_key = repo.module_key(module_id);
+    let evidence = MasteryEvidence::collect(store, repo, module_key)?;
+    Ok(Readiness {
+        mastery: evidence.mastery(&MasteryWeights::load(store)?),
+        missing: evidence.missing(module_key),
+    })
+}
+
+/// 获取学习建议：先修模块未完成时先提示先修模块，再逐条说明缺少的证据
+pub fn get_study_recommendations(store: &Store, repo: &LearningRepo, module_id: &str) -> DbResult<Vec<String>> {
+    let readiness = can_advance_to_next(store, repo, module_id)?;
+    if readiness.can_advance() {
+        return Ok(vec!["恭喜！可以进入下一模块学习了".to_string()]);
+    }
+    let mut recommendations = Vec::new();
+    if let Ok(graph) = crate::path::Graph::new(repo) {
+        let module_key = repo.module_key(module_id);
+        if let crate::path::ModuleState::Locked { missing } = crate::path::state_of(repo, &graph, module_key) {
+            recommendations.push(format!("先完成先修模块：{}", missing.join("、")));
+        }
+    }
+    recommendations.extend(readiness.missing);
+    Ok(recommendations)
 }
@@ -0,0 +1,423 @@
+//! 题库模块
+//!
+//! 从各模块目录下的 questions.toml / questions.json 加载练习题并校验格式，
+//! 代码题会用本机 rustc 实际编译运行来核对答案
+
+use crate::checker::{self, SnippetOutcome};
+use crate::exercise::{normalize_text, CorrectAnswer, Question, QuestionType};
+use crate::repo::LearningModule;
+use anyhow::{Context, Result};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 支持的题库文件名（按优先级）
+const BANK_FILES: &[&str] = &["questions.toml", "questions.json"];
+
+/// 需要运行代码片段核对答案的题型
+const CODE_TYPES: &[&str] = &["code_output", "compiles_or_not"];
+
+/// 题库文件结构
+#[derive(Debug, Deserialize)]
+struct BankFile {
+    #[serde(default)]
+    questions: Vec<RawQuestion>,
+}
+
+/// 题库文件中的一道题（字段全部可选，缺失项在校验时报告）
+#[derive(Debug, Deserialize)]
+struct RawQuestion {
+    #[serde(rename = "type")]
+    question_type: Option<String>,
+    prompt: Option<String>,
+    code: Option<String>,
+    options: Option<Vec<String>>,
+    answer: Option<RawAnswer>,
+    explanation: Option<String>,
+    topic: Option<String>,
+}
+
+/// 题库文件中的答案写法
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawAnswer {
+    Index(i64),
+    Bool(bool),
+    Text(String),
+    List(Vec<String>),
+}
+
+/// 题目格式错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestionError {
+    /// 题目序号（从 1 开始）
+    pub number: usize,
+    pub message: String,
+}
+
+/// 一个模块的题库
+#[derive(Debug, Clone)]
+pub struct QuestionBank {
+    pub path: PathBuf,
+    /// 通过校验的题目
+    pub questions: Vec<Question>,
+    /// 未通过校验的题目
+    pub errors: Vec<QuestionError>,
+}
+
+/// 查找模块目录下的题库文件
+pub fn find_bank_file(module_dir: &Path) -> Option<PathBuf> {
+    BANK_FILES.iter()
+        .map(|name| module_dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// 加载并校验模块题库，模块没有题库文件时返回 None
+pub fn load_bank(module: &LearningModule) -> Result<Option<QuestionBank>> {
+    match find_bank_file(&module.directory) {
+        Some(path) => load_bank_file(&path).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// 加载并校验题库文件
+pub fn load_bank_file(path: &Path) -> Result<QuestionBank> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("无法读取题库：{}", path.display()))?;
+    parse_bank(&content, path)
+}
+
+/// 解析题库内容，格式由文件扩展名决定
+pub fn parse_bank(content: &str, path: &Path) -> Result<QuestionBank> {
+    let file: BankFile = if path.extension().is_some_and(|e| e == "json") {
+        serde_json::from_str(content)
+            .with_context(|| format!("题库 JSON 格式错误：{}", path.display()))?
+    } else {
+        toml::from_str(content)
+            .with_context(|| format!("题库 TOML 格式错误：{}", path.display()))?
+    };
+
+    let mut bank = QuestionBank {
+        path: path.to_path_buf(),
+        questions: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for (i, raw) in file.questions.into_iter().enumerate() {
+        match convert_question(raw) {
+            Ok(question) => bank.questions.push(question),
+            Err(message) => bank.errors.push(QuestionError { number: i + 1, message }),
+        }
+    }
+
+    Ok(bank)
+}
+
+/// 将题库条目转换为 Question，并检查是否符合题型要求
+fn convert_question(raw: RawQuestion) -> std::result::Result<Question, String> {
+    let prompt = raw.prompt
+        .filter(|p| !p.trim().is_empty())
+        .ok_or("缺少题干 prompt")?;
+
+    let question_type = match raw.question_type.as_deref() {
+        Some("multiple_choice") => QuestionType::MultipleChoice,
+        Some("true_false") => QuestionType::TrueFalse,
+        Some("fill_in_blank") => QuestionType::FillInBlank,
+        Some("code_output") => QuestionType::CodeOutput,
+        Some("compiles_or_not") => QuestionType::CompilesOrNot,
+        Some(other) => return Err(format!("未知题型 \"{}\"", other)),
+        None => return Err("缺少题型 type".to_string()),
+    };
+
+    let code = raw.code.filter(|c| !c.trim().is_empty());
+    if question_type.is_code_question() && code.is_none() {
+        return Err("代码题缺少代码片段 code".to_string());
+    }
+
+    let answer = match raw.answer {
+        Some(answer) => answer,
+        None if question_type.is_code_question() => {
+            return Err("缺少正确答案 answer（可运行 validate-questions --record 按实际运行结果填写）".to_string());
+        }
+        None => return Err("缺少正确答案 answer".to_string()),
+    };
+
+    let (options, correct_answer) = match question_type {
+        QuestionType::MultipleChoice => {
+            let options = raw.options.unwrap_or_default();
+            if options.len() < 2 {
+                return Err("选择题至少需要 2 个选项".to_string());
+            }
+            let index = match answer {
+                RawAnswer::Index(i) => i,
+                _ => return Err("选择题的答案必须是选项序号（从 0 开始）".to_string()),
+            };
+            if index < 0 || index as usize >= options.len() {
+                return Err(format!("答案序号 {} 超出选项范围 0-{}", index, options.len() - 1));
+            }
+            (Some(options), CorrectAnswer::Choice(index as usize))
+        }
+        QuestionType::TrueFalse => {
+            let value = match answer {
+                RawAnswer::Bool(b) => b,
+                _ => return Err("判断题的答案必须是 true 或 false".to_string()),
+            };
+            let options = vec!["正确".to_string(), "错误".to_string()];
+            (Some(options), CorrectAnswer::Bool(value))
+        }
+        QuestionType::FillInBlank => {
+            let accepted = match answer {
+                RawAnswer::Text(t) => vec![t],
+                RawAnswer::List(list) => list,
+                _ => return Err("填空题的答案必须是字符串或字符串数组".to_string()),
+            };
+            if accepted.is_empty() || accepted.iter().any(|a| a.trim().is_empty()) {
+                return Err("填空题的答案不能为空".to_string());
+            }
+            (None, CorrectAnswer::Text(accepted))
+        }
+        QuestionType::CodeOutput => {
+            let accepted = match answer {
+                RawAnswer::Text(t) => vec![t],
+                RawAnswer::List(list) => list,
+                _ => return Err("代码输出题的答案必须是程序输出文本".to_string()),
+            };
+            (None, CorrectAnswer::Text(accepted))
+        }
+        QuestionType::CompilesOrNot => {
+            let value = match answer {
+                RawAnswer::Bool(b) => b,
+                _ => return Err("编译判断题的答案必须是 true（能编译）或 false（不能编译）".to_string()),
+            };
+            let options = vec!["能编译".to_string(), "不能编译".to_string()];
+            (Some(options), CorrectAnswer::Bool(value))
+        }
+    };
+
+    Ok(Question {
+        question_type,
+        prompt,
+        code,
+        options,
+        correct_answer,
+        explanation: raw.explanation.unwrap_or_default(),
+        topic: raw.topic.unwrap_or_else(|| "综合".to_string()),
+    })
+}
+
+/// 运行代码题的代码片段，核对题库答案
+///
+/// 返回不一致的说明；不是代码题时返回 None
+pub fn check_code_question(question: &Question) -> Result<Option<String>> {
+    let Some(code) = question.code.as_deref().filter(|_| question.question_type.is_code_question()) else {
+        return Ok(None);
+    };
+
+    let outcome = checker::run_snippet(code)?;
+    let message = match (&question.question_type, &question.correct_answer) {
+        (QuestionType::CodeOutput, CorrectAnswer::Text(accepted)) => {
+            if !outcome.compiled {
+                Some(format!("代码无法编译：{}", first_error(&outcome)))
+            } else {
+                let actual = normalize_text(&outcome.stdout);
+                (!accepted.iter().any(|a| normalize_text(a) == actual)).then(|| {
+                    format!("实际输出为 {:?}，题库答案为 {}", outcome.stdout.trim_end(), question.answer_text())
+                })
+            }
+        }
+        (QuestionType::CompilesOrNot, CorrectAnswer::Bool(expected)) => {
+            (outcome.compiled != *expected).then(|| if outcome.compiled {
+                "代码实际能通过编译，题库答案为「不能编译」".to_string()
+            } else {
+                format!("代码实际无法编译（{}），题库答案为「能编译」", first_error(&outcome))
+            })
+        }
+        _ => None,
+    };
+
+    Ok(message)
+}
+
+/// 编译输出中的第一条错误
+fn first_error(outcome: &SnippetOutcome) -> String {
+    outcome.stderr.lines()
+        .find(|line| line.starts_with("error"))
+        .unwrap_or("未知错误")
+        .to_string()
+}
+
+/// 由代码片段的实际行为得到的答案，代码输出题无法编译时返回 None
+fn recorded_answer(question_type: &str, outcome: &SnippetOutcome) -> Option<RecordedAnswer> {
+    match question_type {
+        "compiles_or_not" => Some(RecordedAnswer::Bool(outcome.compiled)),
+        _ if outcome.compiled => Some(RecordedAnswer::Text(outcome.stdout.trim_end().to_string())),
+        _ => None,
+    }
+}
+
+/// 自动填写的答案
+enum RecordedAnswer {
+    Bool(bool),
+    Text(String),
+}
+
+/// 为缺少答案的代码题运行代码片段，把实际结果写回题库文件
+///
+/// 返回填写的题目数量；无法填写的题目会在校验时照常报错
+pub fn record_code_answers(path: &Path) -> Result<usize> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("无法读取题库：{}", path.display()))?;
+
+    let mut recorded = 0;
+    let updated = if path.extension().is_some_and(|e| e == "json") {
+        let mut doc: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("题库 JSON 格式错误：{}", path.display()))?;
+        let questions = doc.get_mut("questions")
+            .and_then(|q| q.as_array_mut())
+            .into_iter()
+            .flatten();
+
+        for question in questions {
+            let Some(answer) = pending_answer(
+                question.get("type").and_then(|t| t.as_str()),
+                question.get("code").and_then(|c| c.as_str()),
+                question.get("answer").is_some(),
+            )? else {
+                continue;
+            };
+            question["answer"] = match answer {
+                RecordedAnswer::Bool(b) => serde_json::Value::Bool(b),
+                RecordedAnswer::Text(t) => serde_json::Value::String(t),
+            };
+            recorded += 1;
+        }
+        serde_json::to_string_pretty(&doc)? + "\n"
+    } else {
+        let mut doc: toml_edit::DocumentMut = content.parse()
+            .with_context(|| format!("题库 TOML 格式错误：{}", path.display()))?;
+        if let Some(questions) = doc.get_mut("questions").and_then(|q| q.as_array_of_tables_mut()) {
+            for question in questions.iter_mut() {
+                let Some(answer) = pending_answer(
+                    question.get("type").and_then(|t| t.as_str()),
+                    question.get("code").and_then(|c| c.as_str()),
+                    question.contains_key("answer"),
+                )? else {
+                    continue;
+                };
+                question["answer"] = match answer {
+                    RecordedAnswer::Bool(b) => toml_edit::value(b),
+                    RecordedAnswer::Text(t) => toml_edit::value(t),
+                };
+                recorded += 1;
+            }
+        }
+        doc.to_string()
+    };
+
+    if recorded > 0 {
+        fs::write(path, updated)
+            .with_context(|| format!("无法写入题库：{}", path.display()))?;
+    }
+    Ok(recorded)
+}
+
+/// 缺少答案的代码题：运行代码片段得到答案，其余题目返回 None
+fn pending_answer(question_type: Option<&str>, code: Option<&str>, has_answer: bool) -> Result<Option<RecordedAnswer>> {
+    let (Some(question_type), Some(code)) = (question_type, code) else {
+        return Ok(None);
+    };
+    if has_answer || !CODE_TYPES.contains(&question_type) || code.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let outcome = checker::run_snippet(code)?;
+    Ok(recorded_answer(question_type, &outcome))
+}
+
+/// 从模块题库中随机抽取题目
+pub fn pick_questions(module: &LearningModule, count: usize) -> Result<Vec<Question>> {
+    let Some(bank) = load_bank(module)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut questions = bank.questions;
+    questions.shuffle(&mut rand::thread_rng());
+    questions.truncate(count);
+    Ok(questions)
+}
+
+/// 校验所有模块的题库并打印报告，存在错误时返回 Err
+///
+/// `record` 为 true 时，先为缺少答案的代码题填写实际运行结果
+pub fn run_validate(modules: &[LearningModule], record: bool) -> Result<()> {
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🔍 Rust 学习伴侣 - 题库校验      ║");
+    println!("╚════════════════════════════════════════╝\n");
+
+    let mut error_count = 0;
+
+    for module in modules {
+        let Some(path) = find_bank_file(&module.directory) else {
+            println!("⚪ {} - 没有题库文件", module.id);
+            continue;
+        };
+
+        if record {
+            match record_code_answers(&path) {
+                Ok(0) => {}
+                Ok(n) => println!("📝 {} - 已按实际运行结果填写 {} 道代码题的答案", module.id, n),
+                Err(e) => println!("⚠️  {} - 无法填写代码题答案：{:#}", module.id, e),
+            }
+        }
+
+        match load_bank_file(&path) {
+            Ok(bank) => {
+                // 代码题逐个编译运行，核对题库答案
+                let mut mismatches = Vec::new();
+                for question in bank.questions.iter().filter(|q| q.question_type.is_code_question()) {
+                    match check_code_question(question) {
+                        Ok(None) => {}
+                        Ok(Some(message)) => mismatches.push((question.prompt.clone(), message)),
+                        Err(e) => mismatches.push((question.prompt.clone(), format!("{:#}", e))),
+                    }
+                }
+                let code_count = bank.questions.iter()
+                    .filter(|q| q.question_type.is_code_question())
+                    .count();
+
+                if bank.errors.is_empty() && mismatches.is_empty() {
+                    if code_count > 0 {
+                        println!("✅ {} - {} 道题（{} 道代码题已核对）",
+                            module.id, bank.questions.len(), code_count);
+                    } else {
+                        println!("✅ {} - {} 道题", module.id, bank.questions.len());
+                    }
+                    continue;
+                }
+
+                println!("❌ {} - {} 道题有效，{} 道题有误，{} 道代码题与实际运行结果不符",
+                    module.id, bank.questions.len(), bank.errors.len(), mismatches.len());
+                for error in &bank.errors {
+                    println!("   • 第 {} 题：{}", error.number, error.message);
+                }
+                for (prompt, message) in &mismatches {
+                    println!("   • 「{}」：{}", prompt, message);
+                }
+                error_count += bank.errors.len() + mismatches.len();
+            }
+            Err(e) => {
+                println!("❌ {} - {:#}", module.id, e);
+                error_count += 1;
+            }
+        }
+    }
+
+    println!();
+    if error_count > 0 {
+        return Err(anyhow::anyhow!("题库校验发现 {} 处错误", error_count));
+    }
+
+    println!("🎉 所有题库格式正确！");
+    Ok(())
+}
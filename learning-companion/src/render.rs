@@ -0,0 +1,584 @@
+//! 文件渲染模块
+//!
+//! 把 Markdown 和 Rust 源文件渲染成 ratatui 的文本行，供 TUI 文件查看器显示：
+//! Markdown 支持标题、列表、引用、表格、强调和代码块（Rust 代码块语法高亮），
+//! 同时生成目录，用于在章节之间跳转
+
+use crate::markdown::{is_fence, parse_heading, parse_task};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::path::Path;
+
+/// 分隔线的宽度
+const RULE_WIDTH: usize = 40;
+
+/// Rust 关键字
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+
+/// 目录中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub level: usize,
+    pub title: String,
+    /// 在渲染结果中的行号
+    pub line: usize,
+}
+
+/// 渲染结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rendered {
+    pub lines: Vec<Line<'static>>,
+    /// 每一行对应的源文件行号
+    pub sources: Vec<usize>,
+    pub toc: Vec<TocEntry>,
+}
+
+impl Rendered {
+    fn push(&mut self, line: Line<'static>, source: usize) {
+        self.lines.push(line);
+        self.sources.push(source);
+    }
+
+    /// 源文件第 source 行渲染后所在的行（该行被跳过时取其后最近的一行）
+    pub fn line_for_source(&self, source: usize) -> usize {
+        self.sources.iter().position(|&s| s >= source)
+            .unwrap_or(self.lines.len().saturating_sub(1))
+    }
+
+    /// 第 line 行所在的章节（目录中的下标）
+    pub fn section_at(&self, line: usize) -> Option<usize> {
+        self.toc.iter().rposition(|entry| entry.line <= line)
+    }
+
+    /// 每行的纯文本（测试和搜索用）
+    pub fn plain_lines(&self) -> Vec<String> {
+        self.lines.iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+}
+
+/// 按扩展名选择渲染方式
+pub fn render_file(path: &Path, content: &str) -> Rendered {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("md") => render_markdown(content),
+        Some("rs") => render_rust(content),
+        _ => render_plain(content),
+    }
+}
+
+/// 不做任何处理
+pub fn render_plain(content: &str) -> Rendered {
+    let mut rendered = Rendered::default();
+    for (i, line) in content.lines().enumerate() {
+        rendered.push(Line::from(line.to_string()), i);
+    }
+    rendered
+}
+
+/// 高亮 Rust 源文件，目录为顶层的函数、类型、impl 和 mod（以及其中缩进一级的函数）
+pub fn render_rust(content: &str) -> Rendered {
+    let mut highlighter = RustHighlighter::default();
+    let mut rendered = Rendered::default();
+
+    for (i, line) in content.lines().enumerate() {
+        if let Some(entry) = rust_item(line) {
+            rendered.toc.push(TocEntry { line: i, ..entry });
+        }
+        rendered.push(Line::from(highlighter.highlight(line)), i);
+    }
+    rendered
+}
+
+/// 识别可以放进目录的条目
+fn rust_item(line: &str) -> Option<TocEntry> {
+    let indent = line.len() - line.trim_start().len();
+    let level = match indent {
+        0 => 1,
+        4 => 2,
+        _ => return None,
+    };
+    let item = line.trim();
+    let rest = item.strip_prefix("pub(crate) ").or_else(|| item.strip_prefix("pub ")).unwrap_or(item);
+    let rest = rest.strip_prefix("async ").unwrap_or(rest);
+    let kinds: &[&str] = if level == 1 { &["fn ", "struct ", "enum ", "trait ", "impl", "mod "] } else { &["fn "] };
+    if !kinds.iter().any(|kind| rest.starts_with(kind)) {
+        return None;
+    }
+    let title = item.trim_end_matches('{').trim_end_matches(';').trim().to_string();
+    Some(TocEntry { level, title, line: 0 })
+}
+
+/// 逐行高亮 Rust 代码，记住跨行的块注释和字符串
+#[derive(Debug, Default)]
+pub struct RustHighlighter {
+    /// 块注释嵌套深度
+    comment_depth: u32,
+    /// 上一行结束时还在字符串中
+    in_string: bool,
+}
+
+fn comment_style() -> Style {
+    Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)
+}
+
+fn string_style() -> Style {
+    Style::default().fg(Color::Green)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl RustHighlighter {
+    pub fn highlight(&mut self, line: &str) -> Vec<Span<'static>> {
+        let chars: Vec<(usize, char)> = line.char_indices().collect();
+        let byte_at = |i: usize| chars.get(i).map_or(line.len(), |(b, _)| *b);
+        let char_at = |i: usize| chars.get(i).map(|(_, c)| *c);
+
+        // (起始字符, 结束字符, 样式)，未覆盖的部分为普通文本
+        let mut tokens: Vec<(usize, usize, Style)> = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let start = i;
+            if self.comment_depth > 0 {
+                while i < chars.len() && self.comment_depth > 0 {
+                    if char_at(i) == Some('*') && char_at(i + 1) == Some('/') {
+                        self.comment_depth -= 1;
+                        i += 2;
+                    } else if char_at(i) == Some('/') && char_at(i + 1) == Some('*') {
+                        self.comment_depth += 1;
+                        i += 2;
+                    } else {
+                        i += 1;
+                    }
+                }
+                tokens.push((start, i, comment_style()));
+                continue;
+            }
+            if self.in_string {
+                while i < chars.len() {
+                    match char_at(i) {
+                        Some('\\') => i += 2,
+                        Some('"') => {
+                            self.in_string = false;
+                            i += 1;
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+                tokens.push((start, i.min(chars.len()), string_style()));
+                i = i.min(chars.len());
+                continue;
+            }
+
+            let c = chars[i].1;
+            let prev_ident = i > 0 && is_ident_char(chars[i - 1].1);
+            match c {
+                '/' if char_at(i + 1) == Some('/') => {
+                    tokens.push((i, chars.len(), comment_style()));
+                    i = chars.len();
+                }
+                '/' if char_at(i + 1) == Some('*') => {
+                    self.comment_depth = 1;
+                    i += 2;
+                    tokens.push((start, i, comment_style()));
+                }
+                '"' => {
+                    self.in_string = true;
+                    i += 1;
+                    tokens.push((start, i, string_style()));
+                }
+                'b' if !prev_ident && char_at(i + 1) == Some('"') => {
+                    self.in_string = true;
+                    i += 2;
+                    tokens.push((start, i, string_style()));
+                }
+                'r' if !prev_ident && matches!(char_at(i + 1), Some('"') | Some('#')) => {
+                    // 原始字符串 r"..." / r#"..."#（只在本行内查找结尾）
+                    let hashes = chars[i + 1..].iter().take_while(|(_, c)| *c == '#').count();
+                    if char_at(i + 1 + hashes) == Some('"') {
+                        let closing = format!("\"{}", "#".repeat(hashes));
+                        let body_start = byte_at(i + 2 + hashes);
+                        let end_byte = line[body_start..].find(&closing)
+                            .map_or(line.len(), |pos| body_start + pos + closing.len());
+                        let end = chars.iter().position(|(b, _)| *b >= end_byte).unwrap_or(chars.len());
+                        tokens.push((start, end, string_style()));
+                        i = end;
+                    } else {
+                        i = self.word(&chars, i, &mut tokens);
+                    }
+                }
+                '\'' => {
+                    // 字符字面量 'a'、'\n'，否则是生命周期 'a
+                    if char_at(i + 1) == Some('\\') {
+                        let end = (i + 2..chars.len()).find(|&j| char_at(j) == Some('\'')).map_or(chars.len(), |j| j + 1);
+                        tokens.push((start, end, string_style()));
+                        i = end;
+                    } else if char_at(i + 2) == Some('\'') {
+                        tokens.push((start, i + 3, string_style()));
+                        i += 3;
+                    } else {
+                        i += 1;
+                        while char_at(i).is_some_and(is_ident_char) {
+                            i += 1;
+                        }
+                        tokens.push((start, i, Style::default().fg(Color::Cyan)));
+                    }
+                }
+                '#' if char_at(i + 1) == Some('[') || (char_at(i + 1) == Some('!') && char_at(i + 2) == Some('[')) => {
+                    let mut depth = 0;
+                    while i < chars.len() {
+                        match chars[i].1 {
+                            '[' => depth += 1,
+                            ']' => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    i += 1;
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                    tokens.push((start, i, Style::default().fg(Color::LightBlue)));
+                }
+                c if c.is_ascii_digit() && !prev_ident => {
+                    while let Some(c) = char_at(i) {
+                        let is_decimal_point = c == '.' && char_at(i + 1).is_some_and(|n| n.is_ascii_digit());
+                        if is_ident_char(c) || is_decimal_point {
+                            i += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    tokens.push((start, i, Style::default().fg(Color::Cyan)));
+                }
+                c if (c.is_alphabetic() || c == '_') && !prev_ident => {
+                    i = self.word(&chars, i, &mut tokens);
+                }
+                _ => i += 1,
+            }
+        }
+
+        // 合并成 Span，相邻的普通文本连在一起
+        let mut spans = Vec::new();
+        let mut plain_from = 0;
+        for (start, end, style) in tokens {
+            if start > plain_from {
+                spans.push(Span::raw(line[byte_at(plain_from)..byte_at(start)].to_string()));
+            }
+            if end > start {
+                let text = &line[byte_at(start)..byte_at(end)];
+                // 字符串的开头引号和内容分两次识别，合并成一段
+                match spans.last_mut() {
+                    Some(last) if start == plain_from && last.style == style => last.content.to_mut().push_str(text),
+                    _ => spans.push(Span::styled(text.to_string(), style)),
+                }
+            }
+            plain_from = end;
+        }
+        if plain_from < chars.len() {
+            spans.push(Span::raw(line[byte_at(plain_from)..].to_string()));
+        }
+        spans
+    }
+
+    /// 标识符：关键字、宏调用、类型名（大写开头）分别着色，返回结束位置
+    fn word(&self, chars: &[(usize, char)], start: usize, tokens: &mut Vec<(usize, usize, Style)>) -> usize {
+        let mut end = start;
+        while end < chars.len() && is_ident_char(chars[end].1) {
+            end += 1;
+        }
+        let word: String = chars[start..end].iter().map(|(_, c)| c).collect();
+        let next = chars.get(end).map(|(_, c)| *c);
+
+        if next == Some('!') && chars.get(end + 1).map(|(_, c)| *c) != Some('=') {
+            tokens.push((start, end + 1, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)));
+            return end + 1;
+        }
+        if KEYWORDS.contains(&word.as_str()) {
+            tokens.push((start, end, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+        } else if word.starts_with(|c: char| c.is_uppercase()) {
+            tokens.push((start, end, Style::default().fg(Color::Yellow)));
+        }
+        end
+    }
+}
+
+/// 渲染 Markdown，目录包含所有标题
+pub fn render_markdown(content: &str) -> Rendered {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut rendered = Rendered::default();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if is_fence(line) {
+            i = render_code_block(&lines, i, &mut rendered);
+            continue;
+        }
+        if trimmed.starts_with('|') {
+            let end = (i..lines.len()).find(|&j| !lines[j].trim_start().starts_with('|')).unwrap_or(lines.len());
+            render_table(&lines[i..end], i, &mut rendered);
+            i = end;
+            continue;
+        }
+
+        if let Some((level, title)) = parse_heading(line) {
+            rendered.toc.push(TocEntry { level, title: title.clone(), line: rendered.lines.len() });
+            let style = match level {
+                1 => Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                2 => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                3 => Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+                _ => Style::default().add_modifier(Modifier::BOLD),
+            };
+            let spans = inline(&title, style);
+            let width: usize = spans.iter().map(|s| s.width()).sum();
+            rendered.push(Line::from(spans), i);
+            if level <= 2 {
+                let rule = if level == 1 { "═" } else { "─" };
+                rendered.push(Line::from(Span::styled(rule.repeat(width), style)), i);
+            }
+        } else if is_rule(trimmed) {
+            rendered.push(Line::from(Span::styled("─".repeat(RULE_WIDTH), Style::default().fg(Color::DarkGray))), i);
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let mut spans = vec![Span::styled("▎ ", Style::default().fg(Color::DarkGray))];
+            spans.extend(inline(quote.trim_start(), Style::default().fg(Color::Gray).add_modifier(Modifier::ITALIC)));
+            rendered.push(Line::from(spans), i);
+        } else if let Some(spans) = list_item(line) {
+            rendered.push(Line::from(spans), i);
+        } else {
+            rendered.push(Line::from(inline(line, Style::default())), i);
+        }
+        i += 1;
+    }
+
+    rendered
+}
+
+/// `---`、`***`、`___` 分隔线
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && ["-", "*", "_"].iter().any(|m| compact.chars().all(|c| c.to_string() == *m))
+}
+
+/// 列表项：无序列表换成圆点，任务清单换成复选框，有序列表保留序号
+fn list_item(line: &str) -> Option<Vec<Span<'static>>> {
+    let trimmed = line.trim_start();
+    let indent = " ".repeat(line.len() - trimmed.len());
+    let marker_style = Style::default().fg(Color::Cyan);
+
+    if let Some((checked, text)) = parse_task(line) {
+        let (mark, style) = if checked {
+            ("☑ ", Style::default().fg(Color::Green))
+        } else {
+            ("☐ ", Style::default())
+        };
+        let mut spans = vec![Span::raw(indent), Span::styled(mark, style)];
+        spans.extend(inline(&text, Style::default()));
+        return Some(spans);
+    }
+
+    let (marker, rest) = if let Some(rest) = ["- ", "* ", "+ "].iter().find_map(|m| trimmed.strip_prefix(m)) {
+        ("• ".to_string(), rest)
+    } else {
+        let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+        let rest = trimmed[digits..].strip_prefix(". ").filter(|_| digits > 0)?;
+        (format!("{}. ", &trimmed[..digits]), rest)
+    };
+    let mut spans = vec![Span::raw(indent), Span::styled(marker, marker_style)];
+    spans.extend(inline(rest, Style::default()));
+    Some(spans)
+}
+
+/// 渲染从 start 行的围栏开始的代码块，返回代码块之后的行号
+fn render_code_block(lines: &[&str], start: usize, out: &mut Rendered) -> usize {
+    let border = Style::default().fg(Color::DarkGray);
+    let lang = lines[start].trim_start().trim_start_matches(['`', '~']).trim().to_string();
+    let is_rust = matches!(lang.split(',').next().unwrap_or_default(), "rust" | "rs");
+
+    out.push(Line::from(Span::styled(format!("┌─ {}", lang).trim_end().to_string(), border)), start);
+    let mut highlighter = RustHighlighter::default();
+    let mut i = start + 1;
+    while i < lines.len() && !is_fence(lines[i]) {
+        let mut spans = vec![Span::styled("│ ", border)];
+        if is_rust {
+            spans.extend(highlighter.highlight(lines[i]));
+        } else {
+            spans.push(Span::styled(lines[i].to_string(), Style::default().fg(Color::Gray)));
+        }
+        out.push(Line::from(spans), i);
+        i += 1;
+    }
+    out.push(Line::from(Span::styled("└─", border)), i.min(lines.len().saturating_sub(1)));
+    // 跳过结束的围栏（没有结束围栏时代码块到文件末尾）
+    (i + 1).min(lines.len())
+}
+
+/// 表格的列对齐方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// 拆分表格行的单元格
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+/// 表头下的 `|---|:---:|` 分隔行
+fn separator_alignments(cells: &[String]) -> Option<Vec<Align>> {
+    cells.iter()
+        .map(|cell| {
+            let dashes = cell.trim_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Align::Center,
+                (false, true) => Align::Right,
+                _ => Align::Left,
+            })
+        })
+        .collect()
+}
+
+/// 用框线字符渲染表格，列宽按显示宽度（中文占两格）对齐；first 为表格第一行的行号
+fn render_table(rows: &[&str], first: usize, out: &mut Rendered) {
+    let border = Style::default().fg(Color::DarkGray);
+    // 按源文件中的行数计算，下面会去掉分隔行
+    let last = first + rows.len() - 1;
+    let mut rows: Vec<Vec<String>> = rows.iter().map(|row| table_cells(row)).collect();
+    let alignments = rows.get(1).and_then(|row| separator_alignments(row));
+    let has_header = alignments.is_some();
+    if has_header {
+        rows.remove(1);
+    }
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let cells: Vec<Vec<Vec<Span<'static>>>> = rows.iter().enumerate()
+        .map(|(r, row)| {
+            let style = if r == 0 && has_header { Style::default().add_modifier(Modifier::BOLD) } else { Style::default() };
+            (0..columns).map(|c| inline(row.get(c).map_or("", String::as_str), style)).collect()
+        })
+        .collect();
+    let cell_width = |spans: &[Span]| spans.iter().map(|s| s.width()).sum::<usize>();
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| cells.iter().map(|row| cell_width(&row[c])).max().unwrap_or(0))
+        .collect();
+
+    let rule = |left: &str, mid: &str, right: &str| {
+        let parts: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        Line::from(Span::styled(format!("{}{}{}", left, parts.join(mid), right), border))
+    };
+
+    out.push(rule("┌", "┬", "┐"), first);
+    for (r, row) in cells.into_iter().enumerate() {
+        let mut spans = vec![Span::styled("│", border)];
+        for (c, cell) in row.into_iter().enumerate() {
+            let pad = widths[c] - cell_width(&cell);
+            let (before, after) = match alignments.as_ref().and_then(|a| a.get(c)).copied().unwrap_or(Align::Left) {
+                Align::Left => (0, pad),
+                Align::Right => (pad, 0),
+                Align::Center => (pad / 2, pad - pad / 2),
+            };
+            spans.push(Span::raw(" ".repeat(before + 1)));
+            spans.extend(cell);
+            spans.push(Span::raw(" ".repeat(after + 1)));
+            spans.push(Span::styled("│", border));
+        }
+        // 分隔行已经去掉，表头之后的行在源文件中要多算一行
+        let source = if has_header && r > 0 { first + r + 1 } else { first + r };
+        out.push(Line::from(spans), source);
+        if r == 0 && has_header {
+            out.push(rule("├", "┼", "┤"), first + 1);
+        }
+    }
+    out.push(rule("└", "┴", "┘"), last);
+}
+
+/// 行内格式：`代码`、**粗体**、*斜体*、~~删除线~~、[链接](地址)、![图片](地址)
+fn inline(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    let flush = |plain: &mut String, spans: &mut Vec<Span<'static>>| {
+        if !plain.is_empty() {
+            spans.push(Span::styled(std::mem::take(plain), base));
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        // 以 marker 开始、在本行内有对应结尾的片段
+        let delimited = |marker: &str| -> Option<(&str, &str)> {
+            let inner = rest.strip_prefix(marker)?;
+            let end = inner.find(marker).filter(|&end| end > 0)?;
+            Some((&inner[..end], &inner[end + marker.len()..]))
+        };
+        let after_word = plain.chars().last().is_some_and(is_ident_char);
+
+        if c == '\\' && rest.len() > 1 {
+            let escaped = rest[1..].chars().next().unwrap_or_default();
+            plain.push(escaped);
+            rest = &rest[1 + escaped.len_utf8()..];
+        } else if let Some((code, after)) = delimited("`") {
+            flush(&mut plain, &mut spans);
+            spans.push(Span::styled(code.to_string(), base.fg(Color::LightRed)));
+            rest = after;
+        } else if let Some((inner, after)) = delimited("**").or_else(|| delimited("__").filter(|_| !after_word)) {
+            flush(&mut plain, &mut spans);
+            spans.extend(inline(inner, base.add_modifier(Modifier::BOLD)));
+            rest = after;
+        } else if let Some((inner, after)) = delimited("~~") {
+            flush(&mut plain, &mut spans);
+            spans.extend(inline(inner, base.add_modifier(Modifier::CROSSED_OUT)));
+            rest = after;
+        } else if let Some((inner, after)) = delimited("*").or_else(|| delimited("_").filter(|_| !after_word))
+            .filter(|(inner, _)| !inner.starts_with(' '))
+        {
+            flush(&mut plain, &mut spans);
+            spans.extend(inline(inner, base.add_modifier(Modifier::ITALIC)));
+            rest = after;
+        } else if let Some((label, after)) = link(rest) {
+            flush(&mut plain, &mut spans);
+            let link_style = base.fg(Color::Blue).add_modifier(Modifier::UNDERLINED);
+            if rest.starts_with('!') {
+                spans.push(Span::styled(format!("🖼 {}", label), link_style));
+            } else {
+                spans.extend(inline(label, link_style));
+            }
+            rest = after;
+        } else {
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    flush(&mut plain, &mut spans);
+    spans
+}
+
+/// `[文字](地址)` 或 `![说明](地址)`，返回 (文字, 其后的内容)
+fn link(text: &str) -> Option<(&str, &str)> {
+    let inner = text.strip_prefix("![").or_else(|| text.strip_prefix('['))?;
+    let close = inner.find("](")?;
+    let after = &inner[close + 2..];
+    let end = after.find(')')?;
+    Some((&inner[..close], &after[end + 1..]))
+}
@@ -5,13 +5,20 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use serde::{Deserialize, Serialize};
-use anyhow::{Result, Context};
+use anyhow::Result;
+use crate::markdown;
+use crate::progress::TaskType;
 
 /// 学习模块信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LearningModule {
     pub id: String,
+    /// 模块编号，与 进度.md 中的标题编号对应
+    pub number: u32,
+    /// 显示名称（如 01-基础入门）
     pub name: String,
+    /// 先修模块的目录名
+    pub prerequisites: Vec<String>,
     pub directory: PathBuf,
     pub has_readme: bool,
     pub has_exercises: bool,
@@ -19,9 +26,23 @@ pub struct LearningModule {
     pub has_checklist: bool,
 }
 
+/// 模块自带的元数据
+///
+/// 优先读取 Cargo.toml 的 `[package.metadata.learning]`，其次是 README.md 的 front matter
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ModuleMetadata {
+    pub number: Option<u32>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+}
+
 /// 进度文件中的模块状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleProgress {
+    /// 标题中的模块编号
+    pub number: u32,
+    /// 标题文本（如 04-生命周期 ⚠️）
     pub name: String,
     pub status: String, // "[ ]", "[~]", or "[x]"
     pub concept: bool,
@@ -29,6 +50,8 @@ pub struct ModuleProgress {
     pub exercises: bool,
     pub project: bool,
     pub checklist: bool,
+    /// `**完成日期**` 栏填写的内容，未填写时为 None
+    pub completed_at: Option<String>,
 }
 
 /// 学习仓库
@@ -63,10 +86,20 @@ impl LearningRepo {
             // 识别模块目录 (module-XX-*)
             if name.starts_with("module-") && entry.path().is_dir() {
                 let module_path = entry.path();
+                let metadata = read_module_metadata(&module_path);
+
+                let number = metadata.number
+                    .or_else(|| number_from_id(&name))
+                    .unwrap_or(0);
+                let title = metadata.name
+                    .or_else(|| readme_title(&module_path))
+                    .unwrap_or_else(|| name.splitn(3, '-').nth(2).unwrap_or(&name).to_string());
 
                 let module = LearningModule {
                     id: name.clone(),
-                    name: Self::extract_module_name(&name),
+                    number,
+                    name: format!("{:02}-{}", number, title),
+                    prerequisites: metadata.prerequisites,
                     directory: module_path.clone(),
                     has_readme: module_path.join("README.md").exists(),
                     has_exercises: module_path.join("exercises.md").exists(),
@@ -79,36 +112,11 @@ impl LearningRepo {
         }
 
         // 按模块编号排序
-        modules.sort_by(|a, b| a.id.cmp(&b.id));
+        modules.sort_by(|a, b| (a.number, &a.id).cmp(&(b.number, &b.id)));
 
         Ok(modules)
     }
 
-    /// 从目录名提取模块中文名
-    fn extract_module_name(id: &str) -> String {
-        // 从 module-01-basics 提取并映射到中文名
-        let names = vec![
-            ("module-01-basics", "01-基础入门"),
-            ("module-02-ownership", "02-所有权系统"),
-            ("module-03-structs-enums", "03-结构体与枚举"),
-            ("module-04-patterns", "04-模式匹配"),
-            ("module-05-error-handling", "05-错误处理"),
-            ("module-06-collections", "06-集合类型"),
-            ("module-07-generics", "07-泛型与Trait"),
-            ("module-08-lifetimes", "08-生命周期"),
-            ("module-09-concurrency", "09-并发编程"),
-            ("module-10-project", "10-实战项目"),
-        ];
-
-        for (id_pattern, name) in names {
-            if id == id_pattern {
-                return name.to_string();
-            }
-        }
-
-        id.to_string()
-    }
-
     /// 解析 进度.md 文件
     fn parse_progress_file(base_path: &Path) -> Result<Vec<ModuleProgress>> {
         let progress_path = base_path.join("进度.md");
@@ -117,67 +125,7 @@ impl LearningRepo {
         }
 
         let content = fs::read_to_string(&progress_path)?;
-        let mut progress_list = Vec::new();
-
-        // 简单解析（实际可以用正则表达式更精确）
-        let lines: Vec<&str> = content.lines().collect();
-        let mut current_module: Option<ModuleProgress> = None;
-
-        for line in lines {
-            // 检测模块标题
-            if line.contains("### ") && (line.contains("-基础入门")
-                || line.contains("-所有权系统")
-                || line.contains("-结构体")
-                || line.contains("-模式匹配")
-                || line.contains("-错误处理")
-                || line.contains("-集合类型")
-                || line.contains("-泛型")
-                || line.contains("-生命周期")
-                || line.contains("-并发编程")
-                || line.contains("-实战项目"))
-            {
-                if let Some(module) = current_module.take() {
-                    progress_list.push(module);
-                }
-
-                // 提取模块名
-                let name = line.split("### ").nth(1).unwrap_or("").trim().to_string();
-                current_module = Some(ModuleProgress {
-                    name: name.clone(),
-                    status: "[ ]".to_string(),
-                    concept: false,
-                    examples: false,
-                    exercises: false,
-                    project: false,
-                    checklist: false,
-                });
-            }
-
-            // 解析任务状态
-            if let Some(ref mut module) = current_module {
-                if line.contains("- [x] 概念学习") || line.contains("- [x] 代码示例") {
-                    module.concept = true;
-                    module.examples = true;
-                    module.status = "[~]".to_string();
-                }
-                if line.contains("- [x] 练习题完成") {
-                    module.exercises = true;
-                }
-                if line.contains("- [x] 综合练习") {
-                    module.project = true;
-                }
-                if line.contains("- [x] 自检通过") {
-                    module.checklist = true;
-                    module.status = "[x]".to_string();
-                }
-            }
-        }
-
-        if let Some(module) = current_module {
-            progress_list.push(module);
-        }
-
-        Ok(progress_list)
+        Ok(parse_progress(&content))
     }
 
     /// 计算总体完成百分比
@@ -193,8 +141,155 @@ impl LearningRepo {
         (completed as f32 / self.modules.len() as f32) * 100.0
     }
 
-    /// 获取模块进度
-    pub fn get_module_progress(&self, module_name: &str) -> Option<&ModuleProgress> {
-        self.progress.iter().find(|p| p.name.contains(module_name))
+    /// 按目录名、中文名或主题名（如 basics）查找模块
+    pub fn find_module(&self, key: &str) -> Option<&LearningModule> {
+        self.modules.iter().find(|m| {
+            m.id == key
+                || m.name == key
+                || m.id.splitn(3, '-').nth(2) == Some(key)
+        })
+    }
+
+    /// 把模块名或主题名换成目录名（数据库中的模块键），找不到模块时原样返回
+    pub fn module_key<'a>(&'a self, key: &'a str) -> &'a str {
+        self.find_module(key).map_or(key, |m| m.id.as_str())
+    }
+
+    /// 获取模块进度（按模块编号匹配 进度.md 中的标题）
+    pub fn get_module_progress(&self, module_id: &str) -> Option<&ModuleProgress> {
+        let number = self.find_module(module_id)?.number;
+        self.progress.iter().find(|p| p.number == number)
+    }
+}
+
+/// 从 module-07-collections 中提取编号 7
+fn number_from_id(id: &str) -> Option<u32> {
+    id.split('-').nth(1)?.parse().ok()
+}
+
+/// 读取模块元数据，Cargo.toml 中没有时读取 README front matter
+pub fn read_module_metadata(module_dir: &Path) -> ModuleMetadata {
+    let from_manifest = fs::read_to_string(module_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|table| {
+            table.get("package")?
+                .get("metadata")?
+                .get("learning")?
+                .clone()
+                .try_into::<ModuleMetadata>()
+                .ok()
+        });
+    if let Some(metadata) = from_manifest {
+        return metadata;
+    }
+
+    fs::read_to_string(module_dir.join("README.md"))
+        .ok()
+        .and_then(|content| markdown::front_matter(&content))
+        .map(|entries| metadata_from_front_matter(&entries))
+        .unwrap_or_default()
+}
+
+/// 把 front matter 键值转换为模块元数据
+pub fn metadata_from_front_matter(entries: &[(String, String)]) -> ModuleMetadata {
+    let mut metadata = ModuleMetadata::default();
+    for (key, value) in entries {
+        match key.as_str() {
+            "number" => metadata.number = value.parse().ok(),
+            "name" => metadata.name = Some(value.clone()),
+            "prerequisites" => metadata.prerequisites.push(value.clone()),
+            _ => {}
+        }
+    }
+    metadata
+}
+
+/// README 一级标题，去掉编号和括号中的英文名（「07-集合类型 (Collections)」→「集合类型」）
+fn readme_title(module_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(module_dir.join("README.md")).ok()?;
+    let title = content.lines().find_map(|line| line.strip_prefix("# "))?;
+    let title = title.split(" (").next().unwrap_or(title);
+    let title = title.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('-');
+    Some(title.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// 解析 进度.md 内容
+///
+/// 以「NN-名称」开头的标题为一个模块；任务按文字匹配，与顺序无关
+pub fn parse_progress(content: &str) -> Vec<ModuleProgress> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut progress_list = Vec::new();
+
+    for section in markdown::parse_sections(content) {
+        let Some(number) = heading_number(&section.title) else {
+            continue;
+        };
+
+        let mut progress = ModuleProgress {
+            number,
+            name: section.title.clone(),
+            status: "[ ]".to_string(),
+            concept: false,
+            examples: false,
+            exercises: false,
+            project: false,
+            checklist: false,
+            completed_at: None,
+        };
+
+        for (_, line) in section.body(&lines) {
+            if let Some(value) = completion_date_value(line) {
+                progress.completed_at = value;
+                continue;
+            }
+            let Some((checked, text)) = markdown::parse_task(line) else {
+                continue;
+            };
+            match TaskType::from_label(&text) {
+                Some(TaskType::Concept) => progress.concept = checked,
+                Some(TaskType::Examples) => progress.examples = checked,
+                Some(TaskType::Exercises) => progress.exercises = checked,
+                Some(TaskType::Project) => progress.project = checked,
+                Some(TaskType::Checklist) => progress.checklist = checked,
+                None => {}
+            }
+        }
+
+        let tasks = [progress.concept, progress.examples, progress.exercises, progress.project, progress.checklist];
+        progress.status = if tasks.iter().all(|&t| t) {
+            "[x]"
+        } else if tasks.iter().any(|&t| t) {
+            "[~]"
+        } else {
+            "[ ]"
+        }.to_string();
+
+        progress_list.push(progress);
+    }
+
+    progress_list
+}
+
+/// 进度.md 中完成日期栏的标记
+pub const COMPLETION_DATE_LABEL: &str = "**完成日期**";
+
+/// 解析 `**完成日期**：2024-03-01` 行；不是完成日期行时返回 None，未填写（下划线占位）时返回 Some(None)
+pub fn completion_date_value(line: &str) -> Option<Option<String>> {
+    let rest = line.trim().strip_prefix(COMPLETION_DATE_LABEL)?;
+    let value = rest.trim_start_matches(['：', ':']).trim();
+    if value.chars().all(|c| c == '_') {
+        Some(None)
+    } else {
+        Some(Some(value.to_string()))
+    }
+}
+
+/// 标题开头的模块编号（「04-生命周期 ⚠️」→ 4）
+pub fn heading_number(title: &str) -> Option<u32> {
+    let (number, rest) = title.split_once('-')?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) || rest.trim().is_empty() {
+        return None;
     }
+    number.parse().ok()
 }
@@ -0,0 +1,616 @@
+//! 学习报告模块
+//!
+//! 汇总一段时间内的掌握程度变化、练习得分、薄弱知识点、学习时间热力图、成就和连续学习天数，
+//! 生成 Markdown 或单文件 HTML（图表为内嵌 SVG，不依赖外部资源）
+
+use crate::db::Store;
+use crate::repo::LearningRepo;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 报告覆盖的时间段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Period {
+    /// 最近 7 天
+    #[value(alias = "周")]
+    Week,
+    /// 最近 30 天
+    #[value(alias = "月")]
+    Month,
+    /// 全部记录
+    #[value(alias = "全部")]
+    All,
+}
+
+impl Period {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Period::Week => "最近 7 天",
+            Period::Month => "最近 30 天",
+            Period::All => "全部记录",
+        }
+    }
+
+    /// 起始日期；全部记录时为 None
+    pub fn start(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Period::Week => Some(today - Duration::days(6)),
+            Period::Month => Some(today - Duration::days(29)),
+            Period::All => None,
+        }
+    }
+}
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown 文档
+    #[value(name = "md", alias = "markdown")]
+    Markdown,
+    /// 单个 HTML 文件，图表为内嵌 SVG
+    Html,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// 一个模块在报告期内的掌握程度变化
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleTrend {
+    pub module_id: String,
+    pub name: String,
+    /// 期初的掌握程度（报告期开始前最后一次记录），没有时为 None
+    pub initial: Option<f32>,
+    /// 报告期内的记录
+    pub points: Vec<(DateTime<Local>, f32)>,
+}
+
+impl ModuleTrend {
+    /// 期末的掌握程度
+    pub fn current(&self) -> f32 {
+        self.points.last().map(|(_, m)| *m).or(self.initial).unwrap_or(0.0)
+    }
+
+    /// 报告期内的变化
+    pub fn change(&self) -> f32 {
+        self.current() - self.initial.unwrap_or(0.0)
+    }
+}
+
+/// 一次练习的得分
+#[derive(Debug, Clone, PartialEq)]
+pub struct PracticePoint {
+    pub at: DateTime<Local>,
+    pub module_id: String,
+    pub score: f32,
+}
+
+/// 知识点的答错次数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicMisses {
+    pub topic: String,
+    pub misses: u32,
+    pub attempts: u32,
+}
+
+/// 报告中的成就
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportAchievement {
+    pub icon: String,
+    pub name: String,
+    pub description: String,
+    pub unlocked_at: Option<DateTime<Local>>,
+    /// 是否在报告期内解锁
+    pub in_period: bool,
+}
+
+/// 报告数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportData {
+    pub period: Period,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub generated_at: DateTime<Local>,
+    pub modules: Vec<ModuleTrend>,
+    pub practice: Vec<PracticePoint>,
+    /// 按答错次数从多到少排列
+    pub weak_topics: Vec<TopicMisses>,
+    /// 报告期内每天的学习分钟数（没有学习的日子不在其中）
+    pub study_minutes: BTreeMap<NaiveDate, u32>,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub achievements: Vec<ReportAchievement>,
+    pub achievements_total: usize,
+}
+
+/// 报告最多列出的薄弱知识点数
+const WEAK_TOPIC_LIMIT: usize = 10;
+
+/// 按答错次数排序知识点（次数相同时按答错率）
+pub fn rank_weak_topics(reviews: &[(String, bool)]) -> Vec<TopicMisses> {
+    let mut topics: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    for (topic, correct) in reviews {
+        let entry = topics.entry(topic.as_str()).or_default();
+        entry.1 += 1;
+        if !correct {
+            entry.0 += 1;
+        }
+    }
+
+    let mut ranked: Vec<TopicMisses> = topics.into_iter()
+        .filter(|(_, (misses, _))| *misses > 0)
+        .map(|(topic, (misses, attempts))| TopicMisses { topic: topic.to_string(), misses, attempts })
+        .collect();
+    ranked.sort_by(|a, b| b.misses.cmp(&a.misses)
+        .then((b.misses * a.attempts).cmp(&(a.misses * b.attempts))));
+    ranked
+}
+
+impl ReportData {
+    /// 从数据库收集报告数据
+    pub fn collect(store: &Store, repo: &LearningRepo, period: Period, now: DateTime<Local>) -> Result<Self> {
+        let today = now.date_naive();
+        let study_days = store.get_study_minutes_by_date()?;
+        let practice = store.get_all_practice_results()?;
+        let history = store.get_mastery_history()?;
+
+        // 全部记录时从最早的数据开始
+        let earliest = [
+            study_days.first().map(|(d, _)| *d),
+            practice.first().map(|(_, p)| p.timestamp.date_naive()),
+            history.first().map(|s| s.recorded_at.date_naive()),
+        ];
+        let start = period.start(today)
+            .or_else(|| earliest.iter().flatten().min().copied())
+            .unwrap_or(today)
+            .min(today);
+        let in_period = |at: &DateTime<Local>| at.date_naive() >= start && at.date_naive() <= today;
+
+        let mut modules: Vec<ModuleTrend> = Vec::new();
+        for snapshot in &history {
+            let index = match modules.iter().position(|m| m.module_id == snapshot.module_id) {
+                Some(index) => index,
+                None => {
+                    let name = repo.modules.iter()
+                        .find(|m| m.id == snapshot.module_id)
+                        .map_or_else(|| snapshot.module_id.clone(), |m| m.name.clone());
+                    modules.push(ModuleTrend { module_id: snapshot.module_id.clone(), name, initial: None, points: Vec::new() });
+                    modules.len() - 1
+                }
+            };
+            if snapshot.recorded_at.date_naive() < start {
+                modules[index].initial = Some(snapshot.mastery);
+            } else if in_period(&snapshot.recorded_at) {
+                modules[index].points.push((snapshot.recorded_at, snapshot.mastery));
+            }
+        }
+        modules.sort_by_key(|m| repo.modules.iter().position(|r| r.id == m.module_id).unwrap_or(usize::MAX));
+
+        let practice = practice.into_iter()
+            .filter(|(_, p)| in_period(&p.timestamp))
+            .map(|(module_id, p)| PracticePoint { at: p.timestamp, module_id, score: p.score })
+            .collect();
+
+        let reviews: Vec<(String, bool)> = store.get_question_reviews()?
+            .into_iter()
+            .filter(|r| in_period(&r.reviewed_at))
+            .map(|r| (r.topic, r.correct))
+            .collect();
+        let mut weak_topics = rank_weak_topics(&reviews);
+        weak_topics.truncate(WEAK_TOPIC_LIMIT);
+
+        let study_minutes = study_days.into_iter()
+            .filter(|(d, _)| *d >= start && *d <= today)
+            .collect();
+
+        let streak = crate::streak::calculate(store, &now)?;
+
+        let unlocked = store.get_unlocked_achievements()?;
+        let achievements = crate::achievements::definitions().iter()
+            .filter_map(|def| {
+                let record = unlocked.iter().find(|u| u.achievement_type == def.id)?;
+                Some(ReportAchievement {
+                    icon: def.icon.clone(),
+                    name: def.name.clone(),
+                    description: def.description.clone(),
+                    unlocked_at: record.unlocked_at,
+                    in_period: record.unlocked_at.is_some_and(|t| in_period(&t)),
+                })
+            })
+            .collect();
+
+        Ok(ReportData {
+            period,
+            start,
+            end: today,
+            generated_at: now,
+            modules,
+            practice,
+            weak_topics,
+            study_minutes,
+            current_streak: streak.current,
+            longest_streak: streak.longest,
+            achievements,
+            achievements_total: crate::achievements::definitions().len(),
+        })
+    }
+
+    pub fn total_minutes(&self) -> u32 {
+        self.study_minutes.values().sum()
+    }
+
+    pub fn average_score(&self) -> Option<f32> {
+        (!self.practice.is_empty())
+            .then(|| self.practice.iter().map(|p| p.score).sum::<f32>() / self.practice.len() as f32)
+    }
+
+    /// 热力图的周（每周从周一开始，覆盖整个报告期）
+    pub fn heatmap_weeks(&self) -> Vec<[Option<(NaiveDate, u32)>; 7]> {
+        let first_monday = self.start - Duration::days(self.start.weekday().num_days_from_monday() as i64);
+        let mut weeks = Vec::new();
+        let mut monday = first_monday;
+        while monday <= self.end {
+            let mut week = [None; 7];
+            for (i, cell) in week.iter_mut().enumerate() {
+                let date = monday + Duration::days(i as i64);
+                if date >= self.start && date <= self.end {
+                    *cell = Some((date, self.study_minutes.get(&date).copied().unwrap_or(0)));
+                }
+            }
+            weeks.push(week);
+            monday += Duration::days(7);
+        }
+        weeks
+    }
+}
+
+/// 学习时长的热力等级（0-4）
+pub fn heat_level(minutes: u32) -> usize {
+    match minutes {
+        0 => 0,
+        1..=29 => 1,
+        30..=59 => 2,
+        60..=119 => 3,
+        _ => 4,
+    }
+}
+
+const WEEKDAY_LABELS: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+
+/// 用方块字符画趋势（0-100）
+fn sparkline(values: &[f32]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    values.iter()
+        .map(|v| BARS[((v.clamp(0.0, 100.0) / 100.0) * 7.0).round() as usize])
+        .collect()
+}
+
+fn format_minutes(minutes: u32) -> String {
+    if minutes >= 60 {
+        format!("{} 小时 {} 分钟", minutes / 60, minutes % 60)
+    } else {
+        format!("{} 分钟", minutes)
+    }
+}
+
+/// 生成 Markdown 报告
+pub fn render_markdown(data: &ReportData) -> String {
+    let mut md = String::new();
+    md.push_str("# Rust 学习报告\n\n");
+    md.push_str(&format!("- 时间范围：{}（{} 至 {}）\n", data.period.label(), data.start, data.end));
+    md.push_str(&format!("- 生成时间：{}\n", data.generated_at.format("%Y-%m-%d %H:%M")));
+    md.push_str(&format!("- 学习时长：{}，学习 {} 天\n", format_minutes(data.total_minutes()), data.study_minutes.len()));
+    md.push_str(&format!("- 连续学习：当前 {} 天，最长 {} 天\n\n", data.current_streak, data.longest_streak));
+
+    md.push_str("## 掌握程度\n\n");
+    if data.modules.is_empty() {
+        md.push_str("暂无记录。\n\n");
+    } else {
+        md.push_str("| 模块 | 期初 | 当前 | 变化 | 趋势 |\n|------|-----:|-----:|-----:|------|\n");
+        for module in &data.modules {
+            let values: Vec<f32> = module.initial.into_iter().chain(module.points.iter().map(|(_, m)| *m)).collect();
+            md.push_str(&format!(
+                "| {} | {} | {:.0}% | {:+.0} | {} |\n",
+                module.name,
+                module.initial.map_or("-".to_string(), |m| format!("{:.0}%", m)),
+                module.current(),
+                module.change(),
+                sparkline(&values),
+            ));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## 练习得分\n\n");
+    match data.average_score() {
+        None => md.push_str("报告期内没有练习。\n\n"),
+        Some(average) => {
+            let scores: Vec<f32> = data.practice.iter().map(|p| p.score).collect();
+            md.push_str(&format!("共 {} 次练习，平均 {:.0} 分，趋势：{}\n\n", data.practice.len(), average, sparkline(&scores)));
+            md.push_str("| 时间 | 模块 | 得分 |\n|------|------|-----:|\n");
+            for point in &data.practice {
+                md.push_str(&format!("| {} | {} | {:.0} |\n", point.at.format("%m-%d %H:%M"), point.module_id, point.score));
+            }
+            md.push('\n');
+        }
+    }
+
+    md.push_str("## 薄弱知识点\n\n");
+    if data.weak_topics.is_empty() {
+        md.push_str("报告期内没有答错的题目。\n\n");
+    } else {
+        md.push_str("| 知识点 | 答错次数 | 作答次数 |\n|--------|--------:|--------:|\n");
+        for topic in &data.weak_topics {
+            md.push_str(&format!("| {} | {} | {} |\n", topic.topic, topic.misses, topic.attempts));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## 学习时间\n\n");
+    md.push_str("每列一周，颜色越深学习时间越长（· 无，░ <30 分钟，▒ <1 小时，▓ <2 小时，█ ≥2 小时）\n\n```\n");
+    let weeks = data.heatmap_weeks();
+    for (day, label) in WEEKDAY_LABELS.iter().enumerate() {
+        md.push_str(label);
+        md.push(' ');
+        for week in &weeks {
+            md.push(match week[day] {
+                None => ' ',
+                Some((_, minutes)) => ['·', '░', '▒', '▓', '█'][heat_level(minutes)],
+            });
+        }
+        md.push('\n');
+    }
+    md.push_str("```\n\n");
+
+    md.push_str(&format!("## 成就（{}/{}）\n\n", data.achievements.len(), data.achievements_total));
+    if data.achievements.is_empty() {
+        md.push_str("还没有解锁成就。\n");
+    }
+    for achievement in &data.achievements {
+        md.push_str(&format!(
+            "- {} **{}** - {}（{}{}）\n",
+            achievement.icon,
+            achievement.name,
+            achievement.description,
+            achievement.unlocked_at.map_or("-".to_string(), |t| t.format("%Y-%m-%d").to_string()),
+            if achievement.in_period { "，本期解锁" } else { "" },
+        ));
+    }
+
+    md
+}
+
+/// HTML 转义
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+const CHART_COLORS: [&str; 8] = ["#d9480f", "#1c7ed6", "#2b8a3e", "#ae3ec9", "#f59f00", "#0c8599", "#e64980", "#5c940d"];
+const HEAT_COLORS: [&str; 5] = ["#ebedf0", "#ffd8a8", "#ffa94d", "#f76707", "#d9480f"];
+
+/// 折线图：每条线为 (名称, [(横轴位置 0-1, 数值 0-100)])
+fn line_chart_svg(series: &[(String, Vec<(f64, f32)>)], start: NaiveDate, end: NaiveDate) -> String {
+    let (width, height, left, top, bottom) = (640.0, 240.0, 40.0, 10.0, 30.0);
+    let plot_w = width - left - 10.0;
+    let plot_h = height - top - bottom;
+    let x = |t: f64| left + t.clamp(0.0, 1.0) * plot_w;
+    let y = |v: f32| top + (1.0 - v.clamp(0.0, 100.0) as f64 / 100.0) * plot_h;
+
+    let mut svg = format!(r#"<svg viewBox="0 0 {} {}" width="100%" role="img" xmlns="http://www.w3.org/2000/svg">"#, width, height + 20.0 * series.len().div_ceil(3) as f64);
+    for value in [0.0, 50.0, 100.0] {
+        svg.push_str(&format!(
+            r##"<line x1="{l}" x2="{r}" y1="{y}" y2="{y}" stroke="#dee2e6"/><text x="{tx}" y="{ty}" font-size="11" text-anchor="end" fill="#868e96">{v}</text>"##,
+            l = left, r = left + plot_w, y = y(value), tx = left - 6.0, ty = y(value) + 4.0, v = value,
+        ));
+    }
+    svg.push_str(&format!(
+        r##"<text x="{}" y="{}" font-size="11" fill="#868e96">{}</text><text x="{}" y="{}" font-size="11" text-anchor="end" fill="#868e96">{}</text>"##,
+        left, height - 10.0, start.format("%m-%d"), left + plot_w, height - 10.0, end.format("%m-%d"),
+    ));
+
+    for (i, (name, points)) in series.iter().enumerate() {
+        let color = CHART_COLORS[i % CHART_COLORS.len()];
+        let path: Vec<String> = points.iter().map(|(t, v)| format!("{:.1},{:.1}", x(*t), y(*v))).collect();
+        svg.push_str(&format!(r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#, path.join(" "), color));
+        for (t, v) in points {
+            svg.push_str(&format!(r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{}"><title>{:.0}</title></circle>"#, x(*t), y(*v), color, v));
+        }
+        let (lx, ly) = (left + (i % 3) as f64 * 200.0, height + 8.0 + (i / 3) as f64 * 20.0);
+        svg.push_str(&format!(
+            r#"<rect x="{}" y="{}" width="10" height="10" fill="{}"/><text x="{}" y="{}" font-size="12">{}</text>"#,
+            lx, ly, color, lx + 14.0, ly + 9.0, escape(name),
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// 学习时间热力图
+fn heatmap_svg(data: &ReportData) -> String {
+    let cell = 14.0;
+    let weeks = data.heatmap_weeks();
+    let left = 24.0;
+    let mut svg = format!(
+        r#"<svg viewBox="0 0 {} {}" width="{}" role="img" xmlns="http://www.w3.org/2000/svg">"#,
+        left + weeks.len() as f64 * cell + 4.0, 7.0 * cell + 4.0, left + weeks.len() as f64 * cell + 4.0,
+    );
+    for (day, label) in WEEKDAY_LABELS.iter().enumerate() {
+        svg.push_str(&format!(r##"<text x="0" y="{}" font-size="10" fill="#868e96">{}</text>"##, day as f64 * cell + 11.0, label));
+    }
+    for (w, week) in weeks.iter().enumerate() {
+        for (day, entry) in week.iter().enumerate() {
+            if let Some((date, minutes)) = entry {
+                svg.push_str(&format!(
+                    r#"<rect x="{}" y="{}" width="12" height="12" rx="2" fill="{}"><title>{} {} 分钟</title></rect>"#,
+                    left + w as f64 * cell, day as f64 * cell, HEAT_COLORS[heat_level(*minutes)], date, minutes,
+                ));
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// 薄弱知识点条形图
+fn weak_topics_svg(topics: &[TopicMisses]) -> String {
+    let max = topics.iter().map(|t| t.misses).max().unwrap_or(1) as f64;
+    let row = 22.0;
+    let mut svg = format!(r#"<svg viewBox="0 0 640 {}" width="100%" role="img" xmlns="http://www.w3.org/2000/svg">"#, topics.len() as f64 * row);
+    for (i, topic) in topics.iter().enumerate() {
+        let y = i as f64 * row;
+        let width = topic.misses as f64 / max * 380.0;
+        svg.push_str(&format!(
+            r##"<text x="150" y="{}" font-size="12" text-anchor="end">{}</text><rect x="160" y="{}" width="{:.1}" height="16" rx="2" fill="#f76707"/><text x="{:.1}" y="{}" font-size="12" fill="#495057">{}/{}</text>"##,
+            y + 13.0, escape(&topic.topic), y + 2.0, width, 166.0 + width, y + 13.0, topic.misses, topic.attempts,
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// 时间在报告期内的位置（0-1）
+fn time_position(data: &ReportData, at: DateTime<Local>) -> f64 {
+    let span = (data.end - data.start).num_seconds() + 86_400;
+    let start = data.start.and_hms_opt(0, 0, 0).map_or(0, |t| t.and_utc().timestamp());
+    let offset = at.naive_local().and_utc().timestamp() - start;
+    offset as f64 / span as f64
+}
+
+/// 生成单文件 HTML 报告
+pub fn render_html(data: &ReportData) -> String {
+    let mut body = String::new();
+
+    body.push_str(&format!(
+        "<h1>🦀 Rust 学习报告</h1><p class=\"meta\">{}（{} 至 {}）· 生成于 {}</p>",
+        data.period.label(), data.start, data.end, data.generated_at.format("%Y-%m-%d %H:%M"),
+    ));
+    body.push_str("<div class=\"cards\">");
+    for (label, value) in [
+        ("学习时长", format_minutes(data.total_minutes())),
+        ("学习天数", format!("{} 天", data.study_minutes.len())),
+        ("当前连续", format!("{} 天", data.current_streak)),
+        ("最长连续", format!("{} 天", data.longest_streak)),
+        ("平均得分", data.average_score().map_or("-".to_string(), |s| format!("{:.0}", s))),
+    ] {
+        body.push_str(&format!("<div class=\"card\"><div class=\"value\">{}</div><div>{}</div></div>", value, label));
+    }
+    body.push_str("</div>");
+
+    body.push_str("<h2>掌握程度</h2>");
+    if data.modules.is_empty() {
+        body.push_str("<p>暂无记录。</p>");
+    } else {
+        let series: Vec<(String, Vec<(f64, f32)>)> = data.modules.iter()
+            .map(|m| {
+                let mut points: Vec<(f64, f32)> = m.initial.map(|v| (0.0, v)).into_iter().collect();
+                points.extend(m.points.iter().map(|(at, v)| (time_position(data, *at), *v)));
+                if let Some(&(_, last)) = points.last() {
+                    points.push((1.0, last));
+                }
+                (m.name.clone(), points)
+            })
+            .collect();
+        body.push_str(&line_chart_svg(&series, data.start, data.end));
+        body.push_str("<table><tr><th>模块</th><th>期初</th><th>当前</th><th>变化</th></tr>");
+        for module in &data.modules {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.0}%</td><td>{:+.0}</td></tr>",
+                escape(&module.name),
+                module.initial.map_or("-".to_string(), |m| format!("{:.0}%", m)),
+                module.current(),
+                module.change(),
+            ));
+        }
+        body.push_str("</table>");
+    }
+
+    body.push_str("<h2>练习得分</h2>");
+    if data.practice.is_empty() {
+        body.push_str("<p>报告期内没有练习。</p>");
+    } else {
+        let points = data.practice.iter().map(|p| (time_position(data, p.at), p.score)).collect();
+        body.push_str(&line_chart_svg(&[("练习得分".to_string(), points)], data.start, data.end));
+    }
+
+    body.push_str("<h2>薄弱知识点</h2>");
+    if data.weak_topics.is_empty() {
+        body.push_str("<p>报告期内没有答错的题目。</p>");
+    } else {
+        body.push_str("<p class=\"meta\">答错次数/作答次数</p>");
+        body.push_str(&weak_topics_svg(&data.weak_topics));
+    }
+
+    body.push_str("<h2>学习时间</h2>");
+    body.push_str(&heatmap_svg(data));
+
+    body.push_str(&format!("<h2>成就（{}/{}）</h2><ul>", data.achievements.len(), data.achievements_total));
+    if data.achievements.is_empty() {
+        body.push_str("<li>还没有解锁成就。</li>");
+    }
+    for achievement in &data.achievements {
+        body.push_str(&format!(
+            "<li>{} <strong>{}</strong> - {} <span class=\"meta\">{}</span>{}</li>",
+            achievement.icon,
+            escape(&achievement.name),
+            escape(&achievement.description),
+            achievement.unlocked_at.map_or("-".to_string(), |t| t.format("%Y-%m-%d").to_string()),
+            if achievement.in_period { " <span class=\"new\">本期解锁</span>" } else { "" },
+        ));
+    }
+    body.push_str("</ul>");
+
+    format!(r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Rust 学习报告 {start} - {end}</title>
+<style>
+body {{ font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif; max-width: 760px; margin: 2em auto; padding: 0 1em; color: #212529; }}
+h2 {{ border-bottom: 1px solid #dee2e6; padding-bottom: .3em; margin-top: 1.8em; }}
+.meta {{ color: #868e96; font-size: .9em; }}
+.cards {{ display: flex; flex-wrap: wrap; gap: .8em; }}
+.card {{ flex: 1; min-width: 110px; background: #fff4e6; border-radius: 8px; padding: .8em; text-align: center; }}
+.card .value {{ font-size: 1.3em; font-weight: bold; color: #d9480f; }}
+table {{ border-collapse: collapse; width: 100%; margin-top: 1em; }}
+th, td {{ border-bottom: 1px solid #e9ecef; padding: .4em; text-align: left; }}
+.new {{ background: #d3f9d8; color: #2b8a3e; border-radius: 4px; padding: 0 .4em; font-size: .85em; }}
+</style>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#, start = data.start, end = data.end, body = body)
+}
+
+/// 生成报告并写入文件，返回文件路径
+pub fn write_report(store: &Store, repo: &LearningRepo, format: ReportFormat, period: Period, output: Option<&Path>) -> Result<PathBuf> {
+    let now = Local::now();
+    let data = ReportData::collect(store, repo, period, now)?;
+    let content = match format {
+        ReportFormat::Markdown => render_markdown(&data),
+        ReportFormat::Html => render_html(&data),
+    };
+
+    let path = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        PathBuf::from(format!("learning-report-{}.{}", now.format("%Y%m%d"), format.extension()))
+    });
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// 生成学习报告（命令行）
+pub fn run_report(store: &Store, repo: &LearningRepo, format: ReportFormat, period: Period, output: Option<&Path>) -> Result<()> {
+    let path = write_report(store, repo, format, period, output)?;
+    println!("✅ 学习报告（{}）已生成：{}", period.label(), path.display());
+    Ok(())
+}
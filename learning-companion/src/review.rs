@@ -0,0 +1,190 @@
+//! 复习调度模块
+//!
+//! 记录每道题的作答历史，按 SM-2 算法安排下一次复习
+
+use crate::db::{ReviewSchedule, Store};
+use crate::exercise::{PracticeSession, Question, QuestionType};
+use crate::repo::LearningRepo;
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+
+/// 新题目的初始难度系数
+const INITIAL_EASE: f64 = 2.5;
+/// 难度系数下限
+const MIN_EASE: f64 = 1.3;
+
+/// 待复习的题目
+#[derive(Debug, Clone)]
+pub struct DueQuestion {
+    pub module_id: String,
+    pub question: Question,
+    pub due_date: NaiveDate,
+}
+
+/// 题目的稳定标识：题干的 FNV-1a 哈希，代码题再加上题型和代码
+///
+/// 代码题常用相同的题干（如「下面的程序能通过编译吗？」），只靠代码区分；
+/// 修改题干或代码会被视为新题目，复习记录从头开始
+pub fn question_key(question: &Question) -> String {
+    let mut content = question.prompt.trim().to_string();
+    if let Some(code) = &question.code {
+        let kind = match question.question_type {
+            QuestionType::CodeOutput => "code_output",
+            QuestionType::CompilesOrNot => "compiles_or_not",
+            _ => "",
+        };
+        content.push('\0');
+        content.push_str(kind);
+        content.push('\0');
+        content.push_str(code.trim());
+    }
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// 新题目的复习计划（今天到期）
+pub fn new_schedule(module_id: &str, question_key: &str, today: NaiveDate) -> ReviewSchedule {
+    ReviewSchedule {
+        module_id: module_id.to_string(),
+        question_key: question_key.to_string(),
+        ease_factor: INITIAL_EASE,
+        interval_days: 0,
+        repetitions: 0,
+        due_date: today,
+    }
+}
+
+/// 根据本次作答计算下一次复习计划
+///
+/// 答对记为质量 4，答错记为质量 1：答错的题重置为明天复习，答对的题间隔按 1 天、6 天、
+/// 之后乘以难度系数递增
+pub fn next_schedule(prev: &ReviewSchedule, correct: bool, today: NaiveDate) -> ReviewSchedule {
+    let quality: f64 = if correct { 4.0 } else { 1.0 };
+    let ease_factor = (prev.ease_factor
+        + (0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)))
+        .max(MIN_EASE);
+
+    let (repetitions, interval_days) = if correct {
+        let interval = match prev.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (prev.interval_days as f64 * prev.ease_factor).round() as u32,
+        };
+        (prev.repetitions + 1, interval)
+    } else {
+        (0, 1)
+    };
+
+    ReviewSchedule {
+        module_id: prev.module_id.clone(),
+        question_key: prev.question_key.clone(),
+        ease_factor,
+        interval_days,
+        repetitions,
+        due_date: today + Duration::days(interval_days as i64),
+    }
+}
+
+/// 记录一道题的作答并更新复习计划
+pub fn record_answer(store: &Store, module_id: &str, question: &Question, correct: bool) -> Result<()> {
+    let today = Local::now().date_naive();
+    let key = question_key(question);
+
+    store.record_question_review(module_id, &key, &question.topic, correct)?;
+
+    let prev = store.get_review_schedule(module_id, &key)?
+        .unwrap_or_else(|| new_schedule(module_id, &key, today));
+    store.save_review_schedule(&next_schedule(&prev, correct, today))?;
+
+    Ok(())
+}
+
+/// 获取所有模块中截至今天到期的题目
+///
+/// 题库中已删除或题干已修改的题目会被跳过
+pub fn due_questions(store: &Store, repo: &LearningRepo, today: NaiveDate) -> Result<Vec<DueQuestion>> {
+    let schedules = store.get_due_reviews(today)?;
+    let mut due = Vec::new();
+
+    for module in &repo.modules {
+        let module_schedules: Vec<&ReviewSchedule> = schedules.iter()
+            .filter(|s| s.module_id == module.id)
+            .collect();
+        if module_schedules.is_empty() {
+            continue;
+        }
+
+        let Some(bank) = crate::questions::load_bank(module)? else {
+            continue;
+        };
+
+        for schedule in module_schedules {
+            if let Some(question) = bank.questions.iter()
+                .find(|q| question_key(q) == schedule.question_key)
+            {
+                due.push(DueQuestion {
+                    module_id: module.id.clone(),
+                    question: question.clone(),
+                    due_date: schedule.due_date,
+                });
+            }
+        }
+    }
+
+    due.sort_by_key(|d| d.due_date);
+    Ok(due)
+}
+
+/// 今天待复习的题数（与 `due_questions` 一致，不计题库中已删除或修改过的题目）
+pub fn due_count(store: &Store, repo: &LearningRepo) -> Result<usize> {
+    Ok(due_questions(store, repo, Local::now().date_naive())?.len())
+}
+
+/// 运行复习（命令行）
+pub fn run_review(store: &Store, repo: &LearningRepo, limit: usize) -> Result<()> {
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🔁 Rust 学习伴侣 - 今日复习      ║");
+    println!("╚════════════════════════════════════════╝\n");
+
+    let mut due = due_questions(store, repo, Local::now().date_naive())?;
+    if due.is_empty() {
+        println!("🎉 今天没有需要复习的题目！");
+        println!("💡 做练习后，答错的题会在第二天回来复习");
+        return Ok(());
+    }
+
+    let total_due = due.len();
+    due.truncate(limit);
+    println!("今天共有 {} 道题到期，本次复习 {} 道\n", total_due, due.len());
+
+    let mut correct_count = 0;
+    for (i, item) in due.iter().enumerate() {
+        let answer = crate::exercise::ask_question(&item.question, i, due.len())?;
+        let correct = item.question.is_correct(answer.as_ref());
+        if correct {
+            correct_count += 1;
+        }
+        record_answer(store, &item.module_id, &item.question, correct)?;
+        crate::study::touch_module(store, &item.module_id)?;
+    }
+
+    println!("📊 复习完成：{}/{} 正确", correct_count, due.len());
+    println!("💡 答错的题明天会再次出现，答对的题复习间隔会逐渐拉长");
+    crate::achievements::check_and_report(store, repo)?;
+
+    Ok(())
+}
+
+/// 记录练习会话中每道题的作答
+pub fn record_session(store: &Store, session: &PracticeSession, question_modules: &[String]) -> Result<()> {
+    for (i, question) in session.questions.iter().enumerate() {
+        let module_id = question_modules.get(i).unwrap_or(&session.module_id);
+        record_answer(store, module_id, question, session.is_correct(i))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,397 @@
+//! 全文搜索模块
+//!
+//! 遍历各模块目录下的讲解、示例、练习和答案建立倒排索引，按行检索。
+//! 英文和标识符按单词切分（不区分大小写，`get_mut` 同时索引 `get` 和 `mut`），
+//! 中文没有空格分词，按相邻两字（二元组）切分，单字查询按单字匹配
+
+use crate::db::{DbResult, Store};
+use crate::repo::{LearningModule, LearningRepo};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 默认显示的结果数
+pub const DEFAULT_LIMIT: usize = 20;
+/// 结果前后显示的上下文行数
+pub const CONTEXT_LINES: usize = 1;
+
+/// 文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocKind {
+    /// 模块 README（概念讲解）
+    Readme,
+    /// 其他 Markdown 文档（练习说明、自检清单等）
+    Markdown,
+    Example,
+    Exercise,
+    Solution,
+    /// src/、tests/ 下的代码
+    Code,
+}
+
+impl DocKind {
+    fn of(relative: &Path) -> Option<Self> {
+        let ext = relative.extension()?.to_str()?;
+        let in_dir = |name: &str| relative.components().any(|c| c.as_os_str() == name);
+        Some(match ext {
+            "md" if relative.file_name()? == "README.md" => DocKind::Readme,
+            "md" => DocKind::Markdown,
+            "rs" if in_dir("solutions") => DocKind::Solution,
+            "rs" if in_dir("exercises") => DocKind::Exercise,
+            "rs" if in_dir("examples") => DocKind::Example,
+            "rs" => DocKind::Code,
+            _ => return None,
+        })
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DocKind::Readme => "讲解",
+            DocKind::Markdown => "文档",
+            DocKind::Example => "示例",
+            DocKind::Exercise => "练习",
+            DocKind::Solution => "答案",
+            DocKind::Code => "代码",
+        }
+    }
+
+    /// 排序权重：讲解优先，答案靠后
+    fn weight(self) -> f64 {
+        match self {
+            DocKind::Readme => 1.3,
+            DocKind::Markdown | DocKind::Example => 1.1,
+            DocKind::Exercise | DocKind::Code => 1.0,
+            DocKind::Solution => 0.8,
+        }
+    }
+}
+
+/// 索引中的一个文件
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub path: PathBuf,
+    /// 相对仓库根目录的路径
+    pub display: String,
+    pub module_id: String,
+    pub kind: DocKind,
+    pub lines: Vec<String>,
+}
+
+/// 一条搜索结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    pub display: String,
+    pub module_id: String,
+    pub kind: DocKind,
+    /// 命中的行号（从 0 开始）
+    pub line: usize,
+    pub score: f64,
+    /// 命中行及前后的上下文：(行号, 内容)
+    pub context: Vec<(usize, String)>,
+}
+
+/// 是否为中日韩文字（不含标点）
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' | '\u{f900}'..='\u{faff}'
+        | '\u{3040}'..='\u{30ff}' | '\u{ac00}'..='\u{d7af}')
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// 切分文本；for_index 时额外生成下划线拆开的子词和中文单字，查询时只用完整的词和二元组
+fn tokenize(text: &str, for_index: bool) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if is_word_char(chars[i]) {
+            let start = i;
+            while i < chars.len() && is_word_char(chars[i]) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect::<String>().to_lowercase();
+            if for_index && word.contains('_') {
+                tokens.extend(word.split('_').filter(|part| !part.is_empty()).map(String::from));
+            }
+            tokens.push(word);
+        } else if is_cjk(chars[i]) {
+            let start = i;
+            while i < chars.len() && is_cjk(chars[i]) {
+                i += 1;
+            }
+            let run = &chars[start..i];
+            if run.len() == 1 || for_index {
+                tokens.extend(run.iter().map(|c| c.to_string()));
+            }
+            tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>()));
+        } else {
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// 索引用的词
+pub fn index_tokens(text: &str) -> Vec<String> {
+    tokenize(text, true)
+}
+
+/// 查询用的词（去重）
+pub fn query_tokens(query: &str) -> Vec<String> {
+    let mut tokens = tokenize(query, false);
+    tokens.sort();
+    tokens.dedup();
+    tokens
+}
+
+/// 行中与查询匹配的片段（字节范围），用于高亮：完整的查询和查询中的每个词
+pub fn match_ranges(line: &str, query: &str) -> Vec<(usize, usize)> {
+    let lower = line.to_lowercase();
+    // 只有 ASCII 大小写转换不改变字节位置
+    if lower.len() != line.len() {
+        return Vec::new();
+    }
+
+    let mut needles: Vec<String> = vec![query.trim().to_lowercase()];
+    needles.extend(tokenize(query, false).into_iter().filter(|t| t.chars().count() > 1 || !t.is_ascii()));
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for needle in needles.iter().filter(|n| !n.is_empty()) {
+        ranges.extend(lower.match_indices(needle.as_str()).map(|(start, m)| (start, start + m.len())));
+    }
+
+    // 合并重叠的片段
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// 查询原文从开头起在行中连续出现的最长部分占查询的比例（都已转成小写）
+fn phrase_overlap(line: &str, phrase: &str) -> f64 {
+    let total = phrase.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let longest = phrase.char_indices().map(|(i, _)| i).chain([phrase.len()])
+        .rev()
+        .find(|&end| line.contains(&phrase[..end]))
+        .map_or(0, |end| phrase[..end].chars().count());
+    longest as f64 / total as f64
+}
+
+/// 搜索索引
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    pub documents: Vec<Document>,
+    /// 每一行的 (文件, 行号)，下标为行的编号
+    lines: Vec<(usize, usize)>,
+    /// 词 -> 包含它的行编号（升序）
+    postings: HashMap<String, Vec<usize>>,
+    /// 不参与搜索的文件编号（还没通过测试的练习的答案）
+    hidden: HashSet<usize>,
+}
+
+impl SearchIndex {
+    /// 遍历所有模块建立索引
+    pub fn build(repo: &LearningRepo) -> Result<Self> {
+        let mut index = SearchIndex::default();
+        for module in &repo.modules {
+            index.add_module(&repo.path, module)?;
+        }
+        Ok(index)
+    }
+
+    fn add_module(&mut self, root: &Path, module: &LearningModule) -> Result<()> {
+        let walker = WalkDir::new(&module.directory).sort_by_file_name().into_iter()
+            .filter_entry(|e| !(e.file_type().is_dir() && (e.file_name() == "target" || e.file_name().to_string_lossy().starts_with('.'))));
+        for entry in walker {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let Some(kind) = DocKind::of(relative) else {
+                continue;
+            };
+            // 无法按 UTF-8 读取的文件跳过
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            self.add_document(Document {
+                path: path.to_path_buf(),
+                display: relative.display().to_string(),
+                module_id: module.id.clone(),
+                kind,
+                lines: content.lines().map(String::from).collect(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 添加一个文件
+    pub fn add_document(&mut self, document: Document) {
+        let doc = self.documents.len();
+        for (line_no, line) in document.lines.iter().enumerate() {
+            let id = self.lines.len();
+            self.lines.push((doc, line_no));
+            let mut tokens = index_tokens(line);
+            tokens.sort();
+            tokens.dedup();
+            for token in tokens {
+                self.postings.entry(token).or_default().push(id);
+            }
+        }
+        self.documents.push(document);
+    }
+
+    /// 隐藏测试还没通过的练习的答案，避免绕过分级提示的确认和记录直接看到答案；
+    /// 返回隐藏的文件数；读不到测试结果时返回错误
+    pub fn hide_unsolved_solutions(&mut self, store: &Store) -> DbResult<usize> {
+        self.hidden.clear();
+        for (doc, document) in self.documents.iter().enumerate() {
+            if document.kind != DocKind::Solution {
+                continue;
+            }
+            let exercise = document.path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+            if !crate::hints::exercise_passed(store, &document.module_id, &exercise)? {
+                self.hidden.insert(doc);
+            }
+        }
+        Ok(self.hidden.len())
+    }
+
+    /// 搜索；module 不为空时只搜索该模块
+    ///
+    /// 行中至少包含查询的一半的词，按命中的词的稀有程度（IDF）和命中比例打分，
+    /// 连续包含查询原文、标题行和讲解文档加权。
+    /// 查询中没有可索引的词（如 `?`）时按原文逐行查找
+    pub fn search(&self, query: &str, module: Option<&str>, limit: usize) -> Vec<SearchHit> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let phrase = query.to_lowercase();
+        let tokens = query_tokens(query);
+        let total = self.lines.len().max(1) as f64;
+
+        let candidates: Vec<(usize, f64)> = if tokens.is_empty() {
+            (0..self.lines.len())
+                .filter(|&id| self.line_text(id).to_lowercase().contains(&phrase))
+                .map(|id| (id, 1.0))
+                .collect()
+        } else {
+            // 每行命中的词的 IDF 之和和命中的词数
+            let mut matched: HashMap<usize, (f64, usize)> = HashMap::new();
+            for token in &tokens {
+                let Some(list) = self.postings.get(token) else {
+                    continue;
+                };
+                let idf = (total / list.len() as f64).ln() + 1.0;
+                for &id in list {
+                    let entry = matched.entry(id).or_default();
+                    entry.0 += idf;
+                    entry.1 += 1;
+                }
+            }
+            // 至少命中一半的词，命中越全分数越高（`Rc<RefCell<T>>` 中的 T 不一定出现）
+            let required = tokens.len().div_ceil(2);
+            matched.into_iter()
+                .filter(|(_, (_, count))| *count >= required)
+                .map(|(id, (idf, count))| (id, idf * (count as f64 / tokens.len() as f64).powi(2)))
+                .collect()
+        };
+
+        let mut hits: Vec<SearchHit> = candidates.into_iter()
+            .filter_map(|(id, base)| {
+                let (doc, line) = self.lines[id];
+                let document = &self.documents[doc];
+                if self.hidden.contains(&doc) || module.is_some_and(|m| m != document.module_id) {
+                    return None;
+                }
+                let text = &document.lines[line];
+                // 原文连续出现的部分越长越靠前，完整出现时分数为三倍
+                let mut score = base * document.kind.weight() * (1.0 + 2.0 * phrase_overlap(&text.to_lowercase(), &phrase));
+                if document.kind != DocKind::Solution && text.trim_start().starts_with('#') && !text.contains("#[") {
+                    score *= 1.5;
+                }
+                Some(SearchHit {
+                    path: document.path.clone(),
+                    display: document.display.clone(),
+                    module_id: document.module_id.clone(),
+                    kind: document.kind,
+                    line,
+                    score,
+                    context: self.context(doc, line),
+                })
+            })
+            .collect();
+
+        // 分数相同时按模块和文件的顺序
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score)
+            .then_with(|| a.display.cmp(&b.display))
+            .then_with(|| a.line.cmp(&b.line)));
+        hits.truncate(limit);
+        hits
+    }
+
+    fn line_text(&self, id: usize) -> &str {
+        let (doc, line) = self.lines[id];
+        &self.documents[doc].lines[line]
+    }
+
+    fn context(&self, doc: usize, line: usize) -> Vec<(usize, String)> {
+        let lines = &self.documents[doc].lines;
+        let end = (line + CONTEXT_LINES + 1).min(lines.len());
+        (line.saturating_sub(CONTEXT_LINES)..end).map(|i| (i, lines[i].clone())).collect()
+    }
+}
+
+/// 搜索（命令行）
+///
+/// 测试还没通过的练习的答案不参与搜索，要看答案请在 TUI 的练习工作区按 H
+pub fn run_search(store: &Store, repo: &LearningRepo, query: &str, module: Option<&str>, limit: usize) -> Result<()> {
+    let module_id = match module {
+        Some(key) => Some(repo.find_module(key)
+            .ok_or_else(|| anyhow::anyhow!("未找到模块：{}", key))?
+            .id.clone()),
+        None => None,
+    };
+    let mut index = SearchIndex::build(repo)?;
+    let hidden = index.hide_unsolved_solutions(store)?;
+    let hits = index.search(query, module_id.as_deref(), limit);
+
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🔍 Rust 学习伴侣 - 全文搜索      ║");
+    println!("╚════════════════════════════════════════╝\n");
+
+    if hits.is_empty() {
+        println!("没有找到「{}」（共检索 {} 个文件）", query, index.documents.len());
+        return Ok(());
+    }
+    println!("「{}」的前 {} 条结果：\n", query, hits.len());
+
+    for (n, hit) in hits.iter().enumerate() {
+        println!("{:>2}. {}:{}  [{}]", n + 1, hit.display, hit.line + 1, hit.kind.label());
+        for (line, text) in &hit.context {
+            let marker = if *line == hit.line { "›" } else { " " };
+            println!("    {} {:>4} │ {}", marker, line + 1, text);
+        }
+        println!();
+    }
+    println!("💡 在 TUI 的「🔍 全文搜索」中选中结果可以直接打开到对应的行");
+    if hidden > 0 {
+        println!("💡 {} 个测试还没通过的练习的答案不参与搜索，可在 TUI 练习工作区按 H 查看分级提示", hidden);
+    }
+    Ok(())
+}
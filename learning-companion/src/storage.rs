@@ -1,34 +1,180 @@
 //! 存储模块
 //!
-//! 数据导出和持久化
+//! 数据导出和导入：带版本号的 JSON（可导入）或每表一个 CSV
 
-use anyhow::Result;
+use crate::db::{self, ImportMode, ImportStats, Row, Store};
+use anyhow::{Context, Result};
 use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-/// 导出学习数据为 JSON
-pub fn export_data() -> Result<()> {
-    let data = crate::db::export_all_data()?;
+/// 导出文件的格式标识
+pub const EXPORT_FORMAT: &str = "learning-companion-export";
+/// 导出文件的版本，数据结构不兼容地变化时递增
+pub const EXPORT_VERSION: u32 = 1;
 
-    let filename = format!("learning-companion-export-{}.json",
-        Local::now().format("%Y%m%d"));
+/// 导出文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Export {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    /// 表名到所有行
+    pub tables: BTreeMap<String, Vec<Row>>,
+}
 
-    std::fs::write(&filename, data)?;
+impl Export {
+    /// 读取数据库中的所有表
+    pub fn collect(store: &Store) -> Result<Self> {
+        let mut tables = BTreeMap::new();
+        for table in db::DATA_TABLES {
+            tables.insert(table.to_string(), store.dump_table(table)?);
+        }
+        Ok(Export {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: Local::now().to_rfc3339(),
+            tables,
+        })
+    }
 
-    println!("✅ 学习数据已导出到：{}", filename);
+    /// 解析导出文件，拒绝其他格式和更新版本的文件
+    pub fn parse(content: &str) -> Result<Self> {
+        let export: Export = serde_json::from_str(content).context("导出文件格式错误")?;
+        export.validate()?;
+        Ok(export)
+    }
 
-    Ok(())
+    /// 检查格式标识和版本
+    pub fn validate(&self) -> Result<()> {
+        if self.format != EXPORT_FORMAT {
+            anyhow::bail!("不是学习伴侣的导出文件（format = {}）", self.format);
+        }
+        if self.version > EXPORT_VERSION {
+            anyhow::bail!("导出文件版本 {} 比当前程序支持的版本 {} 新，请先升级", self.version, EXPORT_VERSION);
+        }
+        Ok(())
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.tables.values().map(Vec::len).sum()
+    }
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// 单个 JSON 文件，可以用 import 导入
+    Json,
+    /// 每张表一个 CSV 文件，便于用表格软件查看
+    Csv,
+}
+
+/// CSV 字段，包含逗号、引号或换行时加引号
+pub(crate) fn csv_field(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// 一张表的 CSV 内容，表头为所有行中出现过的列
+pub fn table_to_csv(rows: &[Row]) -> String {
+    let mut columns: Vec<&String> = Vec::new();
+    for row in rows {
+        for column in row.keys() {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+    }
+
+    let mut csv = columns.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields: Vec<String> = columns.iter()
+            .map(|column| row.get(*column).map(csv_field).unwrap_or_default())
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
 }
 
-/// 导出学习报告为 Markdown
-pub fn export_report() -> Result<String> {
-    let mut report = String::new();
+/// 写出导出文件，返回写入的路径（CSV 为目录）
+pub fn write_export(export: &Export, format: ExportFormat, output: &Path) -> Result<PathBuf> {
+    match format {
+        ExportFormat::Json => {
+            std::fs::write(output, serde_json::to_string_pretty(export)?)
+                .with_context(|| format!("无法写入 {}", output.display()))?;
+        }
+        ExportFormat::Csv => {
+            std::fs::create_dir_all(output)?;
+            for (table, rows) in &export.tables {
+                std::fs::write(output.join(format!("{}.csv", table)), table_to_csv(rows))?;
+            }
+        }
+    }
+    Ok(output.to_path_buf())
+}
 
-    report.push_str("# Rust 学习报告\n\n");
-    report.push_str(&format!("生成时间：{}\n\n", Local::now().format("%Y-%m-%d %H:%M")));
+/// 导出学习数据（output 为空时写到当前目录）
+pub fn export_data(store: &Store, format: ExportFormat, output: Option<&Path>) -> Result<PathBuf> {
+    let export = Export::collect(store)?;
+    let default_name = match format {
+        ExportFormat::Json => format!("learning-companion-export-{}.json", Local::now().format("%Y%m%d")),
+        ExportFormat::Csv => format!("learning-companion-export-{}", Local::now().format("%Y%m%d")),
+    };
+    let output = output.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(default_name));
+    write_export(&export, format, &output)
+}
 
-    // 从数据库获取数据并格式化
-    let data = crate::db::export_all_data()?;
-    report.push_str(&data);
+/// 导入导出文件
+pub fn import_data(store: &Store, path: &Path, mode: ImportMode) -> Result<ImportStats> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("无法读取 {}", path.display()))?;
+    let export = Export::parse(&content)?;
+    let tables: Vec<(String, Vec<Row>)> = export.tables.into_iter().collect();
+    Ok(store.import_tables(&tables, mode)?)
+}
 
-    Ok(report)
+/// 导出学习数据（命令行）
+pub fn run_export(store: &Store, format: ExportFormat, output: Option<&Path>) -> Result<()> {
+    let path = export_data(store, format, output)?;
+    match format {
+        ExportFormat::Json => println!("✅ 学习数据已导出到：{}", path.display()),
+        ExportFormat::Csv => println!("✅ 学习数据已导出到目录：{}（每张表一个 CSV 文件）", path.display()),
+    }
+    Ok(())
+}
+
+/// 导入学习数据（命令行）；替换前先备份到数据目录的 backups/
+pub fn run_import(store: &Store, path: &Path, mode: ImportMode) -> Result<()> {
+    if mode == ImportMode::Replace {
+        if let Ok(dir) = store.data_dir() {
+            println!("💾 替换前的备份：{}", store.backup(&dir.join("backups"))?.display());
+        }
+    }
+    let stats = import_data(store, path, mode)?;
+
+    match mode {
+        ImportMode::Merge => println!("✅ 已合并导入 {}", path.display()),
+        ImportMode::Replace => println!("✅ 已用 {} 替换本机数据", path.display()),
+    }
+    for (table, inserted, skipped) in &stats.tables {
+        if *skipped > 0 {
+            println!("   • {}：新增 {} 条，跳过 {} 条重复", table, inserted, skipped);
+        } else {
+            println!("   • {}：新增 {} 条", table, inserted);
+        }
+    }
+    println!("📊 共新增 {} 条，跳过 {} 条", stats.inserted(), stats.skipped());
+    Ok(())
 }
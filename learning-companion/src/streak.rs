@@ -0,0 +1,174 @@
+//! 连续学习天数
+//!
+//! 从今天（按设置的时区）往前数连续有学习记录的天数。每周的休息日和手动设置的冻结日
+//! 没有学习时不中断连续记录，但也不计入天数；今天还没学习时从昨天开始数
+
+use crate::clock::{Clock, SystemClock, TimeZone};
+use crate::daemon::{parse_days, weekday_name};
+use crate::db::{SessionTime, Store};
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+
+/// 每周休息日的设置项（JSON 数组，如 `["sun"]`）
+pub const REST_DAYS_KEY: &str = "streak_rest_days";
+/// 冻结日的设置项（JSON 数组，如 `["2024-10-01"]`）
+pub const FREEZE_DAYS_KEY: &str = "streak_freeze_days";
+
+/// 不中断连续记录的日子
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreakRules {
+    /// 每周的休息日
+    pub rest_days: Vec<Weekday>,
+    /// 冻结日（生病、出差等提前或事后标记的日期）
+    pub freeze_days: BTreeSet<NaiveDate>,
+}
+
+impl StreakRules {
+    pub fn load(store: &Store) -> Result<Self> {
+        let rest_days = match store.get_setting(REST_DAYS_KEY)? {
+            Some(json) => {
+                let names: Vec<String> = serde_json::from_str(&json).context("休息日格式错误")?;
+                if names.is_empty() { Vec::new() } else { parse_days(&names.join(","))? }
+            }
+            None => Vec::new(),
+        };
+        let freeze_days = match store.get_setting(FREEZE_DAYS_KEY)? {
+            Some(json) => serde_json::from_str(&json).context("冻结日格式错误")?,
+            None => BTreeSet::new(),
+        };
+        Ok(StreakRules { rest_days, freeze_days })
+    }
+
+    pub fn save(&self, store: &Store) -> Result<()> {
+        let rest: Vec<&str> = self.rest_days.iter().map(|d| weekday_name(*d)).collect();
+        store.save_setting(REST_DAYS_KEY, &serde_json::to_string(&rest)?)?;
+        store.save_setting(FREEZE_DAYS_KEY, &serde_json::to_string(&self.freeze_days)?)?;
+        Ok(())
+    }
+
+    /// 没有学习也不中断连续记录的日子
+    pub fn is_excused(&self, date: NaiveDate) -> bool {
+        self.rest_days.contains(&date.weekday()) || self.freeze_days.contains(&date)
+    }
+}
+
+/// 连续学习天数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streak {
+    /// 截至今天的连续天数，中断后为 0
+    pub current: u32,
+    /// 历史最长连续天数
+    pub longest: u32,
+}
+
+/// 根据学习日期计算连续天数；today 之后的日期忽略
+pub fn compute(studied: &BTreeSet<NaiveDate>, today: NaiveDate, rules: &StreakRules) -> Streak {
+    let Some(&first) = studied.first() else {
+        return Streak::default();
+    };
+
+    let mut streak = Streak::default();
+    let mut run = 0;
+    for day in first.iter_days().take_while(|day| *day <= today) {
+        if studied.contains(&day) {
+            run += 1;
+            streak.longest = streak.longest.max(run);
+        } else if !rules.is_excused(day) && day != today {
+            run = 0;
+        }
+    }
+    streak.current = run;
+    streak
+}
+
+/// 所有学习记录在 tz 中的日期
+pub fn study_dates(store: &Store, tz: TimeZone) -> Result<BTreeSet<NaiveDate>> {
+    Ok(store.get_session_times()?
+        .into_iter()
+        .map(|time| match time {
+            SessionTime::At(started_at) => tz.date_of(started_at),
+            SessionTime::Date(date) => date,
+        })
+        .collect())
+}
+
+/// 按设置的时区和规则计算 clock 当前时间的连续天数
+pub fn calculate(store: &Store, clock: &impl Clock) -> Result<Streak> {
+    let tz = TimeZone::load(store)?;
+    let rules = StreakRules::load(store)?;
+    Ok(compute(&study_dates(store, tz)?, tz.today(clock), &rules))
+}
+
+/// 查看连续学习天数和设置（命令行）
+pub fn run_status(store: &Store) -> Result<()> {
+    let tz = TimeZone::load(store)?;
+    let rules = StreakRules::load(store)?;
+    let today = tz.today(&SystemClock);
+    let streak = compute(&study_dates(store, tz)?, today, &rules);
+
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🔥 Rust 学习伴侣 - 连续学习      ║");
+    println!("╚════════════════════════════════════════╝\n");
+    println!("🔥 当前连续：{} 天", streak.current);
+    println!("🏅 最长连续：{} 天\n", streak.longest);
+
+    println!("📅 今天：{}（时区 {}）", today, tz.describe());
+    if rules.rest_days.is_empty() {
+        println!("😴 每周休息日：无");
+    } else {
+        let names: Vec<&str> = rules.rest_days.iter().map(|d| weekday_name(*d)).collect();
+        println!("😴 每周休息日：{}", names.join(","));
+    }
+    let upcoming: Vec<String> = rules.freeze_days.range(today..).map(|d| d.to_string()).collect();
+    if !upcoming.is_empty() {
+        println!("🧊 即将到来的冻结日：{}", upcoming.join("、"));
+    }
+    println!("\n💡 streak rest sun 设置休息日，streak freeze 冻结今天，streak timezone +08:00 固定时区");
+    Ok(())
+}
+
+/// 设置每周休息日（命令行），`none` 清除
+pub fn run_rest(store: &Store, days: &str) -> Result<()> {
+    let mut rules = StreakRules::load(store)?;
+    rules.rest_days = if days.eq_ignore_ascii_case("none") { Vec::new() } else { parse_days(days)? };
+    if rules.rest_days.len() == 7 {
+        anyhow::bail!("不能每天都是休息日");
+    }
+    rules.save(store)?;
+    if rules.rest_days.is_empty() {
+        println!("✅ 已清除每周休息日");
+    } else {
+        let names: Vec<&str> = rules.rest_days.iter().map(|d| weekday_name(*d)).collect();
+        println!("✅ 每周休息日：{}（这些天不学习不会中断连续记录）", names.join(","));
+    }
+    Ok(())
+}
+
+/// 添加或移除冻结日（命令行），date 为空时为今天
+pub fn run_freeze(store: &Store, date: Option<&str>, remove: bool) -> Result<()> {
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .with_context(|| format!("日期格式应为 YYYY-MM-DD：{}", date))?,
+        None => TimeZone::load(store)?.today(&SystemClock),
+    };
+    let mut rules = StreakRules::load(store)?;
+    if remove {
+        if !rules.freeze_days.remove(&date) {
+            anyhow::bail!("{} 不是冻结日", date);
+        }
+        println!("✅ 已取消冻结 {}", date);
+    } else {
+        rules.freeze_days.insert(date);
+        println!("🧊 已冻结 {}，这天不学习不会中断连续记录", date);
+    }
+    rules.save(store)
+}
+
+/// 设置划分日期的时区（命令行）
+pub fn run_timezone(store: &Store, zone: &str) -> Result<()> {
+    let tz: TimeZone = zone.parse()?;
+    tz.save(store)?;
+    println!("✅ 时区：{}，今天是 {}", tz.describe(), tz.today(&SystemClock));
+    Ok(())
+}
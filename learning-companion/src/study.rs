@@ -0,0 +1,253 @@
+//! 学习计时模块
+//!
+//! 开始/结束学习计时，记录时长、学习过的模块和练习次数到 study_sessions；
+//! 支持番茄钟，程序退出时未结束的计时会在下次启动时恢复
+
+use crate::db::{ActiveSession, Store};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+
+/// 番茄钟默认专注时长（分钟）
+pub const DEFAULT_POMODORO_MINUTES: u32 = 25;
+/// 番茄钟休息时长（分钟）
+pub const BREAK_MINUTES: u32 = 5;
+/// TUI 写入心跳的计时超过该时长没有任何学习活动时视为已中断，在最后一次活动时结束
+pub const STALE_AFTER_HOURS: i64 = 6;
+
+/// 一次已结束的学习
+#[derive(Debug, Clone, PartialEq)]
+pub struct StudySummary {
+    pub date: NaiveDate,
+    pub minutes: u32,
+    pub modules: Vec<String>,
+    pub practice_count: u32,
+    pub notes: Option<String>,
+}
+
+/// 启动时处理上次未结束的计时的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    /// 没有未结束的计时
+    None,
+    /// 计时仍在有效期内，继续计时
+    Resumed(ActiveSession),
+    /// 计时已中断太久，按最后一次活动时间结束并记录
+    Closed(StudySummary),
+}
+
+/// 番茄钟当前阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PomodoroPhase {
+    /// true 为专注，false 为休息
+    pub focus: bool,
+    /// 当前阶段剩余秒数
+    pub remaining_secs: i64,
+    /// 已完成的番茄数
+    pub completed: u32,
+}
+
+/// 根据已计时秒数计算番茄钟阶段（专注 focus_minutes 分钟，休息 BREAK_MINUTES 分钟，循环）
+pub fn pomodoro_phase(elapsed_secs: i64, focus_minutes: u32) -> PomodoroPhase {
+    let focus = focus_minutes.max(1) as i64 * 60;
+    let cycle = focus + BREAK_MINUTES as i64 * 60;
+    let elapsed = elapsed_secs.max(0);
+    let position = elapsed % cycle;
+
+    PomodoroPhase {
+        focus: position < focus,
+        remaining_secs: if position < focus { focus - position } else { cycle - position },
+        completed: (elapsed / cycle) as u32 + u32::from(position >= focus),
+    }
+}
+
+/// 会话从开始到 end 的分钟数（四舍五入）
+pub fn session_minutes(session: &ActiveSession, end: DateTime<Local>) -> u32 {
+    let secs = (end - session.started_at).num_seconds().max(0);
+    ((secs + 30) / 60) as u32
+}
+
+/// 计时是否已中断太久
+///
+/// 只判断 TUI 写入心跳的计时；命令行开始的计时一直有效，直到 `study stop`
+pub fn is_stale(session: &ActiveSession, now: DateTime<Local>) -> bool {
+    session.heartbeat && now - session.last_seen > Duration::hours(STALE_AFTER_HOURS)
+}
+
+/// 格式化为 时:分:秒
+pub fn format_elapsed(secs: i64) -> String {
+    let secs = secs.max(0);
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// 当前正在进行的计时
+pub fn active(store: &Store) -> Option<ActiveSession> {
+    store.get_active_session().ok().flatten()
+}
+
+/// 开始计时；已有计时时报错
+pub fn start(store: &Store, notes: Option<String>, pomodoro_minutes: Option<u32>) -> Result<ActiveSession> {
+    if let Some(session) = store.get_active_session()? {
+        anyhow::bail!("已有进行中的学习计时（开始于 {}）", session.started_at.format("%H:%M"));
+    }
+
+    let now = Local::now();
+    let session = ActiveSession {
+        started_at: now,
+        last_seen: now,
+        modules_studied: Vec::new(),
+        practice_count: 0,
+        notes: notes.filter(|n| !n.trim().is_empty()),
+        pomodoro_minutes,
+        heartbeat: false,
+    };
+    store.save_active_session(&session)?;
+    Ok(session)
+}
+
+/// 结束计时并写入 study_sessions，notes 追加到开始时的备注后
+pub fn stop(store: &Store, notes: Option<String>) -> Result<StudySummary> {
+    let session = store.get_active_session()?
+        .ok_or_else(|| anyhow::anyhow!("没有进行中的学习计时"))?;
+    finish(store, session, Local::now(), notes)
+}
+
+/// 结束计时（end 为结束时间）
+fn finish(store: &Store, session: ActiveSession, end: DateTime<Local>, notes: Option<String>) -> Result<StudySummary> {
+    let notes = [session.notes.clone(), notes.filter(|n| !n.trim().is_empty())]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let summary = StudySummary {
+        date: session.started_at.date_naive(),
+        minutes: session_minutes(&session, end),
+        modules: session.modules_studied.clone(),
+        practice_count: session.practice_count,
+        notes: (!notes.is_empty()).then(|| notes.join("；")),
+    };
+
+    store.record_study_session_at(
+        session.started_at,
+        summary.minutes,
+        summary.modules.clone(),
+        summary.practice_count,
+        summary.notes.clone(),
+    )?;
+    store.clear_active_session()?;
+
+    Ok(summary)
+}
+
+/// 启动时恢复上次未结束的计时
+pub fn recover(store: &Store, now: DateTime<Local>) -> Result<Recovery> {
+    let Some(session) = store.get_active_session()? else {
+        return Ok(Recovery::None);
+    };
+
+    if is_stale(&session, now) {
+        let end = session.last_seen;
+        return Ok(Recovery::Closed(finish(store, session, end, None)?));
+    }
+    Ok(Recovery::Resumed(session))
+}
+
+/// 更新进行中的计时（没有计时时什么都不做）
+fn update_active(store: &Store, update: impl FnOnce(&mut ActiveSession)) -> Result<Option<ActiveSession>> {
+    let Some(mut session) = store.get_active_session()? else {
+        return Ok(None);
+    };
+    update(&mut session);
+    session.last_seen = Local::now();
+    store.save_active_session(&session)?;
+    Ok(Some(session))
+}
+
+/// 记录学习过的模块
+pub fn touch_module(store: &Store, module_id: &str) -> Result<Option<ActiveSession>> {
+    update_active(store, |session| add_module(session, module_id))
+}
+
+/// 记录一次练习
+pub fn record_practice(store: &Store, module_id: &str) -> Result<Option<ActiveSession>> {
+    update_active(store, |session| {
+        add_module(session, module_id);
+        session.practice_count += 1;
+    })
+}
+
+/// 记录仍在学习（TUI 定期调用，用于中断后按最后活动时间结束）
+pub fn heartbeat(store: &Store) -> Result<Option<ActiveSession>> {
+    update_active(store, |session| session.heartbeat = true)
+}
+
+fn add_module(session: &mut ActiveSession, module_id: &str) {
+    if !session.modules_studied.iter().any(|m| m == module_id) {
+        session.modules_studied.push(module_id.to_string());
+    }
+}
+
+/// 打印恢复结果（命令行）
+pub fn print_recovery(recovery: &Recovery) {
+    if let Recovery::Closed(summary) = recovery {
+        println!("⏱️  上次的学习计时没有结束，已按最后一次活动时间记录 {} 分钟", summary.minutes);
+    }
+}
+
+/// 打印一次学习的总结
+fn print_summary(summary: &StudySummary) {
+    println!("⏱️  本次学习 {} 分钟", summary.minutes);
+    if !summary.modules.is_empty() {
+        println!("📚 学习模块：{}", summary.modules.join("、"));
+    }
+    if summary.practice_count > 0 {
+        println!("✏️  完成练习：{} 次", summary.practice_count);
+    }
+    if let Some(notes) = &summary.notes {
+        println!("📝 备注：{}", notes);
+    }
+}
+
+/// 开始计时（命令行）
+pub fn run_start(store: &Store, notes: Option<String>, pomodoro_minutes: Option<u32>) -> Result<()> {
+    let session = start(store, notes, pomodoro_minutes)?;
+    println!("⏱️  开始学习计时（{}）", session.started_at.format("%H:%M"));
+    if let Some(minutes) = session.pomodoro_minutes {
+        println!("🍅 番茄钟：专注 {} 分钟，休息 {} 分钟", minutes, BREAK_MINUTES);
+    }
+    println!("💡 学习结束后运行：learning-companion study stop");
+    Ok(())
+}
+
+/// 结束计时（命令行）
+pub fn run_stop(store: &Store, notes: Option<String>) -> Result<()> {
+    let summary = stop(store, notes)?;
+    println!("✅ 学习计时已结束\n");
+    print_summary(&summary);
+    Ok(())
+}
+
+/// 查看计时状态（命令行）
+pub fn run_status(store: &Store) -> Result<()> {
+    let Some(session) = store.get_active_session()? else {
+        println!("⏸️  当前没有进行中的学习计时");
+        println!("💡 开始计时：learning-companion study start");
+        return Ok(());
+    };
+
+    let elapsed = (Local::now() - session.started_at).num_seconds();
+    println!("⏱️  学习中：{}（开始于 {}）", format_elapsed(elapsed), session.started_at.format("%m-%d %H:%M"));
+    if let Some(minutes) = session.pomodoro_minutes {
+        let phase = pomodoro_phase(elapsed, minutes);
+        println!("🍅 {}，剩余 {}，已完成 {} 个番茄",
+            if phase.focus { "专注中" } else { "休息中" },
+            format_elapsed(phase.remaining_secs),
+            phase.completed);
+    }
+    if !session.modules_studied.is_empty() {
+        println!("📚 学习模块：{}", session.modules_studied.join("、"));
+    }
+    if session.practice_count > 0 {
+        println!("✏️  完成练习：{} 次", session.practice_count);
+    }
+    Ok(())
+}
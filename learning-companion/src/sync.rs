@@ -0,0 +1,426 @@
+//! 进度同步模块
+//!
+//! 在 SQLite 与 进度.md 之间双向同步任务勾选状态、完成日期和总进度条。
+//! 数据库为每一项记录上次同步时的值（基准），据此判断是哪一侧发生了修改；
+//! 两侧都改过且结果不同时视为冲突，交给用户选择，不会自动覆盖。
+
+use crate::db::{ProgressField, Store};
+use crate::markdown;
+use crate::progress::TaskType;
+use crate::repo::{self, LearningRepo, ModuleProgress};
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs;
+
+/// 完成日期在数据库中的字段名
+pub const COMPLETED_AT: &str = "completed_at";
+
+/// 进度.md 中未填写的完成日期占位符
+const DATE_PLACEHOLDER: &str = "________________";
+
+/// 冲突时以哪一侧为准
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    File,
+    Db,
+}
+
+impl Side {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "file" | "文件" => Some(Side::File),
+            "db" | "数据库" => Some(Side::Db),
+            _ => None,
+        }
+    }
+}
+
+/// 需要写到某一侧的一项变更
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub module_id: String,
+    pub number: u32,
+    pub field: String,
+    pub value: String,
+}
+
+/// 两侧都修改过、无法自动合并的一项进度
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncConflict {
+    pub module_id: String,
+    pub number: u32,
+    pub field: String,
+    pub file_value: String,
+    pub db_value: String,
+}
+
+impl SyncConflict {
+    /// 冲突项的中文名称（如「练习题完成」）
+    pub fn label(&self) -> &str {
+        field_label(&self.field)
+    }
+
+    /// 一行冲突说明
+    pub fn describe(&self) -> String {
+        format!("{:02} {}：进度.md 为「{}」，数据库为「{}」",
+            self.number, self.label(),
+            display_value(&self.field, &self.file_value),
+            display_value(&self.field, &self.db_value))
+    }
+
+    fn change(&self, side: Side) -> FieldChange {
+        FieldChange {
+            module_id: self.module_id.clone(),
+            number: self.number,
+            field: self.field.clone(),
+            value: match side {
+                Side::File => self.file_value.clone(),
+                Side::Db => self.db_value.clone(),
+            },
+        }
+    }
+}
+
+/// 一次同步要做的事
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncPlan {
+    /// 数据库一侧较新，需要写入 进度.md
+    pub to_file: Vec<FieldChange>,
+    /// 进度.md 一侧较新，需要写入数据库
+    pub to_db: Vec<FieldChange>,
+    /// 两侧一致，只需记录同步基准
+    pub unchanged: Vec<FieldChange>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+impl SyncPlan {
+    /// 是否有需要告知用户的变更
+    pub fn has_changes(&self) -> bool {
+        !self.to_file.is_empty() || !self.to_db.is_empty()
+    }
+}
+
+/// 单项三方合并的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    Same,
+    TakeFile,
+    TakeDb,
+    Conflict,
+}
+
+/// 根据文件值、数据库值和上次同步的基准值决定合并方向
+///
+/// 数据库从未记录时直接导入文件值；没有基准且两侧不同时无法判断谁更新，视为冲突
+pub fn merge(file: &str, db: Option<&str>, base: Option<&str>) -> Merge {
+    let Some(db) = db else {
+        return Merge::TakeFile;
+    };
+    if file == db {
+        Merge::Same
+    } else if base == Some(db) {
+        Merge::TakeFile
+    } else if base == Some(file) {
+        Merge::TakeDb
+    } else {
+        Merge::Conflict
+    }
+}
+
+/// 进度.md 中一个模块各项的值（任务为 "true"/"false"，完成日期未填写为空串）
+pub fn file_values(progress: &ModuleProgress) -> Vec<(&'static str, String)> {
+    let mut values: Vec<(&'static str, String)> = TaskType::ALL.iter()
+        .map(|task| (task.key(), task_done(progress, *task).to_string()))
+        .collect();
+    values.push((COMPLETED_AT, progress.completed_at.clone().unwrap_or_default()));
+    values
+}
+
+fn task_done(progress: &ModuleProgress, task: TaskType) -> bool {
+    match task {
+        TaskType::Concept => progress.concept,
+        TaskType::Examples => progress.examples,
+        TaskType::Exercises => progress.exercises,
+        TaskType::Project => progress.project,
+        TaskType::Checklist => progress.checklist,
+    }
+}
+
+/// 模块当前已完成的任务：按同步的合并规则综合 进度.md 与数据库
+///
+/// 同步前在 进度.md 中勾选的任务也算完成；冲突未解决时以数据库记录为准，
+/// 没有 进度.md 小节（file 为 None）时只看数据库
+pub fn tasks_done(fields: &[ProgressField], module_id: &str, file: Option<&ModuleProgress>) -> Vec<TaskType> {
+    TaskType::ALL.into_iter()
+        .filter(|task| {
+            let stored = fields.iter().find(|f| f.module_id == module_id && f.field == task.key());
+            let db_value = stored.and_then(|f| f.value.as_deref());
+            let Some(progress) = file else {
+                return db_value == Some("true");
+            };
+            let file_value = task_done(progress, *task).to_string();
+            match merge(&file_value, db_value, stored.and_then(|f| f.synced_value.as_deref())) {
+                Merge::Same | Merge::TakeFile => file_value == "true",
+                Merge::TakeDb | Merge::Conflict => db_value == Some("true"),
+            }
+        })
+        .collect()
+}
+
+/// 比较 进度.md 与数据库，生成同步计划
+///
+/// 进度.md 中没有对应小节的模块不参与同步
+pub fn plan_sync(repo: &LearningRepo, fields: &[ProgressField]) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+
+    for module in &repo.modules {
+        let Some(progress) = repo.progress.iter().find(|p| p.number == module.number) else {
+            continue;
+        };
+
+        for (field, file_value) in file_values(progress) {
+            let stored = fields.iter().find(|f| f.module_id == module.id && f.field == field);
+            let db_value = stored.and_then(|f| f.value.as_deref());
+            let base = stored.and_then(|f| f.synced_value.as_deref());
+
+            let change = |value: &str| FieldChange {
+                module_id: module.id.clone(),
+                number: module.number,
+                field: field.to_string(),
+                value: value.to_string(),
+            };
+
+            match merge(&file_value, db_value, base) {
+                // 首次同步：直接以文件为准建立基准，不算作变更
+                Merge::TakeFile if db_value.is_none() => plan.unchanged.push(change(&file_value)),
+                Merge::Same if base == Some(file_value.as_str()) => {}
+                Merge::Same => plan.unchanged.push(change(&file_value)),
+                Merge::TakeFile => plan.to_db.push(change(&file_value)),
+                Merge::TakeDb => plan.to_file.push(change(db_value.unwrap_or_default())),
+                Merge::Conflict => plan.conflicts.push(SyncConflict {
+                    module_id: module.id.clone(),
+                    number: module.number,
+                    field: field.to_string(),
+                    file_value,
+                    db_value: db_value.unwrap_or_default().to_string(),
+                }),
+            }
+        }
+    }
+
+    plan
+}
+
+/// 把变更写入 进度.md 内容，并按结果重新计算总进度条
+///
+/// 只改动受影响的行，其余内容（心得、表格等）原样保留
+pub fn apply_to_content(content: &str, changes: &[FieldChange], total_modules: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let sections = markdown::parse_sections(content);
+    let mut replacements: Vec<(usize, String)> = Vec::new();
+
+    for change in changes {
+        let Some(section) = sections.iter()
+            .find(|s| repo::heading_number(&s.title) == Some(change.number))
+        else {
+            continue;
+        };
+
+        for (line_no, line) in section.body(&lines) {
+            if change.field == COMPLETED_AT {
+                if repo::completion_date_value(line).is_some() {
+                    let value = if change.value.is_empty() { DATE_PLACEHOLDER } else { &change.value };
+                    replacements.push((line_no, format!("{}：{}", repo::COMPLETION_DATE_LABEL, value)));
+                }
+            } else if let Some((_, text)) = markdown::parse_task(line) {
+                if TaskType::from_label(&text).map(|t| t.key()) == Some(change.field.as_str()) {
+                    replacements.push((line_no, markdown::set_task_checked(line, change.value == "true")));
+                }
+            }
+        }
+    }
+
+    let updated = markdown::replace_lines(content, &replacements);
+    update_total_progress(&updated, total_modules)
+}
+
+/// 重写 `总进度: x/y (z%)` 行及其下一行的进度条，进度条保持原有宽度
+pub fn update_total_progress(content: &str, total_modules: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let Some(line_no) = lines.iter().position(|l| {
+        let l = l.trim_start();
+        l.starts_with("总进度:") || l.starts_with("总进度：")
+    }) else {
+        return content.to_string();
+    };
+
+    let done = repo::parse_progress(content).iter()
+        .filter(|p| p.status == "[x]")
+        .count();
+    let total = total_modules.max(done);
+    let percent = if total == 0 { 0.0 } else { done as f32 / total as f32 * 100.0 };
+
+    let mut replacements = vec![(line_no, format!("总进度: {}/{} ({:.0}%)", done, total, percent))];
+
+    if let Some(bar) = lines.get(line_no + 1) {
+        let width = bar.trim().chars().count();
+        if width > 0 && bar.trim().chars().all(|c| c == '█' || c == '░') {
+            let filled = (width * done + total / 2).checked_div(total).unwrap_or(0);
+            replacements.push((line_no + 1, format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))));
+        }
+    }
+
+    markdown::replace_lines(content, &replacements)
+}
+
+/// 执行同步：无冲突的变更直接写入，冲突按 prefer 处理，未指定时保留不动
+///
+/// 返回本次的同步计划，其中 conflicts 为仍未解决的冲突
+pub fn sync(store: &Store, repo: &LearningRepo, prefer: Option<Side>) -> Result<SyncPlan> {
+    let mut plan = plan_sync(repo, &store.get_progress_fields()?);
+
+    if let Some(side) = prefer {
+        for conflict in std::mem::take(&mut plan.conflicts) {
+            match side {
+                Side::File => plan.to_db.push(conflict.change(side)),
+                Side::Db => plan.to_file.push(conflict.change(side)),
+            }
+        }
+    }
+
+    apply(store, repo, &plan)?;
+    Ok(plan)
+}
+
+/// 按用户的选择解决一项冲突
+pub fn resolve_conflict(store: &Store, repo: &LearningRepo, conflict: &SyncConflict, side: Side) -> Result<()> {
+    let change = conflict.change(side);
+    let plan = match side {
+        Side::File => SyncPlan { to_db: vec![change], ..Default::default() },
+        Side::Db => SyncPlan { to_file: vec![change], ..Default::default() },
+    };
+    apply(store, repo, &plan)
+}
+
+/// 把同步计划写入两侧，并记录新的同步基准
+fn apply(store: &Store, repo: &LearningRepo, plan: &SyncPlan) -> Result<()> {
+    let progress_path = repo.path.join("进度.md");
+    if progress_path.exists() {
+        let content = fs::read_to_string(&progress_path)
+            .with_context(|| format!("无法读取 {}", progress_path.display()))?;
+        let updated = apply_to_content(&content, &plan.to_file, repo.modules.len());
+        if updated != content {
+            fs::write(&progress_path, &updated)
+                .with_context(|| format!("无法写入 {}", progress_path.display()))?;
+        }
+    }
+
+    for change in plan.to_file.iter().chain(&plan.to_db).chain(&plan.unchanged) {
+        store.mark_progress_synced(&change.module_id, &change.field, &change.value)?;
+    }
+
+    Ok(())
+}
+
+/// 在数据库一侧记录任务状态；五项任务全部完成时顺带记录完成日期
+///
+/// 状态未变化时不写入，避免产生多余的同步变更。数据库中还没有记录的项先以 进度.md 的
+/// 当前值建立同步基准，这样随后的同步能判断出是数据库一侧发生了修改
+pub fn record_task(store: &Store, repo: &LearningRepo, module_id: &str, task: TaskType, done: bool) -> Result<()> {
+    let module_id = repo.module_key(module_id);
+    let file_progress = repo.get_module_progress(module_id);
+    let mut fields: Vec<ProgressField> = store.get_progress_fields()?
+        .into_iter()
+        .filter(|f| f.module_id == module_id)
+        .collect();
+
+    if let Some(progress) = file_progress {
+        for (field, value) in file_values(progress) {
+            if !fields.iter().any(|f| f.field == field) {
+                store.mark_progress_synced(module_id, field, &value)?;
+                fields.push(ProgressField {
+                    module_id: module_id.to_string(),
+                    field: field.to_string(),
+                    value: Some(value.clone()),
+                    synced_value: Some(value),
+                });
+            }
+        }
+    }
+
+    let current = |field: &str| fields.iter()
+        .find(|f| f.field == field)
+        .and_then(|f| f.value.clone());
+
+    let value = done.to_string();
+    if current(task.key()).as_deref() != Some(value.as_str()) {
+        store.set_progress_field(module_id, task.key(), &value)?;
+    }
+
+    let all_done = TaskType::ALL.iter().all(|t| {
+        if t.key() == task.key() { done } else { current(t.key()).as_deref() == Some("true") }
+    });
+    if all_done && current(COMPLETED_AT).unwrap_or_default().is_empty() {
+        store.set_progress_field(module_id, COMPLETED_AT, &Local::now().format("%Y-%m-%d").to_string())?;
+    }
+
+    Ok(())
+}
+
+/// 进度项的中文名称
+pub fn field_label(field: &str) -> &str {
+    match TaskType::from_key(field) {
+        Some(task) => task.as_str(),
+        None if field == COMPLETED_AT => "完成日期",
+        None => field,
+    }
+}
+
+/// 进度项取值的显示文字
+pub fn display_value(field: &str, value: &str) -> String {
+    match (field, value) {
+        (COMPLETED_AT, "") => "未填写".to_string(),
+        (COMPLETED_AT, date) => date.to_string(),
+        (_, "true") => "已完成".to_string(),
+        _ => "未完成".to_string(),
+    }
+}
+
+/// 打印同步结果（命令行）
+pub fn print_plan(plan: &SyncPlan) {
+    for change in &plan.to_file {
+        println!("  📝 进度.md ← {:02} {}：{}",
+            change.number, field_label(&change.field), display_value(&change.field, &change.value));
+    }
+    for change in &plan.to_db {
+        println!("  💾 数据库 ← {:02} {}：{}",
+            change.number, field_label(&change.field), display_value(&change.field, &change.value));
+    }
+    if !plan.conflicts.is_empty() {
+        println!("\n⚠️  有 {} 处冲突未处理（两侧都修改过）：", plan.conflicts.len());
+        for conflict in &plan.conflicts {
+            println!("  • {}", conflict.describe());
+        }
+        println!("💡 使用 learning-companion sync --prefer file|db 选择保留哪一侧，或在 TUI 仪表板按 S 逐项处理");
+    }
+}
+
+/// 运行同步（命令行）
+pub fn run_sync(store: &Store, repo: &LearningRepo, prefer: Option<Side>) -> Result<()> {
+    println!("\n╔════════════════════════════════════════╗");
+    println!("║        🔄 Rust 学习伴侣 - 进度同步      ║");
+    println!("╚════════════════════════════════════════╝\n");
+
+    let plan = sync(store, repo, prefer)?;
+    if !plan.has_changes() && plan.conflicts.is_empty() {
+        println!("✓ 进度.md 与数据库已一致");
+        return Ok(());
+    }
+
+    print_plan(&plan);
+    if plan.has_changes() {
+        println!("\n✓ 已同步 {} 项", plan.to_file.len() + plan.to_db.len());
+    }
+    Ok(())
+}
@@ -17,9 +17,17 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::io;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 // 导入项目模块
+use crate::checker::{Diagnostic, DiagnosticLevel, ExerciseCheck, TestRun};
+use crate::examples::{ExampleRun, ExampleTarget, OutputStream, RunEvent, RunOutcome};
+use crate::hints::{DiffOp, DiffRow, HintLevel};
+use crate::exercise::{Answer, QuestionType};
+use crate::db::Store;
 use crate::repo::{LearningRepo, ModuleProgress};
 
 /// 应用状态
@@ -28,6 +36,7 @@ pub enum AppState {
     MainMenu,
     Dashboard { selected_module: usize },
     ModuleDetail { selected_module: usize, selected_task: usize, focus_area: ModuleFocus },
+    Checklist { selected_module: usize, selected_item: usize },
     UpdateProgress { selected_module: usize, selected_task: usize, focus_area: FocusArea },
     UpdateProgressConfirm { selected_module: usize, selected_task: usize, confirmed: bool },
     Practice { selected_module: usize, question_count: usize, focus_field: PracticeField },
@@ -35,12 +44,26 @@ pub enum AppState {
     Achievements,
     RemindSetup { hour: u8, minute: u8, focus_field: TimeField },
     Export,
+    /// 学习计时（pomodoro 为开始计时时是否使用番茄钟）
+    StudyTimer { pomodoro: bool },
+    /// 练习工作区：编辑练习、运行测试、查看诊断
+    Workspace { view: WorkspaceView },
+    /// 练习的分级提示：注释中的提示、骨架、答案和对比
+    Hints { view: HintsView },
+    /// 代码示例运行器：后台编译运行示例，实时显示输出
+    Examples { view: ExamplesView },
+    /// 全文搜索（editing 为正在输入关键词，否则在结果中选择）
+    Search { query: String, results: Vec<crate::search::SearchHit>, selected: usize, editing: bool },
+    /// 班级名册（讲师合并学员的数据包后查看）
+    Roster { roster: crate::classroom::Roster, scroll_offset: usize },
+    /// 进度.md 与数据库的同步冲突，逐项选择保留哪一侧
+    SyncConflicts { conflicts: Vec<crate::sync::SyncConflict>, selected: usize },
     FileViewer {
-        file_path: String,
-        content: String,
-        scroll_offset: usize,
+        view: FileView,
         return_state: Box<AppState>, // 保存返回的状态
     },
+    /// 操作失败（corrupt 为数据库中有损坏的记录，提示运行 db doctor）
+    Error { message: String, corrupt: bool },
 }
 
 /// 焦点区域（用于 UpdateProgress）
@@ -71,13 +94,159 @@ pub enum ModuleFocus {
     Action,
 }
 
+/// 记住文件查看器滚动位置的设置项（JSON 对象，文件路径到行号）
+const VIEWER_POSITIONS_KEY: &str = "viewer_positions";
+
+/// 文件查看器状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileView {
+    /// 标题中显示的文件名
+    pub title: String,
+    /// 文件的完整路径，用于记住滚动位置（目录列表没有）
+    pub path: Option<PathBuf>,
+    pub rendered: crate::render::Rendered,
+    pub scroll_offset: usize,
+    /// 是否显示目录面板
+    pub show_toc: bool,
+    /// 高亮显示的行（从搜索结果打开时为命中的行）
+    pub highlight: Option<usize>,
+    /// 焦点在目录面板上
+    pub toc_focus: bool,
+    pub toc_selected: usize,
+}
+
+impl FileView {
+    fn last_line(&self) -> usize {
+        self.rendered.lines.len().saturating_sub(1)
+    }
+
+    /// 滚动到 offset，目录选中项跟随当前章节
+    fn scroll_to(&mut self, offset: usize) {
+        self.scroll_offset = offset.min(self.last_line());
+        if let Some(section) = self.rendered.section_at(self.scroll_offset) {
+            self.toc_selected = section;
+        }
+    }
+}
+
+/// 练习工作区焦点区域
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkspaceFocus {
+    Exercises,
+    Output,
+}
+
+/// 练习工作区状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceView {
+    pub selected_module: usize,
+    /// 模块 exercises/ 下的练习（不含扩展名）
+    pub exercises: Vec<String>,
+    pub selected: usize,
+    pub focus: WorkspaceFocus,
+    /// 当前练习最近一次运行测试的结果（切换练习时清空）
+    pub check: Option<ExerciseCheck>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub selected_diagnostic: usize,
+    /// 输出窗格的滚动位置
+    pub scroll_offset: usize,
+}
+
+impl WorkspaceView {
+    /// 当前练习的文件
+    fn exercise_file(&self, module_dir: &std::path::Path) -> Option<PathBuf> {
+        let name = self.exercises.get(self.selected)?;
+        Some(module_dir.join("exercises").join(format!("{}.rs", name)))
+    }
+
+    /// 选中第 index 条诊断，输出窗格滚动到它
+    fn select_diagnostic(&mut self, index: usize) {
+        if let Some(diagnostic) = self.diagnostics.get(index) {
+            self.selected_diagnostic = index;
+            self.scroll_offset = diagnostic.output_line;
+        }
+    }
+
+    fn output_lines(&self) -> usize {
+        self.check.as_ref().map_or(0, |c| c.output.lines().count())
+    }
+}
+
+/// 分级提示界面状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct HintsView {
+    pub selected_module: usize,
+    pub exercise: String,
+    /// 正在查看的级别
+    pub level: HintLevel,
+    /// 本次已经解锁到的级别，回看已解锁的内容不再记录和确认
+    pub revealed: HintLevel,
+    /// 等待确认的级别：测试通过前第一次查看答案需要再按一次
+    pub confirm: Option<HintLevel>,
+    pub lines: Vec<Line<'static>>,
+    pub scroll_offset: usize,
+}
+
+/// 代码示例运行器状态（进行中的运行保存在 App::example_run）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExamplesView {
+    pub selected_module: usize,
+    pub examples: Vec<ExampleTarget>,
+    pub selected: usize,
+    /// 输出属于哪个示例（最近一次运行的示例）
+    pub output_for: Option<String>,
+    pub output: Vec<(OutputStream, String)>,
+    /// 最近一次运行的结果和用时，运行中为 None
+    pub finished: Option<(RunOutcome, Duration)>,
+    /// 输出窗格显示到第几行为止（不含）；follow 时始终显示最新的输出
+    pub scroll_bottom: usize,
+    pub follow: bool,
+}
+
+/// 需要离开按键处理、在下一帧绘制之后执行的操作
+#[derive(Debug, Clone, PartialEq)]
+enum Deferred {
+    /// 暂停 TUI，用外部编辑器打开文件（line 为要跳到的行）
+    Edit { file: PathBuf, line: Option<u32> },
+}
+
 /// 练习会话状态
 #[derive(Debug, Clone, PartialEq)]
 pub struct PracticeSession {
+    pub module_id: String,
     pub questions: Vec<crate::exercise::Question>,
     pub current_index: usize,
-    pub answers: Vec<Option<usize>>,
+    pub answers: Vec<Option<Answer>>,
     pub show_result: bool,
+    /// 每道题所属模块（复习时题目来自多个模块）
+    pub question_modules: Vec<String>,
+    /// 是否为今日复习
+    pub is_review: bool,
+}
+
+impl PracticeSession {
+    /// 当前题目是否需要输入文本（填空题、代码输出题）
+    fn is_text_input(&self) -> bool {
+        self.questions.get(self.current_index)
+            .is_some_and(|q| is_text_question(&q.question_type))
+    }
+
+    /// 当前填空题的输入内容（没有则新建）
+    fn current_text_mut(&mut self) -> &mut String {
+        let slot = &mut self.answers[self.current_index];
+        if !matches!(slot, Some(Answer::Text(_))) {
+            *slot = Some(Answer::Text(String::new()));
+        }
+        match slot {
+            Some(Answer::Text(text)) => text,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// 需要输入文本作答的题型
+fn is_text_question(question_type: &QuestionType) -> bool {
+    matches!(question_type, QuestionType::FillInBlank | QuestionType::CodeOutput)
 }
 
 /// 主应用结构
@@ -104,18 +273,42 @@ pub struct App {
     pub should_quit: bool,
     /// 项目路径
     pub project_path: String,
+    /// 学习数据
+    pub store: Store,
+    /// 进行中的学习计时（缓存，开始/结束/心跳时刷新）
+    pub study: Option<crate::db::ActiveSession>,
+    /// 上次写入计时心跳的时间
+    last_heartbeat: Instant,
+    /// 计时中最近一次记录的模块，避免重复写库
+    studied_module: Option<String>,
+    /// 番茄钟上一帧所处阶段（true 为专注），用于阶段切换提醒
+    pomodoro_focus: Option<bool>,
+    /// 等待主循环执行的操作
+    deferred: Option<Deferred>,
+    /// 搜索索引（打开搜索界面时重建）
+    search_index: Option<crate::search::SearchIndex>,
+    /// 后台运行中的代码示例（离开运行器时取消）
+    example_run: Option<ExampleRun>,
+    /// 后台运行中的练习测试（离开工作区后继续，结束时记录结果）
+    test_run: Option<TestRun>,
+    /// 今日待复习的题数（进入仪表板时计算，读取失败时为空）
+    due_reviews: Option<usize>,
 }
 
 impl App {
     /// 创建新应用
-    pub fn new(project_path: String) -> Self {
+    pub fn new(project_path: String, store: Store) -> Self {
         let main_menu_items = vec![
             "📊 查看学习仪表板".to_string(),
             "📚 更新学习进度".to_string(),
             "✏️  开始练习测试".to_string(),
+            "🔁 今日复习".to_string(),
+            "⏱️  学习计时".to_string(),
             "🏆 查看成就".to_string(),
             "⏰ 设置学习提醒".to_string(),
             "📤 导出学习数据".to_string(),
+            "👥 班级名册".to_string(),
+            "🔍 全文搜索".to_string(),
             "退出程序".to_string(),
         ];
 
@@ -131,17 +324,223 @@ impl App {
             message_deadline: None,
             should_quit: false,
             project_path,
+            store,
+            study: None,
+            last_heartbeat: Instant::now(),
+            studied_module: None,
+            pomodoro_focus: None,
+            deferred: None,
+            search_index: None,
+            example_run: None,
+            test_run: None,
+            due_reviews: None,
+        }
+    }
+
+    /// 启动时恢复上次未结束的学习计时
+    fn recover_study(&mut self) {
+        match crate::study::recover(&self.store, chrono::Local::now()) {
+            Ok(crate::study::Recovery::Resumed(session)) => {
+                self.study = Some(session);
+                self.show_message("⏱️ 已恢复进行中的学习计时".to_string());
+            }
+            Ok(crate::study::Recovery::Closed(summary)) => {
+                self.show_message(format!("⏱️ 上次的学习计时没有结束，已记录 {} 分钟", summary.minutes));
+            }
+            Ok(crate::study::Recovery::None) => {}
+            Err(e) => self.show_message(format!("❌ 恢复学习计时失败: {:#}", e)),
+        }
+    }
+
+    /// 每帧调用：写入计时心跳，番茄钟阶段切换时提醒
+    fn tick(&mut self) {
+        let Some(session) = &self.study else {
+            return;
+        };
+
+        if let Some(minutes) = session.pomodoro_minutes {
+            let elapsed = (chrono::Local::now() - session.started_at).num_seconds();
+            let focus = crate::study::pomodoro_phase(elapsed, minutes).focus;
+            if self.pomodoro_focus.is_some_and(|prev| prev != focus) {
+                let _ = crate::notify::send_pomodoro_notification(focus);
+                self.show_message(if focus {
+                    "🍅 休息结束，开始新的专注".to_string()
+                } else {
+                    format!("☕ 专注完成！休息 {} 分钟", crate::study::BREAK_MINUTES)
+                });
+            }
+            self.pomodoro_focus = Some(focus);
+        }
+
+        if self.last_heartbeat.elapsed() >= Duration::from_secs(60) {
+            self.last_heartbeat = Instant::now();
+            if let Ok(session) = crate::study::heartbeat(&self.store) {
+                self.study = session;
+            }
+        }
+    }
+
+    /// 计时中记录当前界面对应的模块
+    fn track_study_module(&mut self) {
+        if self.study.is_none() {
+            return;
+        }
+        let index = match &self.state {
+            AppState::ModuleDetail { selected_module, .. }
+            | AppState::Checklist { selected_module, .. }
+            | AppState::Practice { selected_module, .. } => Some(*selected_module),
+            AppState::Workspace { view } => Some(view.selected_module),
+            AppState::Examples { view } => Some(view.selected_module),
+            AppState::Hints { view } => Some(view.selected_module),
+            _ => None,
+        };
+        let module_id = match &self.state {
+            AppState::PracticeSession { session } => Some(session.module_id.clone()),
+            _ => index.and_then(|i| self.repo.as_ref()?.modules.get(i).map(|m| m.id.clone())),
+        };
+
+        if let Some(module_id) = module_id {
+            if self.studied_module.as_ref() != Some(&module_id) {
+                if let Ok(session) = crate::study::touch_module(&self.store, &module_id) {
+                    self.study = session;
+                }
+                self.studied_module = Some(module_id);
+            }
+        }
+    }
+
+    /// 评估成就，有新解锁时提示（追加在当前消息后）；读取学习数据失败时进入错误界面
+    fn check_achievements(&mut self) {
+        let total = self.repo.as_ref().map_or(0, |r| r.modules.len());
+        let unlocked = match crate::achievements::evaluate(&self.store, total) {
+            Ok(unlocked) => unlocked,
+            Err(e) => {
+                self.show_error(&e);
+                return;
+            }
+        };
+        if unlocked.is_empty() {
+            return;
+        }
+
+        let names: Vec<String> = unlocked.iter().map(|d| format!("{} {}", d.icon, d.name)).collect();
+        let text = format!("🏆 解锁成就：{}", names.join("、"));
+        let msg = match self.message.take() {
+            Some(prev) => format!("{}  {}", prev, text),
+            None => text,
+        };
+        self.show_message(msg);
+    }
+
+    /// 开始或结束学习计时
+    fn toggle_study(&mut self, pomodoro: bool) {
+        if self.study.is_some() {
+            match crate::study::stop(&self.store, None) {
+                Ok(summary) => {
+                    self.study = None;
+                    self.pomodoro_focus = None;
+                    self.studied_module = None;
+                    self.show_message(format!("✅ 本次学习 {} 分钟，已记录", summary.minutes));
+                    self.check_achievements();
+                }
+                Err(e) => self.show_message(format!("❌ {:#}", e)),
+            }
+        } else {
+            let minutes = pomodoro.then_some(crate::study::DEFAULT_POMODORO_MINUTES);
+            match crate::study::start(&self.store, None, minutes) {
+                Ok(session) => {
+                    self.study = Some(session);
+                    self.last_heartbeat = Instant::now();
+                    self.show_message("⏱️ 开始学习计时".to_string());
+                }
+                Err(e) => self.show_message(format!("❌ {:#}", e)),
+            }
         }
     }
 
     /// 确保仓库已加载
     fn ensure_repo(&mut self) -> Result<()> {
         if self.repo.is_none() {
-            self.repo = Some(LearningRepo::new(&self.project_path)?);
+            let repo = LearningRepo::new(&self.project_path)?;
+            // 首次加载时与数据库同步一次，冲突留到仪表板按 S 处理
+            let plan = crate::sync::sync(&self.store, &repo, None)?;
+            self.repo = Some(if plan.has_changes() { LearningRepo::new(&self.project_path)? } else { repo });
+            if !plan.conflicts.is_empty() {
+                self.show_message(format!("⚠️ 进度.md 与数据库有 {} 处冲突，在仪表板按 S 处理", plan.conflicts.len()));
+            }
         }
         Ok(())
     }
 
+    /// 重新读取仓库（进度.md 被改写后调用）
+    fn reload_repo(&mut self) {
+        if let Ok(repo) = LearningRepo::new(&self.project_path) {
+            self.repo = Some(repo);
+        }
+    }
+
+    /// 重新统计今日待复习的题数
+    fn refresh_due_reviews(&mut self) {
+        let Some(repo) = &self.repo else {
+            return;
+        };
+        match crate::review::due_count(&self.store, repo) {
+            Ok(count) => self.due_reviews = Some(count),
+            Err(e) => {
+                self.due_reviews = None;
+                self.show_message(format!("❌ 无法统计待复习的题目: {:#}", e));
+            }
+        }
+    }
+
+    /// 同步 进度.md 与数据库，有冲突时进入冲突处理界面
+    fn sync_progress(&mut self) {
+        let Some(repo) = &self.repo else {
+            return;
+        };
+        match crate::sync::sync(&self.store, repo, None) {
+            Ok(plan) => {
+                let changed = plan.to_file.len() + plan.to_db.len();
+                self.reload_repo();
+                if !plan.conflicts.is_empty() {
+                    self.push_state(AppState::SyncConflicts { conflicts: plan.conflicts, selected: 0 });
+                } else if changed > 0 {
+                    self.show_message(format!("🔄 已同步 {} 项", changed));
+                } else {
+                    self.show_message("✓ 进度.md 与数据库已一致".to_string());
+                }
+            }
+            Err(e) => self.show_message(format!("❌ 同步失败: {:#}", e)),
+        }
+    }
+
+    /// 执行一步操作：返回错误或 panic 时进入错误界面，而不是退出 TUI
+    fn guard(&mut self, action: impl FnOnce(&mut Self) -> Result<()>) {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| action(&mut *self))) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => self.show_error(&e),
+            Err(_) => self.show_panic(),
+        }
+    }
+
+    /// 显示 panic hook 记录的信息
+    fn show_panic(&mut self) {
+        let message = PANIC_MESSAGE.lock().ok().and_then(|mut m| m.take())
+            .unwrap_or_else(|| "未知错误".to_string());
+        self.show_error(&anyhow::anyhow!("程序内部错误：{}", message));
+    }
+
+    /// 显示错误界面，返回时回到主菜单
+    fn show_error(&mut self, err: &anyhow::Error) {
+        let corrupt = err.chain()
+            .any(|e| e.downcast_ref::<crate::db::DbError>().is_some_and(|e| e.is_corrupt()));
+        self.state_stack.clear();
+        self.state = AppState::Error { message: format!("{:#}", err), corrupt };
+        self.message = None;
+        self.message_deadline = None;
+        self.update_help_text();
+    }
+
     /// 显示临时消息（3秒后自动消失）
     fn show_message(&mut self, msg: String) {
         self.message = Some(msg);
@@ -160,34 +559,36 @@ impl App {
             AppState::MainMenu => self.handle_main_menu_key(key),
             AppState::Dashboard { .. } => self.handle_dashboard_key(key),
             AppState::ModuleDetail { .. } => self.handle_module_detail_key(key),
+            AppState::Checklist { .. } => self.handle_checklist_key(key),
             AppState::UpdateProgress { .. } => self.handle_update_progress_key(key),
             AppState::UpdateProgressConfirm { .. } => self.handle_update_progress_confirm_key(key),
             AppState::Practice { .. } => self.handle_practice_key(key),
             AppState::Achievements => self.handle_achievements_key(key),
             AppState::RemindSetup { .. } => self.handle_remind_setup_key(key),
             AppState::Export => self.handle_export_key(key),
+            AppState::StudyTimer { .. } => self.handle_study_timer_key(key),
+            AppState::SyncConflicts { .. } => self.handle_sync_conflicts_key(key),
+            AppState::Roster { .. } => self.handle_roster_key(key),
+            AppState::Search { .. } => self.handle_search_key(key),
+            AppState::Workspace { .. } => self.handle_workspace_key(key),
+            AppState::Examples { .. } => self.handle_examples_key(key),
+            AppState::Hints { .. } => self.handle_hints_key(key),
             AppState::PracticeSession { .. } => self.handle_practice_session_key(key),
             AppState::FileViewer { .. } => self.handle_file_viewer_key(key),
+            AppState::Error { .. } => self.handle_error_key(key),
         }
+        self.track_study_module();
         Ok(())
     }
 
     /// 主菜单按键处理
     fn handle_main_menu_key(&mut self, key: KeyCode) {
         match key {
-            KeyCode::Up => {
-                if self.main_menu_selected > 0 {
-                    self.main_menu_selected -= 1;
-                }
-            }
-            KeyCode::Down => {
-                if self.main_menu_selected < self.main_menu_items.len() - 1 {
-                    self.main_menu_selected += 1;
-                }
-            }
+            KeyCode::Up if self.main_menu_selected > 0 => self.main_menu_selected -= 1,
+            KeyCode::Down if self.main_menu_selected < self.main_menu_items.len() - 1 => self.main_menu_selected += 1,
             KeyCode::Enter => {
                 if let Err(e) = self.enter_main_menu_selection() {
-                    self.show_message(format!("错误: {}", e));
+                    self.show_error(&e);
                 }
             }
             KeyCode::Char('q') | KeyCode::Esc => {
@@ -203,6 +604,7 @@ impl App {
             0 => {
                 self.ensure_repo()?;
                 self.push_state(AppState::Dashboard { selected_module: 0 });
+                self.refresh_due_reviews();
             }
             1 => {
                 self.ensure_repo()?;
@@ -221,24 +623,85 @@ impl App {
                 });
             }
             3 => {
-                self.push_state(AppState::Achievements);
+                self.ensure_repo()?;
+                self.start_review()?;
             }
             4 => {
+                let pomodoro = self.study.as_ref().map_or(true, |s| s.pomodoro_minutes.is_some());
+                self.push_state(AppState::StudyTimer { pomodoro });
+            }
+            5 => {
+                self.ensure_repo()?;
+                self.check_achievements();
+                self.push_state(AppState::Achievements);
+            }
+            6 => {
                 self.push_state(AppState::RemindSetup {
                     hour: 20,
                     minute: 0,
                     focus_field: TimeField::Hour,
                 });
             }
-            5 => {
+            7 => {
                 self.push_state(AppState::Export);
             }
-            6 => self.should_quit = true,
+            8 => {
+                self.ensure_repo()?;
+                self.open_roster()?;
+            }
+            9 => {
+                self.ensure_repo()?;
+                self.open_search()?;
+            }
+            10 => self.should_quit = true,
             _ => {}
         }
         Ok(())
     }
 
+    /// 读取上次 class roster 使用的数据包目录，合并成班级名册
+    fn open_roster(&mut self) -> Result<()> {
+        let Some(repo) = &self.repo else {
+            return Ok(());
+        };
+        let Some(dir) = self.store.get_setting(crate::classroom::CLASSROOM_DIR_KEY)? else {
+            self.show_message("💡 先运行 learning-companion class roster <数据包目录>，之后这里会显示同一份名册".to_string());
+            return Ok(());
+        };
+        let tz = crate::clock::TimeZone::load(&self.store)?;
+        let today = tz.today(&crate::clock::SystemClock);
+        let roster = crate::classroom::Roster::load(repo, std::path::Path::new(&dir), tz, today, crate::classroom::DEFAULT_STALLED_DAYS)?;
+        self.push_state(AppState::Roster { roster, scroll_offset: 0 });
+        Ok(())
+    }
+
+    /// 开始今日复习，没有到期题目时只提示
+    fn start_review(&mut self) -> Result<()> {
+        let Some(repo) = &self.repo else {
+            return Ok(());
+        };
+
+        let today = chrono::Local::now().date_naive();
+        let mut due = crate::review::due_questions(&self.store, repo, today)?;
+        if due.is_empty() {
+            self.show_message("🎉 今天没有需要复习的题目".to_string());
+            return Ok(());
+        }
+        due.truncate(20);
+
+        let session = PracticeSession {
+            module_id: due[0].module_id.clone(),
+            current_index: 0,
+            answers: vec![None; due.len()],
+            show_result: false,
+            question_modules: due.iter().map(|d| d.module_id.clone()).collect(),
+            questions: due.into_iter().map(|d| d.question).collect(),
+            is_review: true,
+        };
+        self.push_state(AppState::PracticeSession { session });
+        Ok(())
+    }
+
     /// 仪表板按键处理
     fn handle_dashboard_key(&mut self, key: KeyCode) {
         if let AppState::Dashboard { ref mut selected_module } = self.state {
@@ -249,11 +712,7 @@ impl App {
                     self.state_stack.clear();
                     self.update_help_text();
                 }
-                KeyCode::Up => {
-                    if *selected_module > 0 {
-                        *selected_module -= 1;
-                    }
-                }
+                KeyCode::Up if *selected_module > 0 => *selected_module -= 1,
                 KeyCode::Down => {
                     if let Some(repo) = &self.repo {
                         if *selected_module < repo.modules.len().saturating_sub(1) {
@@ -285,6 +744,10 @@ impl App {
                     // 查看成就
                     self.push_state(AppState::Achievements);
                 }
+                KeyCode::Char('s') | KeyCode::Char('S') => {
+                    // 与数据库同步进度
+                    self.sync_progress();
+                }
                 KeyCode::Char('o') | KeyCode::Char('O') => {
                     // 进入模块详情界面
                     let module = *selected_module;
@@ -300,145 +763,817 @@ impl App {
         }
     }
 
-    /// 模块详情按键处理
-    fn handle_module_detail_key(&mut self, key: KeyCode) {
-        if let AppState::ModuleDetail { ref mut selected_module, ref mut selected_task, ref mut focus_area } = self.state {
-            match key {
-                KeyCode::Esc | KeyCode::Char('q') => {
-                    // 返回仪表板
-                    if let Some(repo) = &self.repo {
-                        let module = *selected_module;
-                        self.state = AppState::Dashboard { selected_module: module };
-                    } else {
-                        self.state = AppState::MainMenu;
-                    }
-                    self.update_help_text();
+    /// 模块详情按键处理
+    fn handle_module_detail_key(&mut self, key: KeyCode) {
+        if let AppState::ModuleDetail { ref mut selected_module, ref mut selected_task, ref mut focus_area } = self.state {
+            match key {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    // 返回仪表板
+                    if self.repo.is_some() {
+                        let module = *selected_module;
+                        self.state = AppState::Dashboard { selected_module: module };
+                        self.refresh_due_reviews();
+                    } else {
+                        self.state = AppState::MainMenu;
+                    }
+                    self.update_help_text();
+                }
+                KeyCode::Tab => {
+                    *focus_area = match focus_area {
+                        ModuleFocus::TaskList => ModuleFocus::Action,
+                        ModuleFocus::Action => ModuleFocus::TaskList,
+                    };
+                }
+                KeyCode::Up if self.repo.is_some() => {
+                    match focus_area {
+                        ModuleFocus::TaskList => {
+                            if *selected_task > 0 {
+                                *selected_task -= 1;
+                            }
+                        }
+                        ModuleFocus::Action => {
+                            if *selected_task > 0 {
+                                *selected_task -= 1;
+                            }
+                        }
+                    }
+                }
+                KeyCode::Down if self.repo.is_some() => {
+                    match focus_area {
+                        ModuleFocus::TaskList => {
+                            if *selected_task < 4 {
+                                *selected_task += 1;
+                            }
+                        }
+                        ModuleFocus::Action => {
+                            if *selected_task < 4 {
+                                *selected_task += 1;
+                            }
+                        }
+                    }
+                }
+                KeyCode::Char('o') | KeyCode::Char('O') => {
+                    // 在TUI内打开当前任务对应的文件
+                    if let Some(repo) = &self.repo {
+                        if let Some(module) = repo.modules.get(*selected_module) {
+                            let task_files = ["README.md", "examples", "exercises.md", "tests", "自检清单.md"];
+                            if let Some(file) = task_files.get(*selected_task) {
+                                let path = module.directory.join(file);
+                                self.open_file_viewer(path, file.to_string(), None);
+                            }
+                        }
+                    }
+                }
+                KeyCode::Char('c') | KeyCode::Char('C') => {
+                    let module = *selected_module;
+                    self.open_checklist(module);
+                }
+                KeyCode::Char('w') | KeyCode::Char('W') => {
+                    let module = *selected_module;
+                    self.open_workspace(module);
+                }
+                KeyCode::Char('x') | KeyCode::Char('X') => {
+                    let module = *selected_module;
+                    self.open_examples(module);
+                }
+                KeyCode::Char(' ') | KeyCode::Enter if *selected_task == 1 => {
+                    // 代码示例由运行记录决定，直接进入示例运行器
+                    let module = *selected_module;
+                    self.open_examples(module);
+                }
+                KeyCode::Char(' ') | KeyCode::Enter if *selected_task == 2 => {
+                    // 练习题完成由测试结果决定，直接进入练习工作区
+                    let module = *selected_module;
+                    self.open_workspace(module);
+                }
+                KeyCode::Char(' ') | KeyCode::Enter if *selected_task == 4 => {
+                    // 自检通过由清单勾选情况决定，直接进入清单
+                    let module = *selected_module;
+                    self.open_checklist(module);
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    // 进入确认状态，而不是直接标记完成
+                    if let Some(repo) = &self.repo {
+                        if let Some(_module) = repo.modules.get(*selected_module) {
+                            // 提取当前状态值，避免借用冲突
+                            let module_idx = *selected_module;
+                            let task_idx = *selected_task;
+
+                            // 保存当前状态并切换到确认状态
+                            let current_state = self.state.clone();
+                            self.state = AppState::UpdateProgressConfirm {
+                                selected_module: module_idx,
+                                selected_task: task_idx,
+                                confirmed: false,
+                            };
+                            self.state_stack.push(current_state);
+                            self.update_help_text();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 在文件查看器中打开文件（目录显示其中的文件列表）
+    ///
+    /// at_line 为源文件的行号，打开到该行并高亮；为空时回到上次阅读的位置
+    fn open_file_viewer(&mut self, path: PathBuf, title: String, at_line: Option<usize>) {
+        let (title, rendered, source) = if path.is_dir() {
+            let mut listing = format!("目录: {}\n\n", path.display());
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let file_type = if entry.path().is_dir() { "[DIR]" } else { "[FILE]" };
+                    listing.push_str(&format!("{} {}\n", file_type, name));
+                }
+            }
+            (format!("{}/ (目录)", title), crate::render::render_plain(&listing), None)
+        } else {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => (title, crate::render::render_file(&path, &content), Some(path)),
+                Err(_) => {
+                    self.show_message(format!("❌ 无法读取文件: {}", title));
+                    return;
+                }
+            }
+        };
+
+        let saved = source.as_ref().and_then(|path| self.viewer_positions().get(&path.display().to_string()).copied());
+        let mut view = FileView {
+            title,
+            path: source,
+            show_toc: rendered.toc.len() > 1,
+            highlight: at_line.map(|line| rendered.line_for_source(line)),
+            rendered,
+            scroll_offset: 0,
+            toc_focus: false,
+            toc_selected: 0,
+        };
+        // 目标行上方留几行上下文
+        let target = view.highlight.map(|line| line.saturating_sub(3));
+        view.scroll_to(target.or(saved).unwrap_or(0));
+
+        let return_state = Box::new(self.state.clone());
+        let restored = if target.is_some() { 0 } else { view.scroll_offset };
+        self.state = AppState::FileViewer { view, return_state };
+        self.update_help_text();
+        if restored > 0 {
+            self.show_message(format!("📌 回到上次阅读的位置（第 {} 行）", restored + 1));
+        }
+    }
+
+    /// 各文件上次阅读的位置
+    fn viewer_positions(&self) -> std::collections::HashMap<String, usize> {
+        self.store.get_setting(VIEWER_POSITIONS_KEY).ok().flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 记住文件的阅读位置，下次打开时回到这里
+    fn save_viewer_position(&self, view: &FileView) {
+        let Some(path) = &view.path else {
+            return;
+        };
+        let mut positions = self.viewer_positions();
+        positions.insert(path.display().to_string(), view.scroll_offset);
+        if let Ok(json) = serde_json::to_string(&positions) {
+            let _ = self.store.save_setting(VIEWER_POSITIONS_KEY, &json);
+        }
+    }
+
+    /// 重建搜索索引并进入搜索界面（测试还没通过的练习的答案不参与搜索）
+    fn open_search(&mut self) -> Result<()> {
+        let Some(repo) = &self.repo else {
+            return Ok(());
+        };
+        let mut index = crate::search::SearchIndex::build(repo)?;
+        index.hide_unsolved_solutions(&self.store)?;
+        self.search_index = Some(index);
+        self.push_state(AppState::Search { query: String::new(), results: Vec::new(), selected: 0, editing: true });
+        Ok(())
+    }
+
+    /// 搜索界面按键处理
+    fn handle_search_key(&mut self, key: KeyCode) {
+        let AppState::Search { ref mut query, ref mut results, ref mut selected, ref mut editing } = self.state else {
+            return;
+        };
+
+        if *editing {
+            match key {
+                KeyCode::Esc => self.pop_state(),
+                KeyCode::Char(c) => query.push(c),
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Down if !results.is_empty() => {
+                    *editing = false;
+                    self.update_help_text();
+                }
+                KeyCode::Enter => {
+                    let Some(index) = &self.search_index else {
+                        return;
+                    };
+                    *results = index.search(query, None, crate::search::DEFAULT_LIMIT * 2);
+                    *selected = 0;
+                    if results.is_empty() {
+                        let msg = format!("没有找到「{}」", query.trim());
+                        self.show_message(msg);
+                    } else {
+                        *editing = false;
+                        self.update_help_text();
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.pop_state(),
+            KeyCode::Char('/') | KeyCode::Tab => {
+                *editing = true;
+                self.update_help_text();
+            }
+            KeyCode::Up => {
+                if *selected > 0 {
+                    *selected -= 1;
+                } else {
+                    *editing = true;
+                    self.update_help_text();
+                }
+            }
+            KeyCode::Down if *selected + 1 < results.len() => *selected += 1,
+            KeyCode::Enter => {
+                if let Some(hit) = results.get(*selected).cloned() {
+                    self.open_file_viewer(hit.path, hit.display, Some(hit.line));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 打开模块的练习工作区
+    fn open_workspace(&mut self, selected_module: usize) {
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(selected_module)) else {
+            return;
+        };
+
+        match crate::checker::list_exercises(&module.directory) {
+            Ok(exercises) if !exercises.is_empty() => {
+                self.push_state(AppState::Workspace {
+                    view: WorkspaceView {
+                        selected_module,
+                        exercises,
+                        selected: 0,
+                        focus: WorkspaceFocus::Exercises,
+                        check: None,
+                        diagnostics: Vec::new(),
+                        selected_diagnostic: 0,
+                        scroll_offset: 0,
+                    },
+                });
+            }
+            Ok(_) => {
+                let msg = format!("❌ {} 没有练习文件", module.name);
+                self.show_message(msg);
+            }
+            Err(e) => self.show_message(format!("❌ {:#}", e)),
+        }
+    }
+
+    /// 练习工作区按键处理
+    fn handle_workspace_key(&mut self, key: KeyCode) {
+        let AppState::Workspace { ref mut view } = self.state else {
+            return;
+        };
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(view.selected_module)) else {
+            return;
+        };
+
+        match (key, view.focus) {
+            (KeyCode::Esc | KeyCode::Char('q'), _) => self.pop_state(),
+            (KeyCode::Tab, _) => {
+                view.focus = match view.focus {
+                    WorkspaceFocus::Exercises => WorkspaceFocus::Output,
+                    WorkspaceFocus::Output => WorkspaceFocus::Exercises,
+                };
+            }
+            (KeyCode::Char('t') | KeyCode::Char('T'), _) | (KeyCode::Enter, WorkspaceFocus::Exercises) => {
+                if let Some(run) = &self.test_run {
+                    let msg = format!("⏳ {} 的测试还在运行，按 C 取消", run.exercise);
+                    self.show_message(msg);
+                } else if let Some(name) = view.exercises.get(view.selected) {
+                    let msg = format!("🔧 正在测试 {} ...", name);
+                    self.test_run = Some(TestRun::start(module, name, crate::checker::TEST_TIMEOUT));
+                    self.show_message(msg);
+                }
+            }
+            (KeyCode::Char('c') | KeyCode::Char('C'), _) => {
+                if let Some(run) = &self.test_run {
+                    run.cancel();
+                }
+            }
+            (KeyCode::Char('o') | KeyCode::Char('O'), _) => {
+                if let Some(file) = view.exercise_file(&module.directory) {
+                    let title = format!("exercises/{}", file.file_name().unwrap_or_default().to_string_lossy());
+                    self.open_file_viewer(file, title, None);
+                }
+            }
+            (KeyCode::Char('h') | KeyCode::Char('H'), _) => {
+                if let Some(exercise) = view.exercises.get(view.selected).cloned() {
+                    let selected_module = view.selected_module;
+                    self.open_hints(selected_module, exercise);
+                }
+            }
+            (KeyCode::Char('e') | KeyCode::Char('E'), WorkspaceFocus::Exercises) => {
+                if let Some(file) = view.exercise_file(&module.directory) {
+                    self.deferred = Some(Deferred::Edit { file, line: None });
+                }
+            }
+            (KeyCode::Char('e') | KeyCode::Char('E') | KeyCode::Enter, WorkspaceFocus::Output) => {
+                // 打开选中的诊断所在的文件和行，没有诊断时打开练习文件
+                let target = match view.diagnostics.get(view.selected_diagnostic) {
+                    Some(d) => Some((d.location.file.clone(), Some(d.location.line))),
+                    None => view.exercise_file(&module.directory).map(|file| (file, None)),
+                };
+                if let Some((file, line)) = target {
+                    self.deferred = Some(Deferred::Edit { file, line });
+                }
+            }
+            (KeyCode::Up, WorkspaceFocus::Exercises) if view.selected > 0 => {
+                view.selected -= 1;
+                view.check = None;
+                view.diagnostics.clear();
+                view.scroll_offset = 0;
+            }
+            (KeyCode::Down, WorkspaceFocus::Exercises) if view.selected + 1 < view.exercises.len() => {
+                view.selected += 1;
+                view.check = None;
+                view.diagnostics.clear();
+                view.scroll_offset = 0;
+            }
+            (KeyCode::Up, WorkspaceFocus::Output) => view.scroll_offset = view.scroll_offset.saturating_sub(1),
+            (KeyCode::Down, WorkspaceFocus::Output) if view.scroll_offset + 1 < view.output_lines() => view.scroll_offset += 1,
+            (KeyCode::PageUp, WorkspaceFocus::Output) => view.scroll_offset = view.scroll_offset.saturating_sub(20),
+            (KeyCode::PageDown, WorkspaceFocus::Output) => {
+                view.scroll_offset = (view.scroll_offset + 20).min(view.output_lines().saturating_sub(1));
+            }
+            (KeyCode::Char('n') | KeyCode::Char('N'), _) => {
+                let next = view.selected_diagnostic + 1;
+                view.select_diagnostic(next);
+                view.focus = WorkspaceFocus::Output;
+            }
+            (KeyCode::Char('p') | KeyCode::Char('P'), _) => {
+                if let Some(prev) = view.selected_diagnostic.checked_sub(1) {
+                    view.select_diagnostic(prev);
+                }
+                view.focus = WorkspaceFocus::Output;
+            }
+            _ => {}
+        }
+    }
+
+    /// 取出后台测试的结果，记录并同步 进度.md；还停留在该练习的工作区时显示诊断
+    fn poll_test_run(&mut self) -> Result<()> {
+        let Some(run) = self.test_run.take() else {
+            return Ok(());
+        };
+        let Some(result) = run.poll() else {
+            self.test_run = Some(run);
+            return Ok(());
+        };
+        let check = match result {
+            Ok(check) => check,
+            // 超时或取消不算一次测试，不记录
+            Err(e) => {
+                self.show_message(format!("❌ {}：{:#}", run.exercise, e));
+                return Ok(());
+            }
+        };
+        let Some(repo) = &self.repo else {
+            return Ok(());
+        };
+        let Some(position) = repo.modules.iter().position(|m| m.id == run.module_id) else {
+            return Ok(());
+        };
+        let module = &repo.modules[position];
+
+        crate::checker::record_check(&self.store, module, &check)?;
+        let (summary, _) = crate::checker::finish_checks(&self.store, repo, module)?;
+        let diagnostics = crate::checker::parse_diagnostics(&check.output, &module.directory);
+
+        let errors = diagnostics.iter().filter(|d| d.level == DiagnosticLevel::Error).count();
+        let mut msg = if !check.compiled {
+            format!("❌ {} 编译失败：{} 个错误", check.exercise, errors)
+        } else if check.tests.is_empty() {
+            format!("⚪ {} 没有测试用例", check.exercise)
+        } else if check.all_passed() {
+            format!("✅ {} 通过 {}/{}", check.exercise, check.passed_count(), check.tests.len())
+        } else {
+            format!("❌ {} 通过 {}/{}", check.exercise, check.passed_count(), check.tests.len())
+        };
+        if summary.is_complete() {
+            msg.push_str("  🎉 本模块练习全部通过");
+        }
+
+        self.reload_repo();
+        if let AppState::Workspace { ref mut view } = self.state {
+            if view.selected_module == position && view.exercises.get(view.selected) == Some(&check.exercise) {
+                view.focus = if check.all_passed() { WorkspaceFocus::Exercises } else { WorkspaceFocus::Output };
+                view.check = Some(check);
+                view.diagnostics = diagnostics;
+                view.scroll_offset = 0;
+                view.select_diagnostic(0);
+            }
+        }
+        self.show_message(msg);
+        self.check_achievements();
+        Ok(())
+    }
+
+    /// 编辑器退出后调用
+    fn finish_edit(&mut self, status: io::Result<std::process::ExitStatus>) {
+        let editor = crate::editor::configured_editor().join(" ");
+        match status {
+            Ok(status) if status.success() => self.show_message("✏️ 已返回工作区，按 T 运行测试".to_string()),
+            Ok(status) => self.show_message(format!("⚠️ 编辑器 {} 异常退出（{}）", editor, status)),
+            Err(e) => self.show_message(format!("❌ 无法启动编辑器 {}：{}（用 $EDITOR 指定编辑器）", editor, e)),
+        }
+    }
+
+    /// 打开练习的分级提示，从注释中的提示开始
+    fn open_hints(&mut self, selected_module: usize, exercise: String) {
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(selected_module)) else {
+            return;
+        };
+        match hint_lines(&module.directory, &exercise, HintLevel::Hints) {
+            Ok(lines) => self.push_state(AppState::Hints {
+                view: HintsView {
+                    selected_module,
+                    exercise,
+                    level: HintLevel::Hints,
+                    revealed: HintLevel::Hints,
+                    confirm: None,
+                    lines,
+                    scroll_offset: 0,
+                },
+            }),
+            Err(e) => self.show_message(format!("❌ {:#}", e)),
+        }
+    }
+
+    /// 分级提示按键处理
+    fn handle_hints_key(&mut self, key: KeyCode) {
+        let AppState::Hints { ref mut view } = self.state else {
+            return;
+        };
+
+        let target = match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.pop_state();
+                return;
+            }
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => view.level.next(),
+            KeyCode::Left => view.level.prev(),
+            KeyCode::Char('d') | KeyCode::Char('D') => Some(HintLevel::Diff),
+            KeyCode::Up => {
+                view.scroll_offset = view.scroll_offset.saturating_sub(1);
+                None
+            }
+            KeyCode::Down => {
+                if view.scroll_offset + 1 < view.lines.len() {
+                    view.scroll_offset += 1;
+                }
+                None
+            }
+            KeyCode::PageUp => {
+                view.scroll_offset = view.scroll_offset.saturating_sub(20);
+                None
+            }
+            KeyCode::PageDown => {
+                view.scroll_offset = (view.scroll_offset + 20).min(view.lines.len().saturating_sub(1));
+                None
+            }
+            _ => None,
+        };
+        if let Some(level) = target.filter(|level| *level != view.level) {
+            self.show_hint_level(level);
+        }
+    }
+
+    /// 切换到某一级提示；第一次解锁骨架和答案时记录，测试通过前查看答案先要求确认
+    fn show_hint_level(&mut self, level: HintLevel) {
+        let AppState::Hints { ref mut view } = self.state else {
+            return;
+        };
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(view.selected_module)) else {
+            return;
+        };
+
+        let unlocking = level > view.revealed;
+        if unlocking && level.reveals_solution() && view.confirm != Some(level) {
+            let already_counted = crate::hints::exercise_passed(&self.store, &module.id, &view.exercise)
+                .and_then(|passed| Ok(passed || crate::hints::viewed_before_pass(&self.store, &module.id, &view.exercise)?));
+            match already_counted {
+                Ok(true) => {}
+                Ok(false) => {
+                    view.confirm = Some(level);
+                    self.show_message(format!(
+                        "⚠️ 测试还没通过，查看{}会被记录，这个练习通过后按 {:.0}% 计入掌握程度。再按一次确认",
+                        level.label(), crate::hints::ASSISTED_CREDIT * 100.0,
+                    ));
+                    return;
+                }
+                Err(e) => {
+                    self.show_error(&e.into());
+                    return;
+                }
+            }
+        }
+
+        let lines = hint_lines(&module.directory, &view.exercise, level);
+        let lines = match lines {
+            Ok(lines) => lines,
+            Err(e) => {
+                view.confirm = None;
+                self.show_message(format!("❌ {:#}", e));
+                return;
+            }
+        };
+        if unlocking {
+            // 没有记录下来就不显示，避免读取失败时绕过记录看到答案
+            if let Err(e) = crate::hints::record_view(&self.store, module, &view.exercise, level) {
+                self.show_error(&e);
+                return;
+            }
+            view.revealed = level;
+        }
+        view.level = level;
+        view.confirm = None;
+        view.lines = lines;
+        view.scroll_offset = 0;
+    }
+
+    /// 打开模块的代码示例运行器
+    fn open_examples(&mut self, selected_module: usize) {
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(selected_module)) else {
+            return;
+        };
+
+        match crate::examples::list_examples(&module.directory) {
+            Ok(examples) if !examples.is_empty() => {
+                self.push_state(AppState::Examples {
+                    view: ExamplesView {
+                        selected_module,
+                        examples,
+                        selected: 0,
+                        output_for: None,
+                        output: Vec::new(),
+                        finished: None,
+                        scroll_bottom: 0,
+                        follow: true,
+                    },
+                });
+            }
+            Ok(_) => {
+                let msg = format!("❌ {} 没有代码示例", module.name);
+                self.show_message(msg);
+            }
+            Err(e) => self.show_message(format!("❌ {:#}", e)),
+        }
+    }
+
+    /// 代码示例运行器按键处理
+    fn handle_examples_key(&mut self, key: KeyCode) {
+        let AppState::Examples { ref mut view } = self.state else {
+            return;
+        };
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(view.selected_module)) else {
+            return;
+        };
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                // 离开运行器时结束还在运行的示例
+                if let Some(run) = self.example_run.take() {
+                    run.cancel();
                 }
-                KeyCode::Tab => {
-                    *focus_area = match focus_area {
-                        ModuleFocus::TaskList => ModuleFocus::Action,
-                        ModuleFocus::Action => ModuleFocus::TaskList,
-                    };
+                self.pop_state();
+            }
+            KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Down if view.selected + 1 < view.examples.len() => view.selected += 1,
+            KeyCode::Enter | KeyCode::Char('r') | KeyCode::Char('R') => {
+                if let Some(run) = &self.example_run {
+                    let msg = format!("⏳ {} 正在运行，按 C 取消", run.example);
+                    self.show_message(msg);
+                    return;
                 }
-                KeyCode::Up => {
-                    if let Some(repo) = &self.repo {
-                        match focus_area {
-                            ModuleFocus::TaskList => {
-                                if *selected_task > 0 {
-                                    *selected_task -= 1;
-                                }
-                            }
-                            ModuleFocus::Action => {
-                                if *selected_task > 0 {
-                                    *selected_task -= 1;
-                                }
-                            }
-                        }
+                let Some(example) = view.examples.get(view.selected) else {
+                    return;
+                };
+                match ExampleRun::start(&module.directory, &example.name, crate::examples::EXAMPLE_TIMEOUT) {
+                    Ok(run) => {
+                        view.output_for = Some(example.name.clone());
+                        view.output.clear();
+                        view.finished = None;
+                        view.follow = true;
+                        self.example_run = Some(run);
                     }
+                    Err(e) => self.show_message(format!("❌ {:#}", e)),
                 }
-                KeyCode::Down => {
-                    if let Some(repo) = &self.repo {
-                        match focus_area {
-                            ModuleFocus::TaskList => {
-                                if *selected_task < 4 {
-                                    *selected_task += 1;
-                                }
-                            }
-                            ModuleFocus::Action => {
-                                if *selected_task < 4 {
-                                    *selected_task += 1;
-                                }
-                            }
-                        }
-                    }
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') => match &self.example_run {
+                Some(run) => run.cancel(),
+                None => self.show_message("没有正在运行的示例".to_string()),
+            },
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                if let Some(example) = view.examples.get(view.selected) {
+                    let path = example.path.clone();
+                    let title = path.strip_prefix(&module.directory).unwrap_or(&path).display().to_string();
+                    self.open_file_viewer(path, title, None);
                 }
-                KeyCode::Char('o') | KeyCode::Char('O') => {
-                    // 在TUI内打开当前任务对应的文件
-                    if let Some(repo) = &self.repo {
-                        if let Some(module) = repo.modules.get(*selected_module) {
-                            let task_files = ["README.md", "examples", "exercises.md", "tests", "自检清单.md"];
-                            if let Some(file) = task_files.get(*selected_task) {
-                                let path = module.directory.join(file);
+            }
+            KeyCode::PageUp => {
+                let bottom = if view.follow { view.output.len() } else { view.scroll_bottom };
+                view.scroll_bottom = bottom.saturating_sub(10);
+                view.follow = false;
+            }
+            KeyCode::PageDown => {
+                view.scroll_bottom += 10;
+                view.follow = view.scroll_bottom >= view.output.len();
+            }
+            KeyCode::End => view.follow = true,
+            _ => {}
+        }
+    }
 
-                                // 保存当前状态以便返回
-                                let current_state = self.state.clone();
-
-                                // 尝试读取文件内容
-                                match std::fs::read_to_string(&path) {
-                                    Ok(content) => {
-                                        // 切换到文件查看器状态
-                                        self.state = AppState::FileViewer {
-                                            file_path: file.to_string(),
-                                            content,
-                                            scroll_offset: 0,
-                                            return_state: Box::new(current_state),
-                                        };
-                                        self.update_help_text();
-                                    }
-                                    Err(_) => {
-                                        // 如果是目录，显示目录内容
-                                        if path.is_dir() {
-                                            let mut dir_content = String::new();
-                                            dir_content.push_str(&format!("目录: {}\n\n", path.display()));
-
-                                            if let Ok(entries) = std::fs::read_dir(&path) {
-                                                for entry in entries.flatten() {
-                                                    let name = entry.file_name().to_string_lossy().to_string();
-                                                    let file_type = if entry.path().is_dir() { "[DIR]" } else { "[FILE]" };
-                                                    dir_content.push_str(&format!("{} {}\n", file_type, name));
-                                                }
-                                            }
-
-                                            self.state = AppState::FileViewer {
-                                                file_path: format!("{}/ (目录)", file),
-                                                content: dir_content,
-                                                scroll_offset: 0,
-                                                return_state: Box::new(current_state),
-                                            };
-                                            self.update_help_text();
-                                        } else {
-                                            self.show_message(format!("❌ 无法读取文件: {}", file));
-                                        }
-                                    }
+    /// 取出后台示例运行的新输出；运行结束时记录结果并同步 进度.md
+    ///
+    /// 只在运行器界面中取出，打开文件查看器时输出留在 channel 中
+    fn poll_example_run(&mut self) -> Result<()> {
+        let Some(run) = &self.example_run else {
+            return Ok(());
+        };
+        let AppState::Examples { ref mut view } = self.state else {
+            return Ok(());
+        };
+
+        let mut finished = None;
+        for event in run.poll() {
+            match event {
+                RunEvent::Output(stream, line) => view.output.push((stream, line)),
+                RunEvent::Finished { outcome, duration } => finished = Some((outcome, duration)),
+            }
+        }
+        let Some((outcome, duration)) = finished else {
+            return Ok(());
+        };
+
+        let example = run.example.clone();
+        self.example_run = None;
+        view.finished = Some((outcome.clone(), duration));
+        let selected_module = view.selected_module;
+        let Some(repo) = &self.repo else {
+            return Ok(());
+        };
+        let Some(module) = repo.modules.get(selected_module) else {
+            return Ok(());
+        };
+
+        let summary = crate::examples::record_run(&self.store, repo, module, &example, &outcome, duration)?;
+        let mut msg = format!("{} {}（{:.1} 秒）", outcome.describe(), example, duration.as_secs_f32());
+        if summary.is_complete() && outcome.success() {
+            msg.push_str("  🎉 本模块示例全部运行过，已标记「代码示例」");
+        }
+        self.reload_repo();
+        self.show_message(msg);
+        self.check_achievements();
+        Ok(())
+    }
+
+    /// 打开模块的自检清单
+    fn open_checklist(&mut self, selected_module: usize) {
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(selected_module)) else {
+            return;
+        };
+
+        match crate::checklist::load_checklist(module) {
+            Ok(Some(checklist)) if checklist.total() > 0 => {
+                self.push_state(AppState::Checklist { selected_module, selected_item: 0 });
+            }
+            Ok(_) => {
+                let msg = format!("❌ {} 没有自检清单", module.name);
+                self.show_message(msg);
+            }
+            Err(e) => self.show_message(format!("❌ {:#}", e)),
+        }
+    }
+
+    /// 自检清单按键处理
+    fn handle_checklist_key(&mut self, key: KeyCode) {
+        let AppState::Checklist { selected_module, ref mut selected_item } = self.state else {
+            return;
+        };
+        let Some(module) = self.repo.as_ref().and_then(|r| r.modules.get(selected_module)) else {
+            return;
+        };
+        let checklist = match crate::checklist::load_checklist(module) {
+            Ok(Some(checklist)) => checklist,
+            Ok(None) => return,
+            Err(e) => {
+                self.show_message(format!("❌ {:#}", e));
+                return;
+            }
+        };
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.pop_state(),
+            KeyCode::Up if *selected_item > 0 => *selected_item -= 1,
+            KeyCode::Down if *selected_item + 1 < checklist.total() => *selected_item += 1,
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                let Some(item) = checklist.items().nth(*selected_item) else {
+                    return;
+                };
+                // 写回文件，只修改该项所在的行
+                match crate::checklist::toggle_item(&checklist.path, item.line) {
+                    Ok(updated) => {
+                        if updated.is_passed() != checklist.is_passed() {
+                            // 自检状态变化时写回 进度.md 的「自检通过」
+                            let module_id = module.id.clone();
+                            if let Some(repo) = &self.repo {
+                                let synced = crate::sync::record_task(&self.store, repo, &module_id, crate::progress::TaskType::Checklist, updated.is_passed())
+                                    .and_then(|_| crate::sync::sync(&self.store, repo, None));
+                                if let Err(e) = synced {
+                                    self.show_message(format!("❌ 同步进度失败: {:#}", e));
+                                    return;
                                 }
                             }
+                            self.reload_repo();
                         }
-                    }
-                }
-                KeyCode::Char(' ') | KeyCode::Enter => {
-                    // 进入确认状态，而不是直接标记完成
-                    if let Some(repo) = &self.repo {
-                        if let Some(_module) = repo.modules.get(*selected_module) {
-                            // 提取当前状态值，避免借用冲突
-                            let module_idx = *selected_module;
-                            let task_idx = *selected_task;
-
-                            // 保存当前状态并切换到确认状态
-                            let current_state = self.state.clone();
-                            self.state = AppState::UpdateProgressConfirm {
-                                selected_module: module_idx,
-                                selected_task: task_idx,
-                                confirmed: false,
-                            };
-                            self.state_stack.push(current_state);
-                            self.update_help_text();
+                        if updated.is_passed() && !checklist.is_passed() {
+                            self.show_message("🎉 自检通过！".to_string());
+                            self.check_achievements();
+                        } else if !updated.is_passed() && checklist.is_passed() {
+                            self.show_message("⚠️ 勾选比例低于要求，自检不再通过".to_string());
                         }
                     }
+                    Err(e) => self.show_message(format!("❌ 保存失败: {:#}", e)),
                 }
-                _ => {}
             }
+            _ => {}
         }
     }
 
-    /// 使用 VSCode 打开文件或目录
-    fn open_in_vscode(&self, path: &std::path::Path) -> Result<()> {
-        let path_str = path.to_string_lossy();
-        std::process::Command::new("code")
-            .arg("-r")
-            .arg(&*path_str)
-            .spawn()
-            .map(|_| ())
-            .map_err(|e| anyhow::anyhow!("Failed to open VSCode: {}", e))
+    /// 同步冲突按键处理
+    fn handle_sync_conflicts_key(&mut self, key: KeyCode) {
+        let AppState::SyncConflicts { ref mut conflicts, ref mut selected } = self.state else {
+            return;
+        };
+
+        let side = match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.pop_state();
+                return;
+            }
+            KeyCode::Up => {
+                if *selected > 0 {
+                    *selected -= 1;
+                }
+                return;
+            }
+            KeyCode::Down => {
+                if *selected + 1 < conflicts.len() {
+                    *selected += 1;
+                }
+                return;
+            }
+            KeyCode::Char('f') | KeyCode::Char('F') => crate::sync::Side::File,
+            KeyCode::Char('d') | KeyCode::Char('D') => crate::sync::Side::Db,
+            _ => return,
+        };
+
+        let Some(conflict) = conflicts.get(*selected).cloned() else {
+            return;
+        };
+        let Some(repo) = &self.repo else {
+            return;
+        };
+        if let Err(e) = crate::sync::resolve_conflict(&self.store, repo, &conflict, side) {
+            self.show_message(format!("❌ 保存失败: {:#}", e));
+            return;
+        }
+
+        conflicts.remove(*selected);
+        *selected = (*selected).min(conflicts.len().saturating_sub(1));
+        let done = conflicts.is_empty();
+        self.reload_repo();
+        if done {
+            self.pop_state();
+            self.show_message("✅ 冲突已全部处理".to_string());
+        }
     }
 
     /// 更新进度按键处理
@@ -463,19 +1598,17 @@ impl App {
                         FocusArea::TaskList => FocusArea::ModuleList,
                     };
                 }
-                KeyCode::Up => {
-                    if let Some(repo) = &self.repo {
-                        match focus_area {
-                            FocusArea::ModuleList => {
-                                if *selected_module > 0 {
-                                    *selected_module -= 1;
-                                    *selected_task = 0;
-                                }
+                KeyCode::Up if self.repo.is_some() => {
+                    match focus_area {
+                        FocusArea::ModuleList => {
+                            if *selected_module > 0 {
+                                *selected_module -= 1;
+                                *selected_task = 0;
                             }
-                            FocusArea::TaskList => {
-                                if *selected_task > 0 {
-                                    *selected_task -= 1;
-                                }
+                        }
+                        FocusArea::TaskList => {
+                            if *selected_task > 0 {
+                                *selected_task -= 1;
                             }
                         }
                     }
@@ -538,11 +1671,21 @@ impl App {
                             if let Some(module) = repo.modules.get(*selected_module) {
                                 let task_names = ["concept", "examples", "exercises", "project", "checklist"];
                                 let task = task_names.get(*selected_task).unwrap_or(&"concept");
-                                let _ = crate::progress::update_task_status(repo, &module.id, task);
+                                let result = crate::progress::update_task_status(&self.store, repo, &module.id, task);
                                 let module_name = module.name.clone();
+                                let module_id = module.id.clone();
                                 let task_idx = *selected_task;
-                                self.show_message(format!("✅ 已更新 {} 的 {} 任务", module_name,
-                                    ["概念学习", "代码示例", "练习题", "综合练习", "自检"].get(task_idx).unwrap_or(&"")));
+                                if result.is_ok() {
+                                    self.reload_repo();
+                                }
+                                match result {
+                                    Ok(()) => {
+                                        self.show_message(format!("✅ 已更新 {} 的 {} 任务", module_name,
+                                            ["概念学习", "代码示例", "练习题", "综合练习", "自检"].get(task_idx).unwrap_or(&"")));
+                                        self.check_achievements();
+                                    }
+                                    Err(e) => self.show_message(format!("❌ {}，请运行 learning-companion check -m {}", e, module_id)),
+                                }
                             }
                         }
                     }
@@ -561,9 +1704,10 @@ impl App {
             match key {
                 KeyCode::Esc | KeyCode::Char('q') => {
                     // 返回仪表板
-                    if let Some(repo) = &self.repo {
+                    if self.repo.is_some() {
                         let module = *selected_module;
                         self.state = AppState::Dashboard { selected_module: module };
+                        self.refresh_due_reviews();
                     } else {
                         self.state = AppState::MainMenu;
                     }
@@ -575,18 +1719,16 @@ impl App {
                         PracticeField::Count => PracticeField::Module,
                     };
                 }
-                KeyCode::Up => {
-                    if let Some(repo) = &self.repo {
-                        match focus_field {
-                            PracticeField::Module => {
-                                if *selected_module > 0 {
-                                    *selected_module -= 1;
-                                }
+                KeyCode::Up if self.repo.is_some() => {
+                    match focus_field {
+                        PracticeField::Module => {
+                            if *selected_module > 0 {
+                                *selected_module -= 1;
                             }
-                            PracticeField::Count => {
-                                if *question_count < 20 {
-                                    *question_count += 1;
-                                }
+                        }
+                        PracticeField::Count => {
+                            if *question_count < 20 {
+                                *question_count += 1;
                             }
                         }
                     }
@@ -610,31 +1752,33 @@ impl App {
                 KeyCode::Enter => {
                     if let Some(ref repo) = self.repo {
                         if let Some(module) = repo.modules.get(*selected_module) {
-                            // 生成练习题
-                            let questions = match module.id.as_str() {
-                                "module-01-basics" | "01-基础入门" | "basics" => {
-                                    crate::exercise::generate_basics_questions(*question_count)
-                                }
-                                _ => {
-                                    self.show_message("❌ 暂不支持该模块的练习题".to_string());
+                            // 从模块题库抽题
+                            let questions = match crate::questions::pick_questions(module, *question_count) {
+                                Ok(questions) => questions,
+                                Err(e) => {
+                                    self.show_message(format!("❌ 题库加载失败: {:#}", e));
                                     return;
                                 }
                             };
 
                             if !questions.is_empty() {
                                 // 创建练习会话
+                                let answers = vec![None; questions.len()];
                                 let session = PracticeSession {
+                                    module_id: module.id.clone(),
+                                    question_modules: vec![module.id.clone(); questions.len()],
                                     questions,
                                     current_index: 0,
-                                    answers: vec![None; *question_count],
+                                    answers,
                                     show_result: false,
+                                    is_review: false,
                                 };
 
                                 // 切换到练习会话状态
                                 self.state = AppState::PracticeSession { session };
                                 self.update_help_text();
                             } else {
-                                self.show_message("❌ 没有可用的练习题".to_string());
+                                self.show_message(format!("❌ {} 还没有可用的练习题", module.name));
                             }
                         }
                     }
@@ -657,6 +1801,52 @@ impl App {
         }
     }
 
+    /// 班级名册按键处理
+    fn handle_roster_key(&mut self, key: KeyCode) {
+        let AppState::Roster { ref roster, ref mut scroll_offset } = self.state else {
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state = AppState::MainMenu;
+                self.state_stack.clear();
+                self.update_help_text();
+            }
+            KeyCode::Up => *scroll_offset = scroll_offset.saturating_sub(1),
+            KeyCode::Down if *scroll_offset + 1 < roster.lines().len() => *scroll_offset += 1,
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                let msg = match roster.write_csv(None) {
+                    Ok(path) => format!("📄 名册已导出到 {}", path.display()),
+                    Err(e) => format!("❌ 导出失败：{:#}", e),
+                };
+                self.show_message(msg);
+            }
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                self.state = AppState::MainMenu;
+                self.state_stack.clear();
+                if let Err(e) = self.open_roster() {
+                    self.show_error(&e);
+                } else {
+                    self.show_message("🔄 已重新读取数据包".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 错误界面按键处理
+    fn handle_error_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Enter | KeyCode::Esc => {
+                self.state = AppState::MainMenu;
+                self.state_stack.clear();
+                self.update_help_text();
+            }
+            KeyCode::Char('q') => self.should_quit = true,
+            _ => {}
+        }
+    }
+
     /// 提醒设置按键处理
     fn handle_remind_setup_key(&mut self, key: KeyCode) {
         if let AppState::RemindSetup { ref mut hour, ref mut minute, ref mut focus_field } = self.state {
@@ -686,10 +1876,14 @@ impl App {
                     }
                 }
                 KeyCode::Enter => {
-                    let _ = crate::notify::set_reminder(*hour, *minute);
                     let h = *hour;
                     let m = *minute;
-                    self.show_message(format!("⏰ 已设置提醒时间为 {:02}:{:02}", h, m));
+                    match crate::notify::set_reminder(&self.store, h, m) {
+                        Ok(()) if crate::daemon::running_pid(&self.store).is_none() => self.show_message(format!(
+                            "⏰ 已添加每天 {:02}:{:02} 的提醒，运行 learning-companion daemon start 启动提醒", h, m)),
+                        Ok(()) => self.show_message(format!("⏰ 已添加每天 {:02}:{:02} 的提醒", h, m)),
+                        Err(e) => self.show_message(format!("❌ 设置提醒失败：{}", e)),
+                    }
                     // 返回主菜单
                     self.state = AppState::MainMenu;
                     self.state_stack.clear();
@@ -710,8 +1904,26 @@ impl App {
                 self.update_help_text();
             }
             KeyCode::Enter => {
-                let _ = crate::storage::export_data();
-                self.show_message("📤 数据导出完成！".to_string());
+                match crate::storage::export_data(&self.store, crate::storage::ExportFormat::Json, None) {
+                    Ok(path) => self.show_message(format!("📤 数据已导出到 {}", path.display())),
+                    Err(e) => self.show_message(format!("❌ 导出失败：{}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 学习计时按键处理
+    fn handle_study_timer_key(&mut self, key: KeyCode) {
+        let AppState::StudyTimer { ref mut pomodoro } = self.state else {
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.pop_state(),
+            KeyCode::Char('p') | KeyCode::Char('P') if self.study.is_none() => *pomodoro = !*pomodoro,
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let pomodoro = *pomodoro;
+                self.toggle_study(pomodoro);
             }
             _ => {}
         }
@@ -719,10 +1931,31 @@ impl App {
 
     /// 练习会话按键处理
     fn handle_practice_session_key(&mut self, key: KeyCode) {
+        // 填空题：字符键用于输入答案
+        if let AppState::PracticeSession { ref mut session } = self.state {
+            if session.is_text_input() {
+                match key {
+                    KeyCode::Char(c) => {
+                        session.current_text_mut().push(c);
+                        return;
+                    }
+                    KeyCode::Backspace => {
+                        session.current_text_mut().pop();
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
         match key {
             KeyCode::Esc | KeyCode::Char('q') => {
-                // 退出练习会话，返回练习配置界面
-                if let AppState::PracticeSession { .. } = self.state {
+                // 退出练习会话，复习返回主菜单，练习返回练习配置界面
+                if let AppState::PracticeSession { ref session } = self.state {
+                    if session.is_review {
+                        self.pop_state();
+                        return;
+                    }
                     self.state = AppState::Practice {
                         selected_module: 0,
                         question_count: 5,
@@ -731,27 +1964,20 @@ impl App {
                     self.update_help_text();
                 }
             }
-            KeyCode::Char('1') | KeyCode::Char('2') | KeyCode::Char('3') | KeyCode::Char('4') | KeyCode::Char('5') |
-            KeyCode::Char('6') | KeyCode::Char('7') | KeyCode::Char('8') | KeyCode::Char('9') => {
+            KeyCode::Char(c @ '1'..='9') => {
                 // 选择答案
                 if let AppState::PracticeSession { ref mut session } = self.state {
-                    let answer_index = match key {
-                        KeyCode::Char('1') => 0,
-                        KeyCode::Char('2') => 1,
-                        KeyCode::Char('3') => 2,
-                        KeyCode::Char('4') => 3,
-                        KeyCode::Char('5') => 4,
-                        KeyCode::Char('6') => 5,
-                        KeyCode::Char('7') => 6,
-                        KeyCode::Char('8') => 7,
-                        KeyCode::Char('9') => 8,
-                        _ => return,
-                    };
+                    let answer_index = c as usize - '1' as usize;
                     if let Some(question) = session.questions.get(session.current_index) {
-                        if let Some(options) = &question.options {
-                            if answer_index < options.len() {
-                                session.answers[session.current_index] = Some(answer_index);
-                            }
+                        let option_count = question.options.as_ref().map_or(0, Vec::len);
+                        if answer_index < option_count {
+                            let answer = match question.question_type {
+                                QuestionType::TrueFalse | QuestionType::CompilesOrNot => {
+                                    Answer::Bool(answer_index == 0)
+                                }
+                                _ => Answer::Choice(answer_index),
+                            };
+                            session.answers[session.current_index] = Some(answer);
                         }
                     }
                 }
@@ -776,31 +2002,55 @@ impl App {
                 // 如果当前是最后一题，显示结果
                 if let AppState::PracticeSession { ref mut session } = self.state {
                     if session.current_index == session.questions.len() - 1 {
-                        // 计算得分并显示结果
-                        let correct_count = session.answers.iter()
-                            .zip(session.questions.iter())
-                            .filter(|(answer, question)| {
-                                answer.map(|a| a.to_string() == question.correct_answer).unwrap_or(false)
-                            })
-                            .count();
-
-                        let score = (correct_count as f32 / session.questions.len() as f32) * 100.0;
-                        let total = session.questions.len();
-                        let msg = format!(
-                            "✅ 练习完成！得分: {:.1}% ({}/{})",
-                            score, correct_count, total
+                        // 使用与命令行相同的判分逻辑
+                        let graded = crate::exercise::PracticeSession {
+                            module_id: session.module_id.clone(),
+                            questions: session.questions.clone(),
+                            answers: session.answers.clone(),
+                        };
+                        let correct_count = graded.correct_count();
+                        let score = graded.score();
+                        let total = graded.questions.len();
+
+                        let is_review = session.is_review;
+                        let mut msg = format!(
+                            "✅ {}完成！得分: {:.1}% ({}/{})",
+                            if is_review { "复习" } else { "练习" }, score, correct_count, total
                         );
 
-                        // 返回练习配置界面
-                        self.state = AppState::Practice {
-                            selected_module: 0,
-                            question_count: 5,
-                            focus_field: PracticeField::Module,
-                        };
-                        self.update_help_text();
+                        // 每道题都更新复习计划，复习本身不计入练习成绩
+                        let mut saved = crate::review::record_session(&self.store, &graded, &session.question_modules);
+                        if saved.is_ok() && !is_review {
+                            saved = self.store.record_practice_result(
+                                &graded.module_id,
+                                total as u32,
+                                correct_count as u32,
+                                score,
+                                graded.weak_topics(),
+                            ).map_err(Into::into);
+                            if let Ok(Some(study)) = crate::study::record_practice(&self.store, &graded.module_id) {
+                                self.study = Some(study);
+                            }
+                        }
+                        if let Err(e) = saved {
+                            msg.push_str(&format!("（保存失败: {}）", e));
+                        }
+
+                        if is_review {
+                            self.pop_state();
+                        } else {
+                            // 返回练习配置界面
+                            self.state = AppState::Practice {
+                                selected_module: 0,
+                                question_count: 5,
+                                focus_field: PracticeField::Module,
+                            };
+                            self.update_help_text();
+                        }
 
                         // 显示消息（在状态切换后）
                         self.show_message(msg);
+                        self.check_achievements();
                     } else {
                         session.current_index += 1;
                     }
@@ -812,38 +2062,57 @@ impl App {
 
     /// 文件查看器按键处理
     fn handle_file_viewer_key(&mut self, key: KeyCode) {
+        let AppState::FileViewer { ref mut view, ref return_state } = self.state else {
+            return;
+        };
+
         match key {
             KeyCode::Esc | KeyCode::Char('q') => {
-                // 退出文件查看器，返回之前的状态
-                if let AppState::FileViewer { return_state, .. } = &self.state.clone() {
-                    self.state = *return_state.clone();
-                    self.update_help_text();
-                }
+                // 退出文件查看器，记住阅读位置，返回之前的状态
+                let return_state = *return_state.clone();
+                let view = view.clone();
+                self.save_viewer_position(&view);
+                self.state = return_state;
+                self.update_help_text();
             }
-            KeyCode::Up => {
-                // 向上滚动
-                if let AppState::FileViewer { ref mut scroll_offset, .. } = self.state {
-                    *scroll_offset = scroll_offset.saturating_sub(1);
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                if view.rendered.toc.is_empty() {
+                    self.show_message("这个文件没有目录".to_string());
+                } else {
+                    view.show_toc = !view.show_toc;
+                    view.toc_focus = view.show_toc;
                 }
             }
-            KeyCode::Down => {
-                // 向下滚动
-                if let AppState::FileViewer { ref mut scroll_offset, ref content, .. } = self.state {
-                    let max_offset = content.lines().count().saturating_sub(20);
-                    *scroll_offset = (*scroll_offset + 1).min(max_offset);
+            KeyCode::Tab if view.show_toc => view.toc_focus = !view.toc_focus,
+            KeyCode::Up if view.toc_focus => view.toc_selected = view.toc_selected.saturating_sub(1),
+            KeyCode::Down if view.toc_focus => {
+                view.toc_selected = (view.toc_selected + 1).min(view.rendered.toc.len().saturating_sub(1));
+            }
+            KeyCode::Enter if view.toc_focus => {
+                // 跳到选中的章节
+                if let Some(entry) = view.rendered.toc.get(view.toc_selected) {
+                    let line = entry.line;
+                    view.scroll_to(line);
+                    view.toc_focus = false;
                 }
             }
-            KeyCode::PageUp => {
-                // 向上翻页
-                if let AppState::FileViewer { ref mut scroll_offset, .. } = self.state {
-                    *scroll_offset = scroll_offset.saturating_sub(20);
+            KeyCode::Up => view.scroll_to(view.scroll_offset.saturating_sub(1)),
+            KeyCode::Down => view.scroll_to(view.scroll_offset + 1),
+            KeyCode::PageUp => view.scroll_to(view.scroll_offset.saturating_sub(20)),
+            KeyCode::PageDown => view.scroll_to(view.scroll_offset + 20),
+            KeyCode::Home => view.scroll_to(0),
+            KeyCode::End => view.scroll_to(view.last_line()),
+            KeyCode::Char('[') => {
+                // 上一节：当前位置之前最近的标题
+                if let Some(entry) = view.rendered.toc.iter().rev().find(|e| e.line < view.scroll_offset) {
+                    let line = entry.line;
+                    view.scroll_to(line);
                 }
             }
-            KeyCode::PageDown => {
-                // 向下翻页
-                if let AppState::FileViewer { ref mut scroll_offset, ref content, .. } = self.state {
-                    let max_offset = content.lines().count().saturating_sub(20);
-                    *scroll_offset = (*scroll_offset + 20).min(max_offset);
+            KeyCode::Char(']') => {
+                if let Some(entry) = view.rendered.toc.iter().find(|e| e.line > view.scroll_offset) {
+                    let line = entry.line;
+                    view.scroll_to(line);
                 }
             }
             _ => {}
@@ -866,6 +2135,9 @@ impl App {
             self.message = None;
             self.message_deadline = None;
             self.update_help_text();
+            if matches!(self.state, AppState::Dashboard { .. }) {
+                self.refresh_due_reviews();
+            }
         }
     }
 
@@ -873,16 +2145,26 @@ impl App {
     fn update_help_text(&mut self) {
         self.help_text = match self.state {
             AppState::MainMenu => "↑↓ 移动 | Enter 确认 | q 退出".to_string(),
-            AppState::Dashboard { .. } => "↑↓ 选择模块 | O 打开详情 | Enter 更新 | P 练习 | Esc 返回".to_string(),
-            AppState::ModuleDetail { .. } => "↑↓ 选择任务 | O 打开文件 | Space 标记完成 | Tab 切换 | Esc 返回".to_string(),
+            AppState::Dashboard { .. } => "↑↓ 选择模块 | O 打开详情 | Enter 更新 | P 练习 | S 同步 | Esc 返回".to_string(),
+            AppState::ModuleDetail { .. } => "↑↓ 选择任务 | O 打开文件 | X 运行示例 | W 练习工作区 | C 自检清单 | Space 标记完成 | Tab 切换 | Esc 返回".to_string(),
+            AppState::Checklist { .. } => "↑↓ 选择 | Space 勾选/取消 | Esc 返回模块详情".to_string(),
             AppState::UpdateProgress { .. } => "↑↓ 选择 | Tab 切换 | Enter 确认 | Esc 返回模块详情".to_string(),
             AppState::UpdateProgressConfirm { .. } => "←→ 选择 | Enter 确认 | Esc 返回".to_string(),
             AppState::Practice { .. } => "↑↓ 选择 | Tab 切换 | Enter 开始 | Esc 返回仪表板".to_string(),
             AppState::Achievements => "Esc 返回主菜单".to_string(),
             AppState::RemindSetup { .. } => "↑↓ 调整时间 | Tab 切换 | Enter 确认 | Esc 返回".to_string(),
             AppState::Export => "Enter 导出 | Esc 返回".to_string(),
-            AppState::PracticeSession { .. } => "1-9 选择答案 | ←→ 切换题目 | Enter 下一题/完成 | Esc 退出".to_string(),
-            AppState::FileViewer { .. } => "↑↓ 滚动 | PgUp/PgDn 翻页 | Esc 退出".to_string(),
+            AppState::StudyTimer { .. } => "Enter 开始/结束计时 | P 切换番茄钟 | Esc 返回".to_string(),
+            AppState::SyncConflicts { .. } => "↑↓ 选择 | F 保留进度.md | D 保留数据库 | Esc 稍后处理".to_string(),
+            AppState::Roster { .. } => "↑↓ 滚动 | C 导出 CSV | R 重新读取 | Esc 返回".to_string(),
+            AppState::Search { editing: true, .. } => "输入关键词 | Enter 搜索 | ↓ 选择结果 | Esc 返回".to_string(),
+            AppState::Search { .. } => "↑↓ 选择 | Enter 打开到命中的行 | / 修改关键词 | Esc 返回".to_string(),
+            AppState::Hints { .. } => "→ 下一级提示 | ← 上一级 | D 对比答案 | ↑↓ 滚动 | Esc 返回工作区".to_string(),
+            AppState::Examples { .. } => "↑↓ 选择 | Enter 运行 | C 取消 | O 查看源码 | PgUp/PgDn 滚动 | End 跟随输出 | Esc 返回".to_string(),
+            AppState::Workspace { .. } => "↑↓ 选择/滚动 | E 编辑 | O 查看 | T 运行测试 | C 取消测试 | H 提示 | N/P 上/下一条诊断 | Tab 切换 | Esc 返回".to_string(),
+            AppState::PracticeSession { .. } => "1-9 选择答案/输入填空 | ←→ 切换题目 | Enter 下一题/完成 | Esc 退出".to_string(),
+            AppState::FileViewer { .. } => "↑↓ 滚动 | PgUp/PgDn 翻页 | [ ] 上/下一节 | T 目录 | Tab 切换 | Esc 退出".to_string(),
+            AppState::Error { .. } => "Enter 返回主菜单 | q 退出".to_string(),
         };
     }
 
@@ -892,19 +2174,60 @@ impl App {
     }
 }
 
+/// TUI 运行期间最近一次 panic 的信息（由 panic hook 记录，错误界面显示）
+static PANIC_MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+/// 终端处于原始模式和备用屏幕期间持有，drop 时恢复终端（包括出错提前返回时）
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<io::Stdout>>,
+}
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        if let Err(e) = execute!(stdout, EnterAlternateScreen, EnableMouseCapture) {
+            let _ = disable_raw_mode();
+            return Err(e.into());
+        }
+        let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
 /// 运行 TUI 应用
-pub fn run_tui(project_path: &str) -> Result<()> {
+pub fn run_tui(store: Store, project_path: &str) -> Result<()> {
     // 初始化终端
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let mut guard = TerminalGuard::enter()?;
+
+    // panic 时不直接打印到原始模式的终端，而是记录下来显示在错误界面
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|info| {
+        if let Ok(mut message) = PANIC_MESSAGE.lock() {
+            *message = Some(info.to_string());
+        }
+    }));
 
     // 创建应用
-    let mut app = App::new(project_path.to_string());
+    let mut app = App::new(project_path.to_string(), store);
+    app.recover_study();
 
-    // 主循环
+    let result = run_loop(&mut guard.terminal, &mut app);
+
+    std::panic::set_hook(default_hook);
+    result
+}
+
+/// 主循环，终端读写失败时返回错误
+fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<()> {
     loop {
         // 检查消息超时并自动清除
         if let Some(deadline) = app.message_deadline {
@@ -914,12 +2237,45 @@ pub fn run_tui(project_path: &str) -> Result<()> {
             }
         }
 
-        // 绘制界面
-        terminal.draw(|f| ui(f, &mut app))?;
+        app.guard(|app| {
+            app.tick();
+            app.poll_example_run()?;
+            app.poll_test_run()
+        });
+
+        // 绘制界面；绘制时读取数据库失败或 panic 也进入错误界面，下一帧绘制错误界面
+        let drawn = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut result = Ok(());
+            terminal.draw(|f| result = ui(f, app))?;
+            io::Result::Ok(result)
+        }));
+        match drawn {
+            Ok(result) => {
+                if let Err(e) = result? {
+                    app.show_error(&e);
+                    continue;
+                }
+            }
+            Err(_) => {
+                app.show_panic();
+                continue;
+            }
+        }
 
         // 检查是否应该退出
         if app.should_quit {
-            break;
+            return Ok(());
+        }
+
+        // 上一次按键留下的操作在提示绘制出来之后执行
+        if let Some(task) = app.deferred.take() {
+            match task {
+                Deferred::Edit { file, line } => {
+                    let status = suspend(terminal, || crate::editor::editor_command(&file, line).status())?;
+                    app.finish_edit(status);
+                }
+            }
+            continue;
         }
 
         // 读取事件（超时 100ms）
@@ -927,33 +2283,36 @@ pub fn run_tui(project_path: &str) -> Result<()> {
             if let Event::Key(key) = event::read()? {
                 // 只处理按键按下事件，忽略按键释放事件（Windows 会报告两种事件）
                 if key.kind == KeyEventKind::Press {
-                    app.handle_key(key.code)?;
+                    app.guard(|app| app.handle_key(key.code));
                 }
             }
         }
     }
+}
 
-    // 恢复终端
+/// 暂时离开原始模式和备用屏幕运行外部程序（如编辑器），结束后恢复终端并重绘整个屏幕
+fn suspend<T>(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, run: impl FnOnce() -> T) -> Result<T> {
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
     terminal.show_cursor()?;
 
-    Ok(())
+    let result = run();
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(result)
 }
 
-/// 绘制 UI
-fn ui(f: &mut Frame, app: &mut App) {
+/// 绘制 UI，读取数据库失败时返回错误
+fn ui(f: &mut Frame, app: &mut App) -> Result<()> {
     // 获取终端尺寸
     let size = f.size();
 
     // 检查最小尺寸
     if size.width < 80 || size.height < 24 {
         draw_size_warning(f, size);
-        return;
+        return Ok(());
     }
 
     // 创建布局
@@ -963,18 +2322,24 @@ fn ui(f: &mut Frame, app: &mut App) {
         .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)].as_ref())
         .split(size);
 
-    // 绘制标题
-    draw_header(f, chunks[0], &app.title);
+    // 绘制标题，计时中显示计时器
+    match &app.study {
+        Some(session) => draw_header(f, chunks[0], &format!("{}    {}", app.title, study_badge(session))),
+        None => draw_header(f, chunks[0], &app.title),
+    }
 
     // 绘制主内容区
     match &app.state {
         AppState::MainMenu => draw_main_menu(f, chunks[1], app),
-        AppState::Dashboard { .. } => draw_dashboard(f, chunks[1], app),
+        AppState::Dashboard { .. } => draw_dashboard(f, chunks[1], app)?,
         AppState::ModuleDetail { selected_module, selected_task, focus_area } => {
-            draw_module_detail(f, chunks[1], app, *selected_module, *selected_task, *focus_area);
+            draw_module_detail(f, chunks[1], app, *selected_module, *selected_task, *focus_area)?;
+        }
+        AppState::Checklist { selected_module, selected_item } => {
+            draw_checklist(f, chunks[1], app, *selected_module, *selected_item);
         }
         AppState::UpdateProgress { selected_module, selected_task, focus_area } => {
-            draw_update_progress(f, chunks[1], app, *selected_module, *selected_task, *focus_area);
+            draw_update_progress(f, chunks[1], app, *selected_module, *selected_task, *focus_area)?;
         }
         AppState::UpdateProgressConfirm { selected_module, selected_task, confirmed } => {
             draw_update_progress_confirm(f, chunks[1], app, *selected_module, *selected_task, *confirmed);
@@ -982,13 +2347,21 @@ fn ui(f: &mut Frame, app: &mut App) {
         AppState::Practice { selected_module, question_count, focus_field } => {
             draw_practice(f, chunks[1], app, *selected_module, *question_count, *focus_field);
         }
-        AppState::Achievements => draw_achievements(f, chunks[1], app),
+        AppState::Achievements => draw_achievements(f, chunks[1], app)?,
         AppState::RemindSetup { hour, minute, focus_field } => {
             draw_remind_setup(f, chunks[1], *hour, *minute, *focus_field);
         }
         AppState::Export => draw_export(f, chunks[1]),
+        AppState::StudyTimer { pomodoro } => draw_study_timer(f, chunks[1], app.study.as_ref(), *pomodoro),
+        AppState::SyncConflicts { conflicts, selected } => draw_sync_conflicts(f, chunks[1], conflicts, *selected),
+        AppState::Roster { roster, scroll_offset } => draw_roster(f, chunks[1], roster, *scroll_offset),
+        AppState::Search { query, results, selected, editing } => draw_search(f, chunks[1], query, results, *selected, *editing),
+        AppState::Workspace { view } => draw_workspace(f, chunks[1], app, view),
+        AppState::Examples { view } => draw_examples(f, chunks[1], app, view),
+        AppState::Hints { view } => draw_hints(f, chunks[1], app, view)?,
         AppState::PracticeSession { ref session } => draw_practice_session(f, chunks[1], session),
-        AppState::FileViewer { ref file_path, ref content, scroll_offset, .. } => draw_file_viewer(f, chunks[1], file_path, content, *scroll_offset),
+        AppState::FileViewer { ref view, .. } => draw_file_viewer(f, chunks[1], view),
+        AppState::Error { message, corrupt } => draw_error(f, chunks[1], message, *corrupt),
     }
 
     // 绘制消息（如果有）
@@ -999,6 +2372,7 @@ fn ui(f: &mut Frame, app: &mut App) {
         // 没有消息时显示帮助文本
         draw_footer(f, chunks[2], app.get_help_text());
     }
+    Ok(())
 }
 
 /// 绘制标题栏
@@ -1027,6 +2401,27 @@ fn draw_message(f: &mut Frame, area: Rect, message: &str) {
     f.render_widget(msg, area);
 }
 
+/// 绘制错误界面
+fn draw_error(f: &mut Frame, area: Rect, message: &str, corrupt: bool) {
+    let mut text = vec![
+        Line::from(Span::styled("❌ 操作失败", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(message.to_string()),
+        Line::from(""),
+    ];
+    if corrupt {
+        text.push(Line::from("数据库中有无法读取的记录，其余学习数据不受影响。"));
+        text.push(Line::from("退出后运行 learning-companion db doctor 检查，db doctor --fix 修复（修复前自动备份）"));
+        text.push(Line::from(""));
+    }
+    text.push(Line::from("按 Enter 返回主菜单，按 q 退出"));
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("错误").border_style(Style::default().fg(Color::Red)))
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
+
 /// 绘制尺寸警告
 fn draw_size_warning(f: &mut Frame, area: Rect) {
     let warning = Paragraph::new("终端窗口太小！\n请调整到至少 80x24 字符")
@@ -1068,7 +2463,7 @@ fn draw_main_menu(f: &mut Frame, area: Rect, app: &mut App) {
 }
 
 /// 绘制仪表板
-fn draw_dashboard(f: &mut Frame, area: Rect, app: &App) {
+fn draw_dashboard(f: &mut Frame, area: Rect, app: &App) -> Result<()> {
     if let Some(repo) = &app.repo {
         let completion = repo.completion_percentage();
         let completed = repo.progress.iter().filter(|p| p.status == "[x]").count();
@@ -1096,8 +2491,8 @@ fn draw_dashboard(f: &mut Frame, area: Rect, app: &App) {
             Line::from(""),
             Line::from(format!("总体完成度: {:.1}% ({}/{})", completion, completed, total)),
             Line::from(progress_bar),
-            Line::from(""),
-            Line::from("快捷键: ↑↓ 选择模块 | O 详情 | P 练习 | A 成就"),
+            Line::from(format!("🔁 今日待复习: {} 道题", app.due_reviews.map_or_else(|| "?".to_string(), |n| n.to_string()))),
+            Line::from("快捷键: ↑↓ 选择模块 | O 详情 | P 练习 | A 成就 | S 同步进度.md"),
         ];
 
         let stats = Paragraph::new(stats_lines)
@@ -1105,28 +2500,30 @@ fn draw_dashboard(f: &mut Frame, area: Rect, app: &App) {
             .wrap(Wrap { trim: true });
         f.render_widget(stats, chunks[0]);
 
-        // 模块列表 - 可选择
+        // 模块列表 - 可选择，先修模块未完成的显示为锁定
+        let graph = crate::path::Graph::new(repo).ok();
         let mut module_lines: Vec<Line> = Vec::new();
         for (i, module) in repo.modules.iter().enumerate() {
             let progress = repo.get_module_progress(&module.id);
-            let status_icon = if let Some(p) = progress {
-                match p.status.as_str() {
-                    "[x]" => "✅",
-                    "[~]" => "🟡",
-                    _ => "⬜",
-                }
-            } else {
-                "⬜"
+            let locked = graph.as_ref()
+                .is_some_and(|g| !crate::path::state_of(repo, g, &module.id).is_unlocked());
+            let status_icon = match progress.map(|p| p.status.as_str()) {
+                Some("[x]") => "✅",
+                Some("[~]") => "🟡",
+                _ if locked => "🔒",
+                _ => "⬜",
             };
 
+            // 练习题以测试结果为准，自检以清单勾选比例为准
+            let exercises_done = crate::checker::exercise_summary(&app.store, module)?.is_complete();
+            let checklist_done = crate::checklist::is_passed(module);
             let tasks_done = if let Some(p) = progress {
-                let count = [p.concept, p.examples, p.exercises, p.project, p.checklist]
+                [p.concept, p.examples, exercises_done, p.project, checklist_done]
                     .iter()
                     .filter(|&&x| x)
-                    .count();
-                count
+                    .count()
             } else {
-                0
+                usize::from(exercises_done) + usize::from(checklist_done)
             };
 
             let prefix = if i == selected_module { ">> " } else { "   " };
@@ -1157,13 +2554,16 @@ fn draw_dashboard(f: &mut Frame, area: Rect, app: &App) {
             .wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
     }
+    Ok(())
 }
 
 /// 绘制模块详情界面
-fn draw_module_detail(f: &mut Frame, area: Rect, app: &App, selected_module: usize, selected_task: usize, focus_area: ModuleFocus) {
+fn draw_module_detail(f: &mut Frame, area: Rect, app: &App, selected_module: usize, selected_task: usize, focus_area: ModuleFocus) -> Result<()> {
     if let Some(repo) = &app.repo {
         if let Some(module) = repo.modules.get(selected_module) {
             let progress = repo.get_module_progress(&module.id);
+            let exercises = crate::checker::exercise_summary(&app.store, module)?;
+            let checklist = crate::checklist::load_checklist(module).ok().flatten();
 
             // 创建布局：左侧任务列表，右侧文件信息
             let chunks = Layout::default()
@@ -1183,7 +2583,11 @@ fn draw_module_detail(f: &mut Frame, area: Rect, app: &App, selected_module: usi
 
             let mut task_lines: Vec<Line> = Vec::new();
             for (i, task_name) in task_names.iter().enumerate() {
-                let is_done = if let Some(p) = progress {
+                let is_done = if i == 2 {
+                    exercises.is_complete()
+                } else if i == 4 {
+                    checklist.as_ref().is_some_and(|c| c.is_passed())
+                } else if let Some(p) = progress {
                     task_getters[i](p)
                 } else {
                     false
@@ -1238,6 +2642,21 @@ fn draw_module_detail(f: &mut Frame, area: Rect, app: &App, selected_module: usi
                 ]));
             }
 
+            if exercises.checked > 0 {
+                file_info_lines.push(Line::from(""));
+                file_info_lines.push(Line::from(format!(
+                    "🧪 练习测试: {}/{} 通过 (已检查 {}/{})",
+                    exercises.passed, exercises.graded, exercises.checked, exercises.total
+                )));
+            }
+
+            if let Some(c) = &checklist {
+                file_info_lines.push(Line::from(format!(
+                    "📋 自检清单: {}/{} 已勾选 ({:.0}%，需要 {:.0}%)",
+                    c.checked_count(), c.total(), c.ratio() * 100.0, crate::checklist::PASS_RATIO * 100.0
+                )));
+            }
+
             // 操作提示
             let action_style = if focus_area == ModuleFocus::Action {
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
@@ -1249,7 +2668,7 @@ fn draw_module_detail(f: &mut Frame, area: Rect, app: &App, selected_module: usi
             file_info_lines.push(Line::from("---"));
             file_info_lines.push(Line::from("操作:"));
             file_info_lines.push(Line::from(vec![
-                Span::raw("  [O] 打开文件  "),
+                Span::raw("  [O] 打开文件  [X] 运行示例  [W] 练习工作区  [C] 自检清单  "),
                 Span::styled("[Space] 标记完成", action_style),
             ]));
 
@@ -1264,10 +2683,201 @@ fn draw_module_detail(f: &mut Frame, area: Rect, app: &App, selected_module: usi
             f.render_widget(file_info, chunks[1]);
         }
     }
+    Ok(())
+}
+
+/// 绘制自检清单界面
+fn draw_checklist(f: &mut Frame, area: Rect, app: &App, selected_module: usize, selected_item: usize) {
+    let Some(module) = app.repo.as_ref().and_then(|r| r.modules.get(selected_module)) else {
+        return;
+    };
+    let Ok(Some(checklist)) = crate::checklist::load_checklist(module) else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    // 顶部统计
+    let percent = (checklist.ratio() * 100.0) as u16;
+    let status = if checklist.is_passed() { "✅ 自检通过" } else { "⬜ 未通过" };
+    let stats_lines = vec![
+        Line::from(format!(
+            "📋 {} - 已勾选 {}/{} ({}%，需要 {:.0}%)  {}",
+            module.name, checklist.checked_count(), checklist.total(), percent,
+            crate::checklist::PASS_RATIO * 100.0, status
+        )),
+        Line::from(generate_progress_bar(percent, 30)),
+    ];
+    let stats = Paragraph::new(stats_lines)
+        .block(Block::default().borders(Borders::ALL).title("自检清单"))
+        .wrap(Wrap { trim: true });
+    f.render_widget(stats, chunks[0]);
+
+    // 清单项，按分组显示
+    let mut lines: Vec<Line> = Vec::new();
+    let mut selected_line = 0;
+    let mut index = 0;
+    for section in &checklist.sections {
+        if !section.title.is_empty() {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            let done = section.items.iter().filter(|i| i.checked).count();
+            lines.push(Line::from(Span::styled(
+                format!("## {} ({}/{})", section.title, done, section.items.len()),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            )));
+        }
+        for item in &section.items {
+            let selected = index == selected_item;
+            if selected {
+                selected_line = lines.len();
+            }
+            let prefix = if selected { ">> " } else { "   " };
+            let style = if selected {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else if item.checked {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(prefix, style),
+                Span::styled(format!("[{}] {}", if item.checked { 'x' } else { ' ' }, item.text), style),
+            ]));
+            index += 1;
+        }
+    }
+
+    // 让选中项保持在可见区域中部
+    let visible = chunks[1].height.saturating_sub(2) as usize;
+    let scroll = selected_line.saturating_sub(visible / 2) as u16;
+    let items = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("清单项 (Space 勾选)"))
+        .scroll((scroll, 0));
+    f.render_widget(items, chunks[1]);
+}
+
+/// 标题栏中的计时器（如「⏱ 00:12:34」或「🍅 18:20」）
+fn study_badge(session: &crate::db::ActiveSession) -> String {
+    let elapsed = (chrono::Local::now() - session.started_at).num_seconds();
+    match session.pomodoro_minutes {
+        Some(minutes) => {
+            let phase = crate::study::pomodoro_phase(elapsed, minutes);
+            let icon = if phase.focus { "🍅" } else { "☕" };
+            format!("{} {} ⏱ {}", icon, crate::study::format_elapsed(phase.remaining_secs), crate::study::format_elapsed(elapsed))
+        }
+        None => format!("⏱ {}", crate::study::format_elapsed(elapsed)),
+    }
+}
+
+/// 绘制学习计时界面
+fn draw_study_timer(f: &mut Frame, area: Rect, session: Option<&crate::db::ActiveSession>, pomodoro: bool) {
+    let mut lines = Vec::new();
+
+    match session {
+        Some(session) => {
+            let elapsed = (chrono::Local::now() - session.started_at).num_seconds();
+            lines.push(Line::from(Span::styled(
+                format!("⏱️  {}", crate::study::format_elapsed(elapsed)),
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            )));
+            lines.push(Line::from(format!("开始于 {}", session.started_at.format("%H:%M"))));
+            lines.push(Line::from(""));
+
+            if let Some(minutes) = session.pomodoro_minutes {
+                let phase = crate::study::pomodoro_phase(elapsed, minutes);
+                let (label, total, color) = if phase.focus {
+                    ("🍅 专注中", minutes as i64 * 60, Color::Red)
+                } else {
+                    ("☕ 休息中", crate::study::BREAK_MINUTES as i64 * 60, Color::Cyan)
+                };
+                let percent = ((total - phase.remaining_secs) * 100 / total) as u16;
+                lines.push(Line::from(Span::styled(
+                    format!("{}  剩余 {}  已完成 {} 个番茄", label, crate::study::format_elapsed(phase.remaining_secs), phase.completed),
+                    Style::default().fg(color),
+                )));
+                lines.push(Line::from(generate_progress_bar(percent, 30)));
+                lines.push(Line::from(""));
+            }
+
+            let modules = if session.modules_studied.is_empty() {
+                "（打开模块详情或练习后自动记录）".to_string()
+            } else {
+                session.modules_studied.join("、")
+            };
+            lines.push(Line::from(format!("📚 学习模块：{}", modules)));
+            lines.push(Line::from(format!("✏️  完成练习：{} 次", session.practice_count)));
+            lines.push(Line::from(""));
+            lines.push(Line::from("按 Enter 结束计时并记录"));
+        }
+        None => {
+            lines.push(Line::from("⏸️  当前没有进行中的学习计时"));
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!(
+                "番茄钟：{}（按 P 切换）",
+                if pomodoro {
+                    format!("开启，专注 {} 分钟 / 休息 {} 分钟", crate::study::DEFAULT_POMODORO_MINUTES, crate::study::BREAK_MINUTES)
+                } else {
+                    "关闭".to_string()
+                }
+            )));
+            lines.push(Line::from(""));
+            lines.push(Line::from("按 Enter 开始计时；退出程序后计时继续，下次启动时恢复"));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("⏱️ 学习计时"))
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+    f.render_widget(paragraph, area);
+}
+
+/// 绘制同步冲突界面
+fn draw_sync_conflicts(f: &mut Frame, area: Rect, conflicts: &[crate::sync::SyncConflict], selected: usize) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let intro = vec![
+        Line::from(format!("⚠️ 有 {} 项在 进度.md 和数据库中都被修改过，且结果不同", conflicts.len())),
+        Line::from("按 F 以 进度.md 为准，按 D 以数据库为准；Esc 暂不处理"),
+    ];
+    let intro = Paragraph::new(intro)
+        .block(Block::default().borders(Borders::ALL).title("🔄 进度同步冲突"))
+        .wrap(Wrap { trim: true });
+    f.render_widget(intro, chunks[0]);
+
+    let lines: Vec<Line> = conflicts.iter()
+        .enumerate()
+        .map(|(i, conflict)| {
+            let (prefix, style) = if i == selected {
+                (">> ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            } else {
+                ("   ", Style::default().fg(Color::White))
+            };
+            Line::from(vec![
+                Span::styled(prefix, style),
+                Span::styled(conflict.describe(), style),
+            ])
+        })
+        .collect();
+
+    let visible = chunks[1].height.saturating_sub(2) as usize;
+    let scroll = selected.saturating_sub(visible / 2) as u16;
+    let list = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("冲突项"))
+        .scroll((scroll, 0));
+    f.render_widget(list, chunks[1]);
 }
 
 /// 绘制更新进度界面
-fn draw_update_progress(f: &mut Frame, area: Rect, app: &App, selected_module: usize, selected_task: usize, focus_area: FocusArea) {
+fn draw_update_progress(f: &mut Frame, area: Rect, app: &App, selected_module: usize, selected_task: usize, focus_area: FocusArea) -> Result<()> {
     if let Some(repo) = &app.repo {
         // 创建水平布局
         let chunks = Layout::default()
@@ -1319,9 +2929,16 @@ fn draw_update_progress(f: &mut Frame, area: Rect, app: &App, selected_module: u
                 |p| p.checklist,
             ];
 
+            let exercises_done = crate::checker::exercise_summary(&app.store, module)?.is_complete();
+            let checklist_done = crate::checklist::is_passed(module);
+
             let mut task_lines: Vec<Line> = Vec::new();
             for (i, task_name) in task_names.iter().enumerate() {
-                let is_done = if let Some(p) = progress {
+                let is_done = if i == 2 {
+                    exercises_done
+                } else if i == 4 {
+                    checklist_done
+                } else if let Some(p) = progress {
                     task_getters[i](p)
                 } else {
                     false
@@ -1345,6 +2962,7 @@ fn draw_update_progress(f: &mut Frame, area: Rect, app: &App, selected_module: u
             f.render_widget(task_paragraph, chunks[1]);
         }
     }
+    Ok(())
 }
 
 /// 绘制练习界面
@@ -1408,28 +3026,26 @@ fn draw_practice(f: &mut Frame, area: Rect, app: &App, selected_module: usize, q
 }
 
 /// 绘制成就界面
-fn draw_achievements(f: &mut Frame, area: Rect, _app: &App) {
+fn draw_achievements(f: &mut Frame, area: Rect, app: &App) -> Result<()> {
     // 获取成就数据
-    let achievements = crate::db::get_all_achievements().unwrap_or_default();
+    let total_modules = app.repo.as_ref().map_or(0, |r| r.modules.len());
+    let statuses = crate::achievements::statuses(&app.store, total_modules)?;
 
     let mut text = vec![
         Line::from("🏆 成就系统"),
         Line::from(""),
     ];
 
-    if achievements.is_empty() {
-        text.push(Line::from("还没有解锁任何成就"));
-        text.push(Line::from(""));
-        text.push(Line::from("继续学习，解锁更多成就！"));
+    if statuses.is_empty() {
+        text.push(Line::from("没有可用的成就定义"));
     } else {
-        let unlocked_count = achievements.iter().filter(|a| a.unlocked).count();
-        text.push(Line::from(format!("已解锁: {}/{}", unlocked_count, achievements.len())));
-        text.push(Line::from(""));
+        let unlocked_count = statuses.iter().filter(|s| s.unlocked).count();
+        text.push(Line::from(format!("已解锁: {}/{}", unlocked_count, statuses.len())));
         text.push(Line::from(""));
 
-        for achievement in &achievements {
-            let icon = if achievement.unlocked { "🏆" } else { "🔒" };
-            let style = if achievement.unlocked {
+        for status in &statuses {
+            let icon = if status.unlocked { status.def.icon.as_str() } else { "🔒" };
+            let style = if status.unlocked {
                 Style::default().fg(Color::Yellow)
             } else {
                 Style::default().fg(Color::DarkGray)
@@ -1437,16 +3053,21 @@ fn draw_achievements(f: &mut Frame, area: Rect, _app: &App) {
 
             text.push(Line::from(vec![
                 Span::styled(format!("{} ", icon), style),
-                Span::styled(achievement.name.clone(), style),
+                Span::styled(status.def.name.clone(), style.add_modifier(Modifier::BOLD)),
+                Span::styled(format!("  {}", status.def.description), style),
             ]));
 
-            if achievement.unlocked {
-                text.push(Line::from(vec![
-                    Span::raw("   "),
-                    Span::styled(achievement.description.clone(), Style::default().fg(Color::Gray)),
-                ]));
-            }
-            text.push(Line::from(""));
+            let detail = if status.unlocked {
+                status.unlocked_at
+                    .map(|t| format!("解锁于 {}", t.format("%Y-%m-%d %H:%M")))
+                    .unwrap_or_else(|| "已解锁".to_string())
+            } else {
+                format!("进度 {}", status.progress.display())
+            };
+            text.push(Line::from(vec![
+                Span::raw("   "),
+                Span::styled(detail, Style::default().fg(Color::Gray)),
+            ]));
         }
     }
 
@@ -1455,6 +3076,427 @@ fn draw_achievements(f: &mut Frame, area: Rect, _app: &App) {
         .wrap(Wrap { trim: true });
 
     f.render_widget(paragraph, area);
+    Ok(())
+}
+
+/// 把行中与查询匹配的片段标成黄色
+fn highlight_matches(text: &str, query: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut from = 0;
+    for (start, end) in crate::search::match_ranges(text, query) {
+        if start > from {
+            spans.push(Span::styled(text[from..start].to_string(), base));
+        }
+        spans.push(Span::styled(text[start..end].to_string(), base.fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        from = end;
+    }
+    if from < text.len() {
+        spans.push(Span::styled(text[from..].to_string(), base));
+    }
+    spans
+}
+
+/// 绘制搜索界面：输入框和带上下文的结果列表
+fn draw_search(f: &mut Frame, area: Rect, query: &str, results: &[crate::search::SearchHit], selected: usize, editing: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let input_style = if editing { Style::default().fg(Color::Yellow) } else { Style::default() };
+    let cursor = if editing { "▏" } else { "" };
+    let input = Paragraph::new(Line::from(format!("🔍 {}{}", query, cursor)))
+        .block(Block::default().borders(Borders::ALL).title("搜索讲解、示例、练习和答案").border_style(input_style));
+    f.render_widget(input, chunks[0]);
+
+    if results.is_empty() {
+        let hint = Paragraph::new(vec![
+            Line::from(""),
+            Line::from("输入概念、类型或符号后按 Enter，例如 Rc<RefCell<T>>、生命周期、?"),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("结果"))
+        .alignment(Alignment::Center);
+        f.render_widget(hint, chunks[1]);
+        return;
+    }
+
+    // 每条结果占标题、上下文和一个空行，保持选中的结果可见
+    let per_hit = crate::search::CONTEXT_LINES * 2 + 3;
+    let visible = (chunks[1].height.saturating_sub(2) as usize / per_hit).max(1);
+    let first = selected.saturating_sub(visible - 1);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, hit) in results.iter().enumerate().skip(first).take(visible) {
+        let mut title_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+        if i == selected && !editing {
+            title_style = title_style.add_modifier(Modifier::REVERSED);
+        }
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:>2}. {}:{}", i + 1, hit.display, hit.line + 1), title_style),
+            Span::styled(format!("  [{}]", hit.kind.label()), Style::default().fg(Color::DarkGray)),
+        ]));
+        for (line, text) in &hit.context {
+            let base = if *line == hit.line { Style::default() } else { Style::default().fg(Color::DarkGray) };
+            let mut spans = vec![Span::styled(format!("    {:>4} │ ", line + 1), Style::default().fg(Color::DarkGray))];
+            spans.extend(highlight_matches(text, query, base));
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(""));
+    }
+
+    let list = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("结果（{} 条）", results.len())));
+    f.render_widget(list, chunks[1]);
+}
+
+/// 绘制班级名册
+fn draw_roster(f: &mut Frame, area: Rect, roster: &crate::classroom::Roster, scroll_offset: usize) {
+    let text: Vec<Line> = roster.lines().into_iter()
+        .skip(scroll_offset)
+        .map(|line| {
+            let style = if line.starts_with("❌") || line.starts_with("⚠️") {
+                Style::default().fg(Color::Red)
+            } else if !line.starts_with(' ') && !line.is_empty() {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(line, style))
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(format!("班级名册（{} 位学员）", roster.learners.len())))
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+/// 输出窗格中一行的颜色：错误和失败为红色，警告为黄色，位置和提示为青色，通过为绿色
+fn output_line_style(line: &str) -> Style {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("error") || line.contains("panicked at") || line.ends_with("FAILED") || trimmed.starts_with("test result: FAILED") {
+        Style::default().fg(Color::Red)
+    } else if trimmed.starts_with("warning") {
+        Style::default().fg(Color::Yellow)
+    } else if trimmed.starts_with("-->") || trimmed.starts_with("= note") || trimmed.starts_with("= help") || trimmed.starts_with("help:") || trimmed.starts_with("note:") {
+        Style::default().fg(Color::Cyan)
+    } else if line.ends_with("... ok") || trimmed.starts_with("test result: ok") {
+        Style::default().fg(Color::Green)
+    } else {
+        Style::default()
+    }
+}
+
+/// 绘制练习工作区：左侧练习列表，右侧诊断列表和测试输出
+fn draw_workspace(f: &mut Frame, area: Rect, app: &App, view: &WorkspaceView) {
+    let Some(module) = app.repo.as_ref().and_then(|r| r.modules.get(view.selected_module)) else {
+        return;
+    };
+    let results = app.store.get_exercise_results(&module.id).unwrap_or_default();
+    let views = app.store.get_solution_views(&module.id).unwrap_or_default();
+    let focused = |focus: WorkspaceFocus| if view.focus == focus {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .split(area);
+
+    // 练习列表
+    let items: Vec<Line> = view.exercises.iter().enumerate()
+        .map(|(i, name)| {
+            let running = app.test_run.as_ref()
+                .filter(|run| run.module_id == module.id && &run.exercise == name);
+            let status = match (running, results.iter().find(|r| &r.exercise == name)) {
+                (Some(run), _) => format!("🔧 测试中 {} 秒", run.started_at.elapsed().as_secs()),
+                (None, Some(r)) if r.passed() => "✅".to_string(),
+                (None, Some(r)) if !r.compiled => "❌ 编译失败".to_string(),
+                (None, Some(r)) if r.tests_total > 0 => format!("❌ {}/{}", r.tests_passed, r.tests_total),
+                (None, Some(_)) => "⚪".to_string(),
+                (None, None) => "⬜".to_string(),
+            };
+            // 测试通过前看过答案
+            let assisted = views.iter().any(|v| &v.exercise == name && v.before_pass
+                && HintLevel::from_key(&v.level).is_some_and(HintLevel::reveals_solution));
+            let style = if i == view.selected {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let marker = if i == view.selected { "▶ " } else { "  " };
+            let assisted = if assisted { " 👀" } else { "" };
+            Line::from(Span::styled(format!("{}{} {}{}", marker, name, status, assisted), style))
+        })
+        .collect();
+    let list = Paragraph::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!("{} 的练习", module.name)).border_style(focused(WorkspaceFocus::Exercises)));
+    f.render_widget(list, columns[0]);
+
+    let Some(check) = &view.check else {
+        let hint = Paragraph::new(vec![
+            Line::from(""),
+            Line::from("E 用 $EDITOR 打开练习文件，编辑后按 T 运行测试"),
+            Line::from(""),
+            Line::from("卡住时按 H 查看提示，再逐级查看骨架和答案"),
+            Line::from(""),
+            Line::from("编译错误、警告和测试失败会列在这里，Tab 切换到输出后 Enter 跳到出错的行"),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("诊断"))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        f.render_widget(hint, columns[1]);
+        return;
+    };
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(view.diagnostics.len().clamp(1, 8) as u16 + 2), Constraint::Min(0)].as_ref())
+        .split(columns[1]);
+
+    // 诊断列表（显示选中项附近的几条）
+    let skip = view.selected_diagnostic.saturating_sub(7);
+    let diagnostic_lines: Vec<Line> = if view.diagnostics.is_empty() {
+        vec![Line::from(Span::styled("没有编译错误或测试失败", Style::default().fg(Color::Green)))]
+    } else {
+        view.diagnostics.iter().enumerate().skip(skip).take(8)
+            .map(|(i, d)| {
+                let color = match d.level {
+                    DiagnosticLevel::Warning => Color::Yellow,
+                    DiagnosticLevel::Error | DiagnosticLevel::Panic => Color::Red,
+                };
+                let mut style = Style::default().fg(color);
+                if i == view.selected_diagnostic {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let file = d.location.file.strip_prefix(&module.directory).unwrap_or(&d.location.file);
+                let code = d.code.as_deref().map(|c| format!("[{}] ", c)).unwrap_or_default();
+                Line::from(vec![
+                    Span::styled(format!("{} {}:{} ", d.level.icon(), file.display(), d.location.line), style),
+                    Span::raw(format!("{}{}", code, d.message)),
+                ])
+            })
+            .collect()
+    };
+    let errors = view.diagnostics.iter().filter(|d| d.level != DiagnosticLevel::Warning).count();
+    let title = format!("诊断（{} 个错误，{} 个警告）", errors, view.diagnostics.len() - errors);
+    f.render_widget(Paragraph::new(diagnostic_lines).block(Block::default().borders(Borders::ALL).title(title)), rows[0]);
+
+    // 测试输出
+    let output: Vec<Line> = check.output.lines()
+        .skip(view.scroll_offset)
+        .map(|line| Line::from(Span::styled(line.to_string(), output_line_style(line))))
+        .collect();
+    let result = if !check.compiled {
+        "编译失败".to_string()
+    } else {
+        format!("通过 {}/{}", check.passed_count(), check.tests.len())
+    };
+    let output = Paragraph::new(output)
+        .block(Block::default().borders(Borders::ALL)
+            .title(format!("输出：{}（{}，第 {} 行）", check.exercise, result, view.scroll_offset + 1))
+            .border_style(focused(WorkspaceFocus::Output)));
+    f.render_widget(output, rows[1]);
+}
+
+/// 某一级提示的内容
+fn hint_lines(module_dir: &std::path::Path, exercise: &str, level: HintLevel) -> Result<Vec<Line<'static>>> {
+    let exercise_file = module_dir.join("exercises").join(format!("{}.rs", exercise));
+    let solution_file = crate::hints::solution_file(module_dir, exercise);
+    let read = |path: &std::path::Path| std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("无法读取 {}：{}", path.display(), e));
+
+    let lines = match level {
+        HintLevel::Hints => {
+            let hints = crate::hints::extract_hints(&read(&exercise_file)?);
+            if hints.is_empty() {
+                return Ok(vec![
+                    Line::from("这个练习的注释中没有提示"),
+                    Line::from(""),
+                    Line::from(Span::styled("按 → 查看答案的骨架（类型和函数签名）", Style::default().fg(Color::DarkGray))),
+                ]);
+            }
+            let mut lines = Vec::new();
+            for hint in hints {
+                lines.push(Line::from(Span::styled(format!("💡 {}", hint.title), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+                lines.extend(hint.lines.into_iter().map(|line| Line::from(format!("   {}", line))));
+                lines.push(Line::from(""));
+            }
+            lines
+        }
+        HintLevel::Skeleton => crate::render::render_rust(&crate::hints::skeleton(&read(&solution_file)?)).lines,
+        HintLevel::Solution => crate::render::render_rust(&read(&solution_file)?).lines,
+        HintLevel::Diff => {
+            let diff = crate::hints::diff_lines(&read(&exercise_file)?, &read(&solution_file)?);
+            let rows = crate::hints::collapse(&diff, 3);
+            if rows.iter().all(|row| matches!(row, DiffRow::Skipped(_))) {
+                return Ok(vec![Line::from(Span::styled("你的代码与答案一致", Style::default().fg(Color::Green)))]);
+            }
+            let number = |n: Option<usize>| n.map_or("    ".to_string(), |n| format!("{:>4}", n));
+            rows.into_iter()
+                .map(|row| match row {
+                    DiffRow::Skipped(count) => Line::from(Span::styled(
+                        format!("          ⋯ {} 行相同", count),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                    DiffRow::Line(line) => {
+                        let (sign, style) = match line.op {
+                            DiffOp::Same => (' ', Style::default()),
+                            DiffOp::Removed => ('-', Style::default().fg(Color::Red)),
+                            DiffOp::Added => ('+', Style::default().fg(Color::Green)),
+                        };
+                        Line::from(vec![
+                            Span::styled(format!("{} {} ", number(line.old_line), number(line.new_line)), Style::default().fg(Color::DarkGray)),
+                            Span::styled(format!("{} {}", sign, line.text), style),
+                        ])
+                    }
+                })
+                .collect()
+        }
+    };
+    Ok(lines)
+}
+
+/// 绘制分级提示：上方是各级提示的进度和查看记录说明，下方是内容
+fn draw_hints(f: &mut Frame, area: Rect, app: &App, view: &HintsView) -> Result<()> {
+    let Some(module) = app.repo.as_ref().and_then(|r| r.modules.get(view.selected_module)) else {
+        return Ok(());
+    };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let mut steps = Vec::new();
+    for (i, level) in HintLevel::ALL.into_iter().enumerate() {
+        if i > 0 {
+            steps.push(Span::styled(" › ", Style::default().fg(Color::DarkGray)));
+        }
+        let (text, style) = if level == view.level {
+            (level.label().to_string(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD | Modifier::REVERSED))
+        } else if level <= view.revealed {
+            (level.label().to_string(), Style::default())
+        } else {
+            (format!("🔒 {}", level.label()), Style::default().fg(Color::DarkGray))
+        };
+        steps.push(Span::styled(format!(" {} ", text), style));
+    }
+    let status = if crate::hints::exercise_passed(&app.store, &module.id, &view.exercise)? {
+        Span::styled("✅ 测试已通过，查看答案不影响掌握程度", Style::default().fg(Color::Green))
+    } else if crate::hints::viewed_before_pass(&app.store, &module.id, &view.exercise)? {
+        Span::styled(
+            format!("👀 测试通过前已看过答案，通过后按 {:.0}% 计入掌握程度", crate::hints::ASSISTED_CREDIT * 100.0),
+            Style::default().fg(Color::Yellow),
+        )
+    } else {
+        Span::styled("测试通过前查看答案或对比会被记录，并降低这个练习在掌握程度中的分数", Style::default().fg(Color::DarkGray))
+    };
+    let header = Paragraph::new(vec![Line::from(steps), Line::from(status)])
+        .block(Block::default().borders(Borders::ALL).title(format!("{} 的提示", view.exercise)));
+    f.render_widget(header, rows[0]);
+
+    let title = match view.level {
+        HintLevel::Hints => "练习注释中的提示".to_string(),
+        HintLevel::Skeleton => "答案骨架（函数体待实现）".to_string(),
+        HintLevel::Solution => format!("exercises/solutions/{}.rs", view.exercise),
+        HintLevel::Diff => "对比：- 你的代码  + 答案".to_string(),
+    };
+    let content = Paragraph::new(view.lines.iter().skip(view.scroll_offset).cloned().collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(content, rows[1]);
+    Ok(())
+}
+
+/// cargo 自己的进度信息（写在 stderr 中，与示例的输出区分开）
+fn is_cargo_status(line: &str) -> bool {
+    const STATUS: [&str; 9] = ["Compiling", "Finished", "Running", "Blocking", "Updating", "Locking", "Downloading", "Downloaded", "Checking"];
+    line.split_whitespace().next().is_some_and(|word| STATUS.contains(&word))
+}
+
+/// 绘制代码示例运行器：左侧示例列表，右侧运行输出
+fn draw_examples(f: &mut Frame, area: Rect, app: &App, view: &ExamplesView) {
+    let Some(module) = app.repo.as_ref().and_then(|r| r.modules.get(view.selected_module)) else {
+        return;
+    };
+    let runs = app.store.get_example_runs(&module.id).unwrap_or_default();
+    let running = app.example_run.as_ref();
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .split(area);
+
+    // 示例列表
+    let items: Vec<Line> = view.examples.iter().enumerate()
+        .map(|(i, example)| {
+            let status = match runs.iter().find(|r| r.example == example.name) {
+                _ if running.is_some_and(|run| run.example == example.name) => "⏳".to_string(),
+                Some(r) if r.last_success => format!("✅ ×{}", r.runs),
+                Some(r) => format!("❌ ×{}", r.runs),
+                None => "⬜".to_string(),
+            };
+            let style = if i == view.selected {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let marker = if i == view.selected { "▶ " } else { "  " };
+            Line::from(Span::styled(format!("{}{} {}", marker, example.name, status), style))
+        })
+        .collect();
+    let succeeded = view.examples.iter()
+        .filter(|e| runs.iter().any(|r| r.example == e.name && r.successes > 0))
+        .count();
+    let list = Paragraph::new(items)
+        .block(Block::default().borders(Borders::ALL)
+            .title(format!("{} 的示例（{}/{} 已运行）", module.name, succeeded, view.examples.len())));
+    f.render_widget(list, columns[0]);
+
+    let Some(example) = &view.output_for else {
+        let hint = Paragraph::new(vec![
+            Line::from(""),
+            Line::from("Enter 编译并运行选中的示例，输出会实时显示在这里"),
+            Line::from(""),
+            Line::from(format!("运行中按 C 取消，超过 {} 秒自动结束", crate::examples::EXAMPLE_TIMEOUT.as_secs())),
+            Line::from(""),
+            Line::from("所有示例都成功运行过后，进度.md 中的「代码示例」会自动勾选"),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("输出"))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        f.render_widget(hint, columns[1]);
+        return;
+    };
+
+    // 运行输出（scroll_bottom 之前能放下的最后几行）
+    let height = columns[1].height.saturating_sub(2) as usize;
+    let bottom = if view.follow { view.output.len() } else { view.scroll_bottom.min(view.output.len()) };
+    let start = bottom.saturating_sub(height);
+    let output: Vec<Line> = view.output.iter().skip(start).take(height)
+        .map(|(stream, line)| {
+            let style = match stream {
+                OutputStream::Stdout => Style::default(),
+                OutputStream::Stderr if is_cargo_status(line) => Style::default().fg(Color::DarkGray),
+                OutputStream::Stderr => match output_line_style(line) {
+                    style if style == Style::default() => Style::default().fg(Color::Yellow),
+                    style => style,
+                },
+            };
+            Line::from(Span::styled(line.clone(), style))
+        })
+        .collect();
+    let status = match (&view.finished, running) {
+        (Some((outcome, duration)), _) => format!("{}，{:.1} 秒", outcome.describe(), duration.as_secs_f32()),
+        (None, Some(run)) => format!("⏳ 编译运行中 {:.1} 秒", run.started_at.elapsed().as_secs_f32()),
+        (None, None) => "⏹ 已取消".to_string(),
+    };
+    let follow = if view.follow { "" } else { "，End 跟随输出" };
+    let output = Paragraph::new(output)
+        .block(Block::default().borders(Borders::ALL)
+            .title(format!("输出：{}（{}{}）", example, status, follow)));
+    f.render_widget(output, columns[1]);
 }
 
 /// 绘制提醒设置界面
@@ -1503,9 +3545,9 @@ fn draw_export(f: &mut Frame, area: Rect) {
     let text = vec![
         Line::from("📤 导出学习数据"),
         Line::from(""),
-        Line::from("将导出所有学习记录到文件"),
+        Line::from("按 Enter 将所有学习记录导出为当前目录下的 JSON 文件"),
         Line::from(""),
-        Line::from("(功能开发中...)"),
+        Line::from("在新电脑上用 learning-companion import <文件> 导入"),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -1534,20 +3576,31 @@ fn draw_practice_session(f: &mut Frame, area: Rect, session: &PracticeSession) {
         session.questions[session.current_index].topic
     );
 
+    let (heading, block_title) = if session.is_review {
+        ("🔁 今日复习", "复习")
+    } else {
+        ("✏️  练习测试", "练习")
+    };
     let title_lines = vec![
-        Line::from("✏️  练习测试"),
+        Line::from(heading),
         Line::from(""),
         Line::from(progress),
     ];
 
     let title = Paragraph::new(title_lines)
-        .block(Block::default().borders(Borders::ALL).title("练习"))
+        .block(Block::default().borders(Borders::ALL).title(block_title))
         .alignment(Alignment::Center);
     f.render_widget(title, chunks[0]);
 
     // 题目内容区域
     let question = &session.questions[session.current_index];
-    let current_answer = session.answers[session.current_index];
+    let current_answer = session.answers[session.current_index].as_ref();
+    // 当前选中的选项序号（判断题 1 为正确、2 为错误）
+    let selected_option = match current_answer {
+        Some(Answer::Choice(i)) => Some(*i),
+        Some(Answer::Bool(b)) => Some(if *b { 0 } else { 1 }),
+        _ => None,
+    };
 
     let mut content_lines = vec![
         Line::from(""),
@@ -1555,13 +3608,36 @@ fn draw_practice_session(f: &mut Frame, area: Rect, session: &PracticeSession) {
         Line::from(""),
     ];
 
+    // 显示代码片段
+    if let Some(code) = &question.code {
+        for line in code.lines() {
+            content_lines.push(Line::from(Span::styled(
+                format!("   {}", line),
+                Style::default().fg(Color::Cyan),
+            )));
+        }
+        content_lines.push(Line::from(""));
+    }
+
+    // 填空题、代码输出题显示输入框
+    if is_text_question(&question.question_type) {
+        let text = match current_answer {
+            Some(Answer::Text(t)) => t.as_str(),
+            _ => "",
+        };
+        content_lines.push(Line::from(vec![
+            Span::raw("   答案: "),
+            Span::styled(format!("{}_", text), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        ]));
+    }
+
     // 显示选项
     if let Some(options) = &question.options {
         for (i, opt) in options.iter().enumerate() {
-            let prefix = if current_answer == Some(i) { ">> " } else { "   " };
+            let prefix = if selected_option == Some(i) { ">> " } else { "   " };
             let number = format!("{}. ", i + 1);
 
-            let style = if current_answer == Some(i) {
+            let style = if selected_option == Some(i) {
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
             } else {
                 Style::default()
@@ -1575,22 +3651,28 @@ fn draw_practice_session(f: &mut Frame, area: Rect, session: &PracticeSession) {
         }
     }
 
-    // 添加解析（如果已回答）
-    if current_answer.is_some() {
+    // 添加解析（如果已回答；填空题输入过程中不提示）
+    if selected_option.is_some() {
         content_lines.push(Line::from(""));
         content_lines.push(Line::from(format!("💡 解析: {}", question.explanation)));
     }
 
+    // 不裁剪行首空白，保留代码缩进
     let content = Paragraph::new(content_lines)
         .block(Block::default().borders(Borders::ALL).title("题目"))
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
     f.render_widget(content, chunks[1]);
 
     // 操作提示
+    let input_hint = if is_text_question(&question.question_type) {
+        "输入答案"
+    } else {
+        "1-9 选择答案"
+    };
     let help_text = if session.current_index == session.questions.len() - 1 {
-        "1-9 选择答案 | ←→ 切换题目 | Enter 完成练习 | Esc 退出"
+        format!("{} | ←→ 切换题目 | Enter 完成练习 | Esc 退出", input_hint)
     } else {
-        "1-9 选择答案 | ←→ 切换题目 | Enter 下一题 | Esc 退出"
+        format!("{} | ←→ 切换题目 | Enter 下一题 | Esc 退出", input_hint)
     };
 
     let help = Paragraph::new(help_text)
@@ -1601,7 +3683,7 @@ fn draw_practice_session(f: &mut Frame, area: Rect, session: &PracticeSession) {
 }
 
 /// 绘制文件查看器界面
-fn draw_file_viewer(f: &mut Frame, area: Rect, file_path: &str, content: &str, scroll_offset: usize) {
+fn draw_file_viewer(f: &mut Frame, area: Rect, view: &FileView) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -1612,35 +3694,72 @@ fn draw_file_viewer(f: &mut Frame, area: Rect, file_path: &str, content: &str, s
         .split(area);
 
     // 文件信息
-    let info_lines = vec![
-        Line::from("📄 文件查看器"),
-        Line::from(""),
-        Line::from(format!("文件: {}", file_path)),
-    ];
-
-    let info = Paragraph::new(info_lines)
+    let section = view.rendered.section_at(view.scroll_offset)
+        .and_then(|i| view.rendered.toc.get(i))
+        .map(|entry| format!("  §  {}", entry.title))
+        .unwrap_or_default();
+    let info = Paragraph::new(Line::from(format!("📄 {}{}", view.title, section)))
         .block(Block::default().borders(Borders::ALL).title("文件信息"))
         .alignment(Alignment::Center);
     f.render_widget(info, chunks[0]);
 
+    let body = if view.show_toc {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(28), Constraint::Percentage(72)].as_ref())
+            .split(chunks[1])
+    } else {
+        Layout::default().constraints([Constraint::Min(0)].as_ref()).split(chunks[1])
+    };
+    let content_area = body[body.len() - 1];
+
+    // 目录面板，保持选中项可见
+    if view.show_toc {
+        let height = body[0].height.saturating_sub(2) as usize;
+        let current = view.rendered.section_at(view.scroll_offset);
+        let entries: Vec<Line> = view.rendered.toc.iter().enumerate()
+            .skip(view.toc_selected.saturating_sub(height.saturating_sub(1)))
+            .map(|(i, entry)| {
+                let mut style = if Some(i) == current {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                if i == view.toc_selected && view.toc_focus {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(Span::styled(format!("{}{}", "  ".repeat(entry.level.saturating_sub(1)), entry.title), style))
+            })
+            .collect();
+        let border = if view.toc_focus { Style::default().fg(Color::Yellow) } else { Style::default() };
+        let toc = Paragraph::new(entries)
+            .block(Block::default().borders(Borders::ALL).title("目录").border_style(border));
+        f.render_widget(toc, body[0]);
+    }
+
     // 文件内容
-    let lines: Vec<Line> = content
-        .lines()
-        .skip(scroll_offset)
-        .take(area.height as usize - 6) // 减去上下边框和操作区域
-        .map(|line| Line::from(line.to_string()))
+    let lines: Vec<Line> = view.rendered.lines.iter().enumerate()
+        .skip(view.scroll_offset)
+        .take(content_area.height as usize)
+        .map(|(i, line)| {
+            let mut line = line.clone();
+            if view.highlight == Some(i) {
+                line.style = line.style.bg(Color::DarkGray);
+            }
+            line
+        })
         .collect();
-
+    let border = if view.toc_focus { Style::default() } else { Style::default().fg(Color::Yellow) };
     let content_widget = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("内容"))
-        .wrap(Wrap { trim: true });
-    f.render_widget(content_widget, chunks[1]);
+        .block(Block::default().borders(Borders::ALL).title("内容").border_style(border))
+        .wrap(Wrap { trim: false });
+    f.render_widget(content_widget, content_area);
 
     // 操作提示
     let help_text = format!(
-        "↑↓ 滚动 | PgUp/PgDn 翻页 | 总行数: {} | 当前行: {} | Esc 退出",
-        content.lines().count(),
-        scroll_offset + 1
+        "↑↓ 滚动 | PgUp/PgDn 翻页 | [ ] 上/下一节 | T 目录 | 总行数: {} | 当前行: {} | Esc 退出",
+        view.rendered.lines.len(),
+        view.scroll_offset + 1
     );
 
     let help = Paragraph::new(help_text)
@@ -4,12 +4,20 @@
 
 use crate::repo::LearningRepo;
 use anyhow::Result;
-use crate::db;
+use crate::db::Store;
+use crate::clock::SystemClock;
+use crate::{achievements, checker, checklist, review, streak, sync};
 use rand::Rng;
 
 /// 显示仪表板
-pub fn show_dashboard(path: &str) -> Result<()> {
-    let repo = LearningRepo::new(path)?;
+pub fn show_dashboard(store: &Store, path: &str) -> Result<()> {
+    let mut repo = LearningRepo::new(path)?;
+
+    // 先与数据库同步，冲突留给用户处理
+    let plan = sync::sync(store, &repo, None)?;
+    if plan.has_changes() {
+        repo = LearningRepo::new(path)?;
+    }
 
     println!("\n╔════════════════════════════════════════════════════════════════╗");
     println!("║             🦀 Rust 学习伴侣 - 学习仪表板                      ║");
@@ -35,9 +43,20 @@ pub fn show_dashboard(path: &str) -> Result<()> {
     println!(" {}\n", completion);
 
     // 连续学习天数
-    let streak = db::get_streak_days().unwrap_or(0);
-    if streak > 0 {
-        println!("🔥 连续学习：{} 天\n", streak);
+    match streak::calculate(store, &SystemClock) {
+        Ok(streak) if streak.current > 0 => println!("🔥 连续学习：{} 天（最长 {} 天）\n", streak.current, streak.longest),
+        Ok(_) => {}
+        Err(e) => println!("⚠️  无法统计连续学习天数：{}\n", e),
+    }
+
+    if !plan.conflicts.is_empty() {
+        println!("⚠️  进度.md 与数据库有 {} 处冲突（运行 sync 查看并处理）\n", plan.conflicts.len());
+    }
+
+    // 今日待复习
+    let due = review::due_count(store, &repo)?;
+    if due > 0 {
+        println!("🔁 今日待复习：{} 道题（运行 review 开始复习）\n", due);
     }
 
     // 模块列表
@@ -45,14 +64,26 @@ pub fn show_dashboard(path: &str) -> Result<()> {
     println!("📚 模块状态");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
+    // 先修关系有误时不显示解锁状态，由 path 命令报告错误
+    let graph = crate::path::Graph::new(&repo).ok();
+
     for module in &repo.modules {
         let progress = repo.get_module_progress(&module.id);
-        let mastery = db::get_module_mastery(&module.id).unwrap_or(0.0);
+        let readiness = crate::progress::can_advance_to_next(store, &repo, &module.id)?;
+        let mastery = readiness.mastery;
+        // 练习题完成情况以测试结果为准
+        let exercises = checker::exercise_summary(store, module)?;
+        // 自检以清单勾选比例为准
+        let self_check = checklist::load_checklist(module).ok().flatten();
+        let checklist_passed = self_check.as_ref().is_some_and(|c| c.is_passed());
 
         // 状态图标
+        let locked = graph.as_ref()
+            .is_some_and(|g| !crate::path::state_of(&repo, g, &module.id).is_unlocked());
         let status_icon = match progress {
             Some(p) if p.status == "[x]" => "✅",
             Some(p) if p.status == "[~]" => "🟡",
+            _ if locked => "🔒",
             _ => "⬜",
         };
 
@@ -64,9 +95,9 @@ pub fn show_dashboard(path: &str) -> Result<()> {
             let tasks = vec![
                 (p.concept, "概念"),
                 (p.examples, "示例"),
-                (p.exercises, "练习"),
+                (exercises.is_complete(), "练习"),
                 (p.project, "综合"),
-                (p.checklist, "自检"),
+                (checklist_passed, "自检"),
             ];
 
             for (done, name) in tasks {
@@ -76,6 +107,15 @@ pub fn show_dashboard(path: &str) -> Result<()> {
             println!();
         }
 
+        if exercises.checked > 0 {
+            println!("   练习测试：{}/{} 通过（已检查 {}/{}）",
+                exercises.passed, exercises.graded, exercises.checked, exercises.total);
+        }
+
+        if let Some(c) = self_check.as_ref().filter(|c| c.checked_count() > 0) {
+            println!("   自检清单：{}/{} 已勾选（{:.0}%）", c.checked_count(), c.total(), c.ratio() * 100.0);
+        }
+
         // 掌握程度
         if mastery > 0.0 {
             print!("   掌握：");
@@ -88,7 +128,7 @@ pub fn show_dashboard(path: &str) -> Result<()> {
             }
             println!(" {:.1}%", mastery);
 
-            if mastery >= 95.0 {
+            if readiness.can_advance() {
                 println!("   🎉 已掌握，可以进入下一模块！");
             }
         }
@@ -101,12 +141,28 @@ pub fn show_dashboard(path: &str) -> Result<()> {
     println!("💡 学习建议");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    let current_module = repo.modules.first();
+    // 学习路线推荐的模块，先修关系有误时退回第一个尚未掌握的模块
+    let current_module = match crate::path::next_module(store, &repo) {
+        Ok(next) => next.and_then(|id| repo.find_module(&id)),
+        Err(_) => {
+            let mut pending = None;
+            for module in &repo.modules {
+                if !crate::progress::can_advance_to_next(store, &repo, &module.id)?.can_advance() {
+                    pending = Some(module);
+                    break;
+                }
+            }
+            pending.or(repo.modules.first())
+        }
+    };
     if let Some(module) = current_module {
-        let recommendations = crate::progress::get_study_recommendations(&module.id);
+        println!("  当前模块：{}\n", module.name);
+        let recommendations = crate::progress::get_study_recommendations(store, &repo, &module.id)?;
         for rec in recommendations {
             println!("  • {}", rec);
         }
+    } else {
+        println!("  🏁 当前学习路线已全部完成！运行 path tracks 看看其他路线");
     }
 
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -114,56 +170,49 @@ pub fn show_dashboard(path: &str) -> Result<()> {
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     println!("  learning-companion update -m <模块> -t <任务>  更新学习进度");
     println!("  learning-companion practice -m <模块>         开始练习测试");
+    println!("  learning-companion check -m <模块>            运行练习题测试");
+    println!("  learning-companion review                      复习到期题目");
     println!("  learning-companion remind -H <时> -M <分>     设置学习提醒");
+    println!("  learning-companion path                        查看学习路线");
     println!("  learning-companion achievements               查看成就");
+    println!("  learning-companion report --format html        生成学习报告");
     println!("  learning-companion export                      导出学习数据");
+    println!("  learning-companion import <文件>               导入学习数据");
     println!();
 
     Ok(())
 }
 
 /// 显示成就
-pub fn show_achievements() -> Result<()> {
+pub fn show_achievements(store: &Store, repo: &LearningRepo) -> Result<()> {
     println!("\n╔════════════════════════════════════════════════════════════════╗");
     println!("║                   🏆 我的成就                                 ║");
     println!("╚══════════════════════════════════━━━━━━━━━━━━━━━━━━━━━━━━━━━╝\n");
 
-    let achievements = db::get_all_achievements()?;
+    let statuses = achievements::statuses(store, repo.modules.len())?;
+    let unlocked: Vec<_> = statuses.iter().filter(|s| s.unlocked).collect();
 
-    if achievements.is_empty() {
+    if unlocked.is_empty() {
         println!("  还没有解锁任何成就，继续加油！\n");
-        return Ok(());
     }
 
-    for achievement in &achievements {
-        let icon = match achievement.name.as_str() {
-            "first_steps" => "🎓",
-            "week_warrior" => "🔥",
-            "month_master" => "⚡",
-            "practice_perfect" => "💯",
-            "half_way" => "🌟",
-            "completionist" => "👑",
-            _ => "🏅",
-        };
-
-        let date_str = achievement.unlocked_at
-            .map(|d| d.format("%Y-%m-%d").to_string())
+    for status in &unlocked {
+        let date_str = status.unlocked_at
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
             .unwrap_or_else(|| "未知".to_string());
 
-        println!("  {} {} - 解锁于 {}", icon, achievement.description, date_str);
+        println!("  {} {} - {} - 解锁于 {}", status.def.icon, status.def.name, status.def.description, date_str);
     }
 
     println!();
 
-    // 显示待解锁成就
+    // 显示待解锁成就及进度
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("🔒 待解锁成就");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    for achievement in &achievements {
-        if !achievement.unlocked {
-            println!("  🔒 {}", achievement.description);
-        }
+    for status in statuses.iter().filter(|s| !s.unlocked) {
+        println!("  🔒 {} - {}（{}）", status.def.name, status.def.description, status.progress.display());
     }
 
     println!();
@@ -173,7 +222,7 @@ pub fn show_achievements() -> Result<()> {
 
 /// 显示鼓励消息
 pub fn show_encouragement() {
-    let messages = vec![
+    let messages = [
         "太棒了！继续加油！💪",
         "你做得很好！保持这个节奏！🌟",
         "每一步都是进步，继续前进！🚀",
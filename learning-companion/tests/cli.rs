@@ -1,12 +1,45 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// 测试用的数据目录，离开作用域时删除
+struct DataDir(PathBuf);
+
+impl Deref for DataDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<OsStr> for DataDir {
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl Drop for DataDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 每个测试单独的数据目录，避免读写用户的学习数据
+fn data_dir(name: &str) -> DataDir {
+    DataDir(std::env::temp_dir().join(format!("lc-cli-{}-{}", name, std::process::id())))
+}
 
 #[test]
 fn test_cli_runs_without_arguments() {
     // 测试无参数运行（应该进入 TUI 模式）
     // 由于 TUI 需要交互，我们测试进程能正常启动
+    let dir = data_dir("no-args");
     Command::cargo_bin("learning-companion")
         .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
         .timeout(std::time::Duration::from_secs(2))
         .assert()
         .failure(); // TUI 会因为无法初始化终端而退出
@@ -34,8 +67,10 @@ fn test_cli_shows_version() {
 
 #[test]
 fn test_cli_dashboard_command() {
+    let dir = data_dir("dashboard");
     Command::cargo_bin("learning-companion")
         .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
         .args(["--path", ".", "dashboard"])
         .assert()
         .success()
@@ -44,8 +79,10 @@ fn test_cli_dashboard_command() {
 
 #[test]
 fn test_cli_achievements_command() {
+    let dir = data_dir("achievements");
     Command::cargo_bin("learning-companion")
         .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
         .arg("achievements")
         .assert()
         .success();
@@ -53,10 +90,86 @@ fn test_cli_achievements_command() {
 
 #[test]
 fn test_cli_accepts_path_argument() {
+    let dir = data_dir("path");
     Command::cargo_bin("learning-companion")
         .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
         .args(["--path", "."])
         .timeout(std::time::Duration::from_secs(1))
         .assert()
         .failure(); // TUI 无法在测试环境中运行
 }
+
+#[test]
+fn test_cli_data_dir_flag() {
+    let dir = data_dir("flag");
+    Command::cargo_bin("learning-companion")
+        .expect("binary exists")
+        .arg("--data-dir")
+        .arg(&dir)
+        .arg("achievements")
+        .assert()
+        .success();
+    assert!(dir.join("data.db").exists());
+}
+
+#[test]
+fn test_cli_search_command() {
+    let dir = data_dir("search");
+    Command::cargo_bin("learning-companion")
+        .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
+        .args(["--path", "..", "search", "Rc<RefCell<T>>", "--limit", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("module-11-smart-pointers/"))
+        .stdout(predicate::str::contains("Rc<RefCell<"));
+}
+
+#[test]
+fn test_cli_rejects_unknown_export_format() {
+    let dir = data_dir("export-format");
+    Command::cargo_bin("learning-companion")
+        .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
+        .args(["export", "--format", "xml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("[possible values: json, csv]"));
+}
+
+#[test]
+fn test_cli_daemon_start_waits_for_pid_file() {
+    let dir = data_dir("daemon");
+    Command::cargo_bin("learning-companion")
+        .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
+        .args(["daemon", "start"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("已启动"));
+    // 报告启动时 PID 文件已经就绪，日志写入数据目录
+    assert!(dir.join("daemon.pid").exists());
+    assert!(dir.join("daemon.log").exists());
+
+    Command::cargo_bin("learning-companion")
+        .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
+        .args(["daemon", "stop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("已停止"));
+}
+
+#[test]
+fn test_cli_short_t_is_not_tui() {
+    // -t 是 update --task 的简写，不再启动 TUI
+    let dir = data_dir("short-t");
+    Command::cargo_bin("learning-companion")
+        .expect("binary exists")
+        .env("LEARNING_COMPANION_DATA_DIR", &dir)
+        .arg("-t")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument '-t'"));
+}
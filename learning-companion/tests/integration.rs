@@ -70,7 +70,7 @@ fn test_completion_percentage() {
     let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
 
     let completion = repo.completion_percentage();
-    assert!(completion >= 0.0 && completion <= 100.0, "完成度应在 0-100 之间");
+    assert!((0.0..=100.0).contains(&completion), "完成度应在 0-100 之间");
 
     println!("总体完成度: {:.1}%", completion);
 }
@@ -79,19 +79,26 @@ fn test_completion_percentage() {
 fn test_mastery_calculation() {
     // 测试掌握程度计算
     let module_id = "test-module";
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
 
     // 初始掌握程度应该是 0
-    let mastery = calculate_mastery(module_id);
+    let store = &db::Store::open_in_memory().unwrap();
+    let mastery = calculate_mastery(store, &repo, module_id).unwrap();
     assert_eq!(mastery, 0.0, "新模块的掌握程度应该是 0");
 }
 
 #[test]
 fn test_achievement_system() {
     // 测试成就系统
-    let achievements = db::get_all_achievements().unwrap();
+    let store = &db::Store::open_in_memory().unwrap();
+    let achievements = store.get_all_achievements().unwrap();
 
-    // 应该有预定义的成就
-    assert_eq!(achievements.len(), 6, "应该有 6 个预定义成就");
+    // 成就来自 achievements.toml 中的定义
+    let definitions = learning_companion::achievements::definitions();
+    assert_eq!(achievements.len(), definitions.len());
+    assert!(definitions.iter().any(|d| d.id == "week_warrior"));
 
     // 验证每个成就的结构
     for achievement in &achievements {
@@ -106,10 +113,10 @@ fn test_achievement_system() {
 #[test]
 fn test_database_initialization() {
     // 测试数据库初始化
-    db::init_db().unwrap();
+    let store = &db::Store::open_in_memory().unwrap();
 
     // 验证可以获取成就（间接验证数据库可用）
-    let achievements = db::get_all_achievements().unwrap();
+    let achievements = store.get_all_achievements().unwrap();
     assert!(!achievements.is_empty());
 }
 
@@ -123,24 +130,1702 @@ fn test_app_state_transitions() {
 #[test]
 fn test_question_generation() {
     // 测试练习题生成
-    use learning_companion::exercise::{Question, QuestionType};
+    use learning_companion::exercise::{CorrectAnswer, Question, QuestionType};
 
     // 创建一个测试问题
     let question = Question {
         question_type: QuestionType::MultipleChoice,
         prompt: "Rust 中哪个关键字用于声明不可变变量？".to_string(),
+        code: None,
         options: Some(vec![
             "let".to_string(),
             "mut".to_string(),
             "const".to_string(),
             "var".to_string(),
         ]),
-        correct_answer: "0".to_string(),
+        correct_answer: CorrectAnswer::Choice(0),
         explanation: "let 用于声明不可变变量".to_string(),
         topic: "变量声明".to_string(),
     };
 
     assert_eq!(question.options.as_ref().unwrap().len(), 4);
-    assert_eq!(question.correct_answer, "0");
+    assert_eq!(question.correct_answer, CorrectAnswer::Choice(0));
     assert!(!question.explanation.is_empty());
 }
+
+#[test]
+fn test_render_markdown_and_rust() {
+    // 测试文件查看器的渲染：标题进目录，表格按显示宽度对齐，Rust 代码块高亮
+    use learning_companion::render::{render_file, render_markdown};
+    use ratatui::style::Color;
+    use std::path::Path;
+
+    let content = "\
+# 所有权
+
+**移动**语义和 `Clone` 的区别，见 [文档](https://doc.rust-lang.org)。
+
+## 规则
+
+| 类型 | 复制 |
+|------|:----:|
+| i32 | 是 |
+| String | 否 |
+
+- [x] 理解借用
+- 生命周期
+
+```rust
+// 注释
+let s = String::from(\"hi\");
+println!(\"{}\", s);
+```
+
+## 练习
+";
+    let rendered = render_markdown(content);
+    let plain = rendered.plain_lines();
+
+    let titles: Vec<&str> = rendered.toc.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(titles, ["所有权", "规则", "练习"]);
+    assert_eq!(plain[rendered.toc[1].line], "规则");
+    assert_eq!(rendered.section_at(rendered.toc[1].line + 3), Some(1));
+
+    assert!(plain.contains(&"移动语义和 Clone 的区别，见 文档。".to_string()), "行内标记和链接地址不显示");
+    assert!(plain.contains(&"│ 类型   │ 复制 │".to_string()), "中文按两格对齐");
+    assert!(plain.contains(&"│ String │  否  │".to_string()), "居中对齐");
+    let bottom = plain.iter().position(|l| l.starts_with('└')).unwrap();
+    assert_eq!(rendered.sources[bottom], rendered.sources[bottom - 1], "表格底边对应最后一行表格");
+    assert!(plain.contains(&"☑ 理解借用".to_string()));
+    assert!(plain.contains(&"• 生命周期".to_string()));
+
+    let code = rendered.lines.iter().find(|l| l.spans.iter().any(|s| s.content == "let")).unwrap();
+    let style_of = |text: &str| code.spans.iter().find(|s| s.content == text).unwrap().style.fg;
+    assert_eq!(style_of("let"), Some(Color::Magenta));
+    assert_eq!(style_of("String"), Some(Color::Yellow));
+    assert_eq!(style_of("\"hi\""), Some(Color::Green));
+
+    // Rust 文件：目录为顶层条目，块注释跨行
+    let source = "/* 开始\n结束 */\npub struct Point;\nimpl Point {\n    fn new() -> Self { Point }\n}\n";
+    let rendered = render_file(Path::new("exercise1.rs"), source);
+    let titles: Vec<&str> = rendered.toc.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(titles, ["pub struct Point", "impl Point", "fn new() -> Self { Point }"]);
+    assert_eq!(rendered.lines[1].spans[0].style.fg, Some(Color::DarkGray));
+}
+
+#[test]
+fn test_full_text_search() {
+    // 测试全文搜索：中英文混合分词、排序和只有符号的查询
+    use learning_companion::search::{index_tokens, match_ranges, query_tokens, DocKind, Document, SearchIndex};
+    use std::path::PathBuf;
+
+    assert_eq!(query_tokens("Rc<RefCell<T>> 所有权"), ["rc", "refcell", "t", "所有", "有权"]);
+    let tokens = index_tokens("get_mut 借用");
+    for token in ["get_mut", "get", "mut", "借", "用", "借用"] {
+        assert!(tokens.contains(&token.to_string()), "缺少 {}", token);
+    }
+
+    let doc = |display: &str, kind, lines: &[&str]| Document {
+        path: PathBuf::from(display),
+        display: display.to_string(),
+        module_id: display.split('/').next().unwrap().to_string(),
+        kind,
+        lines: lines.iter().map(|l| l.to_string()).collect(),
+    };
+    let mut index = SearchIndex::default();
+    index.add_document(doc("module-11-smart-pointers/README.md", DocKind::Readme, &[
+        "# 智能指针",
+        "## RefCell<T> 和内部可变性",
+        "共享可变数据时常用 Rc<RefCell<T>> 组合。",
+        "Rc 只能在单线程中使用。",
+    ]));
+    index.add_document(doc("module-09-concurrency/examples/errors.rs", DocKind::Example, &[
+        "fn read() -> Result<String, std::io::Error> {",
+        "    let text = std::fs::read_to_string(\"a.txt\")?;",
+        "    Ok(text)",
+        "}",
+    ]));
+
+    let hits = index.search("Rc<RefCell<T>>", None, 10);
+    assert_eq!(hits[0].line, 2, "完整包含查询原文的行排在最前");
+    assert_eq!(hits[0].context.len(), 3);
+
+    let hits = index.search("内部可变", None, 10);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].line, 1);
+
+    // 只有符号时按原文查找
+    let hits = index.search("?", None, 10);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].kind, DocKind::Example);
+
+    assert!(index.search("rc", Some("module-09-concurrency"), 10).is_empty());
+    assert!(index.search("HashMap", None, 10).is_empty());
+
+    assert_eq!(match_ranges("共享 Rc<RefCell<T>>", "refcell"), [(10, 17)]);
+
+    // 练习测试通过前，答案不参与搜索
+    index.add_document(doc("module-11-smart-pointers/exercises/solutions/exercise1_list.rs", DocKind::Solution, &[
+        "let shared = Rc::new(RefCell::new(Vec::new()));",
+    ]));
+    let store = learning_companion::db::Store::open_in_memory().unwrap();
+    assert_eq!(index.hide_unsolved_solutions(&store).unwrap(), 1);
+    assert!(index.search("RefCell::new", None, 10).iter().all(|h| h.kind != DocKind::Solution));
+    store.record_exercise_result("module-11-smart-pointers", "exercise1_list", true, 1, 1, Vec::new()).unwrap();
+    assert_eq!(index.hide_unsolved_solutions(&store).unwrap(), 0);
+    assert!(index.search("RefCell::new", None, 10).iter().any(|h| h.kind == DocKind::Solution));
+}
+
+#[test]
+fn test_background_exercise_tests_time_out() {
+    // 测试在后台运行练习测试：正常结束时返回结果，死循环的测试超时后结束
+    use learning_companion::checker::TestRun;
+    use learning_companion::repo::LearningModule;
+    use std::time::Duration;
+
+    let root = std::env::temp_dir().join(format!("lc-test-run-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("exercises")).unwrap();
+    std::fs::write(root.join("exercises").join("exercise1_add.rs"),
+        "fn add(a: i32, b: i32) -> i32 { a + b }\n\n#[test]\nfn adds() {\n    assert_eq!(add(1, 2), 3);\n}\n").unwrap();
+    std::fs::write(root.join("exercises").join("exercise2_spin.rs"),
+        "#[test]\nfn spins() {\n    loop {\n        std::thread::sleep(std::time::Duration::from_millis(10));\n    }\n}\n").unwrap();
+    let module = LearningModule {
+        id: format!("lc-test-run-{}", std::process::id()),
+        number: 1,
+        name: "01-测试".to_string(),
+        prerequisites: Vec::new(),
+        directory: root.clone(),
+        has_readme: false,
+        has_exercises: true,
+        has_tests: false,
+        has_checklist: false,
+    };
+
+    let run = TestRun::start(&module, "exercise1_add", Duration::from_secs(60));
+    assert_eq!(run.exercise, "exercise1_add");
+    let check = run.recv().unwrap();
+    assert!(check.compiled && check.all_passed());
+
+    let started = std::time::Instant::now();
+    let run = TestRun::start(&module, "exercise2_spin", Duration::from_secs(3));
+    let err = run.recv().unwrap_err();
+    assert!(format!("{:#}", err).contains("超过 3 秒"), "{:#}", err);
+    assert!(started.elapsed() < Duration::from_secs(60));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_parse_exercise_test_output() {
+    // 测试 libtest 输出解析
+    use learning_companion::checker::parse_test_output;
+
+    let output = "\
+running 3 tests
+test tests::test_freezing_point ... ok
+test tests::test_boiling_point ... FAILED
+test tests::test_slow ... ignored
+test result: FAILED. 1 passed; 1 failed; 1 ignored
+";
+
+    let results = parse_test_output(output);
+    assert_eq!(results.len(), 2, "ignored 的测试不参与判分");
+    assert_eq!(results[0].name, "tests::test_freezing_point");
+    assert!(results[0].passed);
+    assert!(!results[1].passed);
+}
+
+#[test]
+fn test_exercise_diagnostics_and_editor_args() {
+    // 测试工作区诊断解析：cargo 输出相对工作区根目录的路径，panic 映射到断言所在的行
+    use learning_companion::checker::{parse_diagnostics, DiagnosticLevel};
+    use learning_companion::editor::editor_args;
+    use std::path::Path;
+
+    let root = std::env::temp_dir().join(format!("lc-diagnostics-{}", std::process::id()));
+    let module_dir = root.join("module-01-basics");
+    std::fs::create_dir_all(module_dir.join("exercises")).unwrap();
+    let file = module_dir.join("exercises").join("exercise1_vars.rs");
+    std::fs::write(&file, "fn main() {}\n").unwrap();
+
+    let output = "\
+warning: unused variable: `y`
+ --> module-01-basics/exercises/exercise1_vars.rs:3:9
+  |
+error[E0384]: cannot assign twice to immutable variable `x`
+ --> module-01-basics/exercises/exercise1_vars.rs:5:5
+  |
+error: aborting due to 1 previous error
+running 1 test
+thread 'tests::test_sum' (4242) panicked at module-01-basics/exercises/exercise1_vars.rs:12:9:
+assertion `left == right` failed
+test tests::test_sum ... FAILED
+";
+    let diagnostics = parse_diagnostics(output, &module_dir);
+    assert_eq!(diagnostics.len(), 3, "没有位置的汇总信息不算诊断");
+
+    assert_eq!(diagnostics[0].level, DiagnosticLevel::Warning);
+    assert_eq!(diagnostics[1].level, DiagnosticLevel::Error);
+    assert_eq!(diagnostics[1].code.as_deref(), Some("E0384"));
+    assert_eq!(diagnostics[1].location.file, file);
+    assert_eq!((diagnostics[1].location.line, diagnostics[1].location.column), (5, 5));
+    assert_eq!(diagnostics[1].output_line, 3);
+
+    assert_eq!(diagnostics[2].level, DiagnosticLevel::Panic);
+    assert_eq!(diagnostics[2].location.line, 12);
+    assert!(diagnostics[2].message.contains("tests::test_sum"));
+    assert!(diagnostics[2].message.contains("assertion"));
+
+    // 编辑器跳到指定行
+    let editor = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    let args = |words: &[&str], line| editor_args(&editor(words), Path::new("a.rs"), line)
+        .into_iter().map(|a| a.to_string_lossy().to_string()).collect::<Vec<_>>();
+    assert_eq!(args(&["vim"], Some(5)), ["+5", "a.rs"]);
+    assert_eq!(args(&["/usr/bin/code", "--wait"], Some(5)), ["--wait", "--goto", "a.rs:5"]);
+    assert_eq!(args(&["hx"], Some(5)), ["a.rs:5"]);
+    assert_eq!(args(&["unknown-editor"], Some(5)), ["a.rs"]);
+    assert_eq!(args(&["vim"], None), ["a.rs"]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_examples_listing_and_background_run() {
+    // 测试示例列表（声明的和自动发现的）、后台运行的输出转发、取消与超时，以及运行记录
+    use learning_companion::examples::{examples_summary, list_examples, ExampleRun, OutputStream, RunEvent, RunOutcome};
+    use std::time::Duration;
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+
+    let basics = repo.find_module("basics").unwrap();
+    let names: Vec<String> = list_examples(&basics.directory).unwrap().into_iter().map(|e| e.name).collect();
+    assert_eq!(names, ["variables", "data_types", "functions"], "声明的示例按 Cargo.toml 中的顺序");
+    let concurrency = list_examples(&repo.find_module("concurrency").unwrap().directory).unwrap();
+    assert!(concurrency.iter().any(|e| e.name == "rwlock"), "没有声明时使用 examples/ 下的文件");
+
+    // 运行记录：所有示例都成功运行过才算完成，之后失败不影响
+    let store = db::Store::open_in_memory().unwrap();
+    for name in &names[..2] {
+        store.record_example_run(&basics.id, name, true, 120).unwrap();
+    }
+    store.record_example_run(&basics.id, "functions", false, 80).unwrap();
+    assert!(!examples_summary(&store, basics).unwrap().is_complete());
+    store.record_example_run(&basics.id, "functions", true, 90).unwrap();
+    store.record_example_run(&basics.id, "functions", false, 70).unwrap();
+    assert!(examples_summary(&store, basics).unwrap().is_complete());
+    let functions = store.get_example_runs(&basics.id).unwrap().into_iter().find(|r| r.example == "functions").unwrap();
+    assert_eq!((functions.runs, functions.successes, functions.last_success), (3, 1, false));
+
+    // 在临时的 crate 中实际运行
+    let root = std::env::temp_dir().join(format!("lc-examples-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("examples")).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"lc-examples\"\nversion = \"0.1.0\"\nedition = \"2021\"\nautoexamples = false\n\n[workspace]\n\n[[example]]\nname = \"hello\"\n\n[[example]]\nname = \"forever\"\n").unwrap();
+    std::fs::write(root.join("examples").join("hello.rs"), "fn main() {\n    println!(\"hello\");\n    eprintln!(\"to stderr\");\n    std::process::exit(3);\n}\n").unwrap();
+    std::fs::write(root.join("examples").join("forever.rs"), "fn main() {\n    loop {\n        println!(\"tick\");\n        std::thread::sleep(std::time::Duration::from_millis(50));\n    }\n}\n").unwrap();
+    std::fs::write(root.join("examples").join("ignored.rs"), "fn main() {}\n").unwrap();
+    let names: Vec<String> = list_examples(&root).unwrap().into_iter().map(|e| e.name).collect();
+    assert_eq!(names, ["hello", "forever"], "autoexamples = false 时不自动发现");
+
+    let collect = |run: &ExampleRun, stop_at: Option<&str>| {
+        let mut lines = Vec::new();
+        while let Some(event) = run.recv() {
+            match event {
+                RunEvent::Output(stream, line) => {
+                    if stop_at == Some(line.as_str()) {
+                        run.cancel();
+                    }
+                    lines.push((stream, line));
+                }
+                RunEvent::Finished { outcome, .. } => return (lines, outcome),
+            }
+        }
+        panic!("没有收到结束事件");
+    };
+
+    let run = ExampleRun::start(&root, "hello", Duration::from_secs(120)).unwrap();
+    let (lines, outcome) = collect(&run, None);
+    assert_eq!(outcome, RunOutcome::Exited { success: false, code: Some(3) });
+    assert!(lines.contains(&(OutputStream::Stdout, "hello".to_string())));
+    assert!(lines.contains(&(OutputStream::Stderr, "to stderr".to_string())));
+
+    let run = ExampleRun::start(&root, "forever", Duration::from_secs(120)).unwrap();
+    let (_, outcome) = collect(&run, Some("tick"));
+    assert_eq!(outcome, RunOutcome::Cancelled);
+
+    let run = ExampleRun::start(&root, "forever", Duration::ZERO).unwrap();
+    let (_, outcome) = collect(&run, None);
+    assert_eq!(outcome, RunOutcome::TimedOut);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_hints_skeleton_and_diff() {
+    // 测试分级提示：注释中的提示、答案骨架、逐行对比，以及测试通过前看答案对掌握程度的影响
+    use learning_companion::checker::exercise_summary;
+    use learning_companion::hints::{collapse, diff_lines, extract_hints, record_view, skeleton, DiffOp, DiffRow, HintLevel};
+    use learning_companion::progress::{MasteryEvidence, MasteryWeights};
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+    let basics = repo.find_module("basics").unwrap();
+
+    let source = std::fs::read_to_string(basics.directory.join("exercises/exercise1_temperature.rs")).unwrap();
+    let hints = extract_hints(&source);
+    let titles: Vec<&str> = hints.iter().map(|h| h.title.as_str()).collect();
+    assert!(titles.contains(&"任务") && titles.contains(&"公式"), "{:?}", titles);
+    assert!(hints.iter().any(|h| h.lines.iter().any(|l| l.contains("F = C × 9/5 + 32")) && h.title.starts_with('第')));
+
+    let solution = r#"//! 解答要点：用 match
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+// 备选实现：fn alt() { unreachable!() }
+pub fn brace(c: char) -> &'static str {
+    match c {
+        '}' => "right }",
+        '{' => "left {",
+        _ => r"{other}",
+    }
+}
+
+impl Shape for [u8; 4] {
+    fn area(&self) -> f64 { 4.0 }
+}
+
+fn main() {
+    println!("{}", brace('{'));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_brace() {
+        assert_eq!(brace('}'), "right }");
+    }
+}
+"#;
+    let skeleton = skeleton(solution);
+    assert_eq!(skeleton.matches("todo!()").count(), 2, "{}", skeleton);
+    assert!(skeleton.contains("//! 解答要点") && skeleton.contains("fn area(&self) -> f64;"));
+    assert!(!skeleton.contains("备选实现") && !skeleton.contains("right }\",\n        '{'"));
+    assert!(skeleton.contains("println!(\"{}\", brace('{'));"), "main 保持原样");
+    assert!(skeleton.contains("assert_eq!(brace('}'), \"right }\");"), "测试保持原样");
+
+    let diff = diff_lines("fn main() {\n    let x = 1;\n    todo!()\n}\n", "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n");
+    let ops: Vec<DiffOp> = diff.iter().map(|l| l.op).collect();
+    assert_eq!(ops, [DiffOp::Same, DiffOp::Same, DiffOp::Removed, DiffOp::Added, DiffOp::Same]);
+    assert_eq!((diff[2].old_line, diff[3].new_line), (Some(3), Some(3)));
+    let rows = collapse(&diff, 1);
+    assert_eq!(rows.first(), Some(&DiffRow::Skipped(1)));
+    assert_eq!(rows.len(), 5);
+
+    // 看过答案后才通过的练习按一半计分；通过之后再看不受影响
+    let mut store = db::Store::open_in_memory().unwrap();
+    store.record_exercise_result(&basics.id, "exercise1_temperature", true, 2, 2, Vec::new()).unwrap();
+    assert!(!record_view(&store, basics, "exercise1_temperature", HintLevel::Solution).unwrap());
+    assert!(record_view(&store, basics, "exercise2_average", HintLevel::Skeleton).unwrap());
+    store.record_exercise_result(&basics.id, "exercise2_average", true, 2, 2, Vec::new()).unwrap();
+    assert_eq!(exercise_summary(&store, basics).unwrap().assisted, 0, "骨架不算看过答案");
+
+    assert!(record_view(&store, basics, "exercise3_distance", HintLevel::Diff).unwrap());
+    store.record_exercise_result(&basics.id, "exercise3_distance", true, 1, 1, Vec::new()).unwrap();
+    assert_eq!(exercise_summary(&store, basics).unwrap().assisted, 1);
+    assert_eq!(store.get_solution_views(&basics.id).unwrap().len(), 3);
+
+    // 读不到测试结果时不记录查看，也不能当作通过前看过答案
+    store.connection().execute_batch(&format!(
+        "INSERT INTO exercise_results (module_id, exercise, checked_at, compiled, tests_total, tests_passed)
+         VALUES ('{}', 'exercise4_broken', '2024-03-01', 1, 'two', 2);",
+        basics.id,
+    )).unwrap();
+    assert!(record_view(&store, basics, "exercise5_fizzbuzz", HintLevel::Solution).is_err());
+    assert_eq!(store.get_solution_views(&basics.id).unwrap().len(), 3);
+
+    let only_exercises = MasteryWeights { tasks: 0.0, exercises: 1.0, practice: 0.0, checklist: 0.0 };
+    let evidence = MasteryEvidence { exercises: Some((4, 4)), exercises_assisted: 1, has_exercises: true, ..Default::default() };
+    assert!((evidence.mastery(&only_exercises) - 87.5).abs() < 0.01);
+}
+
+#[test]
+fn test_exercise_summary_completion() {
+    // 测试练习完成判定：未检查或未通过都不算完成
+    use learning_companion::checker::ExerciseSummary;
+    use learning_companion::db::ExerciseResult;
+
+    let result = |exercise: &str, total: u32, passed: u32| ExerciseResult {
+        exercise: exercise.to_string(),
+        checked_at: String::new(),
+        compiled: true,
+        tests_total: total,
+        tests_passed: passed,
+        failed_tests: Vec::new(),
+    };
+    let exercises = vec!["exercise1_a".to_string(), "exercise2_b".to_string()];
+
+    let partial = ExerciseSummary::from_results(&exercises, &[result("exercise1_a", 3, 3)]);
+    assert!(!partial.is_complete(), "还有练习未检查");
+
+    let failing = ExerciseSummary::from_results(
+        &exercises,
+        &[result("exercise1_a", 3, 3), result("exercise2_b", 2, 1)],
+    );
+    assert!(!failing.is_complete());
+
+    // 没有测试的练习不参与判分
+    let done = ExerciseSummary::from_results(
+        &exercises,
+        &[result("exercise1_a", 3, 3), result("exercise2_b", 0, 0)],
+    );
+    assert_eq!(done.graded, 1);
+    assert!(done.is_complete());
+}
+
+#[test]
+fn test_exercise_summary_reports_db_errors() {
+    // 测试读取测试结果失败时返回错误，不会当作练习未通过而取消 进度.md 中的勾选
+    use learning_companion::checker::{exercise_summary, finish_checks};
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("lc-summary-error-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("module-01-basics")).unwrap();
+    fs::write(dir.join("module-01-basics/Cargo.toml"), "[package]\nname = \"module-01-basics\"\n").unwrap();
+    let progress = "### 01-基础入门\n\n- [ ] 概念学习\n- [x] 练习题完成\n";
+    fs::write(dir.join("进度.md"), progress).unwrap();
+
+    let repo = LearningRepo::new(&dir).unwrap();
+    let basics = repo.find_module("basics").unwrap();
+    let mut store = db::Store::open_in_memory().unwrap();
+    store.connection().execute_batch(
+        "INSERT INTO exercise_results (module_id, exercise, checked_at, compiled, tests_total, tests_passed)
+         VALUES ('module-01-basics', 'exercise1', '2024-03-01', 1, 'three', 3);",
+    ).unwrap();
+
+    assert!(exercise_summary(&store, basics).is_err());
+    assert!(finish_checks(&store, &repo, basics).is_err());
+    assert_eq!(fs::read_to_string(dir.join("进度.md")).unwrap(), progress);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_list_exercises_sorted_by_number() {
+    // 测试练习文件按编号排序，且不包含 solutions 目录
+    use learning_companion::checker::list_exercises;
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let exercises = list_exercises(&parent_dir.join("module-07-collections")).unwrap();
+
+    assert_eq!(exercises.first().map(String::as_str), Some("exercise1_vec_basics"));
+    assert_eq!(exercises.get(9).map(String::as_str), Some("exercise10_btreemap"));
+}
+
+#[test]
+fn test_question_bank_reports_malformed_entries() {
+    // 测试题库校验：缺少答案、序号越界都应报告
+    use learning_companion::exercise::CorrectAnswer;
+    use learning_companion::questions::parse_bank;
+    use std::path::Path;
+
+    let content = r#"
+[[questions]]
+type = "multiple_choice"
+prompt = "正常的题目"
+options = ["a", "b"]
+answer = 1
+
+[[questions]]
+type = "multiple_choice"
+prompt = "缺少答案"
+options = ["a", "b"]
+
+[[questions]]
+type = "multiple_choice"
+prompt = "答案越界"
+options = ["a", "b"]
+answer = 2
+
+[[questions]]
+type = "true_false"
+prompt = "判断题"
+answer = false
+"#;
+
+    let bank = parse_bank(content, Path::new("questions.toml")).unwrap();
+    assert_eq!(bank.questions.len(), 2);
+    assert_eq!(bank.errors.len(), 2);
+    assert_eq!(bank.errors[0].number, 2);
+    assert_eq!(bank.errors[1].number, 3);
+    assert_eq!(bank.questions[1].correct_answer, CorrectAnswer::Bool(false));
+}
+
+#[test]
+fn test_all_module_question_banks_are_valid() {
+    // 测试仓库中每个模块的题库都能通过校验
+    use learning_companion::questions::load_bank;
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+
+    for module in &repo.modules {
+        let bank = load_bank(module).unwrap().expect("每个模块都应有题库");
+        assert!(bank.errors.is_empty(), "{} 题库有误: {:?}", module.id, bank.errors);
+        assert!(!bank.questions.is_empty());
+    }
+
+    assert_eq!(repo.find_module("basics").map(|m| m.id.as_str()), Some("module-01-basics"));
+}
+
+#[test]
+fn test_grading_by_question_type() {
+    // 测试判分：判断题、填空题按各自的答案类型判分，未作答一律判错
+    use learning_companion::exercise::{Answer, CorrectAnswer, PracticeSession, Question, QuestionType};
+
+    let question = |question_type, correct_answer| Question {
+        question_type,
+        prompt: String::new(),
+        code: None,
+        options: Some(vec!["正确".to_string(), "错误".to_string()]),
+        correct_answer,
+        explanation: String::new(),
+        topic: "测试".to_string(),
+    };
+
+    let true_false = question(QuestionType::TrueFalse, CorrectAnswer::Bool(false));
+    assert!(true_false.is_correct(Some(&Answer::Bool(false))));
+    assert!(!true_false.is_correct(Some(&Answer::Bool(true))));
+    assert_eq!(true_false.parse_answer("错"), Some(Answer::Bool(false)));
+    assert_eq!(true_false.parse_answer(""), None);
+
+    let choice = question(QuestionType::MultipleChoice, CorrectAnswer::Choice(0));
+    assert_eq!(choice.parse_answer("  "), None, "空答案不能算作选项 1");
+    assert_eq!(choice.parse_answer("3"), None, "超出范围的选项无效");
+    assert!(!choice.is_correct(None));
+
+    let blank = question(
+        QuestionType::FillInBlank,
+        CorrectAnswer::Text(vec!["Rc<RefCell<T>>".to_string(), "Rc＋RefCell".to_string()]),
+    );
+    assert!(blank.is_correct(Some(&Answer::Text("  rc<refcell<t>> ".to_string()))));
+    assert!(blank.is_correct(Some(&Answer::Text("Ｒｃ+RefCell".to_string()))), "全角字符应视为半角");
+    assert!(!blank.is_correct(Some(&Answer::Text(String::new()))));
+
+    let mut session = PracticeSession::new("test".to_string(), vec![true_false, choice, blank]);
+    session.answers = vec![Some(Answer::Bool(false)), None, Some(Answer::Text("Box".to_string()))];
+    assert_eq!(session.correct_count(), 1);
+    assert_eq!(session.weak_topics().len(), 2);
+}
+
+#[test]
+fn test_review_schedule_sm2() {
+    // 测试 SM-2 调度：答对间隔 1 → 6 → 按难度系数递增，答错重置为明天
+    use chrono::NaiveDate;
+    use learning_companion::review::{new_schedule, next_schedule};
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    let start = new_schedule("module-01-basics", "abc", day(1));
+    assert_eq!(start.due_date, day(1), "新题目当天到期");
+
+    let first = next_schedule(&start, true, day(1));
+    assert_eq!((first.repetitions, first.interval_days), (1, 1));
+    assert_eq!(first.due_date, day(2));
+    assert!((first.ease_factor - 2.5).abs() < 1e-9, "质量 4 不改变难度系数");
+
+    let second = next_schedule(&first, true, day(2));
+    assert_eq!((second.repetitions, second.interval_days), (2, 6));
+    assert_eq!(second.due_date, day(8));
+
+    let third = next_schedule(&second, true, day(8));
+    assert_eq!(third.interval_days, 15);
+    assert_eq!(third.due_date, day(23));
+
+    let failed = next_schedule(&third, false, day(23));
+    assert_eq!((failed.repetitions, failed.interval_days), (0, 1));
+    assert_eq!(failed.due_date, day(24));
+    assert!(failed.ease_factor < third.ease_factor, "答错降低难度系数");
+
+    // 连续答错时难度系数不低于 1.3
+    let mut schedule = failed;
+    for _ in 0..10 {
+        schedule = next_schedule(&schedule, false, day(24));
+    }
+    assert!((schedule.ease_factor - 1.3).abs() < 1e-9);
+}
+
+#[test]
+fn test_review_question_key_is_stable() {
+    // 测试题目标识只取决于题干（代码题还取决于代码），与知识点无关
+    use learning_companion::exercise::{CorrectAnswer, Question, QuestionType};
+    use learning_companion::review::question_key;
+
+    let question = |prompt: &str, topic: &str| Question {
+        question_type: QuestionType::TrueFalse,
+        prompt: prompt.to_string(),
+        code: None,
+        options: None,
+        correct_answer: CorrectAnswer::Bool(true),
+        explanation: String::new(),
+        topic: topic.to_string(),
+    };
+
+    let key = question_key(&question("Rust 中，默认情况下变量是不可变的。", "可变性"));
+    assert_eq!(key.len(), 16);
+    assert_eq!(key, question_key(&question("Rust 中，默认情况下变量是不可变的。 ", "其他")));
+    assert_ne!(key, question_key(&question("数组在 Rust 中可以有不同类型的元素。", "可变性")));
+
+    // 题干相同的代码题按代码区分
+    let code_question = |code: &str| Question {
+        question_type: QuestionType::CompilesOrNot,
+        code: Some(code.to_string()),
+        ..question("下面的程序能通过编译吗？", "借用")
+    };
+    assert_ne!(question_key(&code_question("fn main() {}")), question_key(&code_question("fn main() { let x = 1; }")));
+    assert_eq!(question_key(&code_question("fn main() {}\n")), question_key(&code_question("fn main() {}")));
+}
+
+#[test]
+fn test_review_question_keys_unique_per_module() {
+    // 测试每个模块题库中的题目标识互不相同，否则多道题会共用一条复习计划
+    use learning_companion::questions::load_bank;
+    use learning_companion::review::question_key;
+    use std::collections::HashMap;
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+
+    for module in &repo.modules {
+        let Some(bank) = load_bank(module).unwrap() else {
+            continue;
+        };
+        let mut seen = HashMap::new();
+        for question in &bank.questions {
+            if let Some(prev) = seen.insert(question_key(question), &question.prompt) {
+                panic!("{} 中的两道题标识相同：{} / {}", module.id, prev, question.prompt);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_review_due_count_skips_removed_questions() {
+    // 测试待复习题数与实际能复习的题目一致：题库中已删除或修改过的题目不计入
+    use chrono::{Duration, Local};
+    use learning_companion::db::ReviewSchedule;
+    use learning_companion::questions::load_bank;
+    use learning_companion::review::{due_count, due_questions, question_key};
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+    let basics = repo.find_module("basics").unwrap();
+    let bank = load_bank(basics).unwrap().unwrap();
+
+    let store = db::Store::open_in_memory().unwrap();
+    let yesterday = Local::now().date_naive() - Duration::days(1);
+    for key in [question_key(&bank.questions[0]), "0000000000000000".to_string()] {
+        store.save_review_schedule(&ReviewSchedule {
+            module_id: basics.id.clone(),
+            question_key: key,
+            ease_factor: 2.5,
+            interval_days: 1,
+            repetitions: 1,
+            due_date: yesterday,
+        }).unwrap();
+    }
+    assert_eq!(due_count(&store, &repo).unwrap(), 1);
+    assert_eq!(due_questions(&store, &repo, Local::now().date_naive()).unwrap().len(), 1);
+}
+
+#[test]
+fn test_code_question_bank_entries() {
+    // 测试代码题的解析：必须带代码片段，编译判断题显示为两个选项
+    use learning_companion::exercise::{Answer, CorrectAnswer, QuestionType};
+    use learning_companion::questions::parse_bank;
+    use std::path::Path;
+
+    let content = r#"
+[[questions]]
+type = "compiles_or_not"
+prompt = "能编译吗？"
+code = "fn main() {}"
+answer = true
+
+[[questions]]
+type = "code_output"
+prompt = "输出什么？"
+code = "fn main() { println!(\"1\"); println!(\"2\"); }"
+answer = "1\n2"
+
+[[questions]]
+type = "code_output"
+prompt = "缺少代码"
+answer = "1"
+
+[[questions]]
+type = "compiles_or_not"
+prompt = "缺少答案"
+code = "fn main() {}"
+"#;
+
+    let bank = parse_bank(content, Path::new("questions.toml")).unwrap();
+    assert_eq!(bank.questions.len(), 2);
+    assert_eq!(bank.errors.iter().map(|e| e.number).collect::<Vec<_>>(), vec![3, 4]);
+
+    let compiles = &bank.questions[0];
+    assert_eq!(compiles.question_type, QuestionType::CompilesOrNot);
+    assert_eq!(compiles.correct_answer, CorrectAnswer::Bool(true));
+    assert_eq!(compiles.parse_answer("2"), Some(Answer::Bool(false)));
+    assert_eq!(compiles.answer_text(), "能编译");
+
+    // 多行输出可以用空格分隔作答
+    let output = &bank.questions[1];
+    assert!(output.is_correct(Some(&Answer::Text("1 2".to_string()))));
+}
+
+#[test]
+fn test_code_questions_match_compiler() {
+    // 测试所有权、生命周期题库中的代码题与 rustc 的实际行为一致
+    use learning_companion::questions::{check_code_question, load_bank};
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+
+    for key in ["ownership", "lifetimes"] {
+        let module = repo.find_module(key).unwrap();
+        let bank = load_bank(module).unwrap().expect("题库不存在");
+        let code_questions: Vec<_> = bank.questions.iter()
+            .filter(|q| q.question_type.is_code_question())
+            .collect();
+        assert!(!code_questions.is_empty(), "{} 没有代码题", module.id);
+
+        for question in code_questions {
+            let mismatch = check_code_question(question).unwrap();
+            assert_eq!(mismatch, None, "{}：{}", module.id, question.prompt);
+        }
+    }
+}
+
+#[test]
+fn test_checklist_parse_and_toggle() {
+    // 测试自检清单按 ## 分组解析，勾选只修改对应行
+    use learning_companion::checklist::{parse_checklist, toggle_line};
+    use std::path::Path;
+
+    let content = "# 自检清单\n\n说明文字\n\n## 所有权\n\n- [ ] 理解所有权规则\n- [x] 理解移动\n\n## 借用\n\n- [X] 理解引用\n- [ ] 理解借用规则\n- [ ] 理解切片\n\n```markdown\n- [ ] 代码块中的不算\n```\n";
+    let checklist = parse_checklist(content, Path::new("自检清单.md"));
+
+    let titles: Vec<_> = checklist.sections.iter().map(|s| s.title.as_str()).collect();
+    assert_eq!(titles, vec!["所有权", "借用"]);
+    assert_eq!(checklist.total(), 5);
+    assert_eq!(checklist.checked_count(), 2);
+    assert!(!checklist.is_passed());
+
+    let first = &checklist.sections[0].items[0];
+    assert_eq!(first.text, "理解所有权规则");
+    let updated = toggle_line(content, first.line).unwrap();
+    assert_eq!(updated, content.replacen("- [ ] 理解所有权规则", "- [x] 理解所有权规则", 1));
+
+    let unchecked = toggle_line(&updated, first.line).unwrap();
+    assert_eq!(unchecked, content, "再次切换应恢复原文");
+    assert_eq!(toggle_line(content, 2), None, "非清单行不能切换");
+
+    // 正文中的 `[ ]` / `[x]` 不受影响
+    let literal = "- [x] 区分 `[ ]` 和 `[x]`\n- [ ] 理解 `arr[x]` 索引\n";
+    assert_eq!(toggle_line(literal, 0).unwrap(), "- [ ] 区分 `[ ]` 和 `[x]`\n- [ ] 理解 `arr[x]` 索引\n");
+    assert_eq!(toggle_line(literal, 1).unwrap(), "- [x] 区分 `[ ]` 和 `[x]`\n- [x] 理解 `arr[x]` 索引\n");
+
+    // 勾选到 80% 即自检通过
+    let mut text = updated;
+    for item in parse_checklist(&text, Path::new("自检清单.md")).items().filter(|i| !i.checked).take(1) {
+        text = toggle_line(&text, item.line).unwrap();
+    }
+    assert!(parse_checklist(&text, Path::new("自检清单.md")).is_passed());
+}
+
+#[test]
+fn test_module_metadata_from_manifest() {
+    // 测试模块编号、名称和先修模块来自 Cargo.toml 的 [package.metadata.learning]
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+
+    let numbers: Vec<u32> = repo.modules.iter().map(|m| m.number).collect();
+    assert_eq!(numbers, (1..=12).collect::<Vec<_>>());
+
+    let lifetimes = repo.find_module("lifetimes").unwrap();
+    assert_eq!(lifetimes.name, "04-生命周期");
+    assert_eq!(lifetimes.prerequisites, vec!["module-02-ownership".to_string()]);
+
+    for module in &repo.modules {
+        for prerequisite in &module.prerequisites {
+            assert!(repo.find_module(prerequisite).is_some(), "{} 的先修模块 {} 不存在", module.id, prerequisite);
+        }
+    }
+    // 仓库自带的先修关系不能有循环
+    learning_companion::path::Graph::new(&repo).expect("先修关系图无效");
+}
+
+#[test]
+fn test_module_metadata_from_front_matter() {
+    // 测试没有 Cargo.toml 元数据时读取 README front matter
+    use learning_companion::markdown::front_matter;
+    use learning_companion::repo::metadata_from_front_matter;
+
+    let readme = "---\nnumber: 13\nname: \"异步编程\"\nprerequisites:\n  - module-09-concurrency\n  - module-12-iterators\n---\n\n# 13-异步编程\n";
+    let metadata = metadata_from_front_matter(&front_matter(readme).unwrap());
+    assert_eq!(metadata.number, Some(13));
+    assert_eq!(metadata.name.as_deref(), Some("异步编程"));
+    assert_eq!(metadata.prerequisites, vec!["module-09-concurrency", "module-12-iterators"]);
+
+    let inline = front_matter("---\nprerequisites: [a, 'b']\n---\n").unwrap();
+    assert_eq!(metadata_from_front_matter(&inline).prerequisites, vec!["a", "b"]);
+    assert!(front_matter("# 没有 front matter\n").is_none());
+}
+
+#[test]
+fn test_progress_sections_keyed_by_number() {
+    // 测试 进度.md 按标题编号解析，任务按文字匹配，代码块中的内容不计入
+    use learning_companion::repo::parse_progress;
+
+    let content = "# 学习进度\n\n## 详细进度\n\n### 01-基础入门\n\n- [x] 概念学习\n- [ ] 代码示例\n\n### 13-异步编程 🆕\n\n- [x] 自检通过\n- [x] 综合练习\n- [x] 练习题完成\n- [x] 代码示例\n- [x] 概念学习\n\n```\n### 99-不是模块\n```\n\n### 学习时长\n\n- [x] 概念学习\n";
+    let progress = parse_progress(content);
+
+    let numbers: Vec<u32> = progress.iter().map(|p| p.number).collect();
+    assert_eq!(numbers, vec![1, 13]);
+    assert!(progress[0].concept && !progress[0].examples);
+    assert_eq!(progress[0].status, "[~]");
+    assert_eq!(progress[1].name, "13-异步编程 🆕");
+    assert_eq!(progress[1].status, "[x]");
+}
+
+#[test]
+fn test_sync_three_way_merge() {
+    // 测试以上次同步的值为基准判断哪一侧被修改，两侧都改且不同时为冲突
+    use learning_companion::sync::{merge, Merge};
+
+    assert_eq!(merge("true", None, None), Merge::TakeFile);
+    assert_eq!(merge("true", Some("true"), Some("false")), Merge::Same);
+    assert_eq!(merge("true", Some("false"), Some("false")), Merge::TakeFile);
+    assert_eq!(merge("false", Some("true"), Some("false")), Merge::TakeDb);
+    assert_eq!(merge("2024-03-01", Some("2024-03-05"), Some("")), Merge::Conflict);
+    assert_eq!(merge("true", Some("false"), None), Merge::Conflict);
+}
+
+#[test]
+fn test_sync_rewrites_progress_file() {
+    // 测试写回任务勾选、完成日期和总进度条，其余内容原样保留
+    use learning_companion::repo::parse_progress;
+    use learning_companion::sync::{apply_to_content, FieldChange, COMPLETED_AT};
+
+    let content = "# 学习进度\n\n```\n总进度: 0/2 (0%)\n░░░░░░░░░░\n```\n\n### 01-基础入门\n\n- [x] 概念学习\n- [x] 代码示例\n- [x] 练习题完成\n- [x] 综合练习\n- [ ] 自检通过\n\n**完成日期**：________________\n\n**心得体会**：很有收获\n\n### 02-所有权系统\n\n- [ ] 自检通过\n";
+    let change = |number: u32, field: &str, value: &str| FieldChange {
+        module_id: format!("module-{:02}", number),
+        number,
+        field: field.to_string(),
+        value: value.to_string(),
+    };
+
+    let updated = apply_to_content(content, &[
+        change(1, "checklist", "true"),
+        change(1, COMPLETED_AT, "2024-03-01"),
+    ], 2);
+
+    assert!(updated.contains("总进度: 1/2 (50%)\n█████░░░░░\n"));
+    assert!(updated.contains("**完成日期**：2024-03-01\n"));
+    assert!(updated.contains("**心得体会**：很有收获\n"));
+    assert!(updated.ends_with("### 02-所有权系统\n\n- [ ] 自检通过\n"));
+
+    let progress = parse_progress(&updated);
+    assert_eq!(progress[0].status, "[x]");
+    assert_eq!(progress[0].completed_at.as_deref(), Some("2024-03-01"));
+
+    // 清空完成日期时恢复下划线占位
+    let cleared = apply_to_content(&updated, &[change(1, COMPLETED_AT, "")], 2);
+    assert!(cleared.contains("**完成日期**：________________\n"));
+}
+
+#[test]
+fn test_mastery_counts_unsynced_progress_ticks() {
+    // 测试同步前在 进度.md 中勾选的任务也算作证据，数据库一侧较新时以数据库为准
+    use learning_companion::progress::MasteryEvidence;
+    use learning_companion::sync;
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("lc-evidence-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("module-01-basics")).unwrap();
+    fs::write(dir.join("module-01-basics/Cargo.toml"), "[package]\nname = \"module-01-basics\"\n").unwrap();
+    fs::write(dir.join("进度.md"), "### 01-基础入门\n\n- [x] 概念学习\n- [x] 代码示例\n- [ ] 练习题完成\n").unwrap();
+
+    let repo = LearningRepo::new(&dir).unwrap();
+    assert_eq!(repo.module_key("basics"), "module-01-basics");
+    assert_eq!(repo.module_key("module-99-missing"), "module-99-missing");
+
+    let store = db::Store::open_in_memory().unwrap();
+    let evidence = MasteryEvidence::collect(&store, &repo, "basics").unwrap();
+    assert_eq!(evidence.tasks_done, vec![TaskType::Concept, TaskType::Examples]);
+
+    // 在数据库一侧取消「代码示例」，未同步前也按数据库计算
+    sync::record_task(&store, &repo, "basics", TaskType::Examples, false).unwrap();
+    let evidence = MasteryEvidence::collect(&store, &repo, "basics").unwrap();
+    assert_eq!(evidence.tasks_done, vec![TaskType::Concept]);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_update_task_accepts_module_names() {
+    // 测试用主题名或模块名标记练习题和自检：按测试结果和清单判断，与目录名的效果相同
+    use learning_companion::progress::update_task_status;
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("lc-update-names-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("module-01-basics/exercises")).unwrap();
+    fs::write(dir.join("module-01-basics/Cargo.toml"), "[package]\nname = \"module-01-basics\"\n").unwrap();
+    fs::write(dir.join("module-01-basics/exercises/exercise1_hello.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.join("进度.md"), "### 01-基础入门\n\n- [ ] 概念学习\n- [ ] 练习题完成\n- [ ] 自检通过\n").unwrap();
+
+    let repo = LearningRepo::new(&dir).unwrap();
+    let store = db::Store::open_in_memory().unwrap();
+    assert!(update_task_status(&store, &repo, "basics", "exercises").is_err(), "还没有通过测试");
+
+    store.record_exercise_result("module-01-basics", "exercise1_hello", true, 1, 1, Vec::new()).unwrap();
+    update_task_status(&store, &repo, "basics", "exercises").unwrap();
+    assert!(fs::read_to_string(dir.join("进度.md")).unwrap().contains("- [x] 练习题完成"));
+
+    // 自检按清单勾选比例判断，用模块名指定模块时也会读取清单
+    let name = repo.find_module("basics").unwrap().name.clone();
+    assert_ne!(name, "module-01-basics");
+    assert!(update_task_status(&store, &repo, &name, "checklist").is_err(), "还没有清单");
+    fs::write(dir.join("module-01-basics/自检清单.md"), "- [x] 理解变量\n- [x] 理解函数\n").unwrap();
+    update_task_status(&store, &repo, &name, "checklist").unwrap();
+    assert!(fs::read_to_string(dir.join("进度.md")).unwrap().contains("- [x] 自检通过"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_mastery_from_evidence() {
+    // 测试掌握程度只由证据决定，缺少的证据逐项说明
+    use learning_companion::progress::{weighted_practice_score, MasteryEvidence, MasteryWeights};
+
+    let mut evidence = MasteryEvidence {
+        tasks_done: vec![TaskType::Concept, TaskType::Examples],
+        exercises: Some((3, 4)),
+        exercises_assisted: 0,
+        practice_score: Some(60.0),
+        checklist_ratio: Some(0.5),
+        has_exercises: true,
+        has_checklist: true,
+    };
+    let weights = MasteryWeights::default();
+    // 0.4×20 + 0.75×30 + 0.6×30 + 0.5×20 = 58.5
+    assert!((evidence.mastery(&weights) - 58.5).abs() < 0.01);
+    assert_eq!(evidence.missing("module-01-basics").len(), 4);
+
+    // 没有自检清单的模块不计该项权重
+    evidence.has_checklist = false;
+    assert!((evidence.mastery(&weights) - 48.5 / 80.0 * 100.0).abs() < 0.01);
+
+    let complete = MasteryEvidence {
+        tasks_done: TaskType::ALL.to_vec(),
+        exercises: Some((4, 4)),
+        exercises_assisted: 0,
+        practice_score: Some(90.0),
+        checklist_ratio: Some(1.0),
+        has_exercises: true,
+        has_checklist: true,
+    };
+    assert!(complete.missing("module-01-basics").is_empty());
+    let only_practice = MasteryWeights { tasks: 0.0, exercises: 0.0, practice: 1.0, checklist: 0.0 };
+    assert!((complete.mastery(&only_practice) - 90.0).abs() < 0.01);
+
+    // 近期练习权重更高：14 天前的 0 分只占今天 100 分一半的权重
+    assert_eq!(weighted_practice_score(&[]), None);
+    let score = weighted_practice_score(&[(14.0, 0.0), (0.0, 100.0)]).unwrap();
+    assert!((score - 100.0 / 1.5).abs() < 0.01);
+}
+
+#[test]
+fn test_study_timer_pomodoro_and_recovery() {
+    // 测试番茄钟阶段计算，以及中断过久的计时按最后一次活动时间结束（只限 TUI 写入心跳的计时）
+    use chrono::{Duration, Local};
+    use learning_companion::db::{ActiveSession, Store};
+    use learning_companion::study::{is_stale, pomodoro_phase, recover, session_minutes, format_elapsed, stop, Recovery};
+
+    let phase = pomodoro_phase(10 * 60, 25);
+    assert!(phase.focus);
+    assert_eq!(phase.remaining_secs, 15 * 60);
+    assert_eq!(phase.completed, 0);
+
+    let phase = pomodoro_phase(27 * 60, 25);
+    assert!(!phase.focus);
+    assert_eq!(phase.remaining_secs, 3 * 60);
+    assert_eq!(phase.completed, 1);
+
+    let phase = pomodoro_phase(31 * 60, 25);
+    assert!(phase.focus);
+    assert_eq!(phase.completed, 1);
+
+    let now = Local::now();
+    let session = ActiveSession {
+        started_at: now - Duration::hours(9),
+        last_seen: now - Duration::hours(8),
+        modules_studied: vec!["module-01-basics".to_string()],
+        practice_count: 1,
+        notes: None,
+        pomodoro_minutes: None,
+        heartbeat: true,
+    };
+    assert!(is_stale(&session, now));
+    assert_eq!(session_minutes(&session, session.last_seen), 60);
+    assert!(!is_stale(&session, session.last_seen + Duration::hours(1)));
+    assert_eq!(format_elapsed(3725), "01:02:05");
+
+    let store = Store::open_in_memory().unwrap();
+    store.save_active_session(&session).unwrap();
+    assert!(matches!(recover(&store, now).unwrap(), Recovery::Closed(summary) if summary.minutes == 60));
+
+    // 命令行开始的计时没有心跳，不会在 study stop 之前被结束
+    let cli = ActiveSession { last_seen: session.started_at, heartbeat: false, ..session };
+    store.save_active_session(&cli).unwrap();
+    assert!(!is_stale(&cli, now));
+    assert!(matches!(recover(&store, now).unwrap(), Recovery::Resumed(_)));
+    assert_eq!(stop(&store, None).unwrap().minutes, 9 * 60);
+}
+
+#[test]
+fn test_achievement_rules_and_progress() {
+    // 测试按条件评估成就，并给出未达成成就的进度
+    use learning_companion::achievements::{newly_met, parse_definitions, progress, AchievementStats, Condition};
+
+    let defs = parse_definitions(r#"
+[[achievements]]
+id = "week"
+name = "坚持一周"
+description = "连续学习 7 天"
+condition = { type = "streak_days", days = 7 }
+
+[[achievements]]
+id = "half"
+name = "半程"
+description = "完成一半模块"
+icon = "🌟"
+condition = { type = "modules_percent", percent = 50 }
+"#).unwrap();
+    assert_eq!(defs[0].icon, "🏅");
+
+    let stats = AchievementStats { streak_days: 5, modules_completed: 6, total_modules: 12, ..Default::default() };
+    assert_eq!(progress(&defs[0].condition, &stats).display(), "5/7 天");
+    assert_eq!(progress(&defs[1].condition, &stats).display(), "6/6 个模块");
+
+    let met = newly_met(&defs, &stats, &[]);
+    assert_eq!(met.len(), 1);
+    assert_eq!(met[0].0.id, "half");
+    assert!(newly_met(&defs, &stats, &["half".to_string()]).is_empty());
+
+    // 超出目标时进度显示封顶
+    let score = progress(&Condition::PracticeScore { score: 80 }, &AchievementStats { best_practice_score: 95.0, ..Default::default() });
+    assert!(score.is_met());
+    assert_eq!(score.display(), "80/80 分");
+
+    assert!(parse_definitions("[[achievements]]\nid = \"a\"\nname = \"a\"\ndescription = \"a\"\ncondition = { type = \"unknown\" }\n").is_err());
+}
+
+#[test]
+fn test_reminder_daemon_schedule() {
+    // 测试提醒调度：按星期和时间到点提醒，安静日和已学习的日子跳过，不需要桌面通知
+    use chrono::{DateTime, Local, NaiveDate, TimeZone};
+    use learning_companion::clock::Clock;
+    use learning_companion::daemon::{parse_days, Notifier, QuietDay, Reminder, ReminderConfig, ReminderSchedule, TickOutcome};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct FixedClock(Rc<Cell<DateTime<Local>>>);
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Local> {
+            self.0.get()
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier(Vec<String>);
+    impl Notifier for RecordingNotifier {
+        fn notify(&mut self, _title: &str, body: &str) -> anyhow::Result<()> {
+            self.0.push(body.to_string());
+            Ok(())
+        }
+    }
+
+    let at = |d: u32, h: u32, m: u32| Local.with_ymd_and_hms(2024, 6, d, h, m, 0).unwrap();
+
+    let config = ReminderConfig {
+        schedules: vec![
+            ReminderSchedule::new(parse_days("weekdays").unwrap(), 20, 0).unwrap(),
+            ReminderSchedule::new(parse_days("sat").unwrap(), 10, 30).unwrap(),
+        ],
+        quiet_days: vec![QuietDay::parse("2024-06-05").unwrap()],
+    };
+    assert_eq!(config.schedules[0].describe(), "mon,tue,wed,thu,fri 20:00");
+
+    // 2024-06-03 是周一
+    let now = Rc::new(Cell::new(at(3, 19, 0)));
+    let mut reminder = Reminder::new(FixedClock(now.clone()), RecordingNotifier::default());
+    let never_studied = |_: NaiveDate| Ok(false);
+
+    assert_eq!(config.next_reminder(at(3, 19, 0)), Some(at(3, 20, 0)));
+    assert_eq!(reminder.tick(&config, never_studied).unwrap(), TickOutcome::Idle);
+
+    now.set(at(3, 20, 0));
+    assert_eq!(reminder.tick(&config, never_studied).unwrap(), TickOutcome::Sent(at(3, 20, 0)));
+    // 同一时间不重复提醒
+    now.set(at(3, 20, 1));
+    assert_eq!(reminder.tick(&config, never_studied).unwrap(), TickOutcome::Idle);
+
+    // 周二已经学习过
+    now.set(at(4, 20, 0));
+    let studied = |d: NaiveDate| Ok(d == NaiveDate::from_ymd_opt(2024, 6, 4).unwrap());
+    assert_eq!(reminder.tick(&config, studied).unwrap(), TickOutcome::SkippedStudied(at(4, 20, 0)));
+
+    // 周三是安静日，下次提醒顺延到周四
+    assert_eq!(config.next_reminder(at(4, 21, 0)), Some(at(6, 20, 0)));
+    now.set(at(5, 20, 0));
+    assert_eq!(reminder.tick(&config, never_studied).unwrap(), TickOutcome::SkippedQuiet(at(5, 20, 0)));
+
+    // 周六上午的计划
+    now.set(at(8, 11, 0));
+    assert_eq!(reminder.tick(&config, never_studied).unwrap(), TickOutcome::Sent(at(8, 10, 30)));
+    assert_eq!(reminder.notifier().0.len(), 2);
+
+    // 读取学习记录失败时返回错误，不当作没有学习而发送提醒
+    now.set(at(10, 20, 0));
+    assert!(reminder.tick(&config, |_: NaiveDate| anyhow::bail!("数据库损坏")).is_err());
+    assert_eq!(reminder.notifier().0.len(), 2);
+
+    assert!(parse_days("funday").is_err());
+    // 只接受完整的缩写或全称，拼写错误不会被当成某一天
+    assert!(parse_days("month").is_err());
+    assert!(parse_days("sunny").is_err());
+    assert!(QuietDay::parse("sunny").is_err());
+    assert_eq!(parse_days("Monday, wed").unwrap(), vec![chrono::Weekday::Mon, chrono::Weekday::Wed]);
+    assert!(ReminderSchedule::new(parse_days("daily").unwrap(), 24, 0).is_err());
+}
+
+#[test]
+fn test_export_format_and_csv() {
+    // 测试导出文件的版本校验和 CSV 转义
+    use learning_companion::db::Row;
+    use learning_companion::storage::{table_to_csv, Export, EXPORT_VERSION};
+
+    let json = format!(r#"{{
+        "format": "learning-companion-export",
+        "version": {},
+        "exported_at": "2024-06-01T20:00:00+08:00",
+        "tables": {{ "settings": [{{ "key": "remind_time", "value": "20:00" }}] }}
+    }}"#, EXPORT_VERSION);
+    let export = Export::parse(&json).unwrap();
+    assert_eq!(export.row_count(), 1);
+
+    let newer = json.replace(&format!("\"version\": {}", EXPORT_VERSION), &format!("\"version\": {}", EXPORT_VERSION + 1));
+    assert!(Export::parse(&newer).is_err());
+    assert!(Export::parse(&json.replace("learning-companion-export", "other")).is_err());
+
+    let mut row = Row::new();
+    row.insert("date".to_string(), "2024-06-01".into());
+    row.insert("notes".to_string(), "读了 \"所有权\", 第 4 章".into());
+    row.insert("practice_count".to_string(), 2.into());
+    row.insert("weak_topics".to_string(), serde_json::Value::Null);
+    assert_eq!(
+        table_to_csv(&[row]),
+        "date,notes,practice_count,weak_topics\n2024-06-01,\"读了 \"\"所有权\"\", 第 4 章\",2,\n"
+    );
+}
+
+#[test]
+fn test_import_round_trip_and_merge() {
+    // 测试导出后替换导入到新数据库，每张表的数据完全一致；重复合并导入不产生重复记录；替换前备份
+    use chrono::{Duration, Local, NaiveDate};
+    use learning_companion::db::{ActiveSession, ImportMode, ReviewSchedule, Store, DATA_TABLES};
+    use learning_companion::storage::{export_data, import_data, run_import, ExportFormat};
+
+    let source = Store::open_in_memory().unwrap();
+    let now = Local::now();
+    source.record_study_session_at(now - Duration::days(1), 45, vec!["module-01-basics".to_string()], 2, Some("读了 \"所有权\"".to_string())).unwrap();
+    source.record_study_session_at(now, 30, Vec::new(), 0, None).unwrap();
+    source.update_module_progress("module-01-basics", 72.5).unwrap();
+    source.record_practice_result("module-01-basics", 5, 4, 80.0, vec!["借用".to_string()]).unwrap();
+    source.unlock_achievement("first_study", Some("{\"minutes\":45}")).unwrap();
+    source.record_exercise_result("module-01-basics", "exercise1_temperature", true, 2, 1, vec!["tests::test_freezing".to_string()]).unwrap();
+    source.record_example_run("module-01-basics", "variables", true, 120).unwrap();
+    source.record_solution_view("module-01-basics", "exercise1_temperature", "skeleton", true).unwrap();
+    source.record_question_review("module-01-basics", "abc123", "所有权", false).unwrap();
+    source.save_review_schedule(&ReviewSchedule {
+        module_id: "module-01-basics".to_string(),
+        question_key: "abc123".to_string(),
+        ease_factor: 2.36,
+        interval_days: 6,
+        repetitions: 2,
+        due_date: NaiveDate::from_ymd_opt(2024, 6, 8).unwrap(),
+    }).unwrap();
+    source.set_progress_field("module-01-basics", "concepts", "x").unwrap();
+    source.save_active_session(&ActiveSession {
+        started_at: now,
+        last_seen: now,
+        modules_studied: vec!["module-01-basics".to_string()],
+        practice_count: 1,
+        notes: None,
+        pomodoro_minutes: Some(25),
+        heartbeat: true,
+    }).unwrap();
+    source.save_setting("remind_time", "20:00").unwrap();
+    for table in DATA_TABLES {
+        assert!(!source.dump_table(table).unwrap().is_empty(), "{} 没有测试数据", table);
+    }
+
+    let root = std::env::temp_dir().join(format!("lc-import-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let file = export_data(&source, ExportFormat::Json, Some(&root.join("export.json"))).unwrap();
+
+    // 替换导入到已有其他数据的数据库后与原数据库完全一致
+    let target = Store::open_in_memory().unwrap();
+    target.save_setting("theme", "dark").unwrap();
+    import_data(&target, &file, ImportMode::Replace).unwrap();
+    for table in DATA_TABLES {
+        assert_eq!(target.dump_table(table).unwrap(), source.dump_table(table).unwrap(), "{} 替换导入后不一致", table);
+    }
+
+    // 合并导入两次与一次的结果相同
+    let merged = Store::open_in_memory().unwrap();
+    let first = import_data(&merged, &file, ImportMode::Merge).unwrap();
+    let once: Vec<_> = DATA_TABLES.iter().map(|t| merged.dump_table(t).unwrap()).collect();
+    let second = import_data(&merged, &file, ImportMode::Merge).unwrap();
+    assert_eq!(second.inserted(), 0);
+    assert_eq!(second.skipped(), first.inserted());
+    let twice: Vec<_> = DATA_TABLES.iter().map(|t| merged.dump_table(t).unwrap()).collect();
+    assert_eq!(once, twice);
+
+    // 命令行替换导入前先备份
+    let on_disk = Store::open(&root.join("data")).unwrap();
+    run_import(&on_disk, &file, ImportMode::Replace).unwrap();
+    let backups: Vec<_> = std::fs::read_dir(root.join("data").join("backups")).unwrap().collect();
+    assert_eq!(backups.len(), 1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_learning_report_rendering() {
+    // 测试报告的统计和渲染：薄弱知识点排序、单文件 HTML
+    use chrono::{Local, NaiveDate, TimeZone};
+    use learning_companion::report::{rank_weak_topics, render_html, render_markdown, ModuleTrend, Period, ReportData};
+
+    let topics = rank_weak_topics(&[
+        ("生命周期".to_string(), false),
+        ("借用规则".to_string(), false),
+        ("借用规则".to_string(), false),
+        ("生命周期".to_string(), true),
+        ("模式匹配".to_string(), true),
+    ]);
+    assert_eq!(topics.iter().map(|t| t.topic.as_str()).collect::<Vec<_>>(), vec!["借用规则", "生命周期"]);
+    assert_eq!((topics[1].misses, topics[1].attempts), (1, 2));
+
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+
+    let data = ReportData {
+        period: Period::Week,
+        start: date(1),
+        end: date(7),
+        generated_at: Local.with_ymd_and_hms(2024, 6, 7, 21, 0, 0).unwrap(),
+        modules: vec![ModuleTrend {
+            module_id: "module-01-basics".to_string(),
+            name: "01-基础 <入门>".to_string(),
+            initial: Some(20.0),
+            points: vec![(Local.with_ymd_and_hms(2024, 6, 3, 20, 0, 0).unwrap(), 55.0)],
+        }],
+        practice: Vec::new(),
+        weak_topics: topics,
+        study_minutes: [(date(2), 45), (date(3), 130)].into_iter().collect(),
+        current_streak: 2,
+        longest_streak: 3,
+        achievements: Vec::new(),
+        achievements_total: 8,
+    };
+    assert_eq!(data.total_minutes(), 175);
+    assert_eq!(data.heatmap_weeks().len(), 2);
+
+    let md = render_markdown(&data);
+    assert!(md.contains("| 01-基础 <入门> | 20% | 55% | +35 |"));
+    assert!(md.contains("| 借用规则 | 2 | 2 |"));
+
+    let html = render_html(&data);
+    assert!(html.contains("<svg"));
+    assert!(html.contains("01-基础 &lt;入门&gt;"));
+    assert!(!html.contains("src=") && !html.contains("<link"));
+}
+
+#[test]
+fn test_migrations_upgrade_every_version() {
+    // 测试从每个旧版本（包括没有版本号的早期数据库）升级到最新版本，数据保留，迁移前自动备份
+    use learning_companion::migrate::{current_version, latest_version, migrate, migrate_to, pending, MIGRATIONS};
+    use rusqlite::Connection;
+
+    let dir = std::env::temp_dir().join(format!("lc-migrate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for (i, migration) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.version, i as u32 + 1, "迁移版本必须从 1 开始连续递增");
+    }
+
+    // 早期版本：只有最初的五张表，user_version 为 0
+    let legacy = "CREATE TABLE study_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, date TEXT NOT NULL, duration_minutes INTEGER NOT NULL,
+            modules_studied TEXT NOT NULL, practice_count INTEGER NOT NULL DEFAULT 0, notes TEXT);
+        CREATE TABLE module_progress (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, module_id TEXT NOT NULL UNIQUE, started_at TEXT,
+            completed_at TEXT, mastery_score REAL DEFAULT 0.0, last_updated TEXT NOT NULL);
+        CREATE TABLE practice_results (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, module_id TEXT NOT NULL, timestamp TEXT NOT NULL,
+            questions_total INTEGER NOT NULL, questions_correct INTEGER NOT NULL, score REAL NOT NULL, weak_topics TEXT);
+        CREATE TABLE achievements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, achievement_type TEXT NOT NULL UNIQUE, unlocked_at TEXT NOT NULL, metadata TEXT);
+        CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);";
+
+    for from in 0..latest_version() {
+        let path = dir.join(format!("v{}.db", from));
+        let backups = dir.join(format!("backups-v{}", from));
+        let _ = std::fs::remove_file(&path);
+        let mut conn = Connection::open(&path).unwrap();
+
+        if from == 0 {
+            conn.execute_batch(legacy).unwrap();
+        } else {
+            migrate_to(&mut conn, from, None).unwrap();
+        }
+        assert_eq!(current_version(&conn).unwrap(), from);
+        conn.execute(
+            "INSERT INTO study_sessions (date, duration_minutes, modules_studied) VALUES ('2024-06-01', 45, 'module-01-basics')",
+            [],
+        ).unwrap();
+
+        let report = migrate(&mut conn, Some(&backups)).unwrap();
+        assert_eq!((report.from, report.to), (from, latest_version()));
+        assert_eq!(report.applied, ((from + 1)..=latest_version()).collect::<Vec<_>>());
+        assert!(pending(&conn).unwrap().is_empty());
+
+        let minutes: u32 = conn.query_row("SELECT duration_minutes FROM study_sessions", [], |row| row.get(0)).unwrap();
+        assert_eq!(minutes, 45);
+        for table in learning_companion::db::DATA_TABLES {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get::<_, u32>(0))
+                .unwrap_or_else(|e| panic!("从版本 {} 升级后缺少表 {}：{}", from, table, e));
+        }
+
+        // 备份是迁移前的数据库
+        let backup = Connection::open(report.backup.expect("已有数据的数据库迁移前应备份")).unwrap();
+        assert_eq!(current_version(&backup).unwrap(), from);
+
+        // 再次迁移什么都不做
+        assert!(migrate(&mut conn, Some(&backups)).unwrap().applied.is_empty());
+    }
+
+    // 全新数据库不需要备份；比程序新的数据库拒绝打开
+    let mut conn = Connection::open_in_memory().unwrap();
+    assert!(migrate(&mut conn, Some(&dir.join("fresh"))).unwrap().backup.is_none());
+    conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+    assert!(pending(&conn).is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_store_data_dir_and_profiles() {
+    use db::{Store, resolve_data_dir, profile_dir};
+    use std::path::Path;
+
+    // --data-dir 优先于学习档案
+    let dir = std::env::temp_dir().join(format!("lc-store-test-{}", std::process::id()));
+    assert_eq!(resolve_data_dir(Some(&dir), Some("work")).unwrap(), dir);
+    assert_eq!(resolve_data_dir(None, Some("work")).unwrap(), profile_dir("work").unwrap());
+    assert!(profile_dir("work").unwrap().ends_with(Path::new("profiles").join("work")));
+    assert!(profile_dir("../escape").is_err());
+    assert!(profile_dir("").is_err());
+
+    // 不同数据目录的数据互不影响
+    let first = Store::open(&dir.join("first")).unwrap();
+    let second = Store::open(&dir.join("second")).unwrap();
+    first.save_setting("remind_time", "20:00").unwrap();
+    assert_eq!(first.get_setting("remind_time").unwrap().as_deref(), Some("20:00"));
+    assert_eq!(second.get_setting("remind_time").unwrap(), None);
+    assert_eq!(first.db_path(), Some(dir.join("first").join(db::DB_FILE)));
+
+    // 重新打开能读到已保存的数据
+    drop(first);
+    let reopened = Store::open(&dir.join("first")).unwrap();
+    assert_eq!(reopened.get_setting("remind_time").unwrap().as_deref(), Some("20:00"));
+
+    // 内存数据库不落盘，也没有数据目录
+    let memory = Store::open_in_memory().unwrap();
+    assert!(memory.db_path().is_none());
+    assert!(memory.data_dir().is_err());
+    assert_eq!(memory.get_setting("remind_time").unwrap(), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_db_doctor_repairs_corrupt_rows() {
+    use db::{DbError, Store};
+    use learning_companion::doctor::{diagnose, repair};
+
+    let mut store = Store::open_in_memory().unwrap();
+    store.record_study_session(30, vec!["basics".to_string()], 0, None).unwrap();
+    assert_eq!(store.get_session_times().unwrap().len(), 1);
+    assert_eq!(store.get_module_mastery("basics").unwrap(), None);
+
+    // 手动写入格式错误的日期和时间
+    store.connection().execute_batch(
+        "INSERT INTO study_sessions (date, duration_minutes, modules_studied) VALUES ('yesterday', 10, 'basics');
+         INSERT INTO practice_results (module_id, timestamp, questions_total, questions_correct, score)
+         VALUES ('basics', 'not-a-time', 5, 3, 60.0);",
+    ).unwrap();
+
+    // 损坏的记录返回带位置的错误，而不是 panic 或当作没有数据
+    match store.get_session_times() {
+        Err(DbError::Corrupt { table, column, rowid, value }) => {
+            assert_eq!((table, column, rowid, value.as_str()), ("study_sessions", "date", 2, "yesterday"));
+        }
+        other => panic!("应返回 Corrupt 错误：{:?}", other),
+    }
+    assert!(store.get_all_practice_results().unwrap_err().is_corrupt());
+    assert!(learning_companion::achievements::AchievementStats::collect(&store, 1).is_err(), "不能当作没有学习记录");
+
+    let diagnosis = diagnose(store.connection()).unwrap();
+    assert!(diagnosis.integrity.is_empty());
+    let found: Vec<_> = diagnosis.rows.iter().map(|i| (i.table, i.column, i.rowid)).collect();
+    assert_eq!(found, vec![("study_sessions", "date", 2), ("practice_results", "timestamp", 1)]);
+
+    assert_eq!(repair(store.connection(), &diagnosis.rows).unwrap(), 2);
+    assert!(diagnose(store.connection()).unwrap().is_healthy());
+    assert_eq!(store.get_session_times().unwrap().len(), 1);
+    assert!(store.get_all_practice_results().unwrap().is_empty());
+}
+
+#[test]
+fn test_streak_with_clock_timezone_rest_and_freeze() {
+    use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone as _, Utc, Weekday};
+    use learning_companion::clock::{Clock, TimeZone};
+    use learning_companion::streak::{calculate, compute, Streak, StreakRules};
+    use std::collections::BTreeSet;
+
+    // 2024-06-03 是周一
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+    let days = |list: &[u32]| list.iter().map(|d| date(*d)).collect::<BTreeSet<_>>();
+    let none = StreakRules::default();
+
+    // 从今天往前数；今天还没学习不算中断
+    assert_eq!(compute(&days(&[1, 2, 3, 5, 6]), date(6), &none), Streak { current: 2, longest: 3 });
+    assert_eq!(compute(&days(&[4, 5]), date(6), &none), Streak { current: 2, longest: 2 });
+    // 很久以前的连续记录不是当前连续
+    assert_eq!(compute(&days(&[1, 2, 3]), date(20), &none), Streak { current: 0, longest: 3 });
+    assert_eq!(compute(&BTreeSet::new(), date(6), &none), Streak::default());
+    // 今天之后的日期忽略
+    assert_eq!(compute(&days(&[5, 6, 7]), date(6), &none).current, 2);
+
+    // 休息日（周日 6/2、6/9）和冻结日（6/5）不中断也不计数
+    let rules = StreakRules {
+        rest_days: vec![Weekday::Sun],
+        freeze_days: [date(5)].into_iter().collect(),
+    };
+    assert_eq!(compute(&days(&[1, 3, 4, 6, 7, 8, 10]), date(10), &rules), Streak { current: 7, longest: 7 });
+    assert_eq!(compute(&days(&[1, 3, 4, 6, 7, 8, 10]), date(10), &none), Streak { current: 1, longest: 3 });
+    // 休息日学习照样计数
+    assert_eq!(compute(&days(&[8, 9, 10]), date(10), &rules).current, 3);
+
+    // 时区：22:30 UTC 在 +08:00 已经是第二天
+    let tz: TimeZone = "+08:00".parse().unwrap();
+    assert_eq!("UTC+8".parse::<TimeZone>().unwrap(), tz);
+    assert_eq!("utc".parse::<TimeZone>().unwrap().describe(), "UTC");
+    assert!("+25:00".parse::<TimeZone>().is_err());
+    assert!("Asia/Shanghai".parse::<TimeZone>().is_err());
+    let late = Utc.with_ymd_and_hms(2024, 6, 5, 22, 30, 0).unwrap();
+    assert_eq!(tz.date_of(late.fixed_offset()), date(6));
+    assert_eq!("-05:00".parse::<TimeZone>().unwrap().date_of(late.fixed_offset()), date(5));
+
+    // 注入时钟，按设置的时区计算数据库中的连续天数
+    let store = db::Store::open_in_memory().unwrap();
+    let at = |d: u32, h: u32, offset_hours: i32| -> DateTime<Local> {
+        FixedOffset::east_opt(offset_hours * 3600).unwrap()
+            .with_ymd_and_hms(2024, 6, d, h, 0, 0).unwrap()
+            .with_timezone(&Local)
+    };
+    // 在家（+08:00）学了 6/3、6/4，出差到 -07:00 后在当地 6/4 晚上 20 点（家里已经是 6/5 上午）又学了一次
+    for time in [at(3, 20, 8), at(4, 20, 8), at(4, 20, -7)] {
+        store.record_study_session_at(time, 30, vec!["basics".to_string()], 0, None).unwrap();
+    }
+    tz.save(&store).unwrap();
+    let clock = at(6, 9, 8);
+    assert_eq!(clock.now(), clock);
+    assert_eq!(calculate(&store, &clock).unwrap(), Streak { current: 3, longest: 3 });
+    // 再过一天没有学习，连续中断
+    assert_eq!(calculate(&store, &at(7, 9, 8)).unwrap(), Streak { current: 0, longest: 3 });
+    // 冻结 6/6 后不中断
+    StreakRules { rest_days: Vec::new(), freeze_days: [date(6)].into_iter().collect() }.save(&store).unwrap();
+    assert_eq!(StreakRules::load(&store).unwrap().freeze_days.len(), 1);
+    assert_eq!(calculate(&store, &at(7, 9, 8)).unwrap().current, 3);
+}
+
+#[test]
+fn test_learning_path_graph_and_tracks() {
+    // 测试先修关系图的排序和分层、模块解锁状态、路线包含的模块和推荐的下一个模块
+    use learning_companion::path::{self, Graph, ModuleState, TrackDef};
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("lc-path-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let write_module = |id: &str, number: u32, prerequisites: &[&str]| {
+        let module_dir = dir.join(id);
+        fs::create_dir_all(&module_dir).unwrap();
+        let prerequisites: Vec<String> = prerequisites.iter().map(|p| format!("{:?}", p)).collect();
+        fs::write(module_dir.join("Cargo.toml"), format!(
+            "[package]\nname = \"{}\"\n\n[package.metadata.learning]\nnumber = {}\nprerequisites = [{}]\n",
+            id, number, prerequisites.join(", "),
+        )).unwrap();
+    };
+    write_module("module-01-basics", 1, &[]);
+    write_module("module-02-ownership", 2, &["module-01-basics"]);
+    write_module("module-03-structs", 3, &["module-02-ownership"]);
+    write_module("module-04-lifetimes", 4, &["ownership"]);
+    write_module("module-05-concurrency", 5, &["module-03-structs", "module-04-lifetimes"]);
+    write_module("module-06-collections", 6, &["module-03-structs"]);
+    let all_tasks = "- [x] 概念学习\n- [x] 代码示例\n- [x] 练习题完成\n- [x] 综合练习\n- [x] 自检通过\n";
+    fs::write(dir.join("进度.md"), format!(
+        "### 01-基础入门\n\n{all_tasks}\n### 02-所有权\n\n{all_tasks}\n### 03-结构体\n\n- [x] 概念学习\n\n### 04-生命周期\n\n{all_tasks}\n",
+    )).unwrap();
+
+    let repo = LearningRepo::new(&dir).unwrap();
+    let graph = Graph::new(&repo).unwrap();
+    assert_eq!(graph.order(), ["module-01-basics", "module-02-ownership", "module-03-structs", "module-04-lifetimes", "module-05-concurrency", "module-06-collections"]);
+    assert_eq!(graph.prerequisites("module-04-lifetimes"), ["module-02-ownership"]);
+    assert_eq!(graph.depth("module-05-concurrency"), 3);
+    assert_eq!(graph.dependents("module-03-structs"), vec!["module-05-concurrency", "module-06-collections"]);
+    assert_eq!(graph.ancestors("module-05-concurrency").len(), 4);
+
+    // 03 学习中，05 还差 03，04 完成后 05 的其余先修都已满足
+    let states = path::module_states(&repo, &graph);
+    assert_eq!(states["module-02-ownership"], ModuleState::Done);
+    assert_eq!(states["module-03-structs"], ModuleState::InProgress);
+    assert_eq!(states["module-05-concurrency"], ModuleState::Locked { missing: vec!["module-03-structs".to_string()] });
+    assert_eq!(states["module-06-collections"], ModuleState::Locked { missing: vec!["module-03-structs".to_string()] });
+    assert_eq!(path::recommend(&states, graph.order()), Some("module-03-structs"));
+
+    // 路线只包含目标和全部先修模块
+    let track = TrackDef {
+        id: "fast".to_string(),
+        name: "快速通道".to_string(),
+        description: String::new(),
+        goals: vec!["concurrency".to_string()],
+    };
+    let modules = graph.track_modules(&repo, Some(&track)).unwrap();
+    assert_eq!(modules.len(), 5);
+    assert!(!modules.contains(&"module-06-collections".to_string()));
+    let missing_goal = TrackDef { goals: vec!["module-99-async".to_string()], ..track };
+    assert!(graph.track_modules(&repo, Some(&missing_goal)).is_err());
+
+    // 内置路线有效，自定义路线文件可以添加路线，full 不能占用
+    let store = db::Store::open_in_memory().unwrap();
+    assert!(path::load_tracks(&repo).unwrap().iter().any(|t| t.id == "concurrency"));
+    fs::write(dir.join(path::USER_TRACKS_FILE), "[[tracks]]\nid = \"fast\"\nname = \"快速通道\"\ngoals = [\"concurrency\"]\n").unwrap();
+    assert!(path::find_track(&repo, "fast").unwrap().is_some());
+    assert!(path::find_track(&repo, path::FULL_TRACK).unwrap().is_none());
+    assert!(path::parse_tracks("[[tracks]]\nid = \"full\"\nname = \"x\"\ngoals = [\"a\"]\n").is_err());
+    path::run_use(&store, &repo, "fast").unwrap();
+    assert_eq!(path::selected_track(&store, &repo).unwrap().map(|t| t.id).as_deref(), Some("fast"));
+    assert_eq!(path::next_module(&store, &repo).unwrap().as_deref(), Some("module-03-structs"));
+
+    // 循环依赖和不存在的先修模块会报错
+    write_module("module-01-basics", 1, &["module-06-collections"]);
+    assert!(Graph::new(&LearningRepo::new(&dir).unwrap()).is_err());
+    write_module("module-01-basics", 1, &["module-99-async"]);
+    assert!(Graph::new(&LearningRepo::new(&dir).unwrap()).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_classroom_bundles_and_roster() {
+    // 测试数据包签名校验、同一学员只保留最新的数据包，以及名册的完成情况、薄弱知识点和停滞学员
+    use chrono::{Duration, Local};
+    use learning_companion::classroom::{Bundle, Identity, ModuleStatus, Roster};
+    use learning_companion::clock::TimeZone;
+    use learning_companion::storage::Export;
+
+    let temp_dir = std::env::current_dir().unwrap();
+    let parent_dir = temp_dir.parent().unwrap_or(&temp_dir);
+    let repo = LearningRepo::new(parent_dir).expect("无法创建学习仓库");
+    let now = Local::now();
+
+    // 张三完成了基础入门，所有权的借用规则答错了
+    let zhang = Identity::generate("张三").unwrap();
+    let store = db::Store::open_in_memory().unwrap();
+    for task in TaskType::ALL {
+        store.set_progress_field("module-01-basics", task.key(), "true").unwrap();
+    }
+    store.set_progress_field("module-02-ownership", "concept", "true").unwrap();
+    store.record_question_review("module-02-ownership", "q1", "借用规则", false).unwrap();
+    store.record_question_review("module-02-ownership", "q2", "借用规则", true).unwrap();
+    store.record_practice_result("module-01-basics", 5, 4, 80.0, vec![]).unwrap();
+    let bundle = Bundle::create(&zhang, Export::collect(&store).unwrap()).unwrap();
+    let content = serde_json::to_string_pretty(&bundle).unwrap();
+    assert_eq!(Bundle::parse(&content).unwrap(), bundle);
+
+    // 修改内容或换成别人的公钥都无法通过校验
+    let mut tampered = bundle.clone();
+    tampered.learner.name = "王五".to_string();
+    assert!(tampered.verify().is_err());
+    let mut forged = bundle.clone();
+    forged.learner.public_key = Identity::generate("王五").unwrap().public_key().unwrap();
+    assert!(forged.verify().is_err());
+    assert!(Bundle::parse("{\"format\": \"learning-companion-export\"}").is_err());
+
+    // 同一学员较早的数据包被较新的替换
+    let mut older = Bundle::create(&zhang, Export::collect(&db::Store::open_in_memory().unwrap()).unwrap()).unwrap();
+    older.export.exported_at = (now - Duration::days(3)).to_rfc3339();
+
+    // 李四 20 天前学习过一次，也答错了借用规则
+    let li = Identity::generate("李四").unwrap();
+    let store = db::Store::open_in_memory().unwrap();
+    store.record_study_session_at(now - Duration::days(20), 30, vec!["module-01-basics".to_string()], 0, None).unwrap();
+    let li_bundle = Bundle::create(&li, Export::collect(&store).unwrap()).unwrap();
+
+    let roster = Roster::build(&repo, parent_dir, vec![older, bundle, li_bundle], TimeZone::Local, now.date_naive(), 7).unwrap();
+    assert_eq!(roster.learners.len(), 2);
+    let zhang_summary = &roster.learners.iter().find(|l| l.learner.name == "张三").unwrap();
+    assert_eq!(zhang_summary.status("module-01-basics"), ModuleStatus::Done);
+    assert_eq!(zhang_summary.status("module-02-ownership"), ModuleStatus::Started);
+    assert_eq!(zhang_summary.average_score, Some(80.0));
+    assert_eq!(roster.completion("module-01-basics"), 1);
+
+    assert_eq!(roster.weak_topics.len(), 1);
+    assert_eq!(roster.weak_topics[0].topic, "借用规则");
+    assert_eq!((roster.weak_topics[0].learners, roster.weak_topics[0].misses, roster.weak_topics[0].attempts), (1, 1, 2));
+
+    let stalled: Vec<&str> = roster.stalled().iter().map(|l| l.learner.name.as_str()).collect();
+    assert_eq!(stalled, vec!["李四"]);
+
+    let csv = roster.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("学员,密钥指纹,"));
+    assert!(lines.iter().any(|l| l.starts_with("张三,") && l.contains(",完成,学习中,") && l.ends_with(",80.0,借用规则,否")));
+    assert!(lines.iter().any(|l| l.starts_with("李四,") && l.ends_with(",是")));
+}